@@ -0,0 +1,35 @@
+// Build the browser extension's native messaging host and stage it as a Tauri
+// sidecar (`bundle.externalBin`), which Tauri expects to be suffixed with the
+// target triple. The bundle then installs it next to the app executable.
+
+import { execFileSync } from "node:child_process";
+import { copyFileSync, chmodSync, mkdirSync } from "node:fs";
+import { dirname, join } from "node:path";
+import { fileURLToPath } from "node:url";
+
+const BINARY = "focusflow-native-host";
+const srcTauri = join(dirname(fileURLToPath(import.meta.url)), "..", "src-tauri");
+
+const hostTriple = () =>
+  execFileSync("rustc", ["-vV"], { encoding: "utf8" })
+    .split("\n")
+    .find((line) => line.startsWith("host:"))
+    .slice("host:".length)
+    .trim();
+
+// Set by the Tauri CLI when building for another target
+const target = process.env.TAURI_ENV_TARGET_TRIPLE;
+const triple = target ?? hostTriple();
+const ext = triple.includes("windows") ? ".exe" : "";
+
+const cargoArgs = ["build", "--release", "--bin", BINARY];
+if (target) cargoArgs.push("--target", target);
+execFileSync("cargo", cargoArgs, { cwd: srcTauri, stdio: "inherit" });
+
+const built = join(srcTauri, "target", ...(target ? [target] : []), "release", BINARY + ext);
+const staged = join(srcTauri, "binaries", `${BINARY}-${triple}${ext}`);
+mkdirSync(dirname(staged), { recursive: true });
+copyFileSync(built, staged);
+chmodSync(staged, 0o755);
+
+console.log(`Staged ${BINARY} for ${triple}`);
@@ -1,3 +1,21 @@
+use std::path::PathBuf;
+
+/// Sidecars listed in `bundle.externalBin`
+const SIDECARS: &[&str] = &["focusflow-native-host"];
+
 fn main() {
+    // tauri-build refuses to run without the sidecars, but they are built from
+    // this crate; stand in empty files until scripts/build-native-host.mjs
+    // stages the real ones
+    let target = std::env::var("TARGET").expect("TARGET is set by cargo");
+    let ext = if target.contains("windows") { ".exe" } else { "" };
+    for sidecar in SIDECARS {
+        let path = PathBuf::from("binaries").join(format!("{}-{}{}", sidecar, target, ext));
+        if !path.exists() {
+            std::fs::create_dir_all("binaries").expect("create binaries directory");
+            std::fs::write(&path, b"").expect("write sidecar placeholder");
+        }
+    }
+
     tauri_build::build()
 }
@@ -427,6 +427,8 @@ mod tests {
             current_streak_days: 5,
             top_trigger: Some("social media".to_string()),
             average_session_minutes: 25,
+            average_interruption_score: None,
+            top_interruption_reason: None,
         }
     }
 
@@ -534,6 +536,8 @@ mod tests {
             current_streak_days: 0,
             top_trigger: None,
             average_session_minutes: 25,
+            average_interruption_score: None,
+            top_interruption_reason: None,
         };
 
         let result = guideline.evaluate("Hello", &new_ctx);
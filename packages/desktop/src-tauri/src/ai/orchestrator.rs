@@ -602,6 +602,8 @@ mod tests {
             current_streak_days: 5,
             top_trigger: Some("social media".to_string()),
             average_session_minutes: 25,
+            average_interruption_score: None,
+            top_interruption_reason: None,
         }
     }
 
@@ -675,6 +677,8 @@ mod tests {
             current_streak_days: 10,
             top_trigger: Some("email".into()),
             average_session_minutes: 30,
+            average_interruption_score: None,
+            top_interruption_reason: None,
         };
 
         let matches = orchestrator.evaluate_guidelines("Hello", &high_ctx);
@@ -706,6 +710,8 @@ mod tests {
             current_streak_days: 0,
             top_trigger: None,
             average_session_minutes: 25,
+            average_interruption_score: None,
+            top_interruption_reason: None,
         };
 
         let matches = orchestrator.evaluate_guidelines("", &new_user_ctx);
@@ -541,6 +541,8 @@ mod tests {
             current_streak_days: 5,
             top_trigger: Some("social media".to_string()),
             average_session_minutes: 25,
+            average_interruption_score: None,
+            top_interruption_reason: None,
         }
     }
 
@@ -474,6 +474,8 @@ impl ScenarioPromptBuilder {
             current_streak_days: self.context.streak_days,
             top_trigger: self.context.top_trigger.clone(),
             average_session_minutes: self.context.avg_session_minutes,
+            average_interruption_score: None,
+            top_interruption_reason: None,
         };
 
         let orchestrator = GuidelineOrchestrator::new();
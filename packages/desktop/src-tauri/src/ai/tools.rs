@@ -574,55 +574,23 @@ impl ToolHandler for EndFocusSessionHandler {
             .and_then(|v| v.as_bool())
             .unwrap_or(true);
 
-        // Get and remove active session
-        let session = {
-            let mut active = state.active_session.write().await;
-            match active.take() {
-                Some(s) => s,
-                None => {
-                    return Ok(ToolResult::failure(
-                        "No active session",
-                        "There is no active focus session to end.",
-                    ));
-                }
-            }
+        // Effective focused time, read before the session is ended
+        let duration_minutes = {
+            let active = state.active_session.read().await;
+            let Some(session) = active.as_ref() else {
+                return Ok(ToolResult::failure(
+                    "No active session",
+                    "There is no active focus session to end.",
+                ));
+            };
+            let idle_seconds = state.timer_state.read().await.idle_seconds_at(chrono::Utc::now());
+            (session.elapsed_seconds() - idle_seconds).max(0) / 60
         };
 
-        use crate::db::queries;
-        use crate::state::TimerState;
-
-        // Stop timer
-        {
-            let mut timer_state = state.timer_state.write().await;
-            timer_state.stop();
-            *timer_state = TimerState::default();
-        }
-
-        let end_time = chrono::Utc::now();
-        let duration_seconds = session.elapsed_seconds();
-        let duration_minutes = duration_seconds / 60;
+        // Ending a session from the assistant also ends its Pomodoro cycle
+        crate::commands::cycle::stop_cycle(state).await;
 
-        // Update database
-        queries::end_session(state.pool(), &session.id, end_time, completed).await?;
-
-        // Disable blocking
-        {
-            let mut blocking = state.blocking_state.write().await;
-            blocking.disable();
-            blocking.update_blocked_websites(Vec::new());
-        }
-
-        // Update analytics
-        let date = end_time.format("%Y-%m-%d").to_string();
-        queries::upsert_daily_analytics(
-            state.pool(),
-            &date,
-            duration_seconds,
-            0,
-            if completed { 1 } else { 0 },
-            if completed { 0 } else { 1 },
-        )
-        .await?;
+        let session = crate::commands::focus::end_session(completed, None, state).await?;
 
         let message = if completed {
             format!(
@@ -931,6 +899,7 @@ impl ToolHandler for AddBlockedItemHandler {
         use crate::db::queries;
 
         queries::insert_blocked_item(state.pool(), item_type, value).await?;
+        crate::blocking::blocklist::invalidate();
 
         Ok(ToolResult::success(format!(
             "Added '{}' to blocked {} list. It will be blocked during focus sessions.",
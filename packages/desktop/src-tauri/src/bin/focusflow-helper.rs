@@ -0,0 +1,35 @@
+// bin/focusflow-helper.rs - Privileged helper daemon entry point
+//
+// Usage: focusflow-helper --allow-uid <uid> [--socket <path>] [--hosts <path>]
+// Must run as root; see resources/helper for the systemd units.
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let config = match focusflow_lib::helper::server::HelperConfig::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("focusflow-helper: {}", e);
+            eprintln!("Usage: focusflow-helper --allow-uid <uid> [--socket <path>] [--hosts <path>]");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = focusflow_lib::helper::server::run(config).await {
+        tracing::error!("Helper stopped: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("focusflow-helper is only supported on Linux and macOS");
+    std::process::exit(1);
+}
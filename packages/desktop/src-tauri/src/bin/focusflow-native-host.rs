@@ -0,0 +1,67 @@
+// bin/focusflow-native-host.rs - Browser extension native messaging host
+//
+// Started by the browser; relays the extension's messages to the running app.
+// Usage: focusflow-native-host --install [--chromium-extension <id>]... [--firefox-extension <id>]...
+//        focusflow-native-host --uninstall
+
+use focusflow_lib::extension::manifest::{self, InstallCommand};
+
+const USAGE: &str = "Usage: focusflow-native-host --install [--chromium-extension <id>]... \
+                     [--firefox-extension <id>]... | --uninstall";
+
+fn install(command: InstallCommand) -> focusflow_lib::Result<()> {
+    let targets = manifest::default_targets()?;
+
+    match command {
+        InstallCommand::Install(ids) => {
+            let host_path = std::env::current_exe()?;
+            for result in manifest::install(&host_path, &ids, &targets)? {
+                match result.error {
+                    None => println!("{}: {}", result.browser, result.path),
+                    Some(error) => eprintln!("{}: {}", result.browser, error),
+                }
+            }
+        }
+        InstallCommand::Uninstall => {
+            for path in manifest::uninstall(&targets)? {
+                println!("Removed {}", path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() {
+    // Stdout carries the native messaging protocol
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .init();
+
+    if let Some(command) = InstallCommand::from_args(std::env::args().skip(1)) {
+        let result = command.and_then(install);
+        if let Err(e) = result {
+            eprintln!("focusflow-native-host: {}", e);
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+        return;
+    }
+
+    if let Err(e) = focusflow_lib::extension::host::run().await {
+        tracing::error!("Native host stopped: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("focusflow-native-host is only supported on Linux and macOS");
+    std::process::exit(1);
+}
@@ -0,0 +1,238 @@
+// blocking/allowlist.rs - Web allow-list ("whitelist-only") mode
+//
+// The web counterpart of Focus Time's allowed apps: while a focus session or
+// Focus Time block lists allowed websites, every other site is blocked. The
+// allow-list is expressed as website rules - a catch-all `*` plus one `!site`
+// exception per allowed site - so the DNS sinkhole and the frontend/extension
+// fallback enforce it with the same matcher as regular block rules. The hosts
+// file cannot express "everything except", so it keeps enforcing only the
+// explicit block rules.
+
+use crate::{
+    blocking::rules::{WebsiteMatcher, WebsiteRule},
+    state::AppState,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Domains that stay reachable in allow-list mode
+///
+/// Calendar sync, sign-in, clock sync and OS connectivity checks would
+/// otherwise break while the allow-list is active.
+pub const ALWAYS_ALLOWED_DOMAINS: &[&str] = &[
+    // Calendar providers and sign-in
+    "googleapis.com",
+    "accounts.google.com",
+    "login.microsoftonline.com",
+    "graph.microsoft.com",
+    // Time sync
+    "pool.ntp.org",
+    "time.apple.com",
+    "time.windows.com",
+    // Connectivity / captive portal checks
+    "connectivitycheck.gstatic.com",
+    "captive.apple.com",
+    "msftconnecttest.com",
+    "detectportal.firefox.com",
+    "nmcheck.gnome.org",
+    "connectivity-check.ubuntu.com",
+];
+
+/// Website rules that block everything except the allowed sites
+///
+/// Returns no rules when nothing is allowed, so an empty allow-list never
+/// turns into "block the whole web".
+pub fn allowlist_rules(allowed: &[String]) -> Vec<String> {
+    let mut rules = Vec::new();
+
+    for site in allowed {
+        match WebsiteRule::parse(site) {
+            Ok(rule) if !rule.exception => {
+                let exception = format!("!{}", rule);
+                if !rules.contains(&exception) {
+                    rules.push(exception);
+                }
+            }
+            _ => tracing::debug!("Ignoring invalid allowed site '{}'", site),
+        }
+    }
+
+    if rules.is_empty() {
+        return rules;
+    }
+
+    for domain in ALWAYS_ALLOWED_DOMAINS {
+        let exception = format!("!{}", domain);
+        if !rules.contains(&exception) {
+            rules.push(exception);
+        }
+    }
+    rules.insert(0, "*".to_string());
+
+    rules
+}
+
+/// Allowed websites of the active focus session and Focus Time block
+///
+/// Returns None when neither defines an allow-list.
+pub async fn active_allowed_sites(state: &AppState) -> Option<Vec<String>> {
+    let mut allowed: Vec<String> = state
+        .active_session
+        .read()
+        .await
+        .as_ref()
+        .map(|session| session.allowed_websites.clone())
+        .unwrap_or_default();
+
+    {
+        let focus_time = state.focus_time_state.read().await;
+        if focus_time.active {
+            for site in &focus_time.allowed_sites {
+                if !allowed.contains(site) {
+                    allowed.push(site.clone());
+                }
+            }
+        }
+    }
+
+    if allowed.is_empty() {
+        None
+    } else {
+        Some(allowed)
+    }
+}
+
+/// Website rules currently in force and whether web blocking is enabled
+///
+/// The blocking state's rules, plus the allow-list rules while an allow-list
+/// is active. An active allow-list enables web blocking on its own, since a
+/// Focus Time block does not otherwise enable blocking.
+pub async fn effective_website_rules(state: &AppState) -> (bool, Vec<String>) {
+    let (enabled, rules) = {
+        let blocking_state = state.blocking_state.read().await;
+        (blocking_state.enabled, blocking_state.blocked_websites.clone())
+    };
+    let allowed = active_allowed_sites(state).await;
+
+    with_allowlist(enabled, rules, allowed.as_deref())
+}
+
+fn with_allowlist(mut enabled: bool, mut rules: Vec<String>, allowed: Option<&[String]>) -> (bool, Vec<String>) {
+    if let Some(allowed) = allowed {
+        let allow_rules = allowlist_rules(allowed);
+        if !allow_rules.is_empty() {
+            if !enabled {
+                // Only the allow-list is in force
+                rules.clear();
+            }
+            enabled = true;
+            rules.extend(allow_rules);
+        }
+    }
+
+    (enabled, rules)
+}
+
+/// The effective website rules, compiled
+#[derive(Clone)]
+pub struct CompiledWebsiteRules {
+    pub enabled: bool,
+    pub matcher: Arc<WebsiteMatcher>,
+    /// Hash of the rules and `enabled`, changes whenever either does
+    pub version: u64,
+}
+
+/// What the compiled rules were built from
+#[derive(PartialEq)]
+struct CompiledRulesKey {
+    websites_version: u64,
+    enabled: bool,
+    allowed: Option<Vec<String>>,
+}
+
+/// Last compiled rules, reused until the blocking state or allow-list changes
+static COMPILED_RULES: Mutex<Option<(CompiledRulesKey, CompiledWebsiteRules)>> = Mutex::new(None);
+
+/// Like `effective_website_rules`, compiled once per change of the rules
+///
+/// For per-request checks (extension, blocked page) that would otherwise
+/// recompile every rule on each call.
+pub async fn compiled_website_rules(state: &AppState) -> CompiledWebsiteRules {
+    let allowed = active_allowed_sites(state).await;
+    let blocking_state = state.blocking_state.read().await;
+    let key = CompiledRulesKey {
+        websites_version: blocking_state.websites_version,
+        enabled: blocking_state.enabled,
+        allowed,
+    };
+
+    if let Ok(cached) = COMPILED_RULES.lock() {
+        if let Some((cached_key, rules)) = cached.as_ref() {
+            if *cached_key == key {
+                return rules.clone();
+            }
+        }
+    }
+
+    let (enabled, rules) = with_allowlist(
+        blocking_state.enabled,
+        blocking_state.blocked_websites.clone(),
+        key.allowed.as_deref(),
+    );
+    drop(blocking_state);
+
+    let mut hasher = DefaultHasher::new();
+    rules.hash(&mut hasher);
+    enabled.hash(&mut hasher);
+    let compiled = CompiledWebsiteRules {
+        enabled,
+        matcher: Arc::new(WebsiteMatcher::compile(&rules)),
+        version: hasher.finish(),
+    };
+
+    if let Ok(mut cached) = COMPILED_RULES.lock() {
+        *cached = Some((key, compiled.clone()));
+    }
+
+    compiled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist_rules() {
+        assert!(allowlist_rules(&[]).is_empty());
+        assert!(allowlist_rules(&["not a site".to_string()]).is_empty());
+
+        let rules = allowlist_rules(&["GitHub.com".to_string(), "docs.rs".to_string()]);
+        assert_eq!(rules[0], "*");
+        assert!(rules.contains(&"!github.com".to_string()));
+        assert!(rules.contains(&"!docs.rs".to_string()));
+        assert!(rules.contains(&"!accounts.google.com".to_string()));
+    }
+
+    #[test]
+    fn test_allowlist_blocks_everything_else() {
+        let mut rules = vec!["reddit.com".to_string()];
+        rules.extend(allowlist_rules(&["github.com".to_string(), "*.rust-lang.org".to_string()]));
+        let matcher = WebsiteMatcher::compile(&rules);
+
+        assert!(!matcher.check_domain("github.com").blocked);
+        assert!(!matcher.check_domain("api.github.com").blocked);
+        assert!(!matcher.check_domain("doc.rust-lang.org").blocked);
+        assert!(!matcher.check_domain("www.googleapis.com").blocked);
+
+        let result = matcher.check_domain("youtube.com");
+        assert!(result.blocked);
+        assert_eq!(result.match_type.as_deref(), Some("allowlist"));
+
+        // Explicit block rules still report their own match
+        assert_eq!(matcher.check_domain("reddit.com").match_type.as_deref(), Some("exact"));
+
+        // Allow-list mode never reaches the hosts file
+        assert_eq!(matcher.hosts_domains(), vec!["reddit.com".to_string()]);
+    }
+}
@@ -0,0 +1,505 @@
+// blocking/app_rules.rs - What blocked app rules match against
+//
+// Process names are trivial to change (rename or copy the binary) and say
+// nothing about apps started through an interpreter (Electron, Python, Java).
+// Besides the name, an app rule can therefore target the executable path, the
+// full command line, the SHA-256 of the executable, or a Linux desktop entry
+// (`.desktop` file ID), which is resolved to the program it launches.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use sysinfo::{ProcessRefreshKind, UpdateKind};
+
+/// Executables larger than this are never hashed
+const MAX_HASHED_EXE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Environment variables set by desktop launchers for the started app
+const DESKTOP_FILE_ENV_VARS: &[&str] = &["GIO_LAUNCHED_DESKTOP_FILE", "BAMF_DESKTOP_FILE_HINT"];
+
+/// What an app rule is compared with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppRuleKind {
+    /// Process name (the default)
+    #[default]
+    Name,
+    /// Full path of the executable
+    ExePath,
+    /// All arguments, joined with spaces
+    Cmdline,
+    /// SHA-256 of the executable, hex encoded
+    ExeHash,
+    /// Linux desktop entry ID, e.g. "org.mozilla.firefox"
+    DesktopId,
+}
+
+impl AppRuleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::ExePath => "exe_path",
+            Self::Cmdline => "cmdline",
+            Self::ExeHash => "exe_hash",
+            Self::DesktopId => "desktop_id",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "exe_path" => Ok(Self::ExePath),
+            "cmdline" => Ok(Self::Cmdline),
+            "exe_hash" => Ok(Self::ExeHash),
+            "desktop_id" => Ok(Self::DesktopId),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown app rule kind '{}' (expected name, exe_path, cmdline, exe_hash or desktop_id)",
+                other
+            ))),
+        }
+    }
+
+    /// Process details sysinfo has to load for this kind of rule
+    pub fn refresh_kind(&self, kind: ProcessRefreshKind) -> ProcessRefreshKind {
+        match self {
+            Self::Name => kind,
+            Self::ExePath | Self::ExeHash => kind.with_exe(UpdateKind::OnlyIfNotSet),
+            Self::Cmdline => kind.with_cmd(UpdateKind::OnlyIfNotSet),
+            Self::DesktopId => kind
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_environ(UpdateKind::OnlyIfNotSet),
+        }
+    }
+
+    /// Validate and canonicalize a rule value of this kind
+    ///
+    /// Patterns (`contains`/`regex` match types) are only checked for length.
+    pub fn normalize_value(&self, value: &str, match_type: &str) -> Result<String> {
+        let value = value.trim();
+
+        if value.is_empty() {
+            return Err(Error::InvalidInput("App rule cannot be empty".to_string()));
+        }
+        if value.contains('\0') {
+            return Err(Error::InvalidInput(
+                "App rule contains invalid characters".to_string(),
+            ));
+        }
+        if value.len() > 4096 {
+            return Err(Error::InvalidInput(
+                "App rule is too long (max 4096 characters)".to_string(),
+            ));
+        }
+
+        match self {
+            Self::Name | Self::Cmdline => Ok(value.to_string()),
+            Self::ExePath => {
+                if match_type == "exact" && !Path::new(value).is_absolute() {
+                    return Err(Error::InvalidInput(
+                        "Executable path must be absolute".to_string(),
+                    ));
+                }
+                Ok(value.to_string())
+            }
+            Self::ExeHash => {
+                let hash = value.to_lowercase();
+                if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(Error::InvalidInput(
+                        "Executable hash must be a hex encoded SHA-256".to_string(),
+                    ));
+                }
+                Ok(hash)
+            }
+            Self::DesktopId => {
+                let id = value.strip_suffix(".desktop").unwrap_or(value);
+                if id.is_empty()
+                    || !id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+                {
+                    return Err(Error::InvalidInput(format!("Invalid desktop entry ID: {}", value)));
+                }
+                Ok(id.to_string())
+            }
+        }
+    }
+}
+
+/// What an app rule can inspect about a running process
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessView<'a> {
+    pub name: &'a str,
+    pub exe: Option<&'a Path>,
+    pub cmd: &'a [OsString],
+    pub environ: &'a [OsString],
+}
+
+impl<'a> ProcessView<'a> {
+    pub fn new(name: &'a str, process: &'a sysinfo::Process) -> Self {
+        Self {
+            name,
+            exe: process.exe(),
+            cmd: process.cmd(),
+            environ: process.environ(),
+        }
+    }
+
+    /// Arguments joined with spaces
+    pub fn command_line(&self) -> String {
+        self.cmd
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Value of an environment variable of the process
+    pub fn env_var(&self, key: &str) -> Option<String> {
+        self.environ.iter().find_map(|entry| {
+            let entry = entry.to_string_lossy();
+            entry
+                .strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_string)
+        })
+    }
+}
+
+/// Identity of an executable's contents: its inode, size and mtime
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FileKey {
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+    #[cfg(not(unix))]
+    path: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileKey {
+    /// Key of a hashable executable, None for missing or oversized files
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        if !metadata.is_file() || metadata.len() > MAX_HASHED_EXE_BYTES {
+            return None;
+        }
+
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+
+        Some(Self {
+            #[cfg(unix)]
+            dev: metadata.dev(),
+            #[cfg(unix)]
+            ino: metadata.ino(),
+            #[cfg(not(unix))]
+            path: path.to_path_buf(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// SHA-256 of executables, keyed by (dev, inode, mtime)
+///
+/// Hashing reads the whole executable, so it happens in `load` on the
+/// blocking thread pool; matching only looks up hashes already loaded.
+/// Unreadable executables are remembered as None to avoid retrying them.
+#[derive(Debug, Default)]
+pub struct ExeHashCache {
+    hashes: HashMap<FileKey, Option<String>>,
+}
+
+impl ExeHashCache {
+    /// Hash the executables not hashed yet, off the async runtime
+    pub async fn load(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        let mut missing: Vec<(FileKey, PathBuf)> = Vec::new();
+        for path in paths {
+            if let Some(key) = FileKey::of(&path) {
+                if !self.hashes.contains_key(&key) && !missing.iter().any(|(k, _)| *k == key) {
+                    missing.push((key, path));
+                }
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+
+        let hashed = tokio::task::spawn_blocking(move || {
+            missing
+                .into_iter()
+                .map(|(key, path)| (key, hash_file(&path).ok()))
+                .collect::<Vec<_>>()
+        })
+        .await;
+
+        match hashed {
+            Ok(hashed) => self.hashes.extend(hashed),
+            Err(e) => tracing::warn!("Hashing executables failed: {}", e),
+        }
+    }
+
+    /// Hex encoded SHA-256 of an executable, None if not loaded or unreadable
+    pub fn get(&self, path: &Path) -> Option<String> {
+        self.hashes.get(&FileKey::of(path)?).cloned().flatten()
+    }
+}
+
+/// Hex encoded SHA-256 of a file
+pub fn hash_file(path: &Path) -> Result<String> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// A desktop entry resolved to the program it starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopEntry {
+    pub id: String,
+    /// Executable name or path from `TryExec`/`Exec`
+    pub program: Option<String>,
+}
+
+impl DesktopEntry {
+    /// Look up a desktop entry in the XDG application directories
+    ///
+    /// An entry that cannot be found still matches processes started by a
+    /// launcher that reports the desktop file (see `DESKTOP_FILE_ENV_VARS`).
+    pub fn resolve(id: &str) -> Self {
+        let program = desktop_entry_dirs()
+            .into_iter()
+            .map(|dir| dir.join(format!("{}.desktop", id)))
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| parse_desktop_program(&content));
+
+        Self {
+            id: id.to_string(),
+            program,
+        }
+    }
+
+    /// Check whether a process was started from this desktop entry
+    pub fn matches(&self, process: &ProcessView) -> bool {
+        let launched_from_entry = DESKTOP_FILE_ENV_VARS.iter().any(|key| {
+            process.env_var(key).is_some_and(|desktop_file| {
+                Path::new(&desktop_file).file_stem().is_some_and(|stem| stem == self.id.as_str())
+            })
+        });
+        if launched_from_entry || process.env_var("FLATPAK_ID").as_deref() == Some(self.id.as_str()) {
+            return true;
+        }
+
+        let Some(program) = &self.program else {
+            return false;
+        };
+        let program_name = file_name(program);
+
+        // Absolute programs must match exactly; bare names match the executable or argv[0]
+        if Path::new(program).is_absolute() && process.exe.is_some_and(|exe| exe == Path::new(program)) {
+            return true;
+        }
+        process.exe.is_some_and(|exe| file_name(&exe.to_string_lossy()) == program_name)
+            || process
+                .cmd
+                .first()
+                .is_some_and(|arg0| file_name(&arg0.to_string_lossy()) == program_name)
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// XDG directories holding `.desktop` files, most specific first
+fn desktop_entry_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(data_home) = dirs::data_dir() {
+        dirs.push(data_home.join("applications"));
+    }
+
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(data_dirs.split(':').map(|dir| Path::new(dir).join("applications")));
+
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share/applications"));
+    dirs
+}
+
+/// Program started by a desktop entry (`TryExec`, else the first word of `Exec`)
+fn parse_desktop_program(content: &str) -> Option<String> {
+    let mut in_main_group = false;
+    let mut try_exec = None;
+    let mut exec = None;
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_group {
+            continue;
+        }
+
+        if let Some(value) = line.strip_prefix("TryExec=") {
+            try_exec = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Exec=") {
+            exec = Some(value.trim().to_string());
+        }
+    }
+
+    try_exec.filter(|value| !value.is_empty()).or_else(|| {
+        let exec = exec?;
+        let mut words = exec.split_whitespace().filter(|word| !word.contains('='));
+        let program = words.next()?.trim_matches('"').to_string();
+
+        // `env VAR=x app` wrappers start the real program later
+        match file_name(&program) {
+            "env" => words.next().map(|word| word.trim_matches('"').to_string()),
+            _ => Some(program),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_rule_kind_parsing() {
+        for kind in [
+            AppRuleKind::Name,
+            AppRuleKind::ExePath,
+            AppRuleKind::Cmdline,
+            AppRuleKind::ExeHash,
+            AppRuleKind::DesktopId,
+        ] {
+            assert_eq!(AppRuleKind::parse(kind.as_str()).unwrap(), kind);
+        }
+        assert!(AppRuleKind::parse("window_title").is_err());
+    }
+
+    #[test]
+    fn test_normalize_values() {
+        assert!(AppRuleKind::ExePath.normalize_value("slack", "exact").is_err());
+        assert!(AppRuleKind::ExePath.normalize_value("slack", "contains").is_ok());
+        assert_eq!(
+            AppRuleKind::ExePath.normalize_value(" /usr/bin/slack ", "exact").unwrap(),
+            "/usr/bin/slack"
+        );
+
+        let hash = "AB".repeat(32);
+        assert_eq!(AppRuleKind::ExeHash.normalize_value(&hash, "exact").unwrap(), "ab".repeat(32));
+        assert!(AppRuleKind::ExeHash.normalize_value("abc", "exact").is_err());
+
+        assert_eq!(
+            AppRuleKind::DesktopId
+                .normalize_value("org.mozilla.firefox.desktop", "exact")
+                .unwrap(),
+            "org.mozilla.firefox"
+        );
+        assert!(AppRuleKind::DesktopId.normalize_value("../firefox", "exact").is_err());
+    }
+
+    #[test]
+    fn test_parse_desktop_program() {
+        let entry = "[Desktop Entry]\nName=Slack\nExec=/usr/bin/slack %U\n\n[Desktop Action new]\nExec=other\n";
+        assert_eq!(parse_desktop_program(entry), Some("/usr/bin/slack".to_string()));
+
+        let entry = "[Desktop Entry]\nTryExec=discord\nExec=env GDK_BACKEND=x11 /opt/discord/Discord\n";
+        assert_eq!(parse_desktop_program(entry), Some("discord".to_string()));
+
+        let entry = "[Desktop Entry]\nExec=env GDK_BACKEND=x11 /opt/discord/Discord --flag\n";
+        assert_eq!(parse_desktop_program(entry), Some("/opt/discord/Discord".to_string()));
+
+        assert_eq!(parse_desktop_program("[Desktop Entry]\nName=Empty\n"), None);
+    }
+
+    #[test]
+    fn test_desktop_entry_matches() {
+        let entry = DesktopEntry {
+            id: "com.slack.Slack".to_string(),
+            program: Some("/usr/bin/slack".to_string()),
+        };
+
+        let cmd = os(&["/usr/bin/slack", "--enable-features"]);
+        let view = ProcessView {
+            name: "slack",
+            exe: Some(Path::new("/usr/bin/slack")),
+            cmd: &cmd,
+            environ: &[],
+        };
+        assert!(entry.matches(&view));
+
+        // Launched from the desktop file, whatever the binary
+        let environ = os(&["GIO_LAUNCHED_DESKTOP_FILE=/usr/share/applications/com.slack.Slack.desktop"]);
+        let cmd = os(&["/tmp/renamed"]);
+        let view = ProcessView {
+            name: "renamed",
+            exe: Some(Path::new("/tmp/renamed")),
+            cmd: &cmd,
+            environ: &environ,
+        };
+        assert!(entry.matches(&view));
+
+        let cmd = os(&["/usr/bin/firefox"]);
+        let view = ProcessView {
+            name: "firefox",
+            exe: Some(Path::new("/usr/bin/firefox")),
+            cmd: &cmd,
+            environ: &[],
+        };
+        assert!(!entry.matches(&view));
+    }
+
+    #[tokio::test]
+    async fn test_exe_hash_cache() {
+        let path = std::env::temp_dir().join(format!("focusflow-hash-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"hello").unwrap();
+
+        let mut cache = ExeHashCache::default();
+        assert!(cache.get(&path).is_none());
+
+        cache.load([path.clone(), path.clone()]).await;
+        assert_eq!(
+            cache.get(&path).unwrap(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(cache.hashes.len(), 1);
+
+        // A changed file is a new key and needs hashing again
+        std::fs::write(&path, b"hello world").unwrap();
+        assert!(cache.get(&path).is_none());
+        cache.load([path.clone()]).await;
+        assert_eq!(
+            cache.get(&path).unwrap(),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(cache.get(&path).is_none());
+    }
+}
@@ -0,0 +1,511 @@
+// blocking/blocklist.rs - Effective blocklist resolution
+//
+// The effective blocklist is the union of manually blocked items, the items
+// of every enabled blocking category, items whose daily time budget is used
+// up (see `blocking::budget`) and the apps and websites of the focus profile
+// of an active blocking schedule (see `blocking::scheduler`). Category items
+// mix domains and app names: anything containing a dot (or a `/regex/` or
+// `!exception` rule) is treated as a website rule (see `blocking::rules`),
+// everything else as an app, which is expanded to its process names through
+// the focus time AppRegistry.
+
+use crate::{
+    blocking::{
+        app_rules::AppRuleKind, budget, enforcement::EnforcementPolicy, hosts, rules::WebsiteRule,
+        scheduler::ScheduleProfile,
+    },
+    db::queries::{self, BlockedItem, BlockingCategory},
+    focus_time::AppRegistry,
+    state::AppState,
+    Result,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::hash_map::{Entry, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bumped whenever blocked items or categories change
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Mark cached blocklists (e.g. the process monitor's matchers) as stale
+///
+/// Call after any change to blocked items or blocking categories.
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Current blocklist generation, for cache invalidation
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// Where a blocklist entry came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BlocklistSource {
+    /// Added directly to the block list (or by a focus session)
+    Manual,
+    /// Expanded from an enabled blocking category
+    #[serde(rename_all = "camelCase")]
+    Category {
+        category_id: i64,
+        category_name: String,
+        /// The category item this entry was resolved from (e.g. "discord")
+        item: String,
+    },
+    /// A daily time budget that is used up for today
+    #[serde(rename_all = "camelCase")]
+    Budget { budget_id: i64 },
+    /// The focus profile of an active blocking schedule
+    #[serde(rename_all = "camelCase")]
+    Schedule { schedule_id: i64, profile_id: i64 },
+}
+
+/// A single resolved blocklist entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlocklistEntry {
+    pub value: String,
+    pub match_type: String,
+    /// What happens when a matching app is detected (manual items only; categories kill)
+    pub enforcement: EnforcementPolicy,
+    /// What an app rule matches (always the process name for categories and websites)
+    pub rule_kind: AppRuleKind,
+    pub sources: Vec<BlocklistSource>,
+}
+
+impl BlocklistEntry {
+    /// Entry with the default enforcement, matching by name
+    fn new(value: &str, match_type: &str, source: BlocklistSource) -> Self {
+        Self {
+            value: value.to_string(),
+            match_type: match_type.to_string(),
+            enforcement: EnforcementPolicy::Kill,
+            rule_kind: AppRuleKind::Name,
+            sources: vec![source],
+        }
+    }
+}
+
+/// Websites and apps that blocking currently applies to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveBlocklist {
+    pub websites: Vec<BlocklistEntry>,
+    pub apps: Vec<BlocklistEntry>,
+}
+
+impl EffectiveBlocklist {
+    /// Load manual items, enabled categories, exhausted time budgets and the
+    /// active schedule's profile
+    pub async fn load(state: &AppState) -> Result<Self> {
+        let pool = state.pool();
+        let manual = queries::get_blocked_items(pool, None).await?;
+        let categories = queries::get_enabled_blocking_categories(pool).await?;
+        let registry = AppRegistry::new();
+
+        let mut blocklist = Self::resolve(&manual, &categories, &registry);
+        blocklist.add_exhausted_budgets(pool, &registry).await?;
+        if let Some(profile) = state.schedule_profile.read().await.as_ref() {
+            blocklist.add_schedule_profile(profile, &registry);
+        }
+
+        Ok(blocklist)
+    }
+
+    /// Merge manual items with enabled categories
+    pub fn resolve(
+        manual: &[BlockedItem],
+        categories: &[BlockingCategory],
+        registry: &AppRegistry,
+    ) -> Self {
+        let mut builder = Builder::default();
+
+        for item in manual {
+            match item.item_type.as_str() {
+                "website" => match WebsiteRule::from_blocked_item(&item.value, &item.match_type) {
+                    Ok(rule) => builder.add_website_rule(rule, BlocklistSource::Manual),
+                    Err(e) => tracing::warn!("Skipping invalid website rule '{}': {}", item.value, e),
+                },
+                "app" => builder.add_app(BlocklistEntry {
+                    value: item.value.trim().to_string(),
+                    match_type: item.match_type.clone(),
+                    enforcement: EnforcementPolicy::parse(&item.enforcement).unwrap_or_default(),
+                    rule_kind: AppRuleKind::parse(&item.rule_kind).unwrap_or_default(),
+                    sources: vec![BlocklistSource::Manual],
+                }),
+                _ => {}
+            }
+        }
+
+        for category in categories {
+            let items: Vec<String> = match serde_json::from_str(&category.items) {
+                Ok(items) => items,
+                Err(e) => {
+                    tracing::warn!("Skipping category '{}' with invalid items: {}", category.name, e);
+                    continue;
+                }
+            };
+
+            for item in items {
+                let item = item.trim();
+                if item.is_empty() {
+                    continue;
+                }
+
+                let source = BlocklistSource::Category {
+                    category_id: category.id,
+                    category_name: category.name.clone(),
+                    item: item.to_string(),
+                };
+
+                if item.contains('.') || item.starts_with(['/', '!']) {
+                    builder.add_website(item, source);
+                } else {
+                    for process in registry.expand_allowed_list(&[item.to_string()]) {
+                        builder.add_app(BlocklistEntry::new(process.trim(), "exact", source.clone()));
+                    }
+                }
+            }
+        }
+
+        builder.blocklist
+    }
+
+    /// Block items whose daily time budget is used up
+    async fn add_exhausted_budgets(&mut self, pool: &SqlitePool, registry: &AppRegistry) -> Result<()> {
+        let exhausted = budget::exhausted(pool).await?;
+        let mut builder = Builder::from_blocklist(std::mem::take(self));
+
+        for status in exhausted {
+            let source = BlocklistSource::Budget { budget_id: status.id };
+
+            match status.item_type.as_str() {
+                "website" => builder.add_website(&status.value, source),
+                "app" => {
+                    for process in registry.expand_allowed_list(std::slice::from_ref(&status.value)) {
+                        builder.add_app(BlocklistEntry::new(process.trim(), "exact", source.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        *self = builder.blocklist;
+        Ok(())
+    }
+
+    /// Block the apps and websites of an active schedule's profile
+    ///
+    /// They are only part of the blocklist while the schedule window is open.
+    fn add_schedule_profile(&mut self, profile: &ScheduleProfile, registry: &AppRegistry) {
+        let mut builder = Builder::from_blocklist(std::mem::take(self));
+        let source = BlocklistSource::Schedule {
+            schedule_id: profile.schedule_id,
+            profile_id: profile.profile_id,
+        };
+
+        for website in &profile.blocked_websites {
+            builder.add_website(website, source.clone());
+        }
+        for app in &profile.blocked_apps {
+            for process in registry.expand_allowed_list(std::slice::from_ref(app)) {
+                builder.add_app(BlocklistEntry::new(process.trim(), "exact", source.clone()));
+            }
+        }
+
+        *self = builder.blocklist;
+    }
+
+    /// Website rules, for the hosts file and DNS fallback
+    pub fn domains(&self) -> Vec<String> {
+        self.websites.iter().map(|entry| entry.value.clone()).collect()
+    }
+}
+
+/// Identifies identical rules; values and match types compare case-insensitively
+type EntryKey = (String, String, AppRuleKind);
+
+fn entry_key(entry: &BlocklistEntry) -> EntryKey {
+    (
+        entry.value.to_ascii_lowercase(),
+        entry.match_type.to_ascii_lowercase(),
+        entry.rule_kind,
+    )
+}
+
+/// Accumulates an EffectiveBlocklist, merging identical rules
+///
+/// Entries are indexed by rule, so imported categories with many thousands of
+/// domains still resolve in linear time.
+#[derive(Default)]
+struct Builder {
+    blocklist: EffectiveBlocklist,
+    websites: HashMap<EntryKey, usize>,
+    apps: HashMap<EntryKey, usize>,
+}
+
+impl Builder {
+    fn from_blocklist(blocklist: EffectiveBlocklist) -> Self {
+        fn index(entries: &[BlocklistEntry]) -> HashMap<EntryKey, usize> {
+            let mut index = HashMap::with_capacity(entries.len());
+            for (position, entry) in entries.iter().enumerate() {
+                index.entry(entry_key(entry)).or_insert(position);
+            }
+            index
+        }
+
+        Self {
+            websites: index(&blocklist.websites),
+            apps: index(&blocklist.apps),
+            blocklist,
+        }
+    }
+
+    fn add_website(&mut self, rule: &str, source: BlocklistSource) {
+        match WebsiteRule::parse(rule) {
+            Ok(rule) => self.add_website_rule(rule, source),
+            Err(e) => tracing::warn!("Skipping invalid website rule '{}': {}", rule, e),
+        }
+    }
+
+    fn add_website_rule(&mut self, rule: WebsiteRule, source: BlocklistSource) {
+        add_entry(
+            &mut self.blocklist.websites,
+            &mut self.websites,
+            BlocklistEntry::new(&rule.to_string(), rule.match_type(), source),
+        );
+    }
+
+    fn add_app(&mut self, entry: BlocklistEntry) {
+        add_entry(&mut self.blocklist.apps, &mut self.apps, entry);
+    }
+}
+
+/// Add an entry, merging sources into an existing identical rule
+///
+/// The first source keeps its enforcement policy, so manual items win over categories.
+fn add_entry(entries: &mut Vec<BlocklistEntry>, index: &mut HashMap<EntryKey, usize>, entry: BlocklistEntry) {
+    match index.entry(entry_key(&entry)) {
+        Entry::Occupied(position) => {
+            let existing = &mut entries[*position.get()];
+            for source in entry.sources {
+                if !existing.sources.contains(&source) {
+                    existing.sources.push(source);
+                }
+            }
+        }
+        Entry::Vacant(position) => {
+            position.insert(entries.len());
+            entries.push(entry);
+        }
+    }
+}
+
+/// Website rules of the effective blocklist plus a session's own websites
+pub async fn with_session_websites(state: &AppState, websites: &[String]) -> Result<Vec<String>> {
+    let blocklist = EffectiveBlocklist::load(state).await?;
+
+    let mut builder = Builder::from_blocklist(blocklist);
+    for website in websites {
+        builder.add_website(website, BlocklistSource::Manual);
+    }
+
+    Ok(builder.blocklist.domains())
+}
+
+/// Re-apply website blocking after the blocklist or categories changed
+///
+/// Also invalidates cached blocklists; the rest is a no-op while blocking is disabled.
+pub async fn refresh_website_blocking(state: &AppState) -> Result<()> {
+    invalidate();
+
+    let blocking_enabled = {
+        let blocking_state = state.blocking_state.read().await;
+        blocking_state.enabled
+    };
+
+    if !blocking_enabled {
+        return Ok(());
+    }
+
+    let domains = EffectiveBlocklist::load(state).await?.domains();
+
+    // Update in-memory state for DNS fallback
+    {
+        let mut blocking_state = state.blocking_state.write().await;
+        blocking_state.update_blocked_websites(domains.clone());
+    }
+
+    // Try to update hosts file (may fail without privileges)
+    if let Err(e) = hosts::update_hosts_file(state, &domains).await {
+        tracing::warn!("Hosts file update failed: {}, DNS fallback active", e);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(item_type: &str, value: &str, match_type: &str) -> BlockedItem {
+        BlockedItem {
+            id: 0,
+            item_type: item_type.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            match_type: match_type.to_string(),
+            enforcement: "kill".to_string(),
+            rule_kind: "name".to_string(),
+        }
+    }
+
+    fn category(id: i64, name: &str, items: &str) -> BlockingCategory {
+        BlockingCategory {
+            id,
+            name: name.to_string(),
+            description: None,
+            items: items.to_string(),
+            enabled: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_manual_items_only() {
+        let manual = vec![
+            item("website", "Reddit.com", "exact"),
+            item("app", "slack", "contains"),
+        ];
+
+        let blocklist = EffectiveBlocklist::resolve(&manual, &[], &AppRegistry::new());
+
+        assert_eq!(blocklist.domains(), vec!["reddit.com".to_string()]);
+        assert_eq!(blocklist.apps.len(), 1);
+        assert_eq!(blocklist.apps[0].match_type, "contains");
+        assert_eq!(blocklist.apps[0].sources, vec![BlocklistSource::Manual]);
+    }
+
+    #[test]
+    fn test_category_splits_websites_and_apps() {
+        let categories = vec![category(3, "Gaming", r#"["twitch.tv","discord"]"#)];
+
+        let blocklist = EffectiveBlocklist::resolve(&[], &categories, &AppRegistry::new());
+
+        assert_eq!(blocklist.domains(), vec!["twitch.tv".to_string()]);
+
+        // Apps are expanded to their process names
+        let apps: Vec<&str> = blocklist.apps.iter().map(|a| a.value.as_str()).collect();
+        assert!(apps.contains(&"discord.exe"));
+        assert!(blocklist.apps.iter().all(|a| a.match_type == "exact"));
+        assert!(blocklist.apps.iter().all(|a| a.sources
+            == vec![BlocklistSource::Category {
+                category_id: 3,
+                category_name: "Gaming".to_string(),
+                item: "discord".to_string(),
+            }]));
+    }
+
+    #[test]
+    fn test_unknown_category_app_kept_as_is() {
+        let categories = vec![category(3, "Gaming", r#"["epicgames"]"#)];
+
+        let blocklist = EffectiveBlocklist::resolve(&[], &categories, &AppRegistry::new());
+
+        assert_eq!(blocklist.apps.len(), 1);
+        assert_eq!(blocklist.apps[0].value, "epicgames");
+    }
+
+    #[test]
+    fn test_duplicate_sources_are_merged() {
+        let manual = vec![item("website", "reddit.com", "exact")];
+        let categories = vec![
+            category(1, "Social Media", r#"["reddit.com","twitter.com"]"#),
+            category(9, "Forums", r#"["REDDIT.com"]"#),
+        ];
+
+        let blocklist = EffectiveBlocklist::resolve(&manual, &categories, &AppRegistry::new());
+
+        assert_eq!(blocklist.websites.len(), 2);
+        let reddit = &blocklist.websites[0];
+        assert_eq!(reddit.value, "reddit.com");
+        assert_eq!(reddit.sources.len(), 3);
+        assert_eq!(reddit.sources[0], BlocklistSource::Manual);
+    }
+
+    #[test]
+    fn test_schedule_profile_items_are_added() {
+        let manual = vec![item("website", "reddit.com", "exact")];
+        let mut blocklist = EffectiveBlocklist::resolve(&manual, &[], &AppRegistry::new());
+        let profile = ScheduleProfile {
+            schedule_id: 2,
+            profile_id: 5,
+            blocked_apps: vec!["epicgames".to_string()],
+            blocked_websites: vec!["Reddit.com".to_string(), "news.ycombinator.com".to_string()],
+            ..Default::default()
+        };
+
+        blocklist.add_schedule_profile(&profile, &AppRegistry::new());
+
+        let source = BlocklistSource::Schedule { schedule_id: 2, profile_id: 5 };
+        assert_eq!(blocklist.domains(), vec!["reddit.com".to_string(), "news.ycombinator.com".to_string()]);
+        assert_eq!(blocklist.websites[0].sources, vec![BlocklistSource::Manual, source.clone()]);
+        assert_eq!(blocklist.apps.len(), 1);
+        assert_eq!(blocklist.apps[0].sources, vec![source]);
+    }
+
+    #[test]
+    fn test_website_rules_are_canonicalized() {
+        let manual = vec![
+            item("website", "^news\\.", "regex"),
+            item("website", "https://YouTube.com/shorts/", "exact"),
+        ];
+        let categories = vec![category(4, "Video", r#"["*.twitch.tv","!music.youtube.com"]"#)];
+
+        let blocklist = EffectiveBlocklist::resolve(&manual, &categories, &AppRegistry::new());
+
+        assert_eq!(
+            blocklist.domains(),
+            vec![
+                "/^news\\./".to_string(),
+                "youtube.com/shorts".to_string(),
+                "*.twitch.tv".to_string(),
+                "!music.youtube.com".to_string(),
+            ]
+        );
+        let match_types: Vec<&str> = blocklist.websites.iter().map(|w| w.match_type.as_str()).collect();
+        assert_eq!(match_types, vec!["regex", "path", "wildcard", "exact"]);
+    }
+
+    #[test]
+    fn test_manual_enforcement_wins_over_category() {
+        let mut epic = item("app", "epicgames", "exact");
+        epic.enforcement = "delay".to_string();
+        let categories = vec![category(3, "Gaming", r#"["epicgames","lutris"]"#)];
+
+        let blocklist = EffectiveBlocklist::resolve(&[epic], &categories, &AppRegistry::new());
+
+        let epic = blocklist.apps.iter().find(|a| a.value == "epicgames").unwrap();
+        assert_eq!(epic.enforcement, EnforcementPolicy::Delay);
+        assert_eq!(epic.sources.len(), 2);
+        assert!(blocklist
+            .apps
+            .iter()
+            .filter(|a| a.value != "epicgames")
+            .all(|a| a.enforcement == EnforcementPolicy::Kill));
+    }
+
+    #[test]
+    fn test_invalid_category_json_is_skipped() {
+        let categories = vec![
+            category(1, "Broken", "not json"),
+            category(2, "News", r#"["cnn.com"]"#),
+        ];
+
+        let blocklist = EffectiveBlocklist::resolve(&[], &categories, &AppRegistry::new());
+
+        assert_eq!(blocklist.domains(), vec!["cnn.com".to_string()]);
+    }
+}
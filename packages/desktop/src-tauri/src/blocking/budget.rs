@@ -0,0 +1,726 @@
+// blocking/budget.rs - Daily time budgets for apps and websites
+//
+// A budget allows an app or website for a number of minutes per day ("30
+// minutes of Slack on workdays") instead of blocking it outright. App time is
+// sampled by the process monitor (time a matching app owns the focused window,
+// or is running where focus cannot be detected) and website time is estimated from lookups forwarded by the DNS sinkhole. Once a
+// budget is used up the item joins the effective blocklist until the next
+// budget day, which starts at a configurable local time rather than midnight.
+// While blocking is off, exhausted apps are closed by the tracker itself and
+// exhausted websites are written to the hosts file on their own.
+
+use crate::{
+    blocking::{
+        blocklist, hosts, process,
+        rules::{RuleTarget, WebsiteMatcher, WebsiteRule},
+        scheduler,
+    },
+    db::queries::{self, TimeBudget, TimeBudgetUsage},
+    focus_time::AppRegistry,
+    state::AppState,
+    system::foreground,
+    Error, Result,
+};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::Emitter;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+
+/// user_settings key of the persisted budget settings
+pub const BUDGET_SETTINGS_KEY: &str = "time_budgets";
+
+/// How often focused apps are sampled and website usage is saved
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Longer gaps between samples mean the machine slept; they are not counted
+const MAX_SAMPLE_GAP: Duration = Duration::from_secs(60);
+
+/// A website counts as in use for this long after each lookup
+const WEBSITE_ACTIVITY_WINDOW: Duration = Duration::from_secs(60);
+
+/// Longest daily limit (a full day)
+pub const MAX_DAILY_LIMIT_MINUTES: i64 = 24 * 60;
+
+/// Budget settings shared by all budgets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BudgetSettings {
+    /// Local time ("HH:MM") at which budgets reset
+    pub day_starts_at: String,
+    /// Percentages of the limit at which a warning is shown
+    pub warning_thresholds: Vec<u8>,
+}
+
+impl Default for BudgetSettings {
+    fn default() -> Self {
+        Self {
+            day_starts_at: "00:00".to_string(),
+            warning_thresholds: vec![50, 80, 95],
+        }
+    }
+}
+
+impl BudgetSettings {
+    pub fn validate(&self) -> Result<()> {
+        if scheduler::parse_minutes(&self.day_starts_at).is_none() {
+            return Err(Error::Validation(format!(
+                "Invalid day boundary '{}' (expected HH:MM)",
+                self.day_starts_at
+            )));
+        }
+        if self.warning_thresholds.len() > 5 {
+            return Err(Error::Validation("At most 5 warning thresholds are supported".to_string()));
+        }
+        if self.warning_thresholds.iter().any(|t| !(1..=99).contains(t)) {
+            return Err(Error::Validation(
+                "Warning thresholds must be between 1 and 99 percent".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Minutes after midnight at which a budget day starts
+    fn day_start_minute(&self) -> u32 {
+        scheduler::parse_minutes(&self.day_starts_at).unwrap_or(0)
+    }
+
+    /// The budget day a local time belongs to
+    pub fn budget_day(&self, at: NaiveDateTime) -> NaiveDate {
+        budget_day(at, self.day_start_minute())
+    }
+}
+
+/// The budget day of a local time: before the boundary it is still the previous day
+pub fn budget_day(at: NaiveDateTime, day_start_minute: u32) -> NaiveDate {
+    let minute = at.hour() * 60 + at.minute();
+    if minute < day_start_minute {
+        at.date().pred_opt().unwrap_or(at.date())
+    } else {
+        at.date()
+    }
+}
+
+/// Load the persisted budget settings
+pub async fn load_settings(pool: &SqlitePool) -> Result<BudgetSettings> {
+    let stored = queries::get_user_setting(pool, BUDGET_SETTINGS_KEY).await?;
+
+    Ok(match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid time budget settings: {}", e);
+            BudgetSettings::default()
+        }),
+        None => BudgetSettings::default(),
+    })
+}
+
+/// Persist the budget settings
+pub async fn save_settings(pool: &SqlitePool, settings: &BudgetSettings) -> Result<()> {
+    settings.validate()?;
+    let json = serde_json::to_string(settings)?;
+    queries::set_user_setting(pool, BUDGET_SETTINGS_KEY, &json).await
+}
+
+/// Parse a stored weekday list, falling back to every day
+pub fn parse_days(days_of_week: &str) -> Vec<u32> {
+    match serde_json::from_str::<Vec<u32>>(days_of_week) {
+        Ok(days) => days.into_iter().filter(|d| *d <= 6).collect(),
+        Err(e) => {
+            tracing::warn!("Invalid budget days '{}': {}", days_of_week, e);
+            (0..=6).collect()
+        }
+    }
+}
+
+/// Canonical stored value of a budgeted item
+///
+/// Apps are kept as given (they are expanded like blocking categories);
+/// websites must be a plain domain, which covers its subdomains.
+pub fn normalize_value(item_type: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() || value.len() > 255 {
+        return Err(Error::InvalidInput(
+            "Budget item must be between 1 and 255 characters".to_string(),
+        ));
+    }
+
+    match item_type {
+        "app" => Ok(value.to_string()),
+        "website" => {
+            let rule = WebsiteRule::parse(value)?;
+            match rule.target {
+                RuleTarget::Domain(_) if !rule.exception => Ok(rule.to_string()),
+                _ => Err(Error::InvalidInput(format!(
+                    "Website budgets need a plain domain like example.com, got '{}'",
+                    value
+                ))),
+            }
+        }
+        other => Err(Error::InvalidInput(format!(
+            "Unknown budget item type '{}' (expected app or website)",
+            other
+        ))),
+    }
+}
+
+/// A budget with its usage on the current budget day
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub id: i64,
+    pub item_type: String,
+    pub value: String,
+    pub daily_limit_minutes: i64,
+    pub days_of_week: Vec<u32>,
+    pub enabled: bool,
+    /// Whether the budget applies on the current budget day
+    pub applies_today: bool,
+    /// Current budget day (YYYY-MM-DD)
+    pub day: String,
+    pub used_seconds: i64,
+    pub remaining_seconds: i64,
+    pub exhausted: bool,
+    pub exhausted_at: Option<DateTime<Utc>>,
+}
+
+impl BudgetStatus {
+    fn new(budget: &TimeBudget, usage: Option<&TimeBudgetUsage>, day: NaiveDate) -> Self {
+        let days_of_week = parse_days(&budget.days_of_week);
+        let applies_today = budget.enabled && days_of_week.contains(&day.weekday().num_days_from_sunday());
+        let used_seconds = usage.map(|u| u.used_seconds).unwrap_or(0);
+        let limit_seconds = budget.daily_limit_minutes * 60;
+
+        Self {
+            id: budget.id,
+            item_type: budget.item_type.clone(),
+            value: budget.value.clone(),
+            daily_limit_minutes: budget.daily_limit_minutes,
+            days_of_week,
+            enabled: budget.enabled,
+            applies_today,
+            day: day.to_string(),
+            used_seconds,
+            remaining_seconds: (limit_seconds - used_seconds).max(0),
+            exhausted: applies_today && used_seconds >= limit_seconds,
+            exhausted_at: usage.and_then(|u| u.exhausted_at),
+        }
+    }
+}
+
+/// Every budget with its usage on the current budget day
+pub async fn get_statuses(pool: &SqlitePool) -> Result<Vec<BudgetStatus>> {
+    let settings = load_settings(pool).await?;
+    let day = settings.budget_day(Local::now().naive_local());
+    statuses_for_day(pool, day).await
+}
+
+async fn statuses_for_day(pool: &SqlitePool, day: NaiveDate) -> Result<Vec<BudgetStatus>> {
+    let budgets = queries::get_time_budgets(pool).await?;
+    let usage: HashMap<i64, TimeBudgetUsage> = queries::get_time_budget_usage(pool, &day.to_string())
+        .await?
+        .into_iter()
+        .map(|u| (u.budget_id, u))
+        .collect();
+
+    Ok(budgets
+        .iter()
+        .map(|budget| BudgetStatus::new(budget, usage.get(&budget.id), day))
+        .collect())
+}
+
+/// Budgets used up for the current budget day
+pub async fn exhausted(pool: &SqlitePool) -> Result<Vec<BudgetStatus>> {
+    Ok(get_statuses(pool).await?.into_iter().filter(|s| s.exhausted).collect())
+}
+
+/// Highest warning threshold newly reached by `used_seconds`
+fn next_warning(thresholds: &[u8], warned_percent: i64, used_seconds: i64, limit_seconds: i64) -> Option<u8> {
+    thresholds
+        .iter()
+        .copied()
+        .filter(|t| i64::from(*t) > warned_percent && used_seconds * 100 >= i64::from(*t) * limit_seconds)
+        .max()
+}
+
+/// Add used time to a budget, warning or blocking when thresholds are crossed
+///
+/// Returns true if this use exhausted the budget.
+async fn add_usage(
+    state: &AppState,
+    settings: &BudgetSettings,
+    status: &BudgetStatus,
+    seconds: i64,
+) -> Result<bool> {
+    let usage = queries::add_time_budget_usage(state.pool(), status.id, &status.day, seconds).await?;
+    let limit_seconds = status.daily_limit_minutes * 60;
+
+    if usage.used_seconds >= limit_seconds {
+        if usage.exhausted_at.is_some() {
+            return Ok(false);
+        }
+
+        queries::update_time_budget_status(state.pool(), status.id, &status.day, 100, Some(Utc::now())).await?;
+        blocklist::invalidate();
+
+        tracing::info!("Time budget for {} {} is used up", status.item_type, status.value);
+
+        emit_budget_event(state, "budget-exhausted", status, 100, 0);
+        process::notify(
+            state,
+            "Time Budget Used Up",
+            &format!(
+                "Your {} minutes of {} for today are used up. It is blocked until tomorrow.",
+                status.daily_limit_minutes, status.value
+            ),
+        );
+
+        return Ok(true);
+    }
+
+    if let Some(percent) = next_warning(&settings.warning_thresholds, usage.warned_percent, usage.used_seconds, limit_seconds) {
+        queries::update_time_budget_status(state.pool(), status.id, &status.day, i64::from(percent), None).await?;
+
+        let remaining_seconds = limit_seconds - usage.used_seconds;
+        emit_budget_event(state, "budget-warning", status, percent, remaining_seconds);
+        process::notify(
+            state,
+            "Time Budget Running Low",
+            &format!(
+                "{} minutes of {} left for today.",
+                (remaining_seconds + 59) / 60,
+                status.value
+            ),
+        );
+    }
+
+    Ok(false)
+}
+
+fn emit_budget_event(state: &AppState, event: &str, status: &BudgetStatus, percent: u8, remaining_seconds: i64) {
+    if let Err(e) = state.app_handle.emit(
+        event,
+        serde_json::json!({
+            "budgetId": status.id,
+            "itemType": status.item_type,
+            "value": status.value,
+            "percentUsed": percent,
+            "remainingSeconds": remaining_seconds,
+        }),
+    ) {
+        tracing::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+/// Samples focused apps against their budgets for the process monitor
+///
+/// Also keeps exhausted budgets enforced while blocking is off: exhausted apps
+/// are closed and exhausted websites are written to the hosts file.
+#[derive(Default)]
+pub struct BudgetTracker {
+    system: System,
+    last_sample: Option<Instant>,
+    /// Processes already scheduled to be closed
+    closing: HashSet<u32>,
+    /// Exhausted budgets last seen, to invalidate cached blocklists on change
+    last_exhausted: Option<Vec<i64>>,
+    /// Blocking state and exhausted websites last applied
+    applied_websites: Option<(bool, Vec<String>)>,
+    /// Settings and app registry, reloaded when the blocklist generation changes
+    cached: Option<CachedConfig>,
+    /// Whether the fallback to running time was logged
+    focus_fallback_logged: bool,
+}
+
+struct CachedConfig {
+    generation: u64,
+    settings: BudgetSettings,
+    registry: AppRegistry,
+}
+
+impl BudgetTracker {
+    /// Sample once every `SAMPLE_INTERVAL`; cheap to call on every loop iteration
+    pub async fn tick(&mut self, state: &AppState) {
+        let now = Instant::now();
+        if self.last_sample.is_some_and(|last| now.duration_since(last) < SAMPLE_INTERVAL) {
+            return;
+        }
+
+        let elapsed = self
+            .last_sample
+            .map(|last| now.duration_since(last))
+            .filter(|gap| *gap <= MAX_SAMPLE_GAP)
+            .unwrap_or_default();
+        self.last_sample = Some(now);
+
+        if let Err(e) = self.sample(state, elapsed.as_secs() as i64).await {
+            tracing::error!("Failed to update time budgets: {}", e);
+        }
+    }
+
+    /// Budget settings and app registry, cached until the blocklist changes
+    ///
+    /// Saving budget settings invalidates the blocklist, so edits are picked up.
+    async fn config(&mut self, pool: &SqlitePool) -> Result<&CachedConfig> {
+        let generation = blocklist::generation();
+        if self.cached.as_ref().map(|cached| cached.generation) != Some(generation) {
+            self.cached = Some(CachedConfig {
+                generation,
+                settings: load_settings(pool).await?,
+                registry: AppRegistry::new(),
+            });
+        }
+        Ok(self.cached.as_ref().expect("config was just loaded"))
+    }
+
+    /// Normalized name of the process owning the focused window
+    ///
+    /// Outer None when focus cannot be detected here.
+    async fn focused_app(&mut self) -> Option<Option<String>> {
+        match foreground::foreground_pid().await {
+            Ok(pid) => Some(pid.and_then(|pid| {
+                self.system.refresh_processes_specifics(
+                    ProcessesToUpdate::Some(&[sysinfo::Pid::from_u32(pid)]),
+                    false,
+                    ProcessRefreshKind::nothing(),
+                );
+                self.system
+                    .process(sysinfo::Pid::from_u32(pid))
+                    .map(|process| process::normalize_process_name(&process.name().to_string_lossy()))
+            })),
+            Err(e) => {
+                if !std::mem::replace(&mut self.focus_fallback_logged, true) {
+                    tracing::warn!("Counting app budgets by running time: {}", e);
+                }
+                None
+            }
+        }
+    }
+
+    async fn sample(&mut self, state: &AppState, elapsed_seconds: i64) -> Result<()> {
+        let config = self.config(state.pool()).await?;
+        let settings = config.settings.clone();
+        let day = settings.budget_day(Local::now().naive_local());
+        let mut statuses = statuses_for_day(state.pool(), day).await?;
+
+        let blocking_enabled = {
+            let blocking_state = state.blocking_state.read().await;
+            blocking_state.enabled
+        };
+
+        let app_budgets: Vec<(usize, HashSet<String>)> = {
+            let registry = &config.registry;
+            statuses
+                .iter()
+                .enumerate()
+                .filter(|(_, status)| status.applies_today && status.item_type == "app")
+                .map(|(index, status)| {
+                    let names = registry
+                        .expand_allowed_list(std::slice::from_ref(&status.value))
+                        .iter()
+                        .map(|name| process::normalize_process_name(name))
+                        .collect();
+                    (index, names)
+                })
+                .collect()
+        };
+
+        let focused = if app_budgets.is_empty() {
+            None
+        } else {
+            self.system.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                ProcessRefreshKind::nothing().with_user(UpdateKind::OnlyIfNotSet),
+            );
+            self.focused_app().await
+        };
+
+        for (index, names) in app_budgets {
+            let running: Vec<(sysinfo::Pid, String)> = self
+                .system
+                .processes()
+                .iter()
+                .map(|(pid, process)| (*pid, process.name().to_string_lossy().to_string()))
+                .filter(|(_, name)| names.contains(&process::normalize_process_name(name)))
+                .collect();
+
+            if running.is_empty() {
+                continue;
+            }
+
+            // Only time in front counts, unless focus cannot be detected
+            let in_use = match &focused {
+                Some(focused) => focused.as_ref().is_some_and(|name| names.contains(name)),
+                None => true,
+            };
+
+            let status = &mut statuses[index];
+            if in_use
+                && !status.exhausted
+                && elapsed_seconds > 0
+                && add_usage(state, &settings, status, elapsed_seconds).await?
+            {
+                status.exhausted = true;
+            }
+
+            // With blocking on, exhausted apps are part of the monitor's blocklist
+            if status.exhausted && !blocking_enabled {
+                for (pid, name) in running {
+                    let terminable = self.system.process(pid).is_some_and(process::is_terminable);
+                    if terminable && self.closing.insert(pid.as_u32()) {
+                        process::close_after_grace_period(state, pid, &name);
+                    }
+                }
+            }
+        }
+
+        let system = &self.system;
+        self.closing.retain(|pid| system.process(sysinfo::Pid::from_u32(*pid)).is_some());
+
+        let exhausted_ids: Vec<i64> = statuses.iter().filter(|s| s.exhausted).map(|s| s.id).collect();
+        if self.last_exhausted.as_ref() != Some(&exhausted_ids) {
+            // Covers new exhaustion, budget edits and the day boundary
+            blocklist::invalidate();
+            self.last_exhausted = Some(exhausted_ids);
+        }
+
+        let exhausted_websites: Vec<String> = statuses
+            .iter()
+            .filter(|s| s.exhausted && s.item_type == "website")
+            .map(|s| s.value.clone())
+            .collect();
+        self.apply_websites(state, blocking_enabled, exhausted_websites).await
+    }
+
+    /// Keep exhausted websites blocked, whether or not blocking is on
+    async fn apply_websites(&mut self, state: &AppState, blocking_enabled: bool, websites: Vec<String>) -> Result<()> {
+        let current = (blocking_enabled, websites);
+        if self.applied_websites.as_ref() == Some(&current) {
+            return Ok(());
+        }
+
+        let previous = self.applied_websites.replace(current.clone());
+        let (blocking_enabled, websites) = current;
+
+        if blocking_enabled {
+            // Exhausted websites are part of the effective blocklist
+            let previously_blocked = previous.as_ref().is_some_and(|(_, websites)| !websites.is_empty());
+            if !websites.is_empty() || previously_blocked {
+                blocklist::refresh_website_blocking(state).await?;
+            }
+        } else if !websites.is_empty() {
+            if let Err(e) = hosts::update_hosts_file(state, &websites).await {
+                tracing::warn!("Failed to block exhausted websites in the hosts file: {}", e);
+            }
+        } else if previous.is_some_and(|(enabled, websites)| !enabled && !websites.is_empty()) {
+            if let Err(e) = hosts::clear_hosts_file(state).await {
+                tracing::warn!("Failed to clear hosts file: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Estimated browsing time per website budget, from DNS lookups
+///
+/// Each lookup marks the site as in use for `WEBSITE_ACTIVITY_WINDOW`;
+/// overlapping windows are only counted once.
+#[derive(Debug, Default)]
+struct WebsiteActivity {
+    /// End of the current activity window per budget
+    active_until: HashMap<i64, Instant>,
+}
+
+impl WebsiteActivity {
+    /// Record a lookup, returning the newly covered time
+    fn hit(&mut self, budget_id: i64, now: Instant) -> Duration {
+        let until = now + WEBSITE_ACTIVITY_WINDOW;
+        let covered = self.active_until.get(&budget_id).copied().unwrap_or(now).max(now);
+        self.active_until.insert(budget_id, until);
+        until.saturating_duration_since(covered)
+    }
+}
+
+/// Count names forwarded by the DNS sinkhole against website budgets
+///
+/// Usage is saved every `SAMPLE_INTERVAL`. Runs until the sinkhole stops.
+pub async fn track_website_queries(state: AppState, mut receiver: mpsc::UnboundedReceiver<String>) {
+    let mut activity = WebsiteActivity::default();
+    let mut budgets: Vec<(BudgetStatus, WebsiteMatcher)> = Vec::new();
+    let mut pending: HashMap<i64, Duration> = HashMap::new();
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            name = receiver.recv() => {
+                let Some(name) = name else {
+                    return;
+                };
+                let now = Instant::now();
+                for (status, matcher) in &budgets {
+                    if matcher.check_domain(&name).blocked {
+                        *pending.entry(status.id).or_default() += activity.hit(status.id, now);
+                    }
+                }
+            }
+            _ = interval.tick() => {
+                let settings = match load_settings(state.pool()).await {
+                    Ok(settings) => settings,
+                    Err(e) => {
+                        tracing::error!("Failed to load time budget settings: {}", e);
+                        continue;
+                    }
+                };
+
+                for (status, _) in &budgets {
+                    let Some(seconds) = pending.remove(&status.id).map(|d| d.as_secs() as i64) else {
+                        continue;
+                    };
+                    if seconds > 0 && !status.exhausted {
+                        if let Err(e) = add_usage(&state, &settings, status, seconds).await {
+                            tracing::error!("Failed to record website budget usage: {}", e);
+                        }
+                    }
+                }
+                pending.clear();
+
+                // Reload so new budgets, edits and the day boundary are picked up
+                let day = settings.budget_day(Local::now().naive_local());
+                match statuses_for_day(state.pool(), day).await {
+                    Ok(statuses) => {
+                        budgets = statuses
+                            .into_iter()
+                            .filter(|s| s.applies_today && s.item_type == "website")
+                            .map(|s| {
+                                let matcher = WebsiteMatcher::compile(&[s.value.as_str()]);
+                                (s, matcher)
+                            })
+                            .collect();
+                    }
+                    Err(e) => tracing::error!("Failed to load time budgets: {}", e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn budget(limit_minutes: i64, days: &str) -> TimeBudget {
+        TimeBudget {
+            id: 1,
+            item_type: "app".to_string(),
+            value: "slack".to_string(),
+            daily_limit_minutes: limit_minutes,
+            days_of_week: days.to_string(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_budget_day_boundary() {
+        assert_eq!(budget_day(at(4, 0, 30), 0), at(4, 0, 0).date());
+
+        // With budgets resetting at 04:00, 03:59 still belongs to the previous day
+        let settings = BudgetSettings {
+            day_starts_at: "04:00".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(settings.budget_day(at(4, 3, 59)), at(3, 0, 0).date());
+        assert_eq!(settings.budget_day(at(4, 4, 0)), at(4, 0, 0).date());
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(BudgetSettings::default().validate().is_ok());
+
+        let invalid_boundary = BudgetSettings {
+            day_starts_at: "25:00".to_string(),
+            ..Default::default()
+        };
+        assert!(invalid_boundary.validate().is_err());
+
+        let invalid_threshold = BudgetSettings {
+            warning_thresholds: vec![50, 100],
+            ..Default::default()
+        };
+        assert!(invalid_threshold.validate().is_err());
+    }
+
+    #[test]
+    fn test_normalize_value() {
+        assert_eq!(normalize_value("app", "  Slack ").unwrap(), "Slack");
+        assert_eq!(normalize_value("website", "https://Reddit.com/").unwrap(), "reddit.com");
+        assert!(normalize_value("website", "*.reddit.com").is_err());
+        assert!(normalize_value("website", "/reddit/").is_err());
+        assert!(normalize_value("app", "  ").is_err());
+        assert!(normalize_value("folder", "x").is_err());
+    }
+
+    #[test]
+    fn test_status_remaining_and_days() {
+        // 2025-03-04 is a Tuesday
+        let day = at(4, 12, 0).date();
+        let usage = TimeBudgetUsage {
+            budget_id: 1,
+            day: day.to_string(),
+            used_seconds: 20 * 60,
+            warned_percent: 50,
+            exhausted_at: None,
+        };
+
+        let status = BudgetStatus::new(&budget(30, "[1,2,3,4,5]"), Some(&usage), day);
+        assert!(status.applies_today);
+        assert_eq!(status.remaining_seconds, 10 * 60);
+        assert!(!status.exhausted);
+
+        let status = BudgetStatus::new(&budget(15, "[1,2,3,4,5]"), Some(&usage), day);
+        assert_eq!(status.remaining_seconds, 0);
+        assert!(status.exhausted);
+
+        // Weekend-only budgets don't apply (or block) on a Tuesday
+        let status = BudgetStatus::new(&budget(15, "[0,6]"), Some(&usage), day);
+        assert!(!status.applies_today);
+        assert!(!status.exhausted);
+
+        assert_eq!(parse_days("not json"), (0..=6).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_next_warning() {
+        let thresholds = [50, 80, 95];
+        assert_eq!(next_warning(&thresholds, 0, 10 * 60, 30 * 60), None);
+        assert_eq!(next_warning(&thresholds, 0, 15 * 60, 30 * 60), Some(50));
+        assert_eq!(next_warning(&thresholds, 50, 20 * 60, 30 * 60), None);
+        // Jumping past several thresholds only warns once, for the highest
+        assert_eq!(next_warning(&thresholds, 0, 29 * 60, 30 * 60), Some(95));
+        assert_eq!(next_warning(&thresholds, 95, 29 * 60, 30 * 60), None);
+    }
+
+    #[test]
+    fn test_website_activity_counts_overlaps_once() {
+        let mut activity = WebsiteActivity::default();
+        let start = Instant::now();
+
+        assert_eq!(activity.hit(1, start), WEBSITE_ACTIVITY_WINDOW);
+        // A lookup 20s later only extends the window by 20s
+        assert_eq!(activity.hit(1, start + Duration::from_secs(20)), Duration::from_secs(20));
+        // After a long pause a full window is counted again
+        assert_eq!(activity.hit(1, start + Duration::from_secs(600)), WEBSITE_ACTIVITY_WINDOW);
+        // Budgets are tracked separately
+        assert_eq!(activity.hit(2, start + Duration::from_secs(600)), WEBSITE_ACTIVITY_WINDOW);
+    }
+}
@@ -4,9 +4,14 @@
 // permissions and provides fallback strategies when elevated privileges
 // are not available.
 
-use crate::{Error, Result};
+use crate::{
+    blocking::{
+        doh::{self, BlockingCoverage, DohReport},
+        hosts::get_hosts_path,
+    },
+    helper, Error, Result,
+};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
 
 /// Blocking method availability and capability information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +25,10 @@ pub struct BlockingCapabilities {
     /// Can we terminate processes?
     pub process_termination_available: bool,
 
+    /// Is the privileged helper installed and answering?
+    #[serde(default)]
+    pub helper_available: bool,
+
     /// Recommended blocking method based on available capabilities
     pub recommended_method: BlockingMethod,
 
@@ -31,6 +40,10 @@ pub struct BlockingCapabilities {
 
     /// Current operating system
     pub platform: String,
+
+    /// Browsers whose DNS-over-HTTPS settings let them bypass the hosts file
+    #[serde(default)]
+    pub dns_over_https: DohReport,
 }
 
 /// Available blocking methods in order of effectiveness
@@ -43,6 +56,12 @@ pub enum BlockingMethod {
     /// Process termination (effective for apps, may require elevation)
     ProcessTermination,
 
+    /// Hosts file and process enforcement through the privileged helper daemon
+    PrivilegedHelper,
+
+    /// Local DNS sinkhole resolver (no elevation needed, system DNS must point to it)
+    DnsSinkhole,
+
     /// Frontend-based blocking (least secure, no elevation needed)
     FrontendOnly,
 }
@@ -70,9 +89,12 @@ pub struct ElevationInstructions {
 }
 
 /// Check all blocking capabilities on the current system
-pub async fn check_capabilities() -> BlockingCapabilities {
+///
+/// `sinkhole_running` tells whether the DNS sinkhole answers the DoH canary.
+pub async fn check_capabilities(sinkhole_running: bool) -> BlockingCapabilities {
     let hosts_writable = check_hosts_file_writable().await;
     let process_termination = check_process_termination_available();
+    let helper_available = helper::is_available().await;
     let platform = get_platform_name();
 
     let mut available_methods = Vec::new();
@@ -81,6 +103,8 @@ pub async fn check_capabilities() -> BlockingCapabilities {
     // Check hosts file capability
     if hosts_writable {
         available_methods.push(BlockingMethod::HostsFile);
+    } else if helper_available {
+        // The privileged helper writes the hosts file on our behalf
     } else {
         limitations.push(format!(
             "Hosts file at {} is not writable. Website blocking requires elevated privileges.",
@@ -97,12 +121,21 @@ pub async fn check_capabilities() -> BlockingCapabilities {
         );
     }
 
+    if helper_available {
+        available_methods.push(BlockingMethod::PrivilegedHelper);
+    }
+
+    // The DNS sinkhole listens on an unprivileged port, so it is always available
+    available_methods.push(BlockingMethod::DnsSinkhole);
+
     // Frontend-only is always available as a fallback
     available_methods.push(BlockingMethod::FrontendOnly);
 
     // Determine recommended method
     let recommended_method = if hosts_writable {
         BlockingMethod::HostsFile
+    } else if helper_available {
+        BlockingMethod::PrivilegedHelper
     } else if process_termination {
         BlockingMethod::ProcessTermination
     } else {
@@ -112,14 +145,36 @@ pub async fn check_capabilities() -> BlockingCapabilities {
         BlockingMethod::FrontendOnly
     };
 
+    // Hosts blocking only reaches browsers that resolve through the system
+    let dns_over_https = doh::check_browsers(&doh::active_settings(), sinkhole_running).await;
+    for browser in dns_over_https.browsers.iter().filter(|b| !b.covered) {
+        limitations.push(format!(
+            "{} (profile {}) uses DNS-over-HTTPS{} and bypasses hosts file blocking.",
+            browser.browser,
+            browser.profile,
+            browser
+                .resolver
+                .as_ref()
+                .map(|resolver| format!(" via {}", resolver))
+                .unwrap_or_default()
+        ));
+    }
+    if dns_over_https.coverage != BlockingCoverage::Full && !dns_over_https.resolvers_blocked {
+        limitations.push(
+            "Blocking well-known DNS-over-HTTPS resolvers makes most browsers fall back to the hosts file.".to_string()
+        );
+    }
+
     BlockingCapabilities {
         hosts_file_writable: hosts_writable,
         hosts_file_path: get_hosts_path().display().to_string(),
         process_termination_available: process_termination,
+        helper_available,
         recommended_method,
         available_methods,
         limitations,
         platform,
+        dns_over_https,
     }
 }
 
@@ -169,19 +224,6 @@ fn check_process_termination_available() -> bool {
     true
 }
 
-/// Get platform-specific hosts file path
-fn get_hosts_path() -> PathBuf {
-    #[cfg(target_os = "windows")]
-    {
-        PathBuf::from(r"C:\Windows\System32\drivers\etc\hosts")
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        PathBuf::from("/etc/hosts")
-    }
-}
-
 /// Get current platform name
 fn get_platform_name() -> String {
     #[cfg(target_os = "macos")]
@@ -253,25 +295,32 @@ pub fn get_elevation_instructions() -> ElevationInstructions {
     {
         return ElevationInstructions {
             platform: "Linux".to_string(),
-            primary_method: "Run with sudo or add user to hosts file group".to_string(),
+            primary_method: "Install the FocusFlow privileged helper".to_string(),
             alternative_methods: vec![
+                "Run with sudo".to_string(),
                 "Create a sudoers rule for /etc/hosts".to_string(),
                 "Use capabilities with setcap".to_string(),
             ],
             steps: vec![
-                "Option 1 - Run with sudo:".to_string(),
+                "Option 1 - Privileged helper (recommended, FocusFlow itself stays unprivileged):".to_string(),
+                "  sudo install -m 755 focusflow-helper /usr/bin/focusflow-helper".to_string(),
+                "  sudo cp focusflow-helper@.socket focusflow-helper@.service /etc/systemd/system/".to_string(),
+                "  sudo systemctl enable --now focusflow-helper@$(id -u).socket".to_string(),
+                "".to_string(),
+                "Option 2 - Run with sudo:".to_string(),
                 "  sudo focusflow".to_string(),
                 "".to_string(),
-                "Option 2 - Make hosts file writable (less secure):".to_string(),
+                "Option 3 - Make hosts file writable (less secure):".to_string(),
                 "  sudo chmod 666 /etc/hosts".to_string(),
                 "".to_string(),
-                "Option 3 - Create sudoers rule (recommended):".to_string(),
+                "Option 4 - Create sudoers rule:".to_string(),
                 "  sudo visudo".to_string(),
                 "  Add: your_username ALL=(ALL) NOPASSWD: /usr/bin/tee /etc/hosts".to_string(),
             ],
             security_notes: vec![
                 "Root access is required to modify /etc/hosts on Linux".to_string(),
-                "The sudoers rule option is most secure".to_string(),
+                "The helper only accepts a few fixed requests, and only from your user".to_string(),
+                "The helper only terminates processes owned by your user".to_string(),
                 "Making /etc/hosts world-writable is not recommended for security".to_string(),
                 "FocusFlow only modifies the hosts file for website blocking".to_string(),
             ],
@@ -308,7 +357,14 @@ pub async fn validate_blocking_method(method: &BlockingMethod) -> Result<()> {
             // Process termination is generally available
             // Actual permission errors will be handled during termination attempts
         }
-        BlockingMethod::FrontendOnly => {
+        BlockingMethod::PrivilegedHelper => {
+            if !helper::is_available().await {
+                return Err(Error::PermissionDenied(
+                    "The FocusFlow privileged helper is not running.".to_string()
+                ));
+            }
+        }
+        BlockingMethod::DnsSinkhole | BlockingMethod::FrontendOnly => {
             // Always available
         }
     }
@@ -337,7 +393,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_check_capabilities() {
-        let capabilities = check_capabilities().await;
+        let capabilities = check_capabilities(false).await;
 
         // Should always have at least one method available (FrontendOnly)
         assert!(!capabilities.available_methods.is_empty());
@@ -11,7 +11,8 @@
 // - Optional: Browser extension integration for deeper blocking
 
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use crate::blocking::rules::WebsiteMatcher;
+use std::collections::HashMap;
 
 /// Response containing blocked domains and metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,7 +34,7 @@ pub struct DomainCheckResult {
     pub blocked: bool,
     /// The matched blocked domain (if any)
     pub matched_domain: Option<String>,
-    /// Match type (exact, subdomain, wildcard)
+    /// Match type (exact, subdomain, wildcard, path, regex, or exception when allowed)
     pub match_type: Option<String>,
 }
 
@@ -51,10 +52,11 @@ pub struct BlockingStats {
 /// DNS-level blocking fallback provider
 ///
 /// This doesn't actually run a DNS server, but provides domain blocking information
-/// to the frontend for client-side enforcement.
+/// to the frontend for client-side enforcement. Matching is delegated to the
+/// compiled website rules (see `blocking::rules`).
 pub struct DnsBlockingFallback {
-    /// Set of blocked domains for O(1) lookup
-    blocked_domains: HashSet<String>,
+    /// Compiled blocklist rules
+    matcher: WebsiteMatcher,
     /// Whether blocking is currently enabled
     enabled: bool,
     /// Last time the blocklist was updated
@@ -65,35 +67,23 @@ impl DnsBlockingFallback {
     /// Create a new DNS blocking fallback instance
     pub fn new() -> Self {
         Self {
-            blocked_domains: HashSet::new(),
+            matcher: WebsiteMatcher::default(),
             enabled: false,
             last_updated: chrono::Utc::now(),
         }
     }
 
-    /// Update the blocklist with new domains
+    /// Update the blocklist with new website rules
+    ///
+    /// Accepts plain domains as well as wildcard, path, regex and `!` exception
+    /// rules. Invalid rules are skipped.
     pub fn update_blocklist(&mut self, domains: Vec<String>) {
-        self.blocked_domains.clear();
-
-        for domain in domains {
-            // Normalize domain: lowercase, trim whitespace
-            let normalized = domain.trim().to_lowercase();
-
-            if !normalized.is_empty() {
-                self.blocked_domains.insert(normalized.clone());
-
-                // Also add www. variant if not present
-                if !normalized.starts_with("www.") {
-                    self.blocked_domains.insert(format!("www.{}", normalized));
-                }
-            }
-        }
-
+        self.matcher = WebsiteMatcher::compile(&domains);
         self.last_updated = chrono::Utc::now();
 
         tracing::info!(
-            "Updated DNS fallback blocklist: {} domains (including www. variants)",
-            self.blocked_domains.len()
+            "Updated DNS fallback blocklist: {} rules",
+            self.matcher.rules().len()
         );
     }
 
@@ -112,7 +102,8 @@ impl DnsBlockingFallback {
 
     /// Check if a domain is blocked
     ///
-    /// Supports exact matches and subdomain matching.
+    /// Supports exact matches, subdomain matching, `*.` wildcards and regex rules,
+    /// with `!` exceptions taking precedence. Path rules never block a whole domain.
     /// Example: blocking "example.com" will also block "www.example.com" and "api.example.com"
     pub fn is_domain_blocked(&self, domain: &str) -> DomainCheckResult {
         if !self.enabled {
@@ -123,56 +114,18 @@ impl DnsBlockingFallback {
             };
         }
 
-        let normalized = domain.trim().to_lowercase();
-
-        // Check exact match
-        if self.blocked_domains.contains(&normalized) {
-            return DomainCheckResult {
-                blocked: true,
-                matched_domain: Some(normalized.clone()),
-                match_type: Some("exact".to_string()),
-            };
-        }
-
-        // Check if this is a subdomain of a blocked domain
-        // Example: "api.facebook.com" should match blocked "facebook.com"
-        let parts: Vec<&str> = normalized.split('.').collect();
-
-        // Try progressively shorter domains
-        // api.example.com -> example.com -> com
-        for i in 1..parts.len() {
-            let parent_domain = parts[i..].join(".");
-            if self.blocked_domains.contains(&parent_domain) {
-                return DomainCheckResult {
-                    blocked: true,
-                    matched_domain: Some(parent_domain.clone()),
-                    match_type: Some("subdomain".to_string()),
-                };
-            }
-        }
-
-        DomainCheckResult {
-            blocked: false,
-            matched_domain: None,
-            match_type: None,
-        }
+        self.matcher.check_domain(domain)
     }
 
     /// Get all blocked domains as a response
     #[allow(dead_code)]
     pub fn get_blocked_domains(&self) -> BlockedDomainsResponse {
-        // Return unique base domains (without www. duplicates)
-        let unique_domains: Vec<String> = self
-            .blocked_domains
-            .iter()
-            .filter(|d| !d.starts_with("www."))
-            .cloned()
-            .collect();
+        let rules = self.matcher.rules().to_vec();
 
         BlockedDomainsResponse {
-            domains: unique_domains.clone(),
+            count: rules.len(),
+            domains: rules,
             enabled: self.enabled,
-            count: unique_domains.len(),
             last_updated: self.last_updated.to_rfc3339(),
         }
     }
@@ -181,18 +134,10 @@ impl DnsBlockingFallback {
     pub fn get_stats(&self) -> BlockingStats {
         // Basic implementation - can be extended with categorization
         let mut categories = HashMap::new();
-
-        let unique_domains: Vec<String> = self
-            .blocked_domains
-            .iter()
-            .filter(|d| !d.starts_with("www."))
-            .cloned()
-            .collect();
-
-        categories.insert("all".to_string(), unique_domains);
+        categories.insert("all".to_string(), self.matcher.rules().to_vec());
 
         BlockingStats {
-            total_blocked_domains: self.blocked_domains.len() / 2, // Divide by 2 for www. variants
+            total_blocked_domains: self.matcher.block_rule_count(),
             blocking_enabled: self.enabled,
             categories,
         }
@@ -229,17 +174,18 @@ impl DnsBlockingFallback {
 
     /// Check if a URL is blocked
     ///
-    /// Convenience method that extracts domain from URL and checks if blocked
+    /// Unlike `is_domain_blocked`, path rules (e.g. "youtube.com/shorts") apply.
     #[allow(dead_code)]
     pub fn is_url_blocked(&self, url: &str) -> DomainCheckResult {
-        match Self::extract_domain_from_url(url) {
-            Some(domain) => self.is_domain_blocked(&domain),
-            None => DomainCheckResult {
+        if !self.enabled {
+            return DomainCheckResult {
                 blocked: false,
                 matched_domain: None,
                 match_type: None,
-            },
+            };
         }
+
+        self.matcher.check_url(url)
     }
 }
 
@@ -288,6 +234,41 @@ mod tests {
         assert!(!result.blocked);
     }
 
+    #[test]
+    fn test_wildcard_blocking() {
+        let mut fallback = DnsBlockingFallback::new();
+        fallback.update_blocklist(vec!["*.example.com".to_string()]);
+        fallback.enable();
+
+        let result = fallback.is_domain_blocked("cdn.example.com.");
+        assert!(result.blocked);
+        assert_eq!(result.matched_domain, Some("*.example.com".to_string()));
+        assert_eq!(result.match_type, Some("wildcard".to_string()));
+
+        assert!(fallback.is_domain_blocked("a.b.example.com").blocked);
+        assert!(!fallback.is_domain_blocked("example.com").blocked);
+    }
+
+    #[test]
+    fn test_url_path_and_exception_rules() {
+        let mut fallback = DnsBlockingFallback::new();
+        fallback.update_blocklist(vec![
+            "youtube.com/shorts".to_string(),
+            "reddit.com".to_string(),
+            "!reddit.com/r/rust".to_string(),
+        ]);
+        fallback.enable();
+
+        assert!(!fallback.is_domain_blocked("youtube.com").blocked);
+        assert!(fallback.is_url_blocked("https://www.youtube.com/shorts/xyz").blocked);
+        assert!(!fallback.is_url_blocked("https://www.youtube.com/watch?v=1").blocked);
+
+        assert!(fallback.is_url_blocked("https://reddit.com/r/all").blocked);
+        let allowed = fallback.is_url_blocked("https://reddit.com/r/rust");
+        assert!(!allowed.blocked);
+        assert_eq!(allowed.match_type, Some("exception".to_string()));
+    }
+
     #[test]
     fn test_url_extraction() {
         assert_eq!(
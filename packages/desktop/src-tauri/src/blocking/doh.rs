@@ -0,0 +1,556 @@
+// blocking/doh.rs - DNS-over-HTTPS bypass detection and protection
+//
+// Browsers that resolve names over HTTPS never consult the hosts file (or the
+// system resolver), so hosts blocking silently stops working for them. This
+// module reads the DoH configuration of local Firefox and Chromium-family
+// profiles and can protect the block with:
+//
+// - `use-application-dns.net`, the canary domain Firefox checks before turning
+//   on DoH by default; Firefox stays off DoH only when the canary gets NXDOMAIN
+//   or no addresses, which the hosts file can't express, so the DNS sinkhole
+//   answers it (see `blocking::sinkhole`) and it only counts while that runs
+// - the hostnames of well-known DoH resolvers, added to every hosts block, so
+//   browsers that fall back to the system resolver when DoH fails end up
+//   hitting the block
+//
+// Profiles locked to DoH with no fallback (Firefox mode 3, Chromium "secure")
+// or using a resolver missing from the list remain a bypass, which is
+// reported as part of the blocking capabilities.
+
+use crate::{
+    blocking::{hosts, nuclear},
+    db::queries,
+    state::AppState,
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const DOH_SETTINGS_KEY: &str = "doh_protection";
+
+/// Domain Firefox resolves to decide whether to enable DoH by default
+pub const CANARY_DOMAIN: &str = "use-application-dns.net";
+
+/// Hostnames of well-known public DoH resolvers
+pub const DOH_RESOLVER_DOMAINS: &[&str] = &[
+    "dns.google",
+    "dns.google.com",
+    "cloudflare-dns.com",
+    "mozilla.cloudflare-dns.com",
+    "chrome.cloudflare-dns.com",
+    "1dot1dot1dot1.cloudflare-dns.com",
+    "dns.quad9.net",
+    "dns9.quad9.net",
+    "dns10.quad9.net",
+    "dns11.quad9.net",
+    "doh.opendns.com",
+    "doh.familyshield.opendns.com",
+    "dns.nextdns.io",
+    "firefox.dns.nextdns.io",
+    "chromium.dns.nextdns.io",
+    "doh.cleanbrowsing.org",
+    "dns.adguard.com",
+    "dns.adguard-dns.com",
+    "unfiltered.adguard-dns.com",
+    "doh.dns.sb",
+    "doh.mullvad.net",
+    "dns.mullvad.net",
+    "freedns.controld.com",
+    "doh.xfinity.com",
+    "dns.comss.one",
+];
+
+/// Which DoH-related domains are added to the hosts block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DohSettings {
+    /// Answer the Firefox canary domain with NXDOMAIN in the DNS sinkhole
+    /// (harmless, on by default)
+    pub block_canary: bool,
+    /// Block well-known DoH resolvers (also affects other apps that use them)
+    pub block_resolvers: bool,
+}
+
+impl Default for DohSettings {
+    fn default() -> Self {
+        Self {
+            block_canary: true,
+            block_resolvers: false,
+        }
+    }
+}
+
+impl DohSettings {
+    /// Domains added to every non-empty hosts block
+    ///
+    /// Not the canary: a loopback address in the hosts file still counts as
+    /// resolving for Firefox.
+    pub fn extra_domains(&self) -> Vec<String> {
+        if self.block_resolvers {
+            DOH_RESOLVER_DOMAINS.iter().map(|d| d.to_string()).collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Settings in force, read by `hosts::update_hosts_file`
+static ACTIVE_SETTINGS: Mutex<Option<DohSettings>> = Mutex::new(None);
+
+/// The DoH settings in force (defaults until loaded)
+pub fn active_settings() -> DohSettings {
+    ACTIVE_SETTINGS
+        .lock()
+        .ok()
+        .and_then(|settings| *settings)
+        .unwrap_or_default()
+}
+
+fn set_active_settings(settings: DohSettings) {
+    if let Ok(mut active) = ACTIVE_SETTINGS.lock() {
+        *active = Some(settings);
+    }
+}
+
+/// Load the persisted DoH settings
+pub async fn load_settings(state: &AppState) -> Result<DohSettings> {
+    let stored = queries::get_user_setting(state.pool(), DOH_SETTINGS_KEY).await?;
+
+    Ok(match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid DoH protection settings: {}", e);
+            DohSettings::default()
+        }),
+        None => DohSettings::default(),
+    })
+}
+
+/// Persist new DoH settings and re-apply a running hosts block with them
+///
+/// Dropping protection weakens blocking, so it is refused during a lockdown.
+pub async fn save_settings(state: &AppState, settings: DohSettings) -> Result<()> {
+    let current = active_settings();
+    if (current.block_canary && !settings.block_canary)
+        || (current.block_resolvers && !settings.block_resolvers)
+    {
+        nuclear::ensure_unlocked(state, "turn off DNS-over-HTTPS protection").await?;
+    }
+
+    let json = serde_json::to_string(&settings)?;
+    queries::set_user_setting(state.pool(), DOH_SETTINGS_KEY, &json).await?;
+    set_active_settings(settings);
+
+    refresh_hosts(state).await;
+    Ok(())
+}
+
+/// Load the settings at startup
+pub async fn restore(state: &AppState) {
+    match load_settings(state).await {
+        Ok(settings) => {
+            set_active_settings(settings);
+            refresh_hosts(state).await;
+        }
+        Err(e) => tracing::warn!("Failed to load DoH protection settings: {}", e),
+    }
+}
+
+/// Rewrite a running hosts block so it picks up the current settings
+async fn refresh_hosts(state: &AppState) {
+    let websites = {
+        let blocking = state.blocking_state.read().await;
+        if !blocking.enabled || blocking.blocked_websites.is_empty() {
+            return;
+        }
+        blocking.blocked_websites.clone()
+    };
+
+    if let Err(e) = hosts::update_hosts_file(state, &websites).await {
+        tracing::warn!("Failed to re-apply hosts file with DoH protection: {}", e);
+    }
+}
+
+// ============================================================================
+// Browser detection
+// ============================================================================
+
+/// DoH configuration of a browser profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DohMode {
+    /// DoH turned off
+    Off,
+    /// Browser default: DoH only where the browser decides to (honors the canary)
+    Default,
+    /// DoH turned on, falling back to the system resolver when it fails
+    Automatic,
+    /// DoH only, never falling back to the system resolver
+    Secure,
+}
+
+/// DoH status of one local browser profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrowserDohStatus {
+    pub browser: String,
+    pub profile: String,
+    pub mode: DohMode,
+    /// Resolver hostname, when configured
+    pub resolver: Option<String>,
+    /// Whether hosts blocking still applies to this profile
+    pub covered: bool,
+}
+
+/// How much of the hosts block holds up against DoH
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockingCoverage {
+    /// Every detected profile goes through the hosts file
+    Full,
+    /// Some profiles bypass the hosts file
+    Partial,
+    /// Every detected profile bypasses the hosts file
+    Bypassed,
+}
+
+/// DoH findings reported with the blocking capabilities
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DohReport {
+    pub browsers: Vec<BrowserDohStatus>,
+    pub canary_blocked: bool,
+    pub resolvers_blocked: bool,
+    pub coverage: BlockingCoverage,
+}
+
+impl Default for DohReport {
+    fn default() -> Self {
+        Self {
+            browsers: Vec::new(),
+            canary_blocked: false,
+            resolvers_blocked: false,
+            coverage: BlockingCoverage::Full,
+        }
+    }
+}
+
+/// Parse the DoH settings out of a Firefox `prefs.js` / `user.js`
+///
+/// `network.trr.mode`: 0 = default, 2 = DoH with fallback, 3 = DoH only,
+/// 5 = off. Later lines win, as in Firefox.
+pub fn parse_firefox_prefs(content: &str) -> (DohMode, Option<String>) {
+    let mut mode = DohMode::Default;
+    let mut resolver = None;
+
+    for line in content.lines() {
+        let Some((name, value)) = parse_user_pref(line) else {
+            continue;
+        };
+        match name {
+            "network.trr.mode" => {
+                mode = match value.parse::<u8>() {
+                    Ok(2) | Ok(1) => DohMode::Automatic,
+                    Ok(3) => DohMode::Secure,
+                    Ok(5) => DohMode::Off,
+                    _ => DohMode::Default,
+                }
+            }
+            "network.trr.uri" | "network.trr.custom_uri" => {
+                if let Some(host) = url_host(value.trim_matches('"')) {
+                    resolver = Some(host);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (mode, resolver)
+}
+
+/// Split `user_pref("name", value);` into name and raw value
+fn parse_user_pref(line: &str) -> Option<(&str, &str)> {
+    let inner = line
+        .trim()
+        .strip_prefix("user_pref(")?
+        .strip_suffix(");")?;
+    let (name, value) = inner.split_once(',')?;
+
+    Some((name.trim().trim_matches('"'), value.trim()))
+}
+
+/// Parse the DoH settings out of a Chromium `Local State` file
+pub fn parse_chromium_local_state(content: &str) -> (DohMode, Option<String>) {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return (DohMode::Default, None);
+    };
+    let doh = &json["dns_over_https"];
+
+    let mode = match doh["mode"].as_str() {
+        Some("off") => DohMode::Off,
+        Some("automatic") => DohMode::Automatic,
+        Some("secure") => DohMode::Secure,
+        _ => DohMode::Default,
+    };
+    let resolver = doh["templates"]
+        .as_str()
+        .and_then(|templates| templates.split_whitespace().next())
+        .and_then(url_host);
+
+    (mode, resolver)
+}
+
+/// Host of an `https://host[:port]/path` URL (or URI template)
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://")?;
+    let host = rest
+        .split(['/', '?', '{'])
+        .next()?
+        .split(':')
+        .next()?
+        .to_ascii_lowercase();
+
+    (!host.is_empty()).then_some(host)
+}
+
+fn is_known_resolver(host: &str) -> bool {
+    DOH_RESOLVER_DOMAINS
+        .iter()
+        .any(|known| host == *known || host.ends_with(&format!(".{}", known)))
+}
+
+/// Whether the hosts block still applies to a profile
+pub fn is_covered(mode: DohMode, resolver: Option<&str>, settings: &DohSettings) -> bool {
+    // Without a configured resolver the browser picks a well-known one
+    let resolver_blocked = settings.block_resolvers
+        && match resolver {
+            Some(host) => is_known_resolver(host),
+            None => true,
+        };
+
+    match mode {
+        DohMode::Off => true,
+        // Firefox skips DoH when the canary is blocked; any browser falls back when its resolver is
+        DohMode::Default => settings.block_canary || resolver_blocked,
+        // Fallback kicks in only when the resolver itself is unreachable
+        DohMode::Automatic => resolver_blocked,
+        // No fallback: a blocked resolver just breaks browsing, it never reaches the hosts file
+        DohMode::Secure => false,
+    }
+}
+
+/// Check the DoH configuration of every local browser profile
+///
+/// The canary is only answered while the DNS sinkhole is running.
+pub async fn check_browsers(settings: &DohSettings, sinkhole_running: bool) -> DohReport {
+    let settings = &DohSettings {
+        block_canary: settings.block_canary && sinkhole_running,
+        ..*settings
+    };
+    let profiles = tokio::task::spawn_blocking(find_profiles)
+        .await
+        .unwrap_or_default();
+
+    let browsers: Vec<BrowserDohStatus> = profiles
+        .into_iter()
+        .map(|(browser, profile, mode, resolver)| BrowserDohStatus {
+            covered: is_covered(mode, resolver.as_deref(), settings),
+            browser,
+            profile,
+            mode,
+            resolver,
+        })
+        .collect();
+
+    let covered = browsers.iter().filter(|b| b.covered).count();
+    let coverage = if covered == browsers.len() {
+        BlockingCoverage::Full
+    } else if covered == 0 {
+        BlockingCoverage::Bypassed
+    } else {
+        BlockingCoverage::Partial
+    };
+
+    DohReport {
+        browsers,
+        canary_blocked: settings.block_canary,
+        resolvers_blocked: settings.block_resolvers,
+        coverage,
+    }
+}
+
+type ProfileDoh = (String, String, DohMode, Option<String>);
+
+fn find_profiles() -> Vec<ProfileDoh> {
+    let mut profiles = Vec::new();
+
+    for root in firefox_profile_roots() {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let Ok(prefs) = std::fs::read_to_string(dir.join("prefs.js")) else {
+                continue;
+            };
+            // user.js is applied on top of prefs.js at startup
+            let user = std::fs::read_to_string(dir.join("user.js")).unwrap_or_default();
+            let (mode, resolver) = parse_firefox_prefs(&format!("{}\n{}", prefs, user));
+            profiles.push(("Firefox".to_string(), dir_name(&dir), mode, resolver));
+        }
+    }
+
+    for (browser, dir) in chromium_user_data_dirs() {
+        if let Ok(local_state) = std::fs::read_to_string(dir.join("Local State")) {
+            let (mode, resolver) = parse_chromium_local_state(&local_state);
+            profiles.push((browser.to_string(), dir_name(&dir), mode, resolver));
+        }
+    }
+
+    profiles
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Directories holding Firefox profile directories
+fn firefox_profile_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(".mozilla/firefox"));
+        roots.push(home.join("snap/firefox/common/.mozilla/firefox"));
+        roots.push(home.join(".var/app/org.mozilla.firefox/.mozilla/firefox"));
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Some(config) = dirs::config_dir() {
+        roots.push(config.join("Firefox/Profiles"));
+    }
+
+    #[cfg(target_os = "windows")]
+    if let Some(config) = dirs::config_dir() {
+        roots.push(config.join("Mozilla\\Firefox\\Profiles"));
+    }
+
+    roots
+}
+
+/// User data directories (holding `Local State`) of Chromium-family browsers
+fn chromium_user_data_dirs() -> Vec<(&'static str, PathBuf)> {
+    #[cfg(target_os = "linux")]
+    let (base, browsers) = (
+        dirs::config_dir(),
+        [
+            ("Google Chrome", "google-chrome"),
+            ("Chromium", "chromium"),
+            ("Brave", "BraveSoftware/Brave-Browser"),
+            ("Microsoft Edge", "microsoft-edge"),
+            ("Vivaldi", "vivaldi"),
+        ],
+    );
+
+    #[cfg(target_os = "macos")]
+    let (base, browsers) = (
+        dirs::config_dir(),
+        [
+            ("Google Chrome", "Google/Chrome"),
+            ("Chromium", "Chromium"),
+            ("Brave", "BraveSoftware/Brave-Browser"),
+            ("Microsoft Edge", "Microsoft Edge"),
+            ("Vivaldi", "Vivaldi"),
+        ],
+    );
+
+    #[cfg(target_os = "windows")]
+    let (base, browsers) = (
+        dirs::data_local_dir(),
+        [
+            ("Google Chrome", "Google\\Chrome\\User Data"),
+            ("Chromium", "Chromium\\User Data"),
+            ("Brave", "BraveSoftware\\Brave-Browser\\User Data"),
+            ("Microsoft Edge", "Microsoft\\Edge\\User Data"),
+            ("Vivaldi", "Vivaldi\\User Data"),
+        ],
+    );
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    let (base, browsers): (Option<PathBuf>, [(&'static str, &str); 0]) = (None, []);
+
+    let Some(base) = base else {
+        return Vec::new();
+    };
+
+    browsers
+        .into_iter()
+        .map(|(browser, dir)| (browser, base.join(dir)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_firefox_prefs() {
+        assert_eq!(parse_firefox_prefs(""), (DohMode::Default, None));
+
+        let prefs = r#"
+// Mozilla User Preferences
+user_pref("browser.startup.page", 3);
+user_pref("network.trr.mode", 2);
+user_pref("network.trr.uri", "https://dns.quad9.net/dns-query");
+"#;
+        assert_eq!(
+            parse_firefox_prefs(prefs),
+            (DohMode::Automatic, Some("dns.quad9.net".to_string()))
+        );
+
+        // user.js appended after prefs.js wins
+        let prefs = format!("{}\nuser_pref(\"network.trr.mode\", 5);", prefs);
+        assert_eq!(parse_firefox_prefs(&prefs).0, DohMode::Off);
+    }
+
+    #[test]
+    fn test_parse_chromium_local_state() {
+        let state = r#"{"dns_over_https":{"mode":"secure","templates":"https://doh.example.org:8443/dns-query{?dns}"}}"#;
+        assert_eq!(
+            parse_chromium_local_state(state),
+            (DohMode::Secure, Some("doh.example.org".to_string()))
+        );
+
+        assert_eq!(parse_chromium_local_state(r#"{"browser":{}}"#), (DohMode::Default, None));
+        assert_eq!(parse_chromium_local_state("not json"), (DohMode::Default, None));
+    }
+
+    #[test]
+    fn test_coverage() {
+        let canary_only = DohSettings::default();
+        let everything = DohSettings {
+            block_canary: true,
+            block_resolvers: true,
+        };
+
+        assert!(is_covered(DohMode::Off, None, &DohSettings { block_canary: false, block_resolvers: false }));
+        assert!(is_covered(DohMode::Default, None, &canary_only));
+        assert!(!is_covered(DohMode::Automatic, Some("dns.google"), &canary_only));
+        assert!(is_covered(DohMode::Automatic, Some("dns.google"), &everything));
+        assert!(!is_covered(DohMode::Automatic, Some("doh.example.org"), &everything));
+        assert!(!is_covered(DohMode::Secure, Some("dns.google"), &everything));
+    }
+
+    #[test]
+    fn test_extra_domains() {
+        assert!(DohSettings::default().extra_domains().is_empty());
+
+        let everything = DohSettings {
+            block_canary: true,
+            block_resolvers: true,
+        };
+        assert!(everything.extra_domains().contains(&"mozilla.cloudflare-dns.com".to_string()));
+        assert!(!everything.extra_domains().contains(&CANARY_DOMAIN.to_string()));
+        assert!(is_known_resolver("family.dns.mullvad.net"));
+    }
+}
@@ -0,0 +1,397 @@
+// blocking/enforcement.rs - Per-item enforcement policies for blocked apps
+//
+// A blocked app is either closed (`kill`, the default), only reported
+// (`warn-only`), or paused (`delay`): the process is suspended with SIGSTOP
+// and only continued once the user types a reason or waits out a countdown.
+// Pausing covers the app's whole process tree, so multi-process apps do not
+// keep running in their helpers.
+// Typed reasons are stored as journal entries so trigger insights include them.
+
+use crate::{
+    blocking::{process, termination::{self, TerminationOutcome}},
+    commands::journal::{self, CreateJournalEntryRequest},
+    db::queries,
+    state::AppState,
+    Error, Result,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, Signal, System};
+use tauri::Emitter;
+
+/// How long a delayed app stays paused when no reason is given
+pub const DELAY_COUNTDOWN_SECONDS: i64 = 30;
+
+/// Shortest reason accepted to continue a delayed app right away
+pub const MIN_REASON_LENGTH: usize = 10;
+
+/// What happens when a blocked app is detected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EnforcementPolicy {
+    /// Warn, then close the app after a grace period
+    #[default]
+    Kill,
+    /// Only notify and record the attempt
+    WarnOnly,
+    /// Pause the app until the user gives a reason or waits out a countdown
+    Delay,
+}
+
+impl EnforcementPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Kill => "kill",
+            Self::WarnOnly => "warn-only",
+            Self::Delay => "delay",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "kill" => Ok(Self::Kill),
+            "warn-only" | "warn" => Ok(Self::WarnOnly),
+            "delay" => Ok(Self::Delay),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown enforcement policy '{}' (expected kill, warn-only or delay)",
+                other
+            ))),
+        }
+    }
+}
+
+/// A blocked app paused by the `delay` policy
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelayedProcess {
+    pub id: String,
+    pub pid: u32,
+    /// Every paused process of the app's tree, `pid` first
+    #[serde(skip)]
+    pids: Vec<u32>,
+    pub process_name: String,
+    pub suspended_at: DateTime<Utc>,
+    /// When the app may be continued without a reason
+    pub resumable_at: DateTime<Utc>,
+    /// Continued apps are left alone until they exit
+    pub resumed: bool,
+}
+
+impl DelayedProcess {
+    fn new(pids: Vec<u32>, process_name: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            pid: pids[0],
+            pids,
+            process_name: process_name.to_string(),
+            suspended_at: now,
+            resumable_at: now + chrono::Duration::seconds(DELAY_COUNTDOWN_SECONDS),
+            resumed: false,
+        }
+    }
+
+    /// Check whether the app may be continued now
+    ///
+    /// Either the countdown has elapsed or a long enough reason was typed.
+    pub fn can_resume(&self, reason: Option<&str>, now: DateTime<Utc>) -> bool {
+        now >= self.resumable_at
+            || reason.is_some_and(|r| r.trim().chars().count() >= MIN_REASON_LENGTH)
+    }
+
+    /// Whether a process belongs to the paused tree
+    pub fn covers(&self, pid: u32) -> bool {
+        self.pids.contains(&pid)
+    }
+}
+
+/// Send a signal to a process, returning false if the platform cannot deliver it
+fn send_signal(pid: u32, signal: Signal) -> Result<bool> {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, ProcessRefreshKind::nothing());
+
+    let process = system
+        .process(pid)
+        .ok_or_else(|| Error::ProcessNotFound(format!("PID {}", pid)))?;
+
+    match process.kill_with(signal) {
+        Some(true) => Ok(true),
+        Some(false) => Err(Error::PermissionDenied(format!(
+            "Failed to send {:?} to PID {}",
+            signal, pid
+        ))),
+        None => Ok(false),
+    }
+}
+
+/// Send a signal to every process of a tree, returning false if the platform cannot deliver it
+///
+/// Errors only for the root; helpers that exited in the meantime are skipped.
+fn signal_tree(pids: &[u32], signal: Signal) -> Result<bool> {
+    let Some((root, helpers)) = pids.split_first() else {
+        return Ok(false);
+    };
+
+    let delivered = send_signal(*root, signal)?;
+    if delivered {
+        for pid in helpers {
+            if let Err(e) = send_signal(*pid, signal) {
+                tracing::debug!("Could not send {:?} to PID {}: {}", signal, pid, e);
+            }
+        }
+    }
+    Ok(delivered)
+}
+
+/// Check whether a process is paused or was continued by the delay policy
+///
+/// Covers every process of a paused tree, so helpers of a multi-process app
+/// don't get a prompt of their own.
+pub async fn is_delayed(state: &AppState, pid: u32) -> bool {
+    let delayed = state.delayed_processes.read().await;
+    delayed.iter().any(|d| d.covers(pid))
+}
+
+/// Pause a blocked app and ask the user to confirm continuing it
+///
+/// Returns None if processes cannot be suspended on this platform.
+pub async fn delay_process(state: &AppState, pid: u32, process_name: &str) -> Result<Option<DelayedProcess>> {
+    let mut pids = termination::process_tree(pid, &process::is_terminable)?;
+    // Processes already in a paused tree stay with that one
+    {
+        let delayed = state.delayed_processes.read().await;
+        pids.retain(|&p| p == pid || !delayed.iter().any(|d| d.covers(p)));
+    }
+    if !signal_tree(&pids, Signal::Stop)? {
+        return Ok(None);
+    }
+
+    let delayed = DelayedProcess::new(pids, process_name);
+    state.delayed_processes.write().await.push(delayed.clone());
+
+    tracing::info!("Paused blocked process: {} (PID: {})", process_name, pid);
+
+    if let Err(e) = state.app_handle.emit(
+        "process-delayed",
+        serde_json::json!({
+            "id": delayed.id,
+            "pid": pid,
+            "processName": process_name,
+            "resumableAt": delayed.resumable_at.to_rfc3339(),
+        }),
+    ) {
+        tracing::warn!("Failed to emit process-delayed event: {}", e);
+    }
+
+    Ok(Some(delayed))
+}
+
+/// Continue a paused app
+///
+/// Requires a typed reason of at least `MIN_REASON_LENGTH` characters until
+/// the countdown has elapsed. A given reason is saved as a journal entry.
+pub async fn resume_delayed_process(
+    state: &AppState,
+    id: &str,
+    reason: Option<&str>,
+    trigger_type: Option<&str>,
+) -> Result<DelayedProcess> {
+    let delayed = {
+        let mut delayed_processes = state.delayed_processes.write().await;
+        let delayed = delayed_processes
+            .iter_mut()
+            .find(|d| d.id == id && !d.resumed)
+            .ok_or_else(|| Error::NotFound(format!("No paused app with id {}", id)))?;
+
+        if !delayed.can_resume(reason, Utc::now()) {
+            return Err(Error::Validation(format!(
+                "Type a reason (at least {} characters) or wait until the countdown ends",
+                MIN_REASON_LENGTH
+            )));
+        }
+
+        match signal_tree(&delayed.pids, Signal::Continue) {
+            Ok(_) | Err(Error::ProcessNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        delayed.resumed = true;
+        delayed.clone()
+    };
+
+    tracing::info!("Continued paused process: {} (PID: {})", delayed.process_name, delayed.pid);
+
+    if let Some(reason) = reason.map(str::trim).filter(|r| !r.is_empty()) {
+        let session_id = {
+            let active_session = state.active_session.read().await;
+            active_session.as_ref().map(|s| s.id.clone())
+        };
+
+        let request = CreateJournalEntryRequest {
+            session_id,
+            trigger_type: trigger_type
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .unwrap_or("other")
+                .to_string(),
+            emotion: None,
+            notes: Some(format!("Opened {} during focus: {}", delayed.process_name, reason)),
+            intensity: None,
+        };
+
+        if let Err(e) = journal::insert_journal_entry(state.pool(), request).await {
+            tracing::error!("Failed to record unblock reason: {}", e);
+        }
+    }
+
+    Ok(delayed)
+}
+
+/// Close a paused app instead of continuing it
+pub async fn close_delayed_process(state: &AppState, id: &str) -> Result<()> {
+    let delayed = {
+        let mut delayed_processes = state.delayed_processes.write().await;
+        let index = delayed_processes
+            .iter()
+            .position(|d| d.id == id && !d.resumed)
+            .ok_or_else(|| Error::NotFound(format!("No paused app with id {}", id)))?;
+        delayed_processes.remove(index)
+    };
+
+    // SIGKILL is delivered to stopped processes as well
+    match signal_tree(&delayed.pids, Signal::Kill) {
+        Ok(_) | Err(Error::ProcessNotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+
+    tracing::info!("Closed paused process: {} (PID: {})", delayed.process_name, delayed.pid);
+
+    Ok(())
+}
+
+/// Continue every paused app, e.g. when blocking ends
+pub async fn release_all(state: &AppState) {
+    let mut delayed_processes = state.delayed_processes.write().await;
+
+    for delayed in delayed_processes.iter().filter(|d| !d.resumed) {
+        if let Err(e) = signal_tree(&delayed.pids, Signal::Continue) {
+            tracing::debug!("Could not continue PID {}: {}", delayed.pid, e);
+        }
+    }
+
+    delayed_processes.clear();
+}
+
+/// Forget paused or continued apps that are no longer running
+pub async fn prune_exited(state: &AppState, system: &System) {
+    let mut delayed_processes = state.delayed_processes.write().await;
+    delayed_processes.retain(|d| system.process(sysinfo::Pid::from_u32(d.pid)).is_some());
+}
+
+/// Record a detected blocked app in block_attempts
+///
+/// `item_type` is "app", or "focus_time_app" for apps outside the Focus Time
+/// allowed list. `outcome` is the termination status ("killed", "survived", "protected")
+/// or "warned"/"delayed" for the other policies.
+pub async fn record_app_attempt(
+    state: &AppState,
+    item_type: &str,
+    process_name: &str,
+    outcome: &str,
+    termination: Option<&TerminationOutcome>,
+) {
+    let session_id = {
+        let active_session = state.active_session.read().await;
+        active_session.as_ref().map(|s| s.id.clone())
+    };
+    let user_id = state.get_user_id().await;
+    let termination_result = termination.and_then(|t| serde_json::to_string(t).ok());
+
+    if let Err(e) = queries::record_block_attempt_with_outcome(
+        state.pool(),
+        item_type,
+        process_name,
+        session_id.as_deref(),
+        user_id.as_deref(),
+        outcome,
+        termination_result.as_deref(),
+    )
+    .await
+    {
+        tracing::error!("Failed to record block attempt: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_parsing() {
+        assert_eq!(EnforcementPolicy::parse("kill").unwrap(), EnforcementPolicy::Kill);
+        assert_eq!(EnforcementPolicy::parse("Warn-Only").unwrap(), EnforcementPolicy::WarnOnly);
+        assert_eq!(EnforcementPolicy::parse("delay").unwrap(), EnforcementPolicy::Delay);
+        assert!(EnforcementPolicy::parse("ignore").is_err());
+
+        for policy in [EnforcementPolicy::Kill, EnforcementPolicy::WarnOnly, EnforcementPolicy::Delay] {
+            assert_eq!(EnforcementPolicy::parse(policy.as_str()).unwrap(), policy);
+            assert_eq!(
+                serde_json::to_string(&policy).unwrap(),
+                format!("\"{}\"", policy.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_can_resume() {
+        let delayed = DelayedProcess::new(vec![1], "slack");
+        let now = delayed.suspended_at;
+
+        assert!(!delayed.can_resume(None, now));
+        assert!(!delayed.can_resume(Some("   short  "), now));
+        assert!(delayed.can_resume(Some("Need to answer my manager"), now));
+        assert!(delayed.can_resume(None, delayed.resumable_at));
+    }
+
+    #[test]
+    fn test_covers_whole_tree() {
+        let delayed = DelayedProcess::new(vec![100, 101, 102], "slack");
+
+        assert!(delayed.covers(100));
+        assert!(delayed.covers(102));
+        assert!(!delayed.covers(103));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_suspend_and_continue() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+
+        assert!(send_signal(child.id(), Signal::Stop).unwrap());
+        assert!(send_signal(child.id(), Signal::Continue).unwrap());
+        assert!(send_signal(child.id(), Signal::Kill).unwrap());
+        child.wait().unwrap();
+
+        assert!(matches!(send_signal(u32::MAX - 1, Signal::Stop), Err(Error::ProcessNotFound(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pause_covers_process_tree() {
+        let mut parent = std::process::Command::new("sh")
+            .arg("-c")
+            .arg("sleep 30 & wait")
+            .spawn()
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        let pids = termination::process_tree(parent.id(), &|_| true).unwrap();
+        assert_eq!(pids[0], parent.id());
+        assert!(pids.len() >= 2, "child sleep should be part of the tree: {:?}", pids);
+
+        assert!(signal_tree(&pids, Signal::Stop).unwrap());
+        assert!(signal_tree(&pids, Signal::Continue).unwrap());
+        assert!(signal_tree(&pids, Signal::Kill).unwrap());
+        parent.wait().unwrap();
+    }
+}
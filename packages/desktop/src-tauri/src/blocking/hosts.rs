@@ -1,12 +1,41 @@
 // blocking/hosts.rs - Cross-platform hosts file manipulation
-
-use crate::{Error, Result};
-use chrono::Utc;
-use std::path::PathBuf;
+//
+// Every rewrite is preceded by a backup (`hosts.focusflow.backup.<timestamp>`
+// next to the hosts file), rotated to the newest `MAX_HOSTS_BACKUPS`. While
+// blocking is applied, an integrity loop re-applies the FocusFlow section if
+// another tool removes or edits it, and records a tamper event.
+//
+// On Unix every user gets their own section, keyed by uid in its markers
+// (`# FocusFlow BLOCK START uid=1000`), so users sharing a machine (and their
+// privileged helpers) never overwrite or clear each other's blocks. Sections
+// without a uid were written by older versions and are replaced by whoever
+// writes next.
+
+use crate::{
+    blocking::{doh, rules::WebsiteMatcher},
+    db::queries,
+    helper,
+    state::AppState,
+    Error, Result,
+};
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Emitter;
 
 const FOCUSFLOW_MARKER_START: &str = "# FocusFlow BLOCK START";
 const FOCUSFLOW_MARKER_END: &str = "# FocusFlow BLOCK END";
 
+/// Number of hosts file backups kept
+pub const MAX_HOSTS_BACKUPS: usize = 10;
+
+/// How often the applied FocusFlow section is verified
+const INTEGRITY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Millisecond precision keeps backups taken in quick succession apart
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S%.3f";
+
 /// Get platform-specific hosts file path
 pub fn get_hosts_path() -> PathBuf {
     #[cfg(target_os = "windows")]
@@ -67,154 +96,523 @@ pub async fn check_hosts_permissions() -> bool {
 
 /// Update hosts file with blocked domains
 ///
-/// This function requires elevated privileges on all platforms.
+/// Takes website rules; only plain domain rules that are not covered by an
+/// exception can be expressed in the hosts file (see `WebsiteMatcher::hosts_domains`).
+///
+/// This function requires elevated privileges, either of the app itself or
+/// through the privileged helper (see `crate::helper`).
 /// Uses atomic write pattern: read -> modify -> write to temp -> rename
-pub async fn update_hosts_file(domains: &[String]) -> Result<()> {
-    // Read existing hosts file
-    let content = read_hosts_file().await?;
+pub async fn update_hosts_file(state: &AppState, domains: &[String]) -> Result<()> {
+    let mut domains = WebsiteMatcher::compile(domains).hosts_domains();
+
+    // Keep browsers from resolving around the block over DNS-over-HTTPS
+    if !domains.is_empty() {
+        for extra in doh::active_settings().extra_domains() {
+            if !domains.contains(&extra) {
+                domains.push(extra);
+            }
+        }
+    }
 
-    // Remove old FocusFlow entries
-    let cleaned = remove_focusflow_entries(&content);
+    apply_to_system(&domains).await?;
+    *state.applied_hosts_domains.write().await = domains;
 
-    // Add new entries if any domains provided
-    let new_content = if domains.is_empty() {
-        cleaned
-    } else {
-        add_focusflow_entries(&cleaned, domains)
-    };
+    Ok(())
+}
 
-    // Write atomically
-    write_hosts_file(&new_content).await?;
+/// Write hosts-compatible domains to the system hosts file
+///
+/// Falls back to the privileged helper when the app cannot write the file.
+async fn apply_to_system(domains: &[String]) -> Result<()> {
+    match HostsFile::system().apply(domains).await {
+        Err(Error::PermissionDenied(message)) => {
+            if !helper::is_available().await {
+                return Err(Error::PermissionDenied(message));
+            }
+            tracing::debug!("Hosts file not writable, using the privileged helper");
+            helper::HelperClient::default().apply_blocklist(domains).await
+        }
+        result => result,
+    }
+}
 
-    tracing::info!("Updated hosts file with {} domains", domains.len());
+/// Clear all FocusFlow entries from hosts file
+pub async fn clear_hosts_file(state: &AppState) -> Result<()> {
+    update_hosts_file(state, &[]).await
+}
 
-    Ok(())
+/// Check whether the hosts file still contains a FocusFlow block section
+///
+/// Used at startup to detect entries left behind by a crash without
+/// rewriting (and backing up) the hosts file when there is nothing to clean.
+pub async fn has_focusflow_entries() -> Result<bool> {
+    HostsFile::system().has_focusflow_entries().await
 }
 
-/// Clear all FocusFlow entries from hosts file
-pub async fn clear_hosts_file() -> Result<()> {
-    update_hosts_file(&[]).await
+/// List the backups of the hosts file, newest first
+pub async fn list_hosts_backups() -> Result<Vec<HostsBackup>> {
+    HostsFile::system().list_backups().await
 }
 
-/// Read hosts file with error handling for permission issues
-async fn read_hosts_file() -> Result<String> {
-    let hosts_path = get_hosts_path();
+/// Restore the hosts file from one of its backups
+///
+/// Blocking that is currently applied is written back on top of the restored
+/// file, so a restore never lifts an active block.
+pub async fn restore_hosts_backup(state: &AppState, name: &str) -> Result<()> {
+    let hosts_file = HostsFile::system();
+    hosts_file.restore_backup(name).await?;
 
-    match tokio::fs::read_to_string(&hosts_path).await {
-        Ok(content) => Ok(content),
-        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
-            Err(Error::PermissionDenied(format!(
-                "Cannot read hosts file at {}. Please run with elevated privileges.",
-                hosts_path.display()
-            )))
-        }
-        Err(e) => Err(e.into()),
+    let applied = state.applied_hosts_domains.read().await.clone();
+    if !applied.is_empty() {
+        apply_to_system(&applied).await?;
     }
+
+    Ok(())
 }
 
-/// Create a backup of the hosts file before modification
-async fn backup_hosts_file() -> Result<PathBuf> {
-    let hosts_path = get_hosts_path();
-    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-    let backup_path = hosts_path.with_extension(format!("focusflow.backup.{}", timestamp));
+/// A backup of the hosts file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostsBackup {
+    /// File name, used to select the backup to restore
+    pub name: String,
+    pub path: String,
+    pub created_at: Option<chrono::DateTime<Utc>>,
+    pub size_bytes: u64,
+}
 
-    // Read current content
-    let content = tokio::fs::read_to_string(&hosts_path).await?;
+/// State of the FocusFlow section compared to the domains that were applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostsIntegrity {
+    Intact,
+    /// The FocusFlow section (or one of its markers) is gone
+    Missing,
+    /// The FocusFlow section is present but its entries were edited
+    Modified,
+}
 
-    // Write backup
-    tokio::fs::write(&backup_path, &content).await?;
+/// A hosts file and its FocusFlow backups
+///
+/// Only the FocusFlow section of `owner` is written and checked.
+#[derive(Debug, Clone)]
+pub struct HostsFile {
+    path: PathBuf,
+    owner: Option<u32>,
+}
 
-    tracing::info!("Created hosts file backup at {}", backup_path.display());
+impl HostsFile {
+    /// The system hosts file (see `get_hosts_path`)
+    pub fn system() -> Self {
+        Self::at(get_hosts_path())
+    }
 
-    Ok(backup_path)
-}
+    /// A hosts file at a custom location, with the current user's section
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            owner: current_uid(),
+        }
+    }
 
-/// Clean up temporary files left by failed operations
-async fn cleanup_temp_files() {
-    let hosts_path = get_hosts_path();
-    let temp_path = hosts_path.with_extension("focusflow.tmp");
+    /// Use the section of another user (the privileged helper acts for its peer)
+    pub fn for_uid(mut self, uid: u32) -> Self {
+        self.owner = Some(uid);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 
-    if temp_path.exists() {
-        if let Err(e) = tokio::fs::remove_file(&temp_path).await {
-            tracing::warn!("Failed to clean up temp file {}: {}", temp_path.display(), e);
+    /// Replace the FocusFlow section with entries for the given domains
+    ///
+    /// Domains must already be hosts-compatible (see `update_hosts_file`).
+    pub async fn apply(&self, domains: &[String]) -> Result<()> {
+        // Read existing hosts file
+        let content = self.read().await?;
+
+        // Remove old FocusFlow entries
+        let cleaned = remove_focusflow_entries(&content, self.owner);
+
+        // Add new entries if any domains provided
+        let new_content = if domains.is_empty() {
+            cleaned
         } else {
-            tracing::debug!("Cleaned up orphaned temp file {}", temp_path.display());
+            add_focusflow_entries(&cleaned, domains, self.owner)
+        };
+
+        // Write atomically
+        self.write(&new_content).await?;
+
+        tracing::info!("Updated hosts file with {} domains", domains.len());
+
+        Ok(())
+    }
+
+    /// Check whether the file contains a FocusFlow block section `apply` replaces
+    pub async fn has_focusflow_entries(&self) -> Result<bool> {
+        let content = self.read().await?;
+        let (start, _) = section_markers(self.owner);
+        Ok(content
+            .lines()
+            .any(|line| is_marker(line, &start) || is_marker(line, FOCUSFLOW_MARKER_START)))
+    }
+
+    /// Compare the FocusFlow section with the entries `apply` would write
+    pub async fn check_integrity(&self, domains: &[String]) -> Result<HostsIntegrity> {
+        let content = self.read().await?;
+        Ok(check_focusflow_section(&content, domains, self.owner))
+    }
+
+    /// List the backups of this file, newest first
+    pub async fn list_backups(&self) -> Result<Vec<HostsBackup>> {
+        let Some(dir) = self.path.parent() else {
+            return Ok(Vec::new());
+        };
+        let prefix = self.backup_prefix();
+
+        let mut backups = Vec::new();
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(timestamp) = name.strip_prefix(&prefix) else {
+                continue;
+            };
+
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            backups.push(HostsBackup {
+                created_at: parse_backup_timestamp(timestamp),
+                path: entry.path().display().to_string(),
+                size_bytes: metadata.len(),
+                name,
+            });
         }
+
+        // Timestamps sort lexicographically
+        backups.sort_by(|a, b| b.name.cmp(&a.name));
+
+        Ok(backups)
     }
-}
 
-/// Write hosts file atomically with proper error handling
-///
-/// Creates a backup before modification and cleans up temp files on failure
-async fn write_hosts_file(content: &str) -> Result<()> {
-    let hosts_path = get_hosts_path();
-    let temp_path = hosts_path.with_extension("focusflow.tmp");
+    /// Replace the file with the content of one of its backups
+    pub async fn restore_backup(&self, name: &str) -> Result<()> {
+        // Only restore listed backups, never an arbitrary path
+        let backup = self
+            .list_backups()
+            .await?
+            .into_iter()
+            .find(|backup| backup.name == name)
+            .ok_or_else(|| Error::NotFound(format!("Hosts file backup not found: {}", name)))?;
 
-    // Clean up any leftover temp files from previous failed operations
-    cleanup_temp_files().await;
+        let content = tokio::fs::read_to_string(&backup.path).await?;
+        self.write(&content).await?;
 
-    // Create backup before modification
-    if let Err(e) = backup_hosts_file().await {
-        tracing::warn!("Could not create backup (continuing anyway): {}", e);
+        tracing::info!("Restored hosts file from backup {}", backup.name);
+
+        Ok(())
     }
 
-    // Write to temporary file first
-    match tokio::fs::write(&temp_path, content).await {
-        Ok(_) => {}
-        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
-            return Err(Error::PermissionDenied("Cannot write hosts file. Please run with elevated privileges.\n\
-                 On macOS/Linux: Use sudo or grant accessibility permissions\n\
-                 On Windows: Run as administrator".to_string()));
+    fn backup_prefix(&self) -> String {
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "hosts".to_string());
+        format!("{}.focusflow.backup.", file_name)
+    }
+
+    /// Read hosts file with error handling for permission issues
+    async fn read(&self) -> Result<String> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                Err(Error::PermissionDenied(format!(
+                    "Cannot read hosts file at {}. Please run with elevated privileges.",
+                    self.path.display()
+                )))
+            }
+            Err(e) => Err(e.into()),
         }
-        Err(e) => {
-            // Clean up temp file on failure
-            cleanup_temp_files().await;
-            return Err(e.into());
+    }
+
+    /// Create a backup of the hosts file before modification
+    ///
+    /// Skipped when the newest backup already has the same content. Older
+    /// backups beyond `MAX_HOSTS_BACKUPS` are removed.
+    async fn backup(&self) -> Result<PathBuf> {
+        // Read current content
+        let content = tokio::fs::read_to_string(&self.path).await?;
+
+        let backups = self.list_backups().await?;
+        if let Some(newest) = backups.first() {
+            if tokio::fs::read_to_string(&newest.path).await.ok().as_deref() == Some(content.as_str()) {
+                return Ok(PathBuf::from(&newest.path));
+            }
         }
+
+        let timestamp = Utc::now().format(BACKUP_TIMESTAMP_FORMAT);
+        let backup_path = self
+            .path
+            .with_file_name(format!("{}{}", self.backup_prefix(), timestamp));
+
+        // Write backup
+        tokio::fs::write(&backup_path, &content).await?;
+
+        tracing::info!("Created hosts file backup at {}", backup_path.display());
+
+        self.rotate_backups().await;
+
+        Ok(backup_path)
     }
 
-    // Atomic rename (with fallback for Windows)
-    #[cfg(target_os = "windows")]
-    {
-        // Windows may not support atomic rename to existing file
-        // Use copy + delete pattern as fallback
-        match tokio::fs::rename(&temp_path, &hosts_path).await {
+    /// Remove all but the newest `MAX_HOSTS_BACKUPS` backups
+    async fn rotate_backups(&self) {
+        let backups = match self.list_backups().await {
+            Ok(backups) => backups,
+            Err(e) => {
+                tracing::warn!("Failed to list hosts file backups: {}", e);
+                return;
+            }
+        };
+
+        for backup in backups.iter().skip(MAX_HOSTS_BACKUPS) {
+            if let Err(e) = tokio::fs::remove_file(&backup.path).await {
+                tracing::warn!("Failed to remove old hosts backup {}: {}", backup.path, e);
+            } else {
+                tracing::debug!("Removed old hosts backup {}", backup.path);
+            }
+        }
+    }
+
+    fn temp_path(&self) -> PathBuf {
+        self.path.with_extension("focusflow.tmp")
+    }
+
+    /// Clean up temporary files left by failed operations
+    async fn cleanup_temp_files(&self) {
+        let temp_path = self.temp_path();
+
+        if temp_path.exists() {
+            if let Err(e) = tokio::fs::remove_file(&temp_path).await {
+                tracing::warn!("Failed to clean up temp file {}: {}", temp_path.display(), e);
+            } else {
+                tracing::debug!("Cleaned up orphaned temp file {}", temp_path.display());
+            }
+        }
+    }
+
+    /// Write hosts file atomically with proper error handling
+    ///
+    /// Creates a backup before modification and cleans up temp files on failure
+    async fn write(&self, content: &str) -> Result<()> {
+        let hosts_path = &self.path;
+        let temp_path = self.temp_path();
+
+        // Clean up any leftover temp files from previous failed operations
+        self.cleanup_temp_files().await;
+
+        // Create backup before modification
+        if let Err(e) = self.backup().await {
+            tracing::warn!("Could not create backup (continuing anyway): {}", e);
+        }
+
+        // Write to temporary file first
+        match tokio::fs::write(&temp_path, content).await {
             Ok(_) => {}
-            Err(_) => {
-                // Fallback: copy content then remove temp
-                tokio::fs::copy(&temp_path, &hosts_path).await?;
-                let _ = tokio::fs::remove_file(&temp_path).await;
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                return Err(Error::PermissionDenied("Cannot write hosts file. Please run with elevated privileges.\n\
+                     On macOS/Linux: Use sudo or grant accessibility permissions\n\
+                     On Windows: Run as administrator".to_string()));
+            }
+            Err(e) => {
+                // Clean up temp file on failure
+                self.cleanup_temp_files().await;
+                return Err(e.into());
+            }
+        }
+
+        // Atomic rename (with fallback for Windows)
+        #[cfg(target_os = "windows")]
+        {
+            // Windows may not support atomic rename to existing file
+            // Use copy + delete pattern as fallback
+            match tokio::fs::rename(&temp_path, hosts_path).await {
+                Ok(_) => {}
+                Err(_) => {
+                    // Fallback: copy content then remove temp
+                    tokio::fs::copy(&temp_path, hosts_path).await?;
+                    let _ = tokio::fs::remove_file(&temp_path).await;
+                }
+            }
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            if let Err(e) = tokio::fs::rename(&temp_path, hosts_path).await {
+                // Clean up temp file on failure
+                self.cleanup_temp_files().await;
+                return Err(e.into());
             }
         }
+
+        // Flush DNS cache after modifying hosts file
+        flush_dns_cache().await;
+
+        Ok(())
     }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        if let Err(e) = tokio::fs::rename(&temp_path, &hosts_path).await {
-            // Clean up temp file on failure
-            cleanup_temp_files().await;
-            return Err(e.into());
+/// Parse a backup timestamp, including the second-precision format of older backups
+fn parse_backup_timestamp(timestamp: &str) -> Option<chrono::DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(timestamp, "%Y%m%d_%H%M%S"))
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+/// Periodically verify the applied FocusFlow section and repair it
+///
+/// Runs for the lifetime of the app. Only checks while domains are applied,
+/// so hosts files FocusFlow cannot write are never reported as tampered.
+pub async fn start_integrity_loop(state: AppState) -> Result<()> {
+    let mut interval = tokio::time::interval(INTEGRITY_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        verify_integrity(&state).await;
+    }
+}
+
+/// Check the applied FocusFlow section once and repair it if needed
+///
+/// Also run after the system wakes from sleep, when other tools (VPN clients,
+/// network managers) are most likely to have rewritten the hosts file.
+pub async fn verify_integrity(state: &AppState) {
+    let applied = state.applied_hosts_domains.read().await.clone();
+    if applied.is_empty() {
+        return;
+    }
+
+    let hosts_file = HostsFile::system();
+    let integrity = match hosts_file.check_integrity(&applied).await {
+        Ok(integrity) => integrity,
+        Err(e) => {
+            tracing::debug!("Hosts integrity check skipped: {}", e);
+            return;
         }
+    };
+
+    if integrity == HostsIntegrity::Intact {
+        return;
     }
 
-    // Flush DNS cache after modifying hosts file
-    flush_dns_cache().await;
+    // The blocklist may have been cleared while the file was being read
+    if *state.applied_hosts_domains.read().await != applied {
+        return;
+    }
 
-    Ok(())
+    let details = format!(
+        "FocusFlow hosts entries were {} in {}",
+        if integrity == HostsIntegrity::Missing { "removed" } else { "edited" },
+        hosts_file.path().display()
+    );
+    tracing::warn!("Hosts file tampering detected: {}", details);
+
+    if let Err(e) = apply_to_system(&applied).await {
+        tracing::error!("Failed to re-apply hosts file entries: {}", e);
+    }
+
+    if let Err(e) = queries::record_tamper_event(state.pool(), "hosts_modified", &details).await {
+        tracing::error!("Failed to record tamper event: {}", e);
+    }
+
+    if let Err(e) = state.app_handle.emit(
+        "tamper-detected",
+        serde_json::json!({
+            "eventType": "hosts_modified",
+            "details": details,
+        }),
+    ) {
+        tracing::warn!("Failed to emit tamper-detected: {}", e);
+    }
+}
+
+/// User whose section this process writes; `None` where there are no uids
+fn current_uid() -> Option<u32> {
+    #[cfg(unix)]
+    {
+        // SAFETY: getuid is always successful and has no side effects
+        Some(unsafe { libc::getuid() })
+    }
+
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/// Start and end markers of the section of `owner`
+fn section_markers(owner: Option<u32>) -> (String, String) {
+    match owner {
+        Some(uid) => (
+            format!("{} uid={}", FOCUSFLOW_MARKER_START, uid),
+            format!("{} uid={}", FOCUSFLOW_MARKER_END, uid),
+        ),
+        None => (FOCUSFLOW_MARKER_START.to_string(), FOCUSFLOW_MARKER_END.to_string()),
+    }
+}
+
+/// Markers are matched as whole lines, so one user's marker is never taken
+/// for another's
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.trim() == marker
+}
+
+/// Compare the FocusFlow section of hosts content with the expected entries
+fn check_focusflow_section(content: &str, domains: &[String], owner: Option<u32>) -> HostsIntegrity {
+    let (start_marker, end_marker) = section_markers(owner);
+    let lines: Vec<&str> = content.lines().map(|line| line.trim_end()).collect();
+
+    let Some(start) = lines.iter().position(|line| is_marker(line, &start_marker)) else {
+        return HostsIntegrity::Missing;
+    };
+    let Some(len) = lines[start + 1..]
+        .iter()
+        .position(|line| is_marker(line, &end_marker))
+    else {
+        return HostsIntegrity::Missing;
+    };
+
+    let expected = add_focusflow_entries("", domains, owner);
+    let expected: Vec<&str> = expected.lines().skip_while(|line| line.is_empty()).collect();
+
+    if lines[start..start + len + 2] == expected[..] {
+        HostsIntegrity::Intact
+    } else {
+        HostsIntegrity::Modified
+    }
 }
 
-/// Remove existing FocusFlow entries from hosts content
-fn remove_focusflow_entries(content: &str) -> String {
+/// Remove the FocusFlow entries of `owner` from hosts content
+///
+/// Sections without a uid (from older versions) are removed too; the sections
+/// of other users are kept.
+fn remove_focusflow_entries(content: &str, owner: Option<u32>) -> String {
+    let (start_marker, end_marker) = section_markers(owner);
     let mut result = String::new();
     let mut skip = false;
 
     for line in content.lines() {
-        if line.contains(FOCUSFLOW_MARKER_START) {
+        if is_marker(line, &start_marker) || is_marker(line, FOCUSFLOW_MARKER_START) {
             skip = true;
             continue;
         }
 
-        if line.contains(FOCUSFLOW_MARKER_END) {
+        if is_marker(line, &end_marker) || is_marker(line, FOCUSFLOW_MARKER_END) {
             skip = false;
             continue;
         }
@@ -228,10 +626,11 @@ fn remove_focusflow_entries(content: &str) -> String {
     result
 }
 
-/// Add FocusFlow entries to hosts content
+/// Add a FocusFlow section for `owner` to hosts content
 ///
 /// Security: Validates domains before writing to hosts file to prevent injection
-fn add_focusflow_entries(content: &str, domains: &[String]) -> String {
+fn add_focusflow_entries(content: &str, domains: &[String], owner: Option<u32>) -> String {
+    let (start_marker, end_marker) = section_markers(owner);
     let mut result = content.to_string();
 
     // Ensure content ends with newline
@@ -240,7 +639,7 @@ fn add_focusflow_entries(content: &str, domains: &[String]) -> String {
     }
 
     // Add FocusFlow section
-    result.push_str(FOCUSFLOW_MARKER_START);
+    result.push_str(&start_marker);
     result.push('\n');
 
     for domain in domains {
@@ -267,7 +666,7 @@ fn add_focusflow_entries(content: &str, domains: &[String]) -> String {
         }
     }
 
-    result.push_str(FOCUSFLOW_MARKER_END);
+    result.push_str(&end_marker);
     result.push('\n');
 
     result
@@ -546,7 +945,7 @@ mod tests {
 # FocusFlow BLOCK END\n\
 192.168.1.1 router\n";
 
-        let result = remove_focusflow_entries(content);
+        let result = remove_focusflow_entries(content, None);
         assert!(!result.contains("facebook.com"));
         assert!(result.contains("localhost"));
         assert!(result.contains("router"));
@@ -564,7 +963,7 @@ mod tests {
 # FocusFlow BLOCK END\n\
 10.0.0.1 internal\n";
 
-        let result = remove_focusflow_entries(content);
+        let result = remove_focusflow_entries(content, None);
 
         // All non-FocusFlow content should be preserved
         assert!(result.contains("127.0.0.1 localhost"));
@@ -586,7 +985,7 @@ mod tests {
 ::1 localhost\n\
 192.168.1.1 router\n";
 
-        let result = remove_focusflow_entries(content);
+        let result = remove_focusflow_entries(content, None);
 
         // Content should be preserved as-is
         assert!(result.contains("127.0.0.1 localhost"));
@@ -608,7 +1007,7 @@ mod tests {
 # FocusFlow BLOCK END\n\
 10.0.0.1 internal\n";
 
-        let result = remove_focusflow_entries(content);
+        let result = remove_focusflow_entries(content, None);
 
         assert!(!result.contains("facebook.com"));
         assert!(!result.contains("twitter.com"));
@@ -625,7 +1024,7 @@ mod tests {
 # FocusFlow BLOCK END\n\
 192.168.1.1 router\n";
 
-        let result = remove_focusflow_entries(content);
+        let result = remove_focusflow_entries(content, None);
 
         assert!(result.contains("localhost"));
         assert!(result.contains("router"));
@@ -635,7 +1034,7 @@ mod tests {
 
     #[test]
     fn test_remove_focusflow_entries_handles_empty_content() {
-        let result = remove_focusflow_entries("");
+        let result = remove_focusflow_entries("", None);
         assert_eq!(result, "");
     }
 
@@ -647,9 +1046,8 @@ mod tests {
 # This is not FocusFlow BLOCK START but similar\n\
 192.168.1.1 router\n";
 
-        let result = remove_focusflow_entries(content);
-        // Content should be removed because it contains the marker text
-        // This is expected behavior - the marker is checked with contains()
+        let result = remove_focusflow_entries(content, None);
+        // Markers are matched as whole lines, so nothing is removed
         assert!(result.contains("localhost"));
         assert!(result.contains("router"));
     }
@@ -663,7 +1061,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n";
         let domains = vec!["twitter.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
         assert!(result.contains("127.0.0.1 twitter.com"));
         assert!(result.contains("127.0.0.1 www.twitter.com"));
         assert!(result.contains(FOCUSFLOW_MARKER_START));
@@ -679,7 +1077,7 @@ mod tests {
             "instagram.com".to_string(),
         ];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Check all domains are added
         assert!(result.contains("127.0.0.1 facebook.com"));
@@ -699,7 +1097,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n";
         let domains = vec!["www.example.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // www.example.com should not add www.www.example.com
         assert!(result.contains("127.0.0.1 www.example.com"));
@@ -711,7 +1109,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n";
         let domains: Vec<String> = vec![];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Should still add markers even with no domains
         assert!(result.contains(FOCUSFLOW_MARKER_START));
@@ -728,7 +1126,7 @@ mod tests {
             "another-valid.com".to_string(),
         ];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Valid domains should be added
         assert!(result.contains("127.0.0.1 valid.com"));
@@ -743,7 +1141,7 @@ mod tests {
         let content = "127.0.0.1 localhost"; // No trailing newline
         let domains = vec!["example.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Should add newline before FocusFlow section
         assert!(result.contains("localhost\n"));
@@ -755,7 +1153,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n";
         let domains = vec!["example.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Check IPv4 blocking
         assert!(result.contains("127.0.0.1 example.com"));
@@ -775,7 +1173,7 @@ mod tests {
 192.168.1.1 myrouter\n";
 
         let domains = vec!["blocked.com".to_string()];
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Original content should be preserved
         assert!(result.contains("127.0.0.1 localhost"));
@@ -832,14 +1230,14 @@ mod tests {
         let domains = vec!["facebook.com".to_string(), "twitter.com".to_string()];
 
         // Add entries
-        let with_entries = add_focusflow_entries(original_content, &domains);
+        let with_entries = add_focusflow_entries(original_content, &domains, None);
 
         // Verify entries were added
         assert!(with_entries.contains("facebook.com"));
         assert!(with_entries.contains("twitter.com"));
 
         // Remove entries
-        let cleaned = remove_focusflow_entries(&with_entries);
+        let cleaned = remove_focusflow_entries(&with_entries, None);
 
         // Verify original content is preserved
         assert!(cleaned.contains("127.0.0.1 localhost"));
@@ -858,16 +1256,16 @@ mod tests {
         let original = "127.0.0.1 localhost\n";
 
         // First cycle
-        let with_entries1 = add_focusflow_entries(original, &["site1.com".to_string()]);
-        let cleaned1 = remove_focusflow_entries(&with_entries1);
+        let with_entries1 = add_focusflow_entries(original, &["site1.com".to_string()], None);
+        let cleaned1 = remove_focusflow_entries(&with_entries1, None);
 
         // Second cycle
-        let with_entries2 = add_focusflow_entries(&cleaned1, &["site2.com".to_string()]);
-        let cleaned2 = remove_focusflow_entries(&with_entries2);
+        let with_entries2 = add_focusflow_entries(&cleaned1, &["site2.com".to_string()], None);
+        let cleaned2 = remove_focusflow_entries(&with_entries2, None);
 
         // Third cycle
-        let with_entries3 = add_focusflow_entries(&cleaned2, &["site3.com".to_string()]);
-        let cleaned3 = remove_focusflow_entries(&with_entries3);
+        let with_entries3 = add_focusflow_entries(&cleaned2, &["site3.com".to_string()], None);
+        let cleaned3 = remove_focusflow_entries(&with_entries3, None);
 
         // Original content should be preserved after multiple cycles
         assert!(cleaned3.contains("localhost"));
@@ -881,11 +1279,11 @@ mod tests {
         let original = "127.0.0.1 localhost\n";
 
         // Add initial entries
-        let with_entries1 = add_focusflow_entries(original, &["facebook.com".to_string()]);
+        let with_entries1 = add_focusflow_entries(original, &["facebook.com".to_string()], None);
 
         // Remove and add different entries
-        let cleaned = remove_focusflow_entries(&with_entries1);
-        let with_entries2 = add_focusflow_entries(&cleaned, &["twitter.com".to_string()]);
+        let cleaned = remove_focusflow_entries(&with_entries1, None);
+        let with_entries2 = add_focusflow_entries(&cleaned, &["twitter.com".to_string()], None);
 
         // Should only have twitter.com, not facebook.com
         assert!(with_entries2.contains("twitter.com"));
@@ -897,7 +1295,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n";
         let domains = vec!["api.facebook.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Subdomain should be blocked as-is
         assert!(result.contains("127.0.0.1 api.facebook.com"));
@@ -914,7 +1312,7 @@ mod tests {
             "example.io".to_string(),
         ];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         assert!(result.contains("127.0.0.1 example.co.uk"));
         assert!(result.contains("127.0.0.1 example.com.au"));
@@ -926,7 +1324,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n";
         let domains = vec!["123.example.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         assert!(result.contains("127.0.0.1 123.example.com"));
     }
@@ -1003,7 +1401,7 @@ mod tests {
             .map(|i| format!("domain{}.com", i))
             .collect();
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // All domains should be present
         for i in 0..1000 {
@@ -1028,7 +1426,7 @@ mod tests {
         }
 
         let domains = vec!["blocked.com".to_string()];
-        let result = add_focusflow_entries(&content, &domains);
+        let result = add_focusflow_entries(&content, &domains, None);
 
         // Original entries should be preserved
         assert!(result.contains("192.168.0.0 server0"));
@@ -1051,7 +1449,7 @@ mod tests {
             "FACEBOOK.COM".to_string(),
         ];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Count occurrences of the domain entry
         let count = result.matches("127.0.0.1 facebook.com").count();
@@ -1070,7 +1468,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n";
         let domains = vec!["a.co".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
         assert!(result.contains("127.0.0.1 a.co"));
     }
 
@@ -1117,7 +1515,7 @@ mod tests {
         let content = "127.0.0.1 localhost\r\n::1 localhost\r\n";
         let domains = vec!["example.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Original content should be preserved
         assert!(result.contains("localhost"));
@@ -1128,7 +1526,7 @@ mod tests {
         let content = "127.0.0.1 localhost\n::1 localhost\r\n192.168.1.1 router\r";
         let domains = vec!["example.com".to_string()];
 
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Should not crash and should add entries
         assert!(result.contains(FOCUSFLOW_MARKER_START));
@@ -1145,11 +1543,123 @@ mod tests {
 \n";
 
         let domains = vec!["example.com".to_string()];
-        let result = add_focusflow_entries(content, &domains);
+        let result = add_focusflow_entries(content, &domains, None);
 
         // Empty lines should be preserved
         assert!(result.contains("localhost"));
         assert!(result.contains("router"));
         assert!(result.contains("example.com"));
     }
+
+    // =============================================================================
+    // Backup and Integrity Tests
+    // =============================================================================
+
+    fn temp_hosts_file(content: &str) -> HostsFile {
+        let dir = std::env::temp_dir().join(format!("focusflow-hosts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hosts");
+        std::fs::write(&path, content).unwrap();
+        HostsFile::at(path)
+    }
+
+    #[test]
+    fn test_check_focusflow_section() {
+        let domains = vec!["example.com".to_string()];
+        let content = add_focusflow_entries("127.0.0.1 localhost\n", &domains, None);
+
+        assert_eq!(check_focusflow_section(&content, &domains, None), HostsIntegrity::Intact);
+        assert_eq!(
+            check_focusflow_section(&content.replace("\n", "\r\n"), &domains, None),
+            HostsIntegrity::Intact
+        );
+        assert_eq!(
+            check_focusflow_section(&remove_focusflow_entries(&content, None), &domains, None),
+            HostsIntegrity::Missing
+        );
+        assert_eq!(
+            check_focusflow_section(&content.replace(FOCUSFLOW_MARKER_END, ""), &domains, None),
+            HostsIntegrity::Missing
+        );
+        assert_eq!(
+            check_focusflow_section(&content.replace("127.0.0.1 example.com\n", ""), &domains, None),
+            HostsIntegrity::Modified
+        );
+        assert_eq!(
+            check_focusflow_section(&content, &["other.com".to_string()], None),
+            HostsIntegrity::Modified
+        );
+    }
+
+    #[test]
+    fn test_sections_are_per_user() {
+        let domains = vec!["example.com".to_string()];
+        let content = add_focusflow_entries("127.0.0.1 localhost\n", &domains, Some(1000));
+        let content = add_focusflow_entries(&content, &["other.com".to_string()], Some(1001));
+        assert!(content.contains("# FocusFlow BLOCK START uid=1000\n"));
+
+        assert_eq!(check_focusflow_section(&content, &domains, Some(1000)), HostsIntegrity::Intact);
+        assert_eq!(check_focusflow_section(&content, &domains, None), HostsIntegrity::Missing);
+
+        // Clearing one user's section keeps the other's
+        let cleared = remove_focusflow_entries(&content, Some(1000));
+        assert!(!cleared.contains("example.com"));
+        assert_eq!(
+            check_focusflow_section(&cleared, &["other.com".to_string()], Some(1001)),
+            HostsIntegrity::Intact
+        );
+
+        // A section from before sections were per user is taken over
+        let legacy = add_focusflow_entries("127.0.0.1 localhost\n", &domains, None);
+        assert_eq!(remove_focusflow_entries(&legacy, Some(1000)), "127.0.0.1 localhost\n");
+    }
+
+    #[tokio::test]
+    async fn test_apply_backs_up_and_restores() {
+        let original = "127.0.0.1 localhost\n";
+        let hosts_file = temp_hosts_file(original);
+        let domains = vec!["example.com".to_string()];
+
+        hosts_file.apply(&domains).await.unwrap();
+        assert_eq!(hosts_file.check_integrity(&domains).await.unwrap(), HostsIntegrity::Intact);
+
+        let backups = hosts_file.list_backups().await.unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].name.starts_with("hosts.focusflow.backup."));
+        assert!(backups[0].created_at.is_some());
+
+        // Content matching the newest backup is not backed up twice
+        hosts_file.apply(&domains).await.unwrap();
+        hosts_file.apply(&domains).await.unwrap();
+        assert_eq!(hosts_file.list_backups().await.unwrap().len(), 2);
+
+        hosts_file.restore_backup(&backups[0].name).await.unwrap();
+        assert_eq!(std::fs::read_to_string(hosts_file.path()).unwrap(), original);
+
+        let err = hosts_file.restore_backup("../../etc/passwd").await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+
+        std::fs::remove_dir_all(hosts_file.path().parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_backup_rotation() {
+        let hosts_file = temp_hosts_file("127.0.0.1 localhost\n");
+        let dir = hosts_file.path().parent().unwrap().to_path_buf();
+
+        for day in 1..=MAX_HOSTS_BACKUPS + 3 {
+            let name = format!("hosts.focusflow.backup.202401{:02}_120000", day);
+            std::fs::write(dir.join(name), format!("backup {}\n", day)).unwrap();
+        }
+
+        hosts_file.backup().await.unwrap();
+
+        let backups = hosts_file.list_backups().await.unwrap();
+        assert_eq!(backups.len(), MAX_HOSTS_BACKUPS);
+        // The new backup is kept, the oldest ones are gone
+        assert!(!backups.iter().any(|b| b.name.ends_with("20240101_120000")));
+        assert!(backups.iter().any(|b| b.name.ends_with("20240113_120000")));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
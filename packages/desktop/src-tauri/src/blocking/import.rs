@@ -0,0 +1,886 @@
+// blocking/import.rs - Blocklist import and subscriptions
+//
+// Imports third-party lists into a named blocking category. Lines are parsed
+// one at a time, so the common formats (and files mixing them) all work:
+//
+//   0.0.0.0 ads.example.com      hosts file entries pointing at 0.0.0.0/127.0.0.1
+//   ads.example.com              plain domain lists
+//   ||ads.example.com^           Adblock-style domain rules
+//
+// Comments, localhost entries, cosmetic filters, exceptions and Adblock rules
+// narrowed by options (e.g. `$third-party`) are skipped: blocking here works on
+// whole domains, so importing them would block far more than the list meant to.
+//
+// Domains already in blocked_items are not copied into the category. A list
+// can be subscribed to, in which case it is refreshed in the background and
+// domains dropped from the list are removed from the category again. Removals
+// wait while the nuclear option is active, like any other weakening change.
+
+use crate::{
+    blocking::{blocklist, nuclear, rules::{RuleTarget, WebsiteRule}},
+    db::queries::{self, BlockingCategory, BlocklistSubscription},
+    state::AppState,
+    Error, Result,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::Duration;
+use tauri::Emitter;
+
+/// Refresh interval for new subscriptions
+pub const DEFAULT_REFRESH_INTERVAL_HOURS: i64 = 24;
+
+/// Shortest refresh interval a subscription may ask for
+const MIN_REFRESH_INTERVAL_HOURS: i64 = 1;
+
+/// How often the background task looks for subscriptions that are due
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Failed refreshes are retried after this long rather than the full interval
+const RETRY_AFTER_MINUTES: i64 = 60;
+
+/// Largest list downloaded or read
+const MAX_LIST_BYTES: usize = 32 * 1024 * 1024;
+
+/// Most domains accepted from a single list
+const MAX_LIST_DOMAINS: usize = 250_000;
+
+/// Added and removed domains listed in a report; the counts cover the rest
+const DIFF_SAMPLE_SIZE: usize = 100;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Hosts file addresses that mean "block"; entries pointing elsewhere are redirects
+const SINKHOLE_ADDRESSES: [&str; 4] = ["0.0.0.0", "127.0.0.1", "::", "::1"];
+
+/// Names every hosts file maps to itself
+const LOCAL_NAMES: [&str; 5] = [
+    "localhost",
+    "localhost.localdomain",
+    "local",
+    "broadcasthost",
+    "0.0.0.0",
+];
+
+/// Adblock options that still block the whole domain
+const DOMAIN_WIDE_OPTIONS: [&str; 4] = ["important", "all", "document", "doc"];
+
+/// Predominant format of an imported list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ListFormat {
+    Hosts,
+    #[default]
+    Plain,
+    Adblock,
+}
+
+/// Domains found in a list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedList {
+    /// Unique domains, in list order
+    pub domains: Vec<String>,
+    pub format: ListFormat,
+    /// Rules and entries that could not be imported
+    pub skipped_lines: usize,
+}
+
+/// Outcome of an import or subscription refresh
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub category_id: i64,
+    pub category_name: String,
+    pub subscription_id: Option<i64>,
+    pub source: String,
+    pub format: ListFormat,
+    /// Domains in the list
+    pub total_domains: usize,
+    /// Domains added to the category (at most 100 listed)
+    pub added: Vec<String>,
+    pub added_count: usize,
+    /// Domains removed from the category because the list dropped them (at most 100 listed)
+    pub removed: Vec<String>,
+    pub removed_count: usize,
+    /// List domains left out because they are already in the block list
+    pub duplicates: usize,
+    pub skipped_lines: usize,
+    /// Removals held back until the nuclear option ends
+    pub removals_deferred: bool,
+}
+
+/// Parse a hosts file, plain domain list or Adblock filter list
+pub fn parse_list(text: &str) -> ParsedList {
+    let mut parsed = ParsedList::default();
+    let mut seen = HashSet::new();
+    // Lines recognised per format: hosts, plain, adblock
+    let mut counts = [0usize; 3];
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        // `#` comments, and `!` comments and `[Adblock Plus 2.0]` headers in filter lists
+        if line.is_empty() || line.starts_with(['#', '!', '[']) {
+            continue;
+        }
+
+        let Some((format, names)) = parse_line(line) else {
+            parsed.skipped_lines += 1;
+            continue;
+        };
+
+        counts[format as usize] += 1;
+
+        for name in names {
+            match normalize_domain(name) {
+                Some(domain) => {
+                    if seen.insert(domain.clone()) {
+                        parsed.domains.push(domain);
+                    }
+                }
+                None => parsed.skipped_lines += 1,
+            }
+        }
+    }
+
+    let (hosts, plain, adblock) = (counts[0], counts[1], counts[2]);
+    parsed.format = if hosts > 0 && hosts >= plain && hosts >= adblock {
+        ListFormat::Hosts
+    } else if adblock > plain {
+        ListFormat::Adblock
+    } else {
+        ListFormat::Plain
+    };
+
+    parsed
+}
+
+/// Host names on one non-comment line, or `None` if it cannot be imported
+fn parse_line(line: &str) -> Option<(ListFormat, Vec<&str>)> {
+    if let Some(rule) = line.strip_prefix("||") {
+        return parse_adblock_rule(rule).map(|domain| (ListFormat::Adblock, vec![domain]));
+    }
+
+    // Cosmetic filters (`example.com##.ad`), exceptions and other filter syntax
+    if line.contains("##") || line.contains("#@#") || line.contains("#?#") || line.starts_with("@@") {
+        return None;
+    }
+    if line.contains(['|', '^', '$', '*', '/']) {
+        return None;
+    }
+
+    let line = line.split('#').next().unwrap_or_default();
+    let mut tokens = line.split_whitespace();
+    let first = tokens.next()?;
+
+    if first.parse::<IpAddr>().is_ok() {
+        if !SINKHOLE_ADDRESSES.contains(&first) {
+            return None;
+        }
+        let names = tokens
+            .filter(|name| !is_local_name(name))
+            .collect();
+        return Some((ListFormat::Hosts, names));
+    }
+
+    match tokens.next() {
+        None => Some((ListFormat::Plain, vec![first])),
+        Some(_) => None,
+    }
+}
+
+/// Domain of an Adblock `||domain^` rule (without the leading `||`)
+fn parse_adblock_rule(rule: &str) -> Option<&str> {
+    let (pattern, options) = match rule.split_once('$') {
+        Some((pattern, options)) => (pattern, Some(options)),
+        None => (rule, None),
+    };
+
+    if let Some(options) = options {
+        let domain_wide = options
+            .split(',')
+            .all(|option| DOMAIN_WIDE_OPTIONS.contains(&option.trim()));
+        if !domain_wide {
+            return None;
+        }
+    }
+
+    let domain = pattern
+        .strip_suffix("^|")
+        .or_else(|| pattern.strip_suffix('^'))
+        .unwrap_or(pattern);
+
+    if domain.contains(['/', '*', '^', '|']) {
+        return None;
+    }
+
+    Some(domain)
+}
+
+fn is_local_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    LOCAL_NAMES.contains(&name.as_str()) || name.starts_with("ip6-")
+}
+
+/// Canonical form of a listed domain, or `None` if it is not a plain domain
+fn normalize_domain(name: &str) -> Option<String> {
+    let name = name.trim().trim_end_matches('.');
+    if name.parse::<IpAddr>().is_ok() {
+        return None;
+    }
+
+    match WebsiteRule::parse(name) {
+        Ok(rule) if !rule.exception && matches!(rule.target, RuleTarget::Domain(_)) => Some(rule.to_string()),
+        _ => None,
+    }
+}
+
+/// Read a list from an http(s) URL or a local file
+pub async fn fetch_list(source: &str) -> Result<String> {
+    let source = source.trim();
+
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return download(source).await;
+    }
+
+    let path = Path::new(source.strip_prefix("file://").unwrap_or(source));
+    if !path.is_absolute() {
+        return Err(Error::InvalidInput(
+            "Blocklist source must be an http(s) URL or an absolute file path".to_string(),
+        ));
+    }
+
+    let metadata = tokio::fs::metadata(path).await?;
+    if metadata.len() > MAX_LIST_BYTES as u64 {
+        return Err(Error::InvalidInput(format!(
+            "Blocklist {} is larger than {} MB",
+            path.display(),
+            MAX_LIST_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let bytes = tokio::fs::read(path).await?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+async fn download(url: &str) -> Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("FocusFlow/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| Error::Network(format!("Failed to create HTTP client: {}", e)))?;
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| Error::Network(format!("Failed to download {}: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(Error::Network(format!(
+            "Downloading {} failed with status {}",
+            url,
+            response.status()
+        )));
+    }
+
+    let too_large = || {
+        Error::InvalidInput(format!(
+            "Blocklist {} is larger than {} MB",
+            url,
+            MAX_LIST_BYTES / (1024 * 1024)
+        ))
+    };
+
+    if response.content_length().unwrap_or(0) > MAX_LIST_BYTES as u64 {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Error::Network(format!("Failed to download {}: {}", url, e)))?
+    {
+        if bytes.len() + chunk.len() > MAX_LIST_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// A category's items after a list changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ListMerge {
+    items: Vec<String>,
+    added: Vec<String>,
+    removed: Vec<String>,
+    duplicates: usize,
+    /// Domains the list owns in the category; includes deferred removals
+    recorded: Vec<String>,
+    /// Removals held back until the nuclear option ends
+    deferred: usize,
+}
+
+/// Merge a list into a category's items
+///
+/// `previous` is what the list owned after the last import: the domains it
+/// added to the category. Owned domains it no longer contains are removed
+/// unless another subscription of the category (`others`) still provides them;
+/// domains that subscription added are shared. Domains the category already
+/// had are never taken over, so they survive the list dropping them. Domains
+/// in `blocked` (the block list) are not copied.
+fn merge_list(
+    items: &[String],
+    previous: &[String],
+    current: &[String],
+    others: &HashSet<String>,
+    blocked: &HashSet<String>,
+    allow_removals: bool,
+) -> ListMerge {
+    let current_set: HashSet<&str> = current.iter().map(String::as_str).collect();
+    let owned: HashSet<&str> = previous.iter().map(String::as_str).collect();
+    let dropped: HashSet<&str> = owned
+        .iter()
+        .copied()
+        .filter(|domain| !current_set.contains(domain) && !others.contains(*domain))
+        .collect();
+
+    let mut merge = ListMerge::default();
+    let mut deferred = Vec::new();
+
+    for item in items {
+        if dropped.contains(item.to_ascii_lowercase().as_str()) {
+            if allow_removals {
+                merge.removed.push(item.clone());
+                continue;
+            }
+            deferred.push(item.to_ascii_lowercase());
+        }
+        merge.items.push(item.clone());
+    }
+
+    let mut present: HashSet<String> = merge.items.iter().map(|item| item.to_ascii_lowercase()).collect();
+    for domain in current {
+        if !blocked.contains(domain) && present.insert(domain.clone()) {
+            merge.items.push(domain.clone());
+            merge.added.push(domain.clone());
+            merge.recorded.push(domain.clone());
+            continue;
+        }
+
+        if blocked.contains(domain) {
+            merge.duplicates += 1;
+        }
+        // Still in the category because this list (or a sibling list) put it there
+        if present.contains(domain) && (owned.contains(domain.as_str()) || others.contains(domain)) {
+            merge.recorded.push(domain.clone());
+        }
+    }
+
+    merge.deferred = deferred.len();
+    merge.recorded.extend(deferred);
+
+    merge
+}
+
+/// Import a list into a category, creating the category if needed
+///
+/// With `refresh_interval_hours` the category is also subscribed to the list.
+/// Importing a source that is already subscribed refreshes that subscription.
+pub async fn import_list(
+    state: &AppState,
+    source: &str,
+    category_name: &str,
+    refresh_interval_hours: Option<i64>,
+) -> Result<ImportReport> {
+    let source = source.trim();
+    let category_name = category_name.trim();
+
+    if source.is_empty() {
+        return Err(Error::InvalidInput("Blocklist source cannot be empty".to_string()));
+    }
+    if category_name.is_empty() || category_name.len() > 100 {
+        return Err(Error::InvalidInput(
+            "Category name must be between 1 and 100 characters".to_string(),
+        ));
+    }
+    if let Some(hours) = refresh_interval_hours {
+        if hours < MIN_REFRESH_INTERVAL_HOURS {
+            return Err(Error::InvalidInput(format!(
+                "Refresh interval must be at least {} hour",
+                MIN_REFRESH_INTERVAL_HOURS
+            )));
+        }
+    }
+
+    let parsed = parse_list(&fetch_list(source).await?);
+    check_parsed(source, &parsed)?;
+
+    let pool = state.pool();
+    let category = match queries::get_blocking_category_by_name(pool, category_name).await? {
+        Some(category) => category,
+        None => {
+            let description = format!("Imported from {}", source);
+            let id = queries::insert_blocking_category(pool, category_name, Some(&description), "[]").await?;
+            tracing::info!("Created blocking category '{}' for {}", category_name, source);
+            queries::get_blocking_category(pool, id)
+                .await?
+                .ok_or_else(|| Error::NotFound(format!("Blocking category {}", id)))?
+        }
+    };
+
+    let existing = queries::find_blocklist_subscription(pool, category.id, source).await?;
+    let subscription_id = match refresh_interval_hours {
+        Some(hours) => Some(queries::upsert_blocklist_subscription(pool, category.id, source, hours).await?),
+        None => existing.as_ref().map(|subscription| subscription.id),
+    };
+    let previous = match &existing {
+        Some(subscription) => subscription_domains(subscription),
+        None => Vec::new(),
+    };
+
+    apply_list(state, &category, subscription_id, source, previous, parsed).await
+}
+
+/// Fetch a subscribed list again and apply what changed
+pub async fn refresh_subscription(state: &AppState, id: i64) -> Result<ImportReport> {
+    let pool = state.pool();
+    let subscription = queries::get_blocklist_subscription(pool, id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Blocklist subscription {}", id)))?;
+
+    let parsed = match fetch_list(&subscription.source).await.and_then(|text| {
+        let parsed = parse_list(&text);
+        check_parsed(&subscription.source, &parsed)?;
+        Ok(parsed)
+    }) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            queries::record_blocklist_refresh_error(pool, id, &e.to_string()).await?;
+            return Err(e);
+        }
+    };
+
+    let category = queries::get_blocking_category(pool, subscription.category_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Blocking category {}", subscription.category_id)))?;
+
+    let previous = subscription_domains(&subscription);
+    apply_list(state, &category, Some(id), &subscription.source, previous, parsed).await
+}
+
+/// Remove a subscription, optionally taking its domains out of the category
+pub async fn remove_subscription(state: &AppState, id: i64, remove_domains: bool) -> Result<()> {
+    let pool = state.pool();
+    let subscription = queries::get_blocklist_subscription(pool, id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Blocklist subscription {}", id)))?;
+
+    if remove_domains {
+        nuclear::ensure_unlocked(state, "remove imported blocklist domains").await?;
+
+        if let Some(category) = queries::get_blocking_category(pool, subscription.category_id).await? {
+            let others = other_subscription_domains(pool, &subscription).await?;
+            let items = category_items(&category)?;
+            let merge = merge_list(
+                &items,
+                &subscription_domains(&subscription),
+                &[],
+                &others,
+                &HashSet::new(),
+                true,
+            );
+            queries::set_blocking_category_items(pool, category.id, &serde_json::to_string(&merge.items)?).await?;
+            tracing::info!(
+                "Removed {} imported domains from category '{}'",
+                merge.removed.len(),
+                category.name
+            );
+        }
+    }
+
+    queries::delete_blocklist_subscription(pool, id).await?;
+    blocklist::refresh_website_blocking(state).await?;
+
+    tracing::info!("Removed blocklist subscription {} ({})", id, subscription.source);
+    Ok(())
+}
+
+/// Refresh every enabled subscription whose interval has passed
+pub async fn refresh_due(state: &AppState) {
+    let subscriptions = match queries::get_blocklist_subscriptions(state.pool()).await {
+        Ok(subscriptions) => subscriptions,
+        Err(e) => {
+            tracing::warn!("Failed to load blocklist subscriptions: {}", e);
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    for subscription in subscriptions.iter().filter(|subscription| is_due(subscription, now)) {
+        match refresh_subscription(state, subscription.id).await {
+            Ok(report) => {
+                if report.added_count > 0 || report.removed_count > 0 {
+                    if let Err(e) = state.app_handle.emit("blocklist-subscription-refreshed", &report) {
+                        tracing::warn!("Failed to emit blocklist-subscription-refreshed: {}", e);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Failed to refresh blocklist {}: {}", subscription.source, e),
+        }
+    }
+}
+
+/// Background task keeping subscribed lists up to date
+pub async fn start_refresh_loop(state: AppState) {
+    let mut interval = tokio::time::interval(REFRESH_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        refresh_due(&state).await;
+    }
+}
+
+fn is_due(subscription: &BlocklistSubscription, now: DateTime<Utc>) -> bool {
+    if !subscription.enabled {
+        return false;
+    }
+
+    let Some(last_refreshed_at) = subscription.last_refreshed_at else {
+        return true;
+    };
+
+    let wait = if subscription.last_error.is_some() {
+        ChronoDuration::minutes(RETRY_AFTER_MINUTES)
+    } else {
+        ChronoDuration::hours(subscription.refresh_interval_hours.max(MIN_REFRESH_INTERVAL_HOURS))
+    };
+
+    now >= last_refreshed_at + wait
+}
+
+/// Refuse empty and oversized lists; an empty download would wipe the category
+fn check_parsed(source: &str, parsed: &ParsedList) -> Result<()> {
+    if parsed.domains.is_empty() {
+        return Err(Error::InvalidInput(format!("No domains found in {}", source)));
+    }
+    if parsed.domains.len() > MAX_LIST_DOMAINS {
+        return Err(Error::InvalidInput(format!(
+            "{} lists {} domains; at most {} can be imported",
+            source,
+            parsed.domains.len(),
+            MAX_LIST_DOMAINS
+        )));
+    }
+    Ok(())
+}
+
+/// Merge a parsed list into a category, record it and re-apply blocking
+async fn apply_list(
+    state: &AppState,
+    category: &BlockingCategory,
+    subscription_id: Option<i64>,
+    source: &str,
+    previous: Vec<String>,
+    parsed: ParsedList,
+) -> Result<ImportReport> {
+    let pool = state.pool();
+
+    let blocked: HashSet<String> = queries::get_blocked_items(pool, Some("website"))
+        .await?
+        .into_iter()
+        .map(|item| item.value.trim().to_ascii_lowercase())
+        .collect();
+
+    let others = match subscription_id {
+        Some(id) => match queries::get_blocklist_subscription(pool, id).await? {
+            Some(subscription) => other_subscription_domains(pool, &subscription).await?,
+            None => HashSet::new(),
+        },
+        None => HashSet::new(),
+    };
+
+    let allow_removals = nuclear::ensure_unlocked(state, "remove imported blocklist domains")
+        .await
+        .is_ok();
+
+    let items = category_items(category)?;
+    let merge = merge_list(&items, &previous, &parsed.domains, &others, &blocked, allow_removals);
+
+    if merge.items != items {
+        queries::set_blocking_category_items(pool, category.id, &serde_json::to_string(&merge.items)?).await?;
+    }
+    if let Some(id) = subscription_id {
+        queries::record_blocklist_refresh(pool, id, &serde_json::to_string(&merge.recorded)?).await?;
+    }
+    if merge.items != items {
+        blocklist::refresh_website_blocking(state).await?;
+    }
+
+    tracing::info!(
+        "Imported {} domains from {} into '{}': {} added, {} removed, {} already blocked",
+        parsed.domains.len(),
+        source,
+        category.name,
+        merge.added.len(),
+        merge.removed.len(),
+        merge.duplicates
+    );
+
+    let removals_deferred = merge.deferred > 0;
+    Ok(ImportReport {
+        category_id: category.id,
+        category_name: category.name.clone(),
+        subscription_id,
+        source: source.to_string(),
+        format: parsed.format,
+        total_domains: parsed.domains.len(),
+        added_count: merge.added.len(),
+        added: merge.added.into_iter().take(DIFF_SAMPLE_SIZE).collect(),
+        removed_count: merge.removed.len(),
+        removed: merge.removed.into_iter().take(DIFF_SAMPLE_SIZE).collect(),
+        duplicates: merge.duplicates,
+        skipped_lines: parsed.skipped_lines,
+        removals_deferred,
+    })
+}
+
+/// Refuses to go on when the stored items don't parse, so an import never
+/// overwrites them
+fn category_items(category: &BlockingCategory) -> Result<Vec<String>> {
+    serde_json::from_str(&category.items).map_err(|e| {
+        Error::InvalidInput(format!("Category '{}' has invalid items: {}", category.name, e))
+    })
+}
+
+fn subscription_domains(subscription: &BlocklistSubscription) -> Vec<String> {
+    serde_json::from_str(&subscription.domains).unwrap_or_default()
+}
+
+/// Domains provided by the category's other subscriptions
+async fn other_subscription_domains(
+    pool: &sqlx::SqlitePool,
+    subscription: &BlocklistSubscription,
+) -> Result<HashSet<String>> {
+    Ok(queries::get_blocklist_subscriptions(pool)
+        .await?
+        .iter()
+        .filter(|other| other.category_id == subscription.category_id && other.id != subscription.id)
+        .flat_map(subscription_domains)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn set(values: &[&str]) -> HashSet<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_hosts_file() {
+        let parsed = parse_list(
+            "# Title: test list\n\
+             127.0.0.1 localhost\n\
+             127.0.0.1 localhost.localdomain\n\
+             ::1 localhost ip6-localhost ip6-loopback\n\
+             0.0.0.0 0.0.0.0\n\
+             \n\
+             0.0.0.0 ads.example.com\n\
+             0.0.0.0\tTracker.Example.NET.  # trailing comment\n\
+             127.0.0.1 a.example.org b.example.org\n\
+             0.0.0.0 ads.example.com\n\
+             10.0.0.1 redirect.example.com\n",
+        );
+
+        assert_eq!(parsed.format, ListFormat::Hosts);
+        assert_eq!(
+            parsed.domains,
+            strings(&["ads.example.com", "tracker.example.net", "a.example.org", "b.example.org"])
+        );
+        assert_eq!(parsed.skipped_lines, 1);
+    }
+
+    #[test]
+    fn test_parse_plain_list() {
+        let parsed = parse_list("reddit.com\r\nnews.ycombinator.com\n# comment\nnot a domain\nlocalhost\n1.2.3.4\n");
+
+        assert_eq!(parsed.format, ListFormat::Plain);
+        assert_eq!(parsed.domains, strings(&["reddit.com", "news.ycombinator.com"]));
+        assert_eq!(parsed.skipped_lines, 3);
+    }
+
+    #[test]
+    fn test_parse_adblock_list() {
+        let parsed = parse_list(
+            "[Adblock Plus 2.0]\n\
+             ! Title: test\n\
+             ||ads.example.com^\n\
+             ||popups.example.com^$important\n\
+             ||cdn.example.com^$third-party\n\
+             ||example.org/banner^\n\
+             @@||ok.example.com^\n\
+             example.com##.advert\n\
+             /banner/*/img^\n\
+             ||tracker.example.net^|\n",
+        );
+
+        assert_eq!(parsed.format, ListFormat::Adblock);
+        assert_eq!(
+            parsed.domains,
+            strings(&["ads.example.com", "popups.example.com", "tracker.example.net"])
+        );
+        assert_eq!(parsed.skipped_lines, 5);
+    }
+
+    #[test]
+    fn test_parse_mixed_list_deduplicates() {
+        let parsed = parse_list("0.0.0.0 a.example.com\n||a.example.com^\nA.example.com\nb.example.com\n");
+
+        assert_eq!(parsed.domains, strings(&["a.example.com", "b.example.com"]));
+        assert_eq!(parse_list("").format, ListFormat::Plain);
+    }
+
+    #[test]
+    fn test_merge_first_import_skips_blocked_items() {
+        let merge = merge_list(
+            &strings(&["discord", "twitter.com"]),
+            &[],
+            &strings(&["twitter.com", "reddit.com", "youtube.com"]),
+            &HashSet::new(),
+            &set(&["youtube.com"]),
+            true,
+        );
+
+        assert_eq!(merge.items, strings(&["discord", "twitter.com", "reddit.com"]));
+        assert_eq!(merge.added, strings(&["reddit.com"]));
+        assert!(merge.removed.is_empty());
+        assert_eq!(merge.duplicates, 1);
+        // twitter.com was already in the category and youtube.com wasn't copied
+        assert_eq!(merge.recorded, strings(&["reddit.com"]));
+    }
+
+    #[test]
+    fn test_merge_never_removes_domains_it_did_not_add() {
+        let first = merge_list(
+            &strings(&["twitter.com"]),
+            &[],
+            &strings(&["twitter.com", "reddit.com"]),
+            &HashSet::new(),
+            &HashSet::new(),
+            true,
+        );
+        assert_eq!(first.recorded, strings(&["reddit.com"]));
+
+        let second = merge_list(&first.items, &first.recorded, &[], &HashSet::new(), &HashSet::new(), true);
+        assert_eq!(second.items, strings(&["twitter.com"]));
+        assert_eq!(second.removed, strings(&["reddit.com"]));
+        assert!(second.recorded.is_empty());
+    }
+
+    #[test]
+    fn test_merge_refresh_reports_diff() {
+        let merge = merge_list(
+            &strings(&["discord", "a.com", "b.com", "c.com"]),
+            &strings(&["a.com", "b.com", "c.com"]),
+            &strings(&["a.com", "d.com"]),
+            &set(&["c.com"]),
+            &HashSet::new(),
+            true,
+        );
+
+        // c.com is still provided by another subscription of the category
+        assert_eq!(merge.items, strings(&["discord", "a.com", "c.com", "d.com"]));
+        assert_eq!(merge.added, strings(&["d.com"]));
+        assert_eq!(merge.removed, strings(&["b.com"]));
+        assert_eq!(merge.recorded, strings(&["a.com", "d.com"]));
+    }
+
+    #[test]
+    fn test_merge_defers_removals_when_locked() {
+        let merge = merge_list(
+            &strings(&["a.com", "b.com"]),
+            &strings(&["a.com", "b.com"]),
+            &strings(&["a.com"]),
+            &HashSet::new(),
+            &HashSet::new(),
+            false,
+        );
+
+        assert_eq!(merge.items, strings(&["a.com", "b.com"]));
+        assert!(merge.removed.is_empty());
+        // Still owned by the list, so the next refresh removes it
+        assert_eq!(merge.recorded, strings(&["a.com", "b.com"]));
+        assert_eq!(merge.deferred, 1);
+    }
+
+    fn subscription(last_refreshed_at: Option<DateTime<Utc>>, last_error: Option<&str>) -> BlocklistSubscription {
+        BlocklistSubscription {
+            id: 1,
+            category_id: 1,
+            category_name: "Ads".to_string(),
+            source: "https://example.com/hosts".to_string(),
+            refresh_interval_hours: 24,
+            enabled: true,
+            domains: "[]".to_string(),
+            domain_count: 0,
+            last_refreshed_at,
+            last_error: last_error.map(str::to_string),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_subscription_due() {
+        let now = Utc::now();
+
+        assert!(is_due(&subscription(None, None), now));
+        assert!(!is_due(&subscription(Some(now - ChronoDuration::hours(23)), None), now));
+        assert!(is_due(&subscription(Some(now - ChronoDuration::hours(24)), None), now));
+        assert!(is_due(&subscription(Some(now - ChronoDuration::hours(2)), Some("timeout")), now));
+
+        let mut disabled = subscription(None, None);
+        disabled.enabled = false;
+        assert!(!is_due(&disabled, now));
+    }
+
+    #[test]
+    fn test_category_items_rejects_invalid_json() {
+        let mut category = BlockingCategory {
+            id: 1,
+            name: "Ads".to_string(),
+            description: None,
+            items: r#"["ads.example.com"]"#.to_string(),
+            enabled: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        assert_eq!(category_items(&category).unwrap(), strings(&["ads.example.com"]));
+
+        category.items = "not json".to_string();
+        assert!(category_items(&category).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_list_requires_absolute_path() {
+        assert!(fetch_list("lists/hosts.txt").await.is_err());
+
+        let path = std::env::temp_dir().join(format!("focusflow-import-{}.txt", std::process::id()));
+        std::fs::write(&path, "||ads.example.com^\n").unwrap();
+        let text = fetch_list(&format!("file://{}", path.display())).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(parse_list(&text).domains, strings(&["ads.example.com"]));
+    }
+}
@@ -0,0 +1,1049 @@
+// blocking/interstitial.rs - Local "site blocked" page
+//
+// The hosts file points blocked names at 127.0.0.1 and ::1, which normally
+// ends in a browser connection error. This tiny HTTP server listens on the
+// loopback addresses so that visiting a blocked site over plain HTTP shows a
+// FocusFlow page instead: how long the session has left, why the site is
+// blocked, and a form to log the distraction to the trigger journal (which
+// also records a block attempt). HTTPS visits can't be answered without a
+// certificate for the site, so those still fail to connect.
+//
+// Browsers connect to port 80, which is privileged on most systems, so the
+// page is off until the user turns it on; when the port can't be bound it
+// stays off and blocked sites behave as before.
+
+use crate::{
+    blocking::{
+        allowlist,
+        blocklist::{self, BlocklistSource, EffectiveBlocklist},
+        rules::WebsiteMatcher,
+    },
+    commands::journal::{self, CreateJournalEntryRequest},
+    db::queries,
+    state::{AppState, SessionType},
+    Error, Result,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration};
+
+/// user_settings key holding the blocked page settings
+pub const BLOCKED_PAGE_SETTINGS_KEY: &str = "blocked_page";
+
+/// Path the "log distraction" form posts to
+pub const DISTRACTION_PATH: &str = "/__focusflow/distraction";
+
+/// Trigger types offered by the form (see `commands::journal::TriggerType`)
+pub const TRIGGER_TYPES: &[&str] = &[
+    "boredom",
+    "anxiety",
+    "stress",
+    "fatigue",
+    "notification",
+    "person",
+    "environment",
+    "other",
+];
+
+/// How long a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest request line plus headers accepted
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// Largest form body accepted
+const MAX_BODY_BYTES: usize = 4 * 1024;
+
+/// Longest distraction note kept
+const MAX_NOTES_CHARS: usize = 500;
+
+/// Blocked page settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedPageSettings {
+    /// Serve the page while the app is running
+    pub enabled: bool,
+    /// Port to listen on; browsers only come to port 80
+    pub port: u16,
+}
+
+impl Default for BlockedPageSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 80,
+        }
+    }
+}
+
+/// Whether the blocked page is being served
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedPageStatus {
+    pub settings: BlockedPageSettings,
+    pub running: bool,
+    /// Addresses the page is served on
+    pub listen_addrs: Vec<String>,
+}
+
+/// Why a site is blocked right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BlockReason {
+    /// A focus session is running
+    #[serde(rename_all = "camelCase")]
+    Session { task_title: Option<String> },
+    /// A blocking schedule is in effect
+    #[serde(rename_all = "camelCase")]
+    Schedule { until: Option<String> },
+    /// A Focus Time calendar block is active
+    #[serde(rename_all = "camelCase")]
+    FocusTime { title: Option<String> },
+    /// The site is in an enabled blocking category
+    #[serde(rename_all = "camelCase")]
+    Category { name: String },
+    /// The site's daily time budget is used up
+    TimeBudget,
+    /// The site is on the block list
+    Blocklist,
+    /// Only the session's or Focus Time's allowed sites are reachable
+    NotAllowed,
+}
+
+impl BlockReason {
+    /// One-line explanation for the page
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Session { task_title: Some(task) } => format!("You're in a focus session: {}", task),
+            Self::Session { task_title: None } => "You're in a focus session".to_string(),
+            Self::Schedule { until: Some(until) } => format!("Your blocking schedule runs until {}", until),
+            Self::Schedule { until: None } => "Your blocking schedule is active".to_string(),
+            Self::FocusTime { title: Some(title) } => format!("Focus Time is on: {}", title),
+            Self::FocusTime { title: None } => "Focus Time is on".to_string(),
+            Self::Category { name } => format!("It's in your \"{}\" category", name),
+            Self::TimeBudget => "You've used up today's time budget for it".to_string(),
+            Self::Blocklist => "It's on your block list".to_string(),
+            Self::NotAllowed => "It isn't one of the sites allowed right now".to_string(),
+        }
+    }
+}
+
+/// What the page shows for a host
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedPage {
+    pub host: String,
+    pub blocked: bool,
+    pub reasons: Vec<BlockReason>,
+    /// Seconds left in the session or Focus Time block
+    pub remaining_seconds: Option<i64>,
+}
+
+/// A distraction logged from the page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distraction {
+    pub trigger_type: String,
+    pub notes: Option<String>,
+}
+
+impl Distraction {
+    /// Read the submitted form, falling back to "other" for unknown triggers
+    pub fn from_form(body: &str) -> Self {
+        let mut trigger_type = None;
+        let mut notes = None;
+
+        for (key, value) in parse_form(body) {
+            match key.as_str() {
+                "trigger" => trigger_type = Some(value),
+                "notes" => notes = Some(value),
+                _ => {}
+            }
+        }
+
+        let trigger_type = trigger_type
+            .filter(|t| TRIGGER_TYPES.contains(&t.as_str()))
+            .unwrap_or_else(|| "other".to_string());
+        let notes = notes
+            .map(|n| n.trim().chars().take(MAX_NOTES_CHARS).collect::<String>())
+            .filter(|n| !n.is_empty());
+
+        Self { trigger_type, notes }
+    }
+}
+
+/// What the server needs from the rest of the app
+#[async_trait]
+pub trait BlockedPageBackend: Send + Sync {
+    /// Describe why `url` is blocked
+    async fn page(&self, host: &str, url: &str) -> BlockedPage;
+
+    /// Save a distraction logged for a blocked host
+    async fn log_distraction(&self, host: &str, distraction: &Distraction) -> Result<()>;
+}
+
+/// A running blocked page server
+///
+/// The listener tasks stop when the server is stopped or dropped.
+pub struct BlockedPageServer {
+    listen_addrs: Vec<SocketAddr>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl BlockedPageServer {
+    /// Listen on 127.0.0.1 and, where available, ::1
+    pub async fn start(port: u16, backend: Arc<dyn BlockedPageBackend>) -> Result<Self> {
+        let v4 = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await.map_err(|e| {
+            Error::System(format!("Failed to bind blocked page on 127.0.0.1:{}: {}", port, e))
+        })?;
+        let port = v4.local_addr()?.port();
+
+        let mut listeners = vec![v4];
+        // Hosts entries map blocked names to ::1 too, but IPv6 may be disabled
+        match TcpListener::bind((Ipv6Addr::LOCALHOST, port)).await {
+            Ok(v6) => listeners.push(v6),
+            Err(e) => tracing::debug!("Blocked page not served on [::1]:{}: {}", port, e),
+        }
+
+        let mut listen_addrs = Vec::new();
+        let mut tasks = Vec::new();
+        for listener in listeners {
+            listen_addrs.push(listener.local_addr()?);
+            tasks.push(tokio::spawn(serve(listener, backend.clone())));
+        }
+
+        tracing::info!("Blocked page served on port {}", port);
+
+        Ok(Self { listen_addrs, tasks })
+    }
+
+    /// Addresses the page is served on
+    pub fn listen_addrs(&self) -> &[SocketAddr] {
+        &self.listen_addrs
+    }
+
+    /// Stop the listeners
+    pub fn stop(self) {
+        tracing::info!("Blocked page server stopped");
+    }
+}
+
+impl Drop for BlockedPageServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn serve(listener: TcpListener, backend: Arc<dyn BlockedPageBackend>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(handle_connection(stream, backend.clone()));
+            }
+            Err(e) => {
+                tracing::debug!("Blocked page accept error: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, backend: Arc<dyn BlockedPageBackend>) {
+    let response = match timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Some(request)) => respond(&request, backend.as_ref()).await,
+        Ok(None) => Response::text(400, "Bad Request"),
+        Err(_) => return,
+    };
+
+    if let Err(e) = stream.write_all(&response.to_bytes()).await {
+        tracing::debug!("Failed to send blocked page: {}", e);
+    }
+    let _ = stream.shutdown().await;
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+/// The parts of an HTTP request the page cares about
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Request {
+    method: String,
+    /// Path including the query string
+    target: String,
+    host: Option<String>,
+    origin: Option<String>,
+    content_length: usize,
+    body: String,
+}
+
+impl Request {
+    fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or("/")
+    }
+
+    fn query(&self) -> &str {
+        self.target.split_once('?').map(|(_, query)| query).unwrap_or("")
+    }
+}
+
+/// Parse the request line and headers (without the blank line)
+fn parse_head(head: &str) -> Option<Request> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_ascii_uppercase();
+    let target = request_line.next()?.to_string();
+    if !request_line.next()?.starts_with("HTTP/1.") {
+        return None;
+    }
+
+    let mut request = Request {
+        method,
+        target,
+        ..Request::default()
+    };
+
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "host" => request.host = normalize_host(value),
+            "origin" => request.origin = Some(value.to_string()),
+            "content-length" => request.content_length = value.parse().ok()?,
+            _ => {}
+        }
+    }
+
+    Some(request)
+}
+
+/// Lowercase host name without port or trailing dot; None for IP literals
+fn normalize_host(value: &str) -> Option<String> {
+    if value.starts_with('[') {
+        return None;
+    }
+    let host = value.split(':').next()?.trim().trim_end_matches('.').to_ascii_lowercase();
+    if host.is_empty() || host.parse::<Ipv4Addr>().is_ok() {
+        return None;
+    }
+    Some(host)
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    };
+
+    let mut request = parse_head(std::str::from_utf8(&buf[..head_end]).ok()?)?;
+    if request.content_length > MAX_BODY_BYTES {
+        return None;
+    }
+
+    let mut body = buf.split_off(head_end + 4);
+    while body.len() < request.content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(request.content_length);
+    request.body = String::from_utf8_lossy(&body).into_owned();
+
+    Some(request)
+}
+
+/// Decode an `application/x-www-form-urlencoded` body
+fn parse_form(body: &str) -> Vec<(String, String)> {
+    body.split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let decode = |s: &str| urlencoding::decode(&s.replace('+', " ")).ok().map(|s| s.into_owned());
+            Some((decode(key)?, decode(value)?))
+        })
+        .collect()
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    location: Option<String>,
+    body: String,
+}
+
+impl Response {
+    fn html(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "text/html; charset=utf-8",
+            location: None,
+            body,
+        }
+    }
+
+    fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            location: None,
+            body: body.to_string(),
+        }
+    }
+
+    fn see_other(location: &str) -> Self {
+        Self {
+            status: 303,
+            content_type: "text/plain; charset=utf-8",
+            location: Some(location.to_string()),
+            body: String::new(),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            303 => "See Other",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n",
+            self.status,
+            reason,
+            self.content_type,
+            self.body.len()
+        );
+        if let Some(location) = &self.location {
+            head.push_str(&format!("Location: {}\r\n", location));
+        }
+        head.push_str("\r\n");
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes
+    }
+}
+
+async fn respond(request: &Request, backend: &dyn BlockedPageBackend) -> Response {
+    let Some(host) = request.host.as_deref() else {
+        return Response::text(404, "FocusFlow blocked page");
+    };
+    let url = format!("http://{}{}", host, request.path());
+
+    match (request.method.as_str(), request.path()) {
+        ("POST", DISTRACTION_PATH) => {
+            // Only the page itself may log distractions, not other sites' forms
+            // or clients that don't say where the request comes from
+            let expected_origin = format!("http://{}", host);
+            if request.origin.as_deref() != Some(expected_origin.as_str()) {
+                return Response::text(403, "Forbidden");
+            }
+
+            let page = backend.page(host, &url).await;
+            if !page.blocked {
+                return Response::see_other("/");
+            }
+
+            let distraction = Distraction::from_form(&request.body);
+            if let Err(e) = backend.log_distraction(host, &distraction).await {
+                tracing::error!("Failed to log distraction on {}: {}", host, e);
+                return Response::text(500, "Could not save the distraction");
+            }
+            Response::see_other("/?logged=1")
+        }
+        ("GET" | "HEAD", _) => {
+            let page = backend.page(host, &url).await;
+            let logged = request.query().split('&').any(|pair| pair == "logged=1");
+            let status = if page.blocked { 200 } else { 404 };
+            let mut response = Response::html(status, render_page(&page, logged));
+            if request.method == "HEAD" {
+                response.body.clear();
+            }
+            response
+        }
+        _ => Response::text(405, "Method Not Allowed"),
+    }
+}
+
+// ============================================================================
+// Page
+// ============================================================================
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// "1h 05m" or "12m" left
+fn format_remaining(seconds: i64) -> String {
+    let minutes = (seconds.max(0) + 59) / 60;
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+fn render_page(page: &BlockedPage, logged: bool) -> String {
+    let host = escape_html(&page.host);
+
+    let content = if page.blocked {
+        let remaining = page
+            .remaining_seconds
+            .map(|s| format!("<p class=\"remaining\">{} left</p>", format_remaining(s)))
+            .unwrap_or_default();
+
+        let reasons: String = page
+            .reasons
+            .iter()
+            .map(|reason| format!("<li>{}</li>", escape_html(&reason.describe())))
+            .collect();
+
+        let form = if logged {
+            "<p class=\"logged\">Logged. Back to work!</p>".to_string()
+        } else {
+            let options: String = TRIGGER_TYPES
+                .iter()
+                .map(|t| format!("<option value=\"{}\">{}</option>", t, t.replace('_', " ")))
+                .collect();
+            format!(
+                "<form method=\"post\" action=\"{}\">\
+                 <label>What pulled you here?<select name=\"trigger\">{}</select></label>\
+                 <label>Notes<textarea name=\"notes\" rows=\"3\" maxlength=\"{}\"></textarea></label>\
+                 <button type=\"submit\">Log distraction</button></form>",
+                DISTRACTION_PATH, options, MAX_NOTES_CHARS
+            )
+        };
+
+        format!(
+            "<h1>{} is blocked</h1>{}<ul>{}</ul>{}",
+            host, remaining, reasons, form
+        )
+    } else {
+        format!(
+            "<h1>{} isn't blocked</h1>\
+             <p>FocusFlow isn't blocking this site. If it was just unblocked, reload in a minute.</p>",
+            host
+        )
+    };
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>Blocked by FocusFlow</title><style>\
+         body{{font-family:system-ui,sans-serif;background:#0f172a;color:#e2e8f0;\
+         display:flex;justify-content:center;padding:10vh 1rem;margin:0}}\
+         main{{max-width:32rem;width:100%}}h1{{font-size:1.5rem;word-break:break-all}}\
+         .remaining{{font-size:2.5rem;font-weight:600;margin:.5rem 0}}\
+         label{{display:block;margin:1rem 0 .25rem}}select,textarea{{display:block;width:100%;\
+         margin-top:.25rem;font:inherit;box-sizing:border-box}}\
+         button{{margin-top:1rem;padding:.5rem 1rem;font:inherit}}.logged{{color:#86efac}}\
+         </style></head><body><main>{}</main></body></html>",
+        content
+    )
+}
+
+// ============================================================================
+// App integration
+// ============================================================================
+
+/// Block list website rules, compiled once, with the sources of each rule
+pub struct SiteSources {
+    matcher: WebsiteMatcher,
+    /// Sources keyed by canonical rule, as reported by the matcher
+    sources: HashMap<String, Vec<BlocklistSource>>,
+}
+
+impl SiteSources {
+    pub fn new(blocklist: &EffectiveBlocklist) -> Self {
+        let rules: Vec<&str> = blocklist.websites.iter().map(|entry| entry.value.as_str()).collect();
+        let mut sources: HashMap<String, Vec<BlocklistSource>> = HashMap::new();
+        for entry in &blocklist.websites {
+            sources
+                .entry(entry.value.clone())
+                .or_default()
+                .extend(entry.sources.iter().cloned());
+        }
+
+        Self {
+            matcher: WebsiteMatcher::compile(&rules),
+            sources,
+        }
+    }
+
+    /// Explain which rule blocks a URL
+    ///
+    /// A site that matches no block list entry is blocked by an allow-list.
+    pub fn reasons(&self, url: &str) -> Vec<BlockReason> {
+        let mut reasons = Vec::new();
+
+        let matched = self.matcher.check_url(url);
+        let sources = matched
+            .matched_domain
+            .filter(|_| matched.blocked)
+            .and_then(|rule| self.sources.get(&rule))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for source in sources {
+            let reason = match source {
+                BlocklistSource::Manual => BlockReason::Blocklist,
+                BlocklistSource::Category { category_name, .. } => BlockReason::Category {
+                    name: category_name.clone(),
+                },
+                BlocklistSource::Budget { .. } => BlockReason::TimeBudget,
+                // The schedule itself is reported with the other blocking contexts
+                BlocklistSource::Schedule { .. } => BlockReason::Blocklist,
+            };
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+        }
+
+        if reasons.is_empty() {
+            reasons.push(BlockReason::NotAllowed);
+        }
+
+        reasons
+    }
+}
+
+/// Backend answering from the app state
+pub struct AppBackend {
+    state: AppState,
+    /// Compiled block list, with the blocklist generation it was loaded at
+    site_sources: tokio::sync::Mutex<Option<(u64, Arc<SiteSources>)>>,
+}
+
+impl AppBackend {
+    pub fn new(state: AppState) -> Self {
+        Self {
+            state,
+            site_sources: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// The block list rules and their sources, reloaded when the blocklist changes
+    async fn site_sources(&self) -> Result<Arc<SiteSources>> {
+        let generation = blocklist::generation();
+        let mut cached = self.site_sources.lock().await;
+        if let Some((cached_generation, sources)) = cached.as_ref() {
+            if *cached_generation == generation {
+                return Ok(sources.clone());
+            }
+        }
+
+        let sources = Arc::new(SiteSources::new(&EffectiveBlocklist::load(&self.state).await?));
+        *cached = Some((generation, sources.clone()));
+        Ok(sources)
+    }
+
+    /// What turned blocking on, and how long it has left
+    async fn active_reasons(&self) -> (Vec<BlockReason>, Option<i64>) {
+        let mut reasons = Vec::new();
+        let mut remaining_seconds = None;
+        let now = Utc::now();
+
+        let session = self.state.active_session.read().await.clone();
+        if let Some(session) = session.filter(|s| s.session_type != SessionType::Break) {
+            let elapsed = {
+                let timer = self.state.timer_state.read().await;
+                timer.elapsed_at(session.start_time, now)
+            };
+            remaining_seconds = Some((session.planned_duration_minutes as i64 * 60 - elapsed).max(0));
+            reasons.push(BlockReason::Session {
+                task_title: session.task_title,
+            });
+        }
+
+        let schedule_id = self.state.blocking_state.read().await.schedule_id;
+        if let Some(schedule_id) = schedule_id {
+            let until = match queries::get_enabled_blocking_schedules(self.state.pool()).await {
+                Ok(schedules) => schedules.into_iter().find(|s| s.id == schedule_id).map(|s| s.end_time),
+                Err(e) => {
+                    tracing::debug!("Failed to load blocking schedules: {}", e);
+                    None
+                }
+            };
+            reasons.push(BlockReason::Schedule { until });
+        }
+
+        {
+            let focus_time = self.state.focus_time_state.read().await;
+            if focus_time.active {
+                if remaining_seconds.is_none() {
+                    remaining_seconds = focus_time.ends_at.map(|ends_at| (ends_at - now).num_seconds().max(0));
+                }
+                reasons.push(BlockReason::FocusTime {
+                    title: focus_time.event_title.clone(),
+                });
+            }
+        }
+
+        (reasons, remaining_seconds)
+    }
+}
+
+#[async_trait]
+impl BlockedPageBackend for AppBackend {
+    async fn page(&self, host: &str, url: &str) -> BlockedPage {
+        let rules = allowlist::compiled_website_rules(&self.state).await;
+        let blocked = rules.enabled && rules.matcher.check_url(url).blocked;
+
+        let mut page = BlockedPage {
+            host: host.to_string(),
+            blocked,
+            ..BlockedPage::default()
+        };
+        if !blocked {
+            return page;
+        }
+
+        let (mut reasons, remaining_seconds) = self.active_reasons().await;
+        match self.site_sources().await {
+            Ok(sources) => reasons.extend(sources.reasons(url)),
+            Err(e) => tracing::debug!("Failed to load blocklist for blocked page: {}", e),
+        }
+
+        page.reasons = reasons;
+        page.remaining_seconds = remaining_seconds;
+        page
+    }
+
+    async fn log_distraction(&self, host: &str, distraction: &Distraction) -> Result<()> {
+        let session_id = {
+            let active_session = self.state.active_session.read().await;
+            active_session.as_ref().map(|s| s.id.clone())
+        };
+        let user_id = self.state.get_user_id().await;
+
+        let notes = match &distraction.notes {
+            Some(notes) => format!("Tried to open {}: {}", host, notes),
+            None => format!("Tried to open {}", host),
+        };
+        let request = CreateJournalEntryRequest {
+            session_id: session_id.clone(),
+            trigger_type: distraction.trigger_type.clone(),
+            emotion: None,
+            notes: Some(notes),
+            intensity: None,
+        };
+        journal::insert_journal_entry(self.state.pool(), request).await?;
+
+        queries::record_block_attempt(
+            self.state.pool(),
+            "website",
+            host,
+            session_id.as_deref(),
+            user_id.as_deref(),
+        )
+        .await?;
+
+        tracing::info!("Logged distraction on blocked site {}", host);
+
+        Ok(())
+    }
+}
+
+/// Load the persisted blocked page settings
+pub async fn load_settings(state: &AppState) -> Result<BlockedPageSettings> {
+    let stored = queries::get_user_setting(state.pool(), BLOCKED_PAGE_SETTINGS_KEY).await?;
+
+    Ok(match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid blocked page settings: {}", e);
+            BlockedPageSettings::default()
+        }),
+        None => BlockedPageSettings::default(),
+    })
+}
+
+/// Persist the blocked page settings
+pub async fn save_settings(state: &AppState, settings: &BlockedPageSettings) -> Result<()> {
+    let json = serde_json::to_string(settings)?;
+    queries::set_user_setting(state.pool(), BLOCKED_PAGE_SETTINGS_KEY, &json).await
+}
+
+/// Serve the blocked page for the app, replacing a running server
+pub async fn start_for_app(state: &AppState, port: u16) -> Result<()> {
+    // Release the port before rebinding
+    stop_for_app(state).await;
+
+    let server = BlockedPageServer::start(port, Arc::new(AppBackend::new(state.clone()))).await?;
+    *state.blocked_page.write().await = Some(server);
+
+    Ok(())
+}
+
+/// Stop serving the blocked page, if running
+pub async fn stop_for_app(state: &AppState) {
+    let server = state.blocked_page.write().await.take();
+    if let Some(server) = server {
+        server.stop();
+    }
+}
+
+/// Start the server at launch if enabled
+pub async fn restore_for_app(state: &AppState) -> Result<()> {
+    let settings = load_settings(state).await?;
+    if settings.enabled {
+        start_for_app(state, settings.port).await?;
+    }
+    Ok(())
+}
+
+/// Current settings and whether the page is being served
+pub async fn status(state: &AppState) -> Result<BlockedPageStatus> {
+    let settings = load_settings(state).await?;
+    let running = state.blocked_page.read().await;
+
+    Ok(BlockedPageStatus {
+        settings,
+        running: running.is_some(),
+        listen_addrs: running
+            .as_ref()
+            .map(|server| server.listen_addrs().iter().map(ToString::to_string).collect())
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::{app_rules::AppRuleKind, blocklist::BlocklistEntry, enforcement::EnforcementPolicy};
+    use std::sync::Mutex;
+
+    /// Backend blocking a fixed set of hosts and remembering logged distractions
+    struct FakeBackend {
+        blocked: Vec<&'static str>,
+        logged: Mutex<Vec<(String, Distraction)>>,
+    }
+
+    impl FakeBackend {
+        fn new(blocked: Vec<&'static str>) -> Arc<Self> {
+            Arc::new(Self {
+                blocked,
+                logged: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl BlockedPageBackend for FakeBackend {
+        async fn page(&self, host: &str, _url: &str) -> BlockedPage {
+            BlockedPage {
+                host: host.to_string(),
+                blocked: self.blocked.contains(&host),
+                reasons: vec![BlockReason::Session { task_title: None }, BlockReason::Blocklist],
+                remaining_seconds: Some(25 * 60),
+            }
+        }
+
+        async fn log_distraction(&self, host: &str, distraction: &Distraction) -> Result<()> {
+            self.logged.lock().unwrap().push((host.to_string(), distraction.clone()));
+            Ok(())
+        }
+    }
+
+    async fn send(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[test]
+    fn test_parse_head() {
+        let request = parse_head(
+            "POST /__focusflow/distraction?x=1 HTTP/1.1\r\nHost: Reddit.com.:80\r\nContent-Length: 12\r\nOrigin: http://reddit.com",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path(), DISTRACTION_PATH);
+        assert_eq!(request.query(), "x=1");
+        assert_eq!(request.host.as_deref(), Some("reddit.com"));
+        assert_eq!(request.origin.as_deref(), Some("http://reddit.com"));
+        assert_eq!(request.content_length, 12);
+
+        assert!(parse_head("GET /").is_none());
+        assert!(parse_head("GET / HTTP/1.1\r\nContent-Length: lots").is_none());
+        assert_eq!(normalize_host("127.0.0.1:80"), None);
+        assert_eq!(normalize_host("[::1]"), None);
+    }
+
+    #[test]
+    fn test_distraction_from_form() {
+        let distraction = Distraction::from_form("trigger=boredom&notes=just+checking+%3Cstuff%3E");
+        assert_eq!(distraction.trigger_type, "boredom");
+        assert_eq!(distraction.notes.as_deref(), Some("just checking <stuff>"));
+
+        let distraction = Distraction::from_form("trigger=hacking&notes=++");
+        assert_eq!(distraction.trigger_type, "other");
+        assert_eq!(distraction.notes, None);
+    }
+
+    #[test]
+    fn test_render_page_escapes_and_shows_remaining() {
+        let page = BlockedPage {
+            host: "<script>.com".to_string(),
+            blocked: true,
+            reasons: vec![BlockReason::Category {
+                name: "Social".to_string(),
+            }],
+            remaining_seconds: Some(65 * 60),
+        };
+
+        let html = render_page(&page, false);
+        assert!(html.contains("&lt;script&gt;.com is blocked"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("1h 05m left"));
+        assert!(html.contains("&quot;Social&quot; category"));
+        assert!(html.contains(DISTRACTION_PATH));
+
+        assert!(render_page(&page, true).contains("Logged"));
+        assert_eq!(format_remaining(61), "2m");
+    }
+
+    #[test]
+    fn test_site_sources() {
+        let entry = |value: &str, sources: Vec<BlocklistSource>| BlocklistEntry {
+            value: value.to_string(),
+            match_type: "exact".to_string(),
+            enforcement: EnforcementPolicy::Kill,
+            rule_kind: AppRuleKind::Name,
+            sources,
+        };
+        let blocklist = EffectiveBlocklist {
+            websites: vec![
+                entry(
+                    "reddit.com",
+                    vec![
+                        BlocklistSource::Manual,
+                        BlocklistSource::Category {
+                            category_id: 1,
+                            category_name: "Social".to_string(),
+                            item: "reddit.com".to_string(),
+                        },
+                    ],
+                ),
+                entry("youtube.com", vec![BlocklistSource::Budget { budget_id: 3 }]),
+            ],
+            apps: Vec::new(),
+        };
+        let sources = SiteSources::new(&blocklist);
+
+        assert_eq!(
+            sources.reasons("http://www.reddit.com/r/rust"),
+            vec![
+                BlockReason::Blocklist,
+                BlockReason::Category {
+                    name: "Social".to_string()
+                }
+            ]
+        );
+        assert_eq!(sources.reasons("http://youtube.com/"), vec![BlockReason::TimeBudget]);
+        assert_eq!(sources.reasons("http://news.example.com/"), vec![BlockReason::NotAllowed]);
+    }
+
+    #[tokio::test]
+    async fn test_serves_page_and_logs_distraction() {
+        let backend = FakeBackend::new(vec!["reddit.com"]);
+        let server = BlockedPageServer::start(0, backend.clone()).await.unwrap();
+        let addr = server.listen_addrs()[0];
+
+        let response = send(addr, "GET /r/rust HTTP/1.1\r\nHost: reddit.com\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("reddit.com is blocked"));
+        assert!(response.contains("25m left"));
+
+        let response = send(addr, "GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        let body = "trigger=fatigue&notes=tired";
+        let response = send(
+            addr,
+            &format!(
+                "POST {} HTTP/1.1\r\nHost: reddit.com\r\nOrigin: http://reddit.com\r\nContent-Length: {}\r\n\r\n{}",
+                DISTRACTION_PATH,
+                body.len(),
+                body
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 303"));
+        assert!(response.contains("Location: /?logged=1"));
+
+        let logged = backend.logged.lock().unwrap().clone();
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].0, "reddit.com");
+        assert_eq!(logged[0].1.trigger_type, "fatigue");
+        assert_eq!(logged[0].1.notes.as_deref(), Some("tired"));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_cross_site_distraction_posts() {
+        let backend = FakeBackend::new(vec!["reddit.com"]);
+        let server = BlockedPageServer::start(0, backend.clone()).await.unwrap();
+        let addr = server.listen_addrs()[0];
+
+        let response = send(
+            addr,
+            &format!(
+                "POST {} HTTP/1.1\r\nHost: reddit.com\r\nOrigin: http://evil.example\r\nContent-Length: 0\r\n\r\n",
+                DISTRACTION_PATH
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 403"));
+
+        let response = send(
+            addr,
+            &format!(
+                "POST {} HTTP/1.1\r\nHost: reddit.com\r\nContent-Length: 0\r\n\r\n",
+                DISTRACTION_PATH
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 403"));
+        assert!(backend.logged.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,459 @@
+// blocking/launch.rs - Process launch event sources for the monitoring loop
+//
+// The monitoring loop waits on a `LaunchEventSource` instead of a fixed
+// interval. On Linux the netlink proc connector reports every exec as it
+// happens, so blocked apps are caught immediately and only the new process is
+// inspected. Subscribing needs CAP_NET_ADMIN; without it (and on other
+// platforms) a polling source rescans all processes every few seconds.
+
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+/// How often the polling source rescans all processes
+pub const POLL_INTERVAL: Duration = Duration::from_millis(2000);
+
+/// Event-driven sources still rescan everything this often, in case events were lost
+pub const FULL_SCAN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What the monitoring loop should do next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchEvent {
+    /// These processes just started (or replaced their executable)
+    Launched(Vec<u32>),
+    /// Periodic wake-up; `full_scan` asks for a rescan of all processes
+    Tick { full_scan: bool },
+}
+
+/// Source of process launch notifications
+#[async_trait]
+pub trait LaunchEventSource: Send {
+    /// Wait for the next event
+    async fn next_event(&mut self) -> LaunchEvent;
+
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+}
+
+/// Pick the best launch event source available on this system
+pub fn default_source() -> Box<dyn LaunchEventSource> {
+    #[cfg(target_os = "linux")]
+    match netlink::open() {
+        Ok(source) => return Box::new(source),
+        Err(e) => tracing::info!("Process launch events unavailable ({}), polling instead", e),
+    }
+
+    Box::new(PollingSource::new(POLL_INTERVAL))
+}
+
+fn ticker(period: Duration) -> Interval {
+    let mut ticker = interval(period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+/// Rescans all processes at a fixed interval
+pub struct PollingSource {
+    ticker: Interval,
+}
+
+impl PollingSource {
+    pub fn new(period: Duration) -> Self {
+        Self { ticker: ticker(period) }
+    }
+}
+
+#[async_trait]
+impl LaunchEventSource for PollingSource {
+    async fn next_event(&mut self) -> LaunchEvent {
+        self.ticker.tick().await;
+        LaunchEvent::Tick { full_scan: true }
+    }
+
+    fn name(&self) -> &'static str {
+        "polling"
+    }
+}
+
+/// Launch notifications delivered by a background reader
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notification {
+    Exec(u32),
+    /// Events were dropped; only a full rescan is reliable
+    Overflow,
+}
+
+/// Turns notifications from a channel into launch events
+///
+/// Also ticks at `POLL_INTERVAL` so the loop keeps its periodic bookkeeping,
+/// asking for a full rescan every `FULL_SCAN_INTERVAL`. Once the reader has
+/// gone away it behaves like `PollingSource`.
+pub struct ChannelSource {
+    name: &'static str,
+    receiver: tokio::sync::mpsc::UnboundedReceiver<Notification>,
+    ticker: Interval,
+    ticks_since_full_scan: u32,
+    ticks_per_full_scan: u32,
+    closed: bool,
+}
+
+impl ChannelSource {
+    pub fn new(name: &'static str, receiver: tokio::sync::mpsc::UnboundedReceiver<Notification>) -> Self {
+        Self {
+            name,
+            receiver,
+            ticker: ticker(POLL_INTERVAL),
+            ticks_since_full_scan: 0,
+            ticks_per_full_scan: (FULL_SCAN_INTERVAL.as_millis() / POLL_INTERVAL.as_millis()) as u32,
+            closed: false,
+        }
+    }
+
+    /// Collect everything already queued behind the first notification
+    fn drain(&mut self, first: Notification) -> LaunchEvent {
+        let mut pids = Vec::new();
+        let mut overflow = false;
+        let mut next = Some(first);
+
+        while let Some(notification) = next {
+            match notification {
+                Notification::Exec(pid) if !pids.contains(&pid) => pids.push(pid),
+                Notification::Exec(_) => {}
+                Notification::Overflow => overflow = true,
+            }
+            next = self.receiver.try_recv().ok();
+        }
+
+        if overflow {
+            self.ticks_since_full_scan = 0;
+            LaunchEvent::Tick { full_scan: true }
+        } else {
+            LaunchEvent::Launched(pids)
+        }
+    }
+}
+
+#[async_trait]
+impl LaunchEventSource for ChannelSource {
+    async fn next_event(&mut self) -> LaunchEvent {
+        if !self.closed {
+            // A due tick goes first, so a steady stream of launches can't starve
+            // full scans and the rest of the loop's periodic work
+            tokio::select! {
+                biased;
+                _ = self.ticker.tick() => {}
+                notification = self.receiver.recv() => match notification {
+                    Some(notification) => return self.drain(notification),
+                    None => {
+                        tracing::warn!("{} launch events stopped, falling back to polling", self.name);
+                        self.closed = true;
+                    }
+                },
+            }
+        } else {
+            self.ticker.tick().await;
+        }
+
+        self.ticks_since_full_scan += 1;
+        let full_scan = self.closed || self.ticks_since_full_scan >= self.ticks_per_full_scan;
+        if full_scan {
+            self.ticks_since_full_scan = 0;
+        }
+
+        LaunchEvent::Tick { full_scan }
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub mod netlink {
+    //! Linux proc connector (`NETLINK_CONNECTOR` / `CN_IDX_PROC`)
+
+    use super::{ChannelSource, Notification};
+    use crate::{Error, Result};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    const NETLINK_CONNECTOR: libc::c_int = 11;
+    const CN_IDX_PROC: u32 = 1;
+    const CN_VAL_PROC: u32 = 1;
+    const PROC_CN_MCAST_LISTEN: u32 = 1;
+    const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+
+    const NLMSG_HDRLEN: usize = 16;
+    const CN_MSG_LEN: usize = 20;
+    /// what + cpu + timestamp_ns, followed by the event data
+    const PROC_EVENT_HEADER_LEN: usize = 16;
+
+    /// Subscribe to exec events from the kernel (needs CAP_NET_ADMIN)
+    pub fn open() -> Result<ChannelSource> {
+        let socket = subscribe()?;
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        std::thread::Builder::new()
+            .name("focusflow-proc-events".to_string())
+            .spawn(move || read_events(socket, sender))?;
+
+        tracing::info!("Listening for process launch events via netlink");
+
+        Ok(ChannelSource::new("netlink", receiver))
+    }
+
+    fn last_os_error(action: &str) -> Error {
+        let e = std::io::Error::last_os_error();
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => {
+                Error::PermissionDenied(format!("Failed to {} proc connector: {}", action, e))
+            }
+            _ => Error::System(format!("Failed to {} proc connector: {}", action, e)),
+        }
+    }
+
+    fn subscribe() -> Result<OwnedFd> {
+        // SAFETY: plain socket(2) call; the descriptor is owned right away
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                NETLINK_CONNECTOR,
+            )
+        };
+        if fd < 0 {
+            return Err(last_os_error("open"));
+        }
+        // SAFETY: fd is a valid descriptor we exclusively own
+        let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // SAFETY: sockaddr_nl is plain old data; all-zero is a valid value
+        let mut address: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        address.nl_groups = CN_IDX_PROC;
+
+        // SAFETY: address points to a properly sized sockaddr_nl
+        let bound = unsafe {
+            libc::bind(
+                socket.as_raw_fd(),
+                &address as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if bound < 0 {
+            return Err(last_os_error("bind"));
+        }
+
+        let message = listen_message();
+        // SAFETY: message is a live buffer of the given length
+        let sent = unsafe {
+            libc::send(
+                socket.as_raw_fd(),
+                message.as_ptr() as *const libc::c_void,
+                message.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(last_os_error("subscribe to"));
+        }
+
+        Ok(socket)
+    }
+
+    /// nlmsghdr + cn_msg + PROC_CN_MCAST_LISTEN
+    fn listen_message() -> Vec<u8> {
+        let total_len = NLMSG_HDRLEN + CN_MSG_LEN + 4;
+        let mut message = Vec::with_capacity(total_len);
+
+        // nlmsghdr
+        message.extend_from_slice(&(total_len as u32).to_ne_bytes());
+        message.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+        message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+        message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+        message.extend_from_slice(&std::process::id().to_ne_bytes());
+
+        // cn_msg
+        message.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+        message.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+        message.extend_from_slice(&0u32.to_ne_bytes()); // ack
+        message.extend_from_slice(&4u16.to_ne_bytes()); // len
+        message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+
+        message.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+        message
+    }
+
+    /// Forward exec events until the receiving side goes away
+    fn read_events(socket: OwnedFd, sender: tokio::sync::mpsc::UnboundedSender<Notification>) {
+        let mut buffer = vec![0u8; 8192];
+
+        loop {
+            // SAFETY: buffer is a live, writable buffer of the given length
+            let received = unsafe {
+                libc::recv(
+                    socket.as_raw_fd(),
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                    0,
+                )
+            };
+
+            let notifications = if received < 0 {
+                let e = std::io::Error::last_os_error();
+                match e.raw_os_error() {
+                    Some(libc::EINTR) => continue,
+                    Some(libc::ENOBUFS) => vec![Notification::Overflow],
+                    _ => {
+                        tracing::error!("Reading process launch events failed: {}", e);
+                        return;
+                    }
+                }
+            } else {
+                parse_exec_events(&buffer[..received as usize])
+                    .into_iter()
+                    .map(Notification::Exec)
+                    .collect()
+            };
+
+            for notification in notifications {
+                if sender.send(notification).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Extract the process ids of exec events from a netlink datagram
+    pub(crate) fn parse_exec_events(datagram: &[u8]) -> Vec<u32> {
+        let read_u32 = |bytes: &[u8], offset: usize| {
+            bytes
+                .get(offset..offset + 4)
+                .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        };
+
+        let mut pids = Vec::new();
+        let mut offset = 0;
+
+        while let Some(message_len) = read_u32(datagram, offset).map(|len| len as usize) {
+            if message_len < NLMSG_HDRLEN || offset + message_len > datagram.len() {
+                break;
+            }
+            let message = &datagram[offset..offset + message_len];
+
+            let event = NLMSG_HDRLEN + CN_MSG_LEN;
+            if read_u32(message, event) == Some(PROC_EVENT_EXEC) {
+                // exec_proc_event { process_pid, process_tgid }; the tgid is the process id
+                if let Some(tgid) = read_u32(message, event + PROC_EVENT_HEADER_LEN + 4) {
+                    pids.push(tgid);
+                }
+            }
+
+            // Messages are aligned to 4 bytes
+            offset += (message_len + 3) & !3;
+        }
+
+        pids
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn event_message(what: u32, pid: u32, tgid: u32) -> Vec<u8> {
+            let mut message = listen_message();
+            message.truncate(NLMSG_HDRLEN + CN_MSG_LEN);
+            message.extend_from_slice(&what.to_ne_bytes());
+            message.extend_from_slice(&0u32.to_ne_bytes()); // cpu
+            message.extend_from_slice(&0u64.to_ne_bytes()); // timestamp
+            message.extend_from_slice(&pid.to_ne_bytes());
+            message.extend_from_slice(&tgid.to_ne_bytes());
+            let len = message.len() as u32;
+            message[..4].copy_from_slice(&len.to_ne_bytes());
+            message
+        }
+
+        #[test]
+        fn test_listen_message_layout() {
+            let message = listen_message();
+            assert_eq!(message.len(), 40);
+            assert_eq!(u32::from_ne_bytes(message[..4].try_into().unwrap()), 40);
+            assert_eq!(
+                u32::from_ne_bytes(message[36..40].try_into().unwrap()),
+                PROC_CN_MCAST_LISTEN
+            );
+        }
+
+        #[test]
+        fn test_parse_exec_events() {
+            let mut datagram = event_message(PROC_EVENT_EXEC, 4242, 4240);
+            datagram.extend(event_message(0x1, 10, 10)); // fork
+            datagram.extend(event_message(PROC_EVENT_EXEC, 77, 77));
+
+            assert_eq!(parse_exec_events(&datagram), vec![4240, 77]);
+        }
+
+        #[test]
+        fn test_parse_truncated_datagram() {
+            let datagram = event_message(PROC_EVENT_EXEC, 1, 1);
+            assert!(parse_exec_events(&datagram[..30]).is_empty());
+            assert!(parse_exec_events(&[]).is_empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_channel_source_batches_launches() {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut source = ChannelSource::new("test", receiver);
+
+        // The first tick of a tokio interval completes immediately
+        assert_eq!(source.next_event().await, LaunchEvent::Tick { full_scan: false });
+
+        sender.send(Notification::Exec(10)).unwrap();
+        sender.send(Notification::Exec(11)).unwrap();
+        sender.send(Notification::Exec(10)).unwrap();
+        assert_eq!(source.next_event().await, LaunchEvent::Launched(vec![10, 11]));
+
+        sender.send(Notification::Exec(12)).unwrap();
+        sender.send(Notification::Overflow).unwrap();
+        assert_eq!(source.next_event().await, LaunchEvent::Tick { full_scan: true });
+    }
+
+    #[tokio::test]
+    async fn test_channel_source_ticks_between_launches() {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut source = ChannelSource {
+            ticker: ticker(Duration::from_millis(20)),
+            ..ChannelSource::new("test", receiver)
+        };
+        assert_eq!(source.next_event().await, LaunchEvent::Tick { full_scan: false });
+
+        // Launches keep coming, but the overdue tick is not skipped
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        sender.send(Notification::Exec(10)).unwrap();
+        assert_eq!(source.next_event().await, LaunchEvent::Tick { full_scan: false });
+        assert_eq!(source.next_event().await, LaunchEvent::Launched(vec![10]));
+    }
+
+    #[tokio::test]
+    async fn test_channel_source_falls_back_to_polling() {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut source = ChannelSource::new("test", receiver);
+        drop(sender);
+
+        assert_eq!(source.next_event().await, LaunchEvent::Tick { full_scan: true });
+        assert_eq!(source.next_event().await, LaunchEvent::Tick { full_scan: true });
+    }
+
+    #[tokio::test]
+    async fn test_polling_source_always_rescans() {
+        let mut source = PollingSource::new(Duration::from_millis(1));
+        assert_eq!(source.next_event().await, LaunchEvent::Tick { full_scan: true });
+        assert_eq!(source.name(), "polling");
+    }
+}
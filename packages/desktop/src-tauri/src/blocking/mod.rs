@@ -1,6 +1,20 @@
 // blocking/mod.rs - Blocking functionality modules
 
+pub mod allowlist;
+pub mod app_rules;
+pub mod blocklist;
+pub mod budget;
 pub mod capabilities;
+pub mod doh;
 pub mod hosts;
+pub mod import;
+pub mod interstitial;
+pub mod launch;
+pub mod nuclear;
 pub mod process;
+pub mod rules;
+pub mod scheduler;
+pub mod sinkhole;
+pub mod termination;
 pub mod dns;
+pub mod enforcement;
@@ -0,0 +1,410 @@
+// blocking/nuclear.rs - Tamper-resistant nuclear option enforcement
+//
+// The nuclear option is a time-locked lockdown that cannot be cancelled. Its
+// countdown is drawn down by a monotonic clock instead of the wall clock, it is
+// persisted with the session checkpoint, and the process monitoring loop
+// re-asserts blocking on every tick while it is active.
+
+use crate::{
+    blocking::{blocklist::EffectiveBlocklist, hosts},
+    db::queries,
+    state::AppState,
+    Error, Result,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tauri::Emitter;
+use tokio::time::Duration;
+
+/// Longest allowed lockdown (one day)
+pub const MAX_NUCLEAR_DURATION_MINUTES: i32 = 24 * 60;
+
+/// Wall-clock drift from monotonic time tolerated before it is treated as tampering
+pub const NUCLEAR_CLOCK_SKEW_TOLERANCE_MS: i64 = 120_000;
+
+/// Most downtime credited to a lockdown across a reboot, on top of the new boot's uptime
+///
+/// Covers the time between the last checkpoint and shutdown plus the machine
+/// being off. A longer gap is treated as a clock jump and stays on the lockdown.
+pub const NUCLEAR_MAX_REBOOT_GAP_MS: i64 = 15 * 60_000;
+
+/// How often the countdown is persisted while a lockdown is running
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// `boot_id` prefix of readings from the process-local clock
+const PROCESS_CLOCK_PREFIX: &str = "process-";
+
+/// A monotonic clock reading
+///
+/// Readings are only comparable when `boot_id` matches. On Linux the clock is
+/// CLOCK_BOOTTIME (via /proc/uptime), which keeps counting through suspend and
+/// across app restarts within a boot. Elsewhere it is process-local, so a
+/// restart is handled like a reboot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonotonicStamp {
+    pub boot_id: String,
+    pub millis: u64,
+}
+
+impl MonotonicStamp {
+    /// Read the monotonic clock
+    pub fn now() -> Self {
+        #[cfg(target_os = "linux")]
+        if let Some(stamp) = Self::from_boot_clock() {
+            return stamp;
+        }
+
+        Self::from_process_clock()
+    }
+
+    /// Whether the clock keeps counting while the system is suspended
+    ///
+    /// True for the boot clock; the process-local fallback stops during
+    /// suspend on some platforms.
+    pub fn counts_suspend(&self) -> bool {
+        !self.boot_id.starts_with(PROCESS_CLOCK_PREFIX)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn from_boot_clock() -> Option<Self> {
+        let boot_id = std::fs::read_to_string("/proc/sys/kernel/random/boot_id").ok()?;
+        let uptime = std::fs::read_to_string("/proc/uptime").ok()?;
+        let seconds: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+
+        Some(Self {
+            boot_id: boot_id.trim().to_string(),
+            millis: (seconds * 1000.0) as u64,
+        })
+    }
+
+    fn from_process_clock() -> Self {
+        static PROCESS_CLOCK: OnceLock<(String, Instant)> = OnceLock::new();
+        let (id, start) = PROCESS_CLOCK
+            .get_or_init(|| (format!("{}{}", PROCESS_CLOCK_PREFIX, uuid::Uuid::new_v4()), Instant::now()));
+
+        Self {
+            boot_id: id.clone(),
+            millis: start.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Validate a requested lockdown length
+pub fn validate_duration(duration_minutes: i32) -> Result<()> {
+    if !(1..=MAX_NUCLEAR_DURATION_MINUTES).contains(&duration_minutes) {
+        return Err(Error::InvalidInput(format!(
+            "Duration must be between 1 and {} minutes",
+            MAX_NUCLEAR_DURATION_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// Refuse changes that would weaken blocking while a lockdown is running
+pub async fn ensure_unlocked(state: &AppState, action: &str) -> Result<()> {
+    let nuclear_state = state.nuclear_option_state.read().await;
+
+    if nuclear_state.active {
+        tracing::warn!("Refused to {} while nuclear option is active", action);
+        return Err(Error::InvalidInput(format!(
+            "Cannot {} while the nuclear option is active ({} minutes remaining)",
+            action,
+            (nuclear_state.remaining_seconds() + 59) / 60
+        )));
+    }
+
+    Ok(())
+}
+
+/// Turn on blocking with the full stored blocklist
+///
+/// Does nothing if blocking is already enabled by a session, schedule or toggle.
+pub async fn apply_lockdown_blocking(state: &AppState) -> Result<()> {
+    let already_enabled = {
+        let blocking_state = state.blocking_state.read().await;
+        blocking_state.enabled
+    };
+
+    if already_enabled {
+        return Ok(());
+    }
+
+    let domains = EffectiveBlocklist::load(state).await?.domains();
+
+    {
+        let mut blocking_state = state.blocking_state.write().await;
+        blocking_state.enable();
+        blocking_state.update_blocked_websites(domains.clone());
+    }
+
+    if let Err(e) = hosts::update_hosts_file(state, &domains).await {
+        tracing::warn!("Hosts file update failed: {}, DNS fallback active", e);
+    }
+
+    tracing::info!("Nuclear option blocking applied ({} websites)", domains.len());
+
+    Ok(())
+}
+
+/// Log and record a detected clock change during a lockdown
+pub async fn record_clock_tamper(state: &AppState, skew_seconds: i64) {
+    let details = format!(
+        "System clock moved {} by {}s during nuclear option",
+        if skew_seconds >= 0 { "forward" } else { "backward" },
+        skew_seconds.abs()
+    );
+
+    tracing::warn!("Clock tampering detected: {}", details);
+
+    if let Err(e) = queries::record_tamper_event(state.pool(), "clock_change", &details).await {
+        tracing::error!("Failed to record tamper event: {}", e);
+    }
+
+    if let Err(e) = state.app_handle.emit(
+        "tamper-detected",
+        serde_json::json!({
+            "eventType": "clock_change",
+            "details": details,
+        }),
+    ) {
+        tracing::warn!("Failed to emit tamper-detected: {}", e);
+    }
+}
+
+/// Per-loop bookkeeping for nuclear option enforcement
+#[derive(Debug, Default)]
+pub struct NuclearEnforcer {
+    last_checkpoint: Option<Instant>,
+}
+
+impl NuclearEnforcer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance the countdown and keep blocking in force
+    ///
+    /// Called from the process monitoring loop on every tick.
+    pub async fn tick(&mut self, state: &AppState) {
+        let outcome = {
+            let mut nuclear_state = state.nuclear_option_state.write().await;
+            if !nuclear_state.active {
+                return;
+            }
+            nuclear_state.advance(Utc::now(), MonotonicStamp::now())
+        };
+
+        if let Some(skew_seconds) = outcome.clock_skew_seconds {
+            record_clock_tamper(state, skew_seconds).await;
+        }
+
+        if outcome.expired {
+            self.last_checkpoint = None;
+            end_lockdown(state).await;
+            return;
+        }
+
+        // Re-assert blocking in case it was turned off behind our back
+        if let Err(e) = apply_lockdown_blocking(state).await {
+            tracing::error!("Failed to enforce nuclear option blocking: {}", e);
+        }
+
+        let checkpoint_due = self
+            .last_checkpoint
+            .map_or(true, |at| at.elapsed() >= CHECKPOINT_INTERVAL);
+        if checkpoint_due || outcome.clock_skew_seconds.is_some() {
+            state.checkpoint_session().await;
+            self.last_checkpoint = Some(Instant::now());
+        }
+    }
+}
+
+/// Release blocking held by an expired lockdown
+pub(crate) async fn end_lockdown(state: &AppState) {
+    let session_active = {
+        let active_session = state.active_session.read().await;
+        active_session.is_some()
+    };
+
+    // Leave blocking owned by a session or schedule in place
+    let release_blocking = {
+        let mut blocking_state = state.blocking_state.write().await;
+        let release = !session_active && blocking_state.schedule_id.is_none();
+        if release {
+            blocking_state.disable();
+            blocking_state.update_blocked_websites(Vec::new());
+        } else if !session_active {
+            // The schedule's window is still open; it releases blocking when it closes
+            blocking_state.schedule_owns_blocking = true;
+        }
+        release
+    };
+
+    if release_blocking {
+        if let Err(e) = hosts::clear_hosts_file(state).await {
+            tracing::warn!("Failed to clear hosts file: {}", e);
+        }
+    }
+
+    state.checkpoint_session().await;
+
+    tracing::info!("Nuclear option ended");
+
+    if let Err(e) = state.app_handle.emit("nuclear-option-ended", ()) {
+        tracing::warn!("Failed to emit nuclear-option-ended: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{NuclearAdvance, NuclearOptionState};
+    use chrono::{DateTime, Duration as ChronoDuration};
+
+    fn stamp(boot_id: &str, millis: u64) -> MonotonicStamp {
+        MonotonicStamp {
+            boot_id: boot_id.to_string(),
+            millis,
+        }
+    }
+
+    fn start() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-17T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_validate_duration() {
+        assert!(validate_duration(1).is_ok());
+        assert!(validate_duration(90).is_ok());
+        assert!(validate_duration(MAX_NUCLEAR_DURATION_MINUTES).is_ok());
+        assert!(validate_duration(0).is_err());
+        assert!(validate_duration(-5).is_err());
+        assert!(validate_duration(MAX_NUCLEAR_DURATION_MINUTES + 1).is_err());
+    }
+
+    #[test]
+    fn test_counts_down_with_monotonic_time() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(10, start(), stamp("boot", 1_000));
+
+        let outcome = nuclear.advance(start() + ChronoDuration::minutes(4), stamp("boot", 241_000));
+        assert_eq!(outcome, NuclearAdvance::default());
+        assert!(nuclear.active);
+        assert_eq!(nuclear.remaining_seconds(), 6 * 60);
+    }
+
+    #[test]
+    fn test_clock_forward_does_not_end_lockdown() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(30, start(), stamp("boot", 0));
+
+        // Wall clock jumps a day ahead, only 2s of real time passed
+        let outcome = nuclear.advance(start() + ChronoDuration::days(1), stamp("boot", 2_000));
+        assert!(!outcome.expired);
+        assert!(outcome.clock_skew_seconds.unwrap() > 0);
+        assert!(nuclear.active);
+        assert_eq!(nuclear.remaining_seconds(), 30 * 60 - 2);
+    }
+
+    #[test]
+    fn test_clock_backward_is_detected() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(30, start(), stamp("boot", 0));
+
+        let outcome = nuclear.advance(start() - ChronoDuration::hours(1), stamp("boot", 60_000));
+        assert!(outcome.clock_skew_seconds.unwrap() < 0);
+        assert_eq!(nuclear.remaining_seconds(), 29 * 60);
+    }
+
+    #[test]
+    fn test_expires_when_time_runs_out() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(1, start(), stamp("boot", 0));
+
+        let outcome = nuclear.advance(start() + ChronoDuration::seconds(61), stamp("boot", 61_000));
+        assert!(outcome.expired);
+        assert!(!nuclear.active);
+        assert!(nuclear.ends_at.is_none());
+    }
+
+    #[test]
+    fn test_reboot_uses_wall_clock_gap() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(60, start(), stamp("boot-a", 500_000));
+
+        // Rebooted 10 minutes later; monotonic clock restarted
+        let outcome = nuclear.advance(start() + ChronoDuration::minutes(10), stamp("boot-b", 30_000));
+        assert_eq!(outcome, NuclearAdvance::default());
+        assert_eq!(nuclear.remaining_seconds(), 50 * 60);
+    }
+
+    #[test]
+    fn test_reboot_with_clock_moved_forward() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(60, start(), stamp("boot-a", 0));
+
+        // Up for a minute after the reboot, but the wall clock says a day passed
+        let outcome = nuclear.advance(start() + ChronoDuration::days(1), stamp("boot-b", 60_000));
+        assert!(outcome.clock_skew_seconds.unwrap() > 0);
+        assert!(!outcome.expired);
+        assert_eq!(nuclear.remaining_seconds(), 60 * 60 - 60 - NUCLEAR_MAX_REBOOT_GAP_MS / 1000);
+    }
+
+    #[test]
+    fn test_reboot_with_clock_moved_backward() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(60, start(), stamp("boot-a", 0));
+
+        let outcome = nuclear.advance(start() - ChronoDuration::hours(2), stamp("boot-b", 0));
+        assert!(outcome.clock_skew_seconds.unwrap() < 0);
+        assert_eq!(nuclear.remaining_seconds(), 60 * 60);
+    }
+
+    #[test]
+    fn test_sleep_counts_by_boot_clock() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(60, start(), stamp("boot", 0));
+
+        // The boot clock counted the 20 minute sleep
+        let outcome = nuclear.advance_across_sleep(start() + ChronoDuration::minutes(20), stamp("boot", 1_200_000));
+        assert_eq!(outcome, NuclearAdvance::default());
+        assert_eq!(nuclear.remaining_seconds(), 40 * 60);
+
+        // A wall clock moved forward during the sleep is reported, not credited
+        let outcome = nuclear.advance_across_sleep(start() + ChronoDuration::hours(5), stamp("boot", 1_260_000));
+        assert!(outcome.clock_skew_seconds.unwrap() > 0);
+        assert!(!outcome.expired);
+        assert_eq!(nuclear.remaining_seconds(), 39 * 60);
+    }
+
+    #[test]
+    fn test_sleep_counts_by_wall_clock_on_process_clock() {
+        let mut nuclear = NuclearOptionState::default();
+        nuclear.activate(60, start(), stamp("process-1", 0));
+
+        // Slept 20 minutes on a clock that stood still
+        let outcome = nuclear.advance_across_sleep(start() + ChronoDuration::minutes(20), stamp("process-1", 1_000));
+        assert_eq!(outcome, NuclearAdvance::default());
+        assert_eq!(nuclear.remaining_seconds(), 40 * 60);
+
+        // The next tick measures from the new anchor
+        let outcome = nuclear.advance(start() + ChronoDuration::minutes(21), stamp("process-1", 61_000));
+        assert_eq!(outcome, NuclearAdvance::default());
+
+        let outcome = nuclear.advance_across_sleep(start() + ChronoDuration::hours(2), stamp("process-1", 62_000));
+        assert!(outcome.expired);
+        assert!(!nuclear.active);
+    }
+
+    #[test]
+    fn test_inactive_state_does_not_advance() {
+        let mut nuclear = NuclearOptionState::default();
+        let outcome = nuclear.advance(start(), stamp("boot", 0));
+        assert_eq!(outcome, NuclearAdvance::default());
+        assert!(!nuclear.active);
+    }
+}
@@ -1,16 +1,41 @@
 // blocking/process.rs - Efficient process monitoring and termination
 
-use crate::{db::queries, state::AppState, Error, Result};
+use crate::{
+    blocking::{
+        app_rules::{AppRuleKind, DesktopEntry, ExeHashCache, ProcessView},
+        blocklist::{self, BlocklistEntry, EffectiveBlocklist},
+        budget::BudgetTracker,
+        enforcement::{self, EnforcementPolicy},
+        launch::{self, LaunchEvent},
+        nuclear::NuclearEnforcer,
+        termination::{self, TerminationOutcome},
+    },
+    helper,
+    state::AppState,
+    Error, Result,
+};
 use regex::Regex;
-use sysinfo::{ProcessRefreshKind, RefreshKind, System};
+use sysinfo::{ProcessRefreshKind, RefreshKind, System, UpdateKind};
 use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri_plugin_notification::NotificationExt;
-use tokio::time::{interval, Duration};
+use tokio::time::Duration;
 
 /// Monitoring loop configuration
-const MONITOR_INTERVAL_MS: u64 = 2000; // Check every 2 seconds
 const GRACE_PERIOD_MS: u64 = 3000; // Give 3 seconds before killing
 
+/// Set to make the monitoring loop rescan every process on its next event
+static FULL_SCAN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Ask the monitoring loop to rescan every process on its next event
+///
+/// Used after the system wakes from sleep, when launch events may have been
+/// missed.
+pub fn request_full_scan() {
+    FULL_SCAN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
 /// Critical system processes that should never be terminated
 /// Platform-specific protection lists to prevent accidental system damage
 #[cfg(target_os = "windows")]
@@ -122,10 +147,19 @@ struct ProcessMatcher {
     value: String,
     match_type: MatchType,
     regex: Option<Regex>,
+    kind: AppRuleKind,
+    /// Resolved desktop entry for `AppRuleKind::DesktopId` rules
+    desktop_entry: Option<DesktopEntry>,
 }
 
 impl ProcessMatcher {
+    /// Matcher for a process name rule
+    #[cfg(test)]
     fn new(value: String, match_type: String) -> Result<Self> {
+        Self::with_kind(value, match_type, AppRuleKind::Name)
+    }
+
+    fn with_kind(value: String, match_type: String, kind: AppRuleKind) -> Result<Self> {
         let match_type = MatchType::from_str(&match_type);
         let regex = if match_type == MatchType::Regex {
             Some(Regex::new(&value).map_err(|e| {
@@ -135,13 +169,45 @@ impl ProcessMatcher {
             None
         };
 
+        let desktop_entry = (kind == AppRuleKind::DesktopId).then(|| DesktopEntry::resolve(&value));
+
         Ok(Self {
             value,
             match_type,
             regex,
+            kind,
+            desktop_entry,
         })
     }
 
+    /// Check if a process matches this blocked item, according to the rule kind
+    fn matches_process(&self, process: &ProcessView, hashes: &ExeHashCache) -> bool {
+        match self.kind {
+            AppRuleKind::Name => self.matches(process.name),
+            AppRuleKind::ExePath => process
+                .exe
+                .is_some_and(|exe| self.matches_text(&exe.to_string_lossy())),
+            AppRuleKind::Cmdline => !process.cmd.is_empty() && self.matches_text(&process.command_line()),
+            AppRuleKind::ExeHash => process
+                .exe
+                .and_then(|exe| hashes.get(exe))
+                .is_some_and(|hash| hash.eq_ignore_ascii_case(&self.value)),
+            AppRuleKind::DesktopId => self
+                .desktop_entry
+                .as_ref()
+                .is_some_and(|entry| entry.matches(process)),
+        }
+    }
+
+    /// Match a path or command line: exact is case-sensitive, without name normalization
+    fn matches_text(&self, text: &str) -> bool {
+        match self.match_type {
+            MatchType::Exact => text == self.value,
+            MatchType::Contains => self.contains_match(text),
+            MatchType::Regex => self.regex_match(text),
+        }
+    }
+
     /// Check if a process name matches this blocked item
     fn matches(&self, process_name: &str) -> bool {
         match self.match_type {
@@ -175,13 +241,88 @@ impl ProcessMatcher {
     }
 }
 
+/// Compile matchers for blocked apps, each paired with its enforcement policy
+fn compile_matchers(apps: &[BlocklistEntry]) -> Vec<(ProcessMatcher, EnforcementPolicy)> {
+    apps.iter()
+        .filter_map(|item| {
+            match ProcessMatcher::with_kind(item.value.clone(), item.match_type.clone(), item.rule_kind) {
+                Ok(matcher) => Some((matcher, item.enforcement)),
+                Err(e) => {
+                    tracing::warn!(
+                        "Invalid blocking rule for '{}': {}. Skipping.",
+                        item.value,
+                        e
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Compiled matchers for the effective app blocklist
+///
+/// Only reloaded from the database when `blocklist::invalidate` was called.
+#[derive(Default)]
+struct MatcherCache {
+    generation: Option<u64>,
+    matchers: Vec<(ProcessMatcher, EnforcementPolicy)>,
+    hashes: ExeHashCache,
+}
+
+impl MatcherCache {
+    /// Reload the matchers if the blocklist changed, returning whether it did
+    async fn refresh(&mut self, state: &AppState) -> Result<bool> {
+        let generation = blocklist::generation();
+        if self.generation == Some(generation) {
+            return Ok(false);
+        }
+
+        let apps = EffectiveBlocklist::load(state).await?.apps;
+        self.matchers = compile_matchers(&apps);
+        self.generation = Some(generation);
+
+        Ok(true)
+    }
+
+    /// Hash the executables of scanned processes when a rule needs them
+    async fn load_hashes(&mut self, scan: &System) {
+        if !self.matchers.iter().any(|(matcher, _)| matcher.kind == AppRuleKind::ExeHash) {
+            return;
+        }
+
+        let exes = scan.processes().values().filter_map(|process| process.exe().map(Path::to_path_buf));
+        self.hashes.load(exes.collect::<Vec<_>>()).await;
+    }
+
+    /// Enforcement policy of the first rule matching a process
+    fn find(&self, process: &ProcessView) -> Option<EnforcementPolicy> {
+        self.matchers
+            .iter()
+            .find(|(matcher, _)| matcher.matches_process(process, &self.hashes))
+            .map(|(_, enforcement)| *enforcement)
+    }
+
+    /// Process details the current rules need
+    fn refresh_kind(&self) -> ProcessRefreshKind {
+        self.matchers
+            .iter()
+            .fold(monitor_refresh_kind(), |kind, (matcher, _)| matcher.kind.refresh_kind(kind))
+    }
+}
+
+/// Process details the monitoring loop needs (name is always loaded)
+fn monitor_refresh_kind() -> ProcessRefreshKind {
+    ProcessRefreshKind::nothing().with_user(UpdateKind::OnlyIfNotSet)
+}
+
 /// Normalize a process name for exact matching
 ///
 /// This handles platform-specific variations:
 /// - Case-insensitive comparison (Chrome == chrome == CHROME)
 /// - Windows .exe extension stripping (chrome.exe == chrome)
 /// - Consistent lowercasing
-fn normalize_process_name(name: &str) -> String {
+pub(crate) fn normalize_process_name(name: &str) -> String {
     let mut normalized = name.to_lowercase();
 
     // Strip .exe extension on all platforms for consistency
@@ -206,6 +347,11 @@ fn is_protected_process(process_name: &str) -> bool {
     })
 }
 
+/// Check if a blocked app may be terminated: user-owned and not a protected process
+pub(crate) fn is_terminable(process: &sysinfo::Process) -> bool {
+    !is_protected_process(&process.name().to_string_lossy()) && is_user_owned_process(process)
+}
+
 /// Check if a process is owned by the current user
 ///
 /// Returns true if the process is owned by the current user.
@@ -241,18 +387,12 @@ fn is_user_owned_process(process: &sysinfo::Process) -> bool {
 #[cfg(any(target_os = "macos", target_os = "linux"))]
 fn is_user_owned_process(process: &sysinfo::Process) -> bool {
     // On Unix-like systems, check if the process UID matches current user
-    // System processes typically run as root (UID 0) or low UIDs (< 1000)
+    // System processes run as root (UID 0) or service accounts, never as us
 
     if let Some(user_id) = process.user_id() {
-        // Get the numeric UID
-        let user_id_str = format!("{:?}", user_id);
-
-        // Try to parse as a number
-        if let Ok(uid) = user_id_str.parse::<u32>() {
-            // System processes typically have UID < 1000
-            // User processes have UID >= 1000 on most systems
-            return uid >= 1000;
-        }
+        // SAFETY: getuid is always successful and has no side effects
+        let current_uid = unsafe { libc::getuid() };
+        return **user_id == current_uid;
     }
 
     // If we can't determine ownership, err on the side of caution
@@ -267,22 +407,36 @@ fn is_user_owned_process(_process: &sysinfo::Process) -> bool {
 
 /// Start the process monitoring loop
 ///
-/// This runs in the background and checks for blocked processes at regular intervals.
-/// Uses efficient delta updates from sysinfo to minimize CPU usage.
+/// This runs in the background and reacts to process launches reported by a
+/// `LaunchEventSource`: newly started processes are checked right away, and all
+/// processes are rescanned when blocking starts, the blocklist changes or the
+/// source asks for it (every tick when polling).
 ///
 /// Supports two blocking modes:
 /// 1. Standard blocking: Blocks specific apps in the blocklist
 /// 2. Focus Time (inverse blocking): Blocks ALL apps EXCEPT those in the allowed list
 pub async fn start_monitoring_loop(state: AppState) -> Result<()> {
-    let mut system = System::new_with_specifics(
-        RefreshKind::nothing().with_processes(ProcessRefreshKind::everything()),
-    );
-
-    let mut interval = interval(Duration::from_millis(MONITOR_INTERVAL_MS));
+    let mut system = System::new();
+    let mut source = launch::default_source();
     let mut warned_processes: HashSet<String> = HashSet::new();
+    let mut nuclear_enforcer = NuclearEnforcer::new();
+    let mut matcher_cache = MatcherCache::default();
+    let mut budget_tracker = BudgetTracker::default();
+    // Whether the last scan ran in Focus Time mode (None while nothing is blocked)
+    let mut last_mode: Option<bool> = None;
+
+    tracing::info!("Process monitoring uses {} launch detection", source.name());
 
     loop {
-        interval.tick().await;
+        let event = source.next_event().await;
+
+        // Keep a running nuclear option lockdown in force (re-enables blocking if needed)
+        if matches!(event, LaunchEvent::Tick { .. }) {
+            nuclear_enforcer.tick(&state).await;
+        }
+
+        // Daily time budgets apply whether or not blocking is on
+        budget_tracker.tick(&state).await;
 
         // Check if standard blocking is enabled
         let blocking_enabled = {
@@ -303,15 +457,66 @@ pub async fn start_monitoring_loop(state: AppState) -> Result<()> {
         // If neither mode is active, clear warnings and continue
         if !blocking_enabled && focus_time_state.is_none() {
             warned_processes.clear();
+            enforcement::release_all(&state).await;
+            last_mode = None;
             continue;
         }
 
-        // Refresh process list (only updates changed processes)
-        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        // Standard mode matchers are cached until the blocklist changes
+        let blocklist_changed = if focus_time_state.is_none() {
+            match matcher_cache.refresh(&state).await {
+                Ok(changed) => changed,
+                Err(e) => {
+                    tracing::error!("Failed to fetch blocked apps: {}", e);
+                    continue;
+                }
+            }
+        } else {
+            false
+        };
+
+        let mode = Some(focus_time_state.is_some());
+        let rescan = mode != last_mode
+            || blocklist_changed
+            || FULL_SCAN_REQUESTED.swap(false, Ordering::SeqCst);
+        last_mode = mode;
+
+        // Refresh only what the event calls for, loading what the app rules inspect
+        let refresh_kind = if focus_time_state.is_some() {
+            monitor_refresh_kind()
+        } else {
+            matcher_cache.refresh_kind()
+        };
+        let mut launched_system;
+        let (scan, full_scan): (&System, bool) = match event {
+            LaunchEvent::Launched(pids) if !rescan => {
+                let pids: Vec<sysinfo::Pid> = pids.into_iter().map(sysinfo::Pid::from_u32).collect();
+                launched_system = System::new();
+                launched_system.refresh_processes_specifics(
+                    sysinfo::ProcessesToUpdate::Some(&pids),
+                    true,
+                    refresh_kind,
+                );
+                (&launched_system, false)
+            }
+            LaunchEvent::Tick { full_scan: false } if !rescan => {
+                let mut blocking_state = state.blocking_state.write().await;
+                blocking_state.update_last_check();
+                continue;
+            }
+            _ => {
+                system.refresh_processes_specifics(
+                    sysinfo::ProcessesToUpdate::All,
+                    true,
+                    refresh_kind,
+                );
+                (&system, true)
+            }
+        };
 
         // Handle Focus Time inverse blocking (takes priority when active)
         if let Some(ref ft_state) = focus_time_state {
-            for (pid, process) in system.processes() {
+            for (pid, process) in scan.processes() {
                 let process_name = process.name().to_string_lossy();
 
                 // Safety check: Skip protected system processes
@@ -374,35 +579,32 @@ pub async fn start_monitoring_loop(state: AppState) -> Result<()> {
                                 return;
                             }
 
-                            if let Err(e) = terminate_process(pid_copy) {
-                                tracing::error!("Failed to terminate {}: {}", process_name_copy, e);
-                            } else {
-                                tracing::info!(
-                                    "Focus Time: Terminated non-allowed process: {} (PID: {})",
-                                    process_name_copy,
-                                    pid_copy
-                                );
-
-                                // Record the block attempt
-                                let session_id = {
-                                    let active_session = state_clone.active_session.read().await;
-                                    active_session.as_ref().map(|s| s.id.clone())
-                                };
-
-                                let user_id = state_clone.get_user_id().await;
-
-                                if let Err(e) = queries::record_block_attempt(
-                                    state_clone.pool(),
-                                    "focus_time_app",
-                                    &process_name_copy,
-                                    session_id.as_deref(),
-                                    user_id.as_deref(),
-                                )
-                                .await
-                                {
-                                    tracing::error!("Failed to record block attempt: {}", e);
+                            let outcome = match terminate_blocked_process(&state_clone, pid_copy).await {
+                                Ok(outcome) => outcome,
+                                Err(e) => {
+                                    tracing::error!("Failed to terminate {}: {}", process_name_copy, e);
+                                    return;
                                 }
+                            };
+
+                            tracing::info!(
+                                "Focus Time: Terminated non-allowed process: {} (PID: {}, {})",
+                                process_name_copy,
+                                pid_copy,
+                                outcome.status()
+                            );
+
+                            // Record the block attempt
+                            enforcement::record_app_attempt(
+                                &state_clone,
+                                "focus_time_app",
+                                &process_name_copy,
+                                outcome.status(),
+                                Some(&outcome),
+                            )
+                            .await;
 
+                            if outcome.status() == "killed" {
                                 let _ = app_handle
                                     .notification()
                                     .builder()
@@ -419,18 +621,20 @@ pub async fn start_monitoring_loop(state: AppState) -> Result<()> {
             }
 
             // Clean up Focus Time warned processes and continue to next iteration
-            warned_processes.retain(|key| {
-                if !key.starts_with("ft:") {
-                    return true; // Keep non-focus-time entries
-                }
-                let parts: Vec<&str> = key.split(':').collect();
-                if parts.len() >= 3 {
-                    if let Ok(pid) = parts[2].parse::<usize>() {
-                        return system.process(sysinfo::Pid::from(pid)).is_some();
+            if full_scan {
+                warned_processes.retain(|key| {
+                    if !key.starts_with("ft:") {
+                        return true; // Keep non-focus-time entries
                     }
-                }
-                false
-            });
+                    let parts: Vec<&str> = key.split(':').collect();
+                    if parts.len() >= 3 {
+                        if let Ok(pid) = parts[2].parse::<usize>() {
+                            return system.process(sysinfo::Pid::from(pid)).is_some();
+                        }
+                    }
+                    false
+                });
+            }
 
             // Update last check timestamp
             {
@@ -443,43 +647,14 @@ pub async fn start_monitoring_loop(state: AppState) -> Result<()> {
         }
 
         // Standard blocking mode (only when Focus Time is not active)
-        // Get blocked apps from database
-        let blocked_items = match queries::get_blocked_items(state.pool(), Some("app")).await {
-            Ok(items) => items,
-            Err(e) => {
-                tracing::error!("Failed to fetch blocked apps: {}", e);
-                continue;
-            }
-        };
-
-        if blocked_items.is_empty() {
+        if matcher_cache.matchers.is_empty() {
             continue;
         }
 
-        // Build process matchers from blocked items
-        let matchers: Vec<ProcessMatcher> = blocked_items
-            .iter()
-            .filter_map(|item| {
-                match ProcessMatcher::new(item.value.clone(), item.match_type.clone()) {
-                    Ok(matcher) => Some(matcher),
-                    Err(e) => {
-                        tracing::warn!(
-                            "Invalid blocking rule for '{}': {}. Skipping.",
-                            item.value,
-                            e
-                        );
-                        None
-                    }
-                }
-            })
-            .collect();
-
-        if matchers.is_empty() {
-            continue;
-        }
+        matcher_cache.load_hashes(scan).await;
 
         // Check for blocked processes (standard mode)
-        for (pid, process) in system.processes() {
+        for (pid, process) in scan.processes() {
             let process_name = process.name().to_string_lossy();
 
             // Safety check: Skip protected system processes
@@ -493,88 +668,70 @@ pub async fn start_monitoring_loop(state: AppState) -> Result<()> {
             }
 
             // Check if this process matches any blocked rule
-            let is_blocked = matchers.iter().any(|matcher| matcher.matches(&process_name));
-
-            if is_blocked {
-                let process_key = format!("{}:{}", process_name, pid);
-
-                if !warned_processes.contains(&process_key) {
-                    // First detection: send warning notification
-                    tracing::warn!("Detected blocked process: {} (PID: {})", process_name, pid);
-
-                    if let Err(e) = state
-                        .app_handle
-                        .notification()
-                        .builder()
-                        .title("Blocked Application Detected")
-                        .body(format!(
-                            "{} is blocked during focus sessions. It will be closed shortly.",
-                            process.name().to_string_lossy()
-                        ))
-                        .show()
-                    {
-                        tracing::warn!("Failed to send notification: {}", e);
-                    }
-
-                    warned_processes.insert(process_key.clone());
-
-                    // Schedule termination after grace period
-                    let pid_copy = *pid;
-                    let process_name_copy = process_name.to_string();
-                    let app_handle = state.app_handle.clone();
-                    let state_clone = state.clone();
-
-                    tokio::spawn(async move {
-                        tokio::time::sleep(Duration::from_millis(GRACE_PERIOD_MS)).await;
-
-                        if let Err(e) = terminate_process(pid_copy) {
-                            tracing::error!("Failed to terminate {}: {}", process_name_copy, e);
-                        } else {
-                            tracing::info!("Terminated blocked process: {} (PID: {})", process_name_copy, pid_copy);
-
-                            // Record the block attempt
-                            let session_id = {
-                                let active_session = state_clone.active_session.read().await;
-                                active_session.as_ref().map(|s| s.id.clone())
-                            };
+            let Some(enforcement) = matcher_cache.find(&ProcessView::new(&process_name, process)) else {
+                continue;
+            };
 
-                            let user_id = state_clone.get_user_id().await;
+            let process_key = format!("{}:{}", process_name, pid);
 
-                            if let Err(e) = queries::record_block_attempt(
-                                state_clone.pool(),
-                                "app",
-                                &process_name_copy,
-                                session_id.as_deref(),
-                                user_id.as_deref(),
-                            )
-                            .await
-                            {
-                                tracing::error!("Failed to record block attempt: {}", e);
-                            } else {
-                                tracing::debug!("Recorded block attempt for: {}", process_name_copy);
-                            }
+            // Paused apps (and ones the user chose to continue) are handled by the user
+            if warned_processes.contains(&process_key) || enforcement::is_delayed(&state, pid.as_u32()).await {
+                continue;
+            }
 
-                            let _ = app_handle
-                                .notification()
-                                .builder()
-                                .title("Application Closed")
-                                .body(format!("{} has been closed to help you focus.", process_name_copy))
-                                .show();
+            tracing::warn!(
+                "Detected blocked process: {} (PID: {}, enforcement: {})",
+                process_name,
+                pid,
+                enforcement.as_str()
+            );
+            warned_processes.insert(process_key);
+
+            match enforcement {
+                EnforcementPolicy::WarnOnly => {
+                    notify(
+                        &state,
+                        "Blocked Application Detected",
+                        &format!("{} is on your block list. Is this what you meant to focus on?", process_name),
+                    );
+                    enforcement::record_app_attempt(&state, "app", &process_name, "warned", None).await;
+                }
+                EnforcementPolicy::Delay => {
+                    match enforcement::delay_process(&state, pid.as_u32(), &process_name).await {
+                        Ok(Some(_)) => {
+                            notify(
+                                &state,
+                                "Blocked Application Paused",
+                                &format!(
+                                    "{} is paused. Type why you need it, or wait {} seconds, to continue.",
+                                    process_name,
+                                    enforcement::DELAY_COUNTDOWN_SECONDS
+                                ),
+                            );
+                            enforcement::record_app_attempt(&state, "app", &process_name, "delayed", None).await;
                         }
-                    });
+                        // Suspending is not supported here; close the app instead
+                        Ok(None) => schedule_termination(&state, *pid, &process_name),
+                        Err(e) => tracing::error!("Failed to pause {}: {}", process_name, e),
+                    }
                 }
+                EnforcementPolicy::Kill => schedule_termination(&state, *pid, &process_name),
             }
         }
 
         // Clean up warned processes that are no longer running
-        warned_processes.retain(|key| {
-            let pid_str = key.split(':').nth(1).unwrap_or("");
-            if let Ok(pid) = pid_str.parse::<usize>() {
-                system.process(sysinfo::Pid::from(pid)).is_some()
-            } else {
-                false
-            }
-        });
+        if full_scan {
+            enforcement::prune_exited(&state, &system).await;
+
+            warned_processes.retain(|key| {
+                let pid_str = key.split(':').nth(1).unwrap_or("");
+                if let Ok(pid) = pid_str.parse::<usize>() {
+                    system.process(sysinfo::Pid::from(pid)).is_some()
+                } else {
+                    false
+                }
+            });
+        }
 
         // Update last check timestamp
         {
@@ -584,51 +741,137 @@ pub async fn start_monitoring_loop(state: AppState) -> Result<()> {
     }
 }
 
-/// Terminate a process by PID
-///
-/// Uses platform-specific APIs for graceful then forceful termination.
-/// Includes safety checks to prevent terminating critical system processes.
-fn terminate_process(pid: sysinfo::Pid) -> Result<()> {
-    let mut system = System::new();
-    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+/// Show a desktop notification, logging failures
+pub(crate) fn notify(state: &AppState, title: &str, body: &str) {
+    if let Err(e) = state.app_handle.notification().builder().title(title).body(body).show() {
+        tracing::warn!("Failed to send notification: {}", e);
+    }
+}
 
-    if let Some(process) = system.process(pid) {
-        let process_name = process.name().to_string_lossy();
+/// Warn about a blocked app and close it after the grace period
+fn schedule_termination(state: &AppState, pid: sysinfo::Pid, process_name: &str) {
+    notify(
+        state,
+        "Blocked Application Detected",
+        &format!("{} is blocked during focus sessions. It will be closed shortly.", process_name),
+    );
 
-        // Safety check 1: Verify it's not a protected system process
-        if is_protected_process(&process_name) {
-            return Err(Error::System(format!(
-                "Refusing to terminate protected system process: {} (PID: {})",
-                process_name, pid
-            )));
-        }
+    close_after_grace_period(state, pid, process_name);
+}
 
-        // Safety check 2: Verify it's user-owned
-        if !is_user_owned_process(process) {
-            return Err(Error::System(format!(
-                "Refusing to terminate non-user-owned process: {} (PID: {})",
-                process_name, pid
-            )));
-        }
+/// Close a blocked app after the grace period, recording the outcome
+pub(crate) fn close_after_grace_period(state: &AppState, pid: sysinfo::Pid, process_name: &str) {
+    let process_name = process_name.to_string();
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(GRACE_PERIOD_MS)).await;
+
+        let outcome = match terminate_blocked_process(&state, pid).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                tracing::error!("Failed to terminate {}: {}", process_name, e);
+                return;
+            }
+        };
 
-        // All safety checks passed, attempt termination
-        tracing::debug!(
-            "Terminating user process: {} (PID: {})",
+        tracing::info!(
+            "Terminated blocked process: {} (PID: {}, {}: {} killed, {} survived, {} protected)",
             process_name,
-            pid
+            pid,
+            outcome.status(),
+            outcome.killed.len(),
+            outcome.survived.len(),
+            outcome.protected.len()
         );
 
-        // Try graceful termination first
-        if process.kill() {
-            Ok(())
-        } else {
-            Err(Error::System(format!(
-                "Failed to terminate process {} (PID: {})",
-                process_name, pid
-            )))
+        enforcement::record_app_attempt(&state, "app", &process_name, outcome.status(), Some(&outcome)).await;
+
+        match outcome.status() {
+            "killed" => notify(
+                &state,
+                "Application Closed",
+                &format!("{} has been closed to help you focus.", process_name),
+            ),
+            "survived" => notify(
+                &state,
+                "Application Still Running",
+                &format!("{} could not be fully closed.", process_name),
+            ),
+            _ => {}
+        }
+    });
+}
+
+/// Terminate a blocked process and everything it started
+///
+/// Sends SIGTERM to the process tree, then SIGKILL after the configured
+/// timeout. Protected and other users' processes are never signalled.
+/// Processes that survive (usually missing privileges) are handed to the
+/// privileged helper when it is running.
+async fn terminate_blocked_process(state: &AppState, pid: sysinfo::Pid) -> Result<TerminationOutcome> {
+    let settings = termination::load_settings(state.pool()).await?;
+    let mut outcome = termination::terminate_tree(pid.as_u32(), settings.graceful_timeout(), &is_terminable).await?;
+
+    if !outcome.survived.is_empty() && helper::is_available().await {
+        let client = helper::HelperClient::default();
+        let mut survived = Vec::new();
+
+        for pid in std::mem::take(&mut outcome.survived) {
+            match client.kill_process(pid).await {
+                Ok(()) => outcome.killed.push(pid),
+                Err(e) => {
+                    tracing::debug!("Helper could not terminate PID {}: {}", pid, e);
+                    survived.push(pid);
+                }
+            }
         }
+        outcome.survived = survived;
+    }
+
+    Ok(outcome)
+}
+
+/// Terminate a process on behalf of a privileged helper client
+///
+/// Ownership is checked against the client's uid rather than the current
+/// user, so the helper (running as root) never kills another user's process.
+#[cfg(unix)]
+pub(crate) fn terminate_process_for_uid(pid: u32, uid: u32) -> Result<()> {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_user(UpdateKind::Always),
+    );
+
+    let process = system
+        .process(pid)
+        .ok_or_else(|| Error::ProcessNotFound(format!("PID {}", pid)))?;
+    let process_name = process.name().to_string_lossy();
+
+    if is_protected_process(&process_name) {
+        return Err(Error::PermissionDenied(format!(
+            "Refusing to terminate protected system process: {} (PID: {})",
+            process_name, pid
+        )));
+    }
+
+    if process.user_id().map(|owner| **owner) != Some(uid) {
+        return Err(Error::PermissionDenied(format!(
+            "Process {} (PID: {}) is not owned by uid {}",
+            process_name, pid, uid
+        )));
+    }
+
+    if process.kill() {
+        Ok(())
     } else {
-        Err(Error::ProcessNotFound(format!("PID {}", pid)))
+        Err(Error::System(format!(
+            "Failed to terminate process {} (PID: {})",
+            process_name, pid
+        )))
     }
 }
 
@@ -916,4 +1159,79 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_rule_kinds_match_beyond_process_name() {
+        let cmd: Vec<std::ffi::OsString> = ["/usr/bin/python3", "/opt/tools/game.py", "--fullscreen"]
+            .iter()
+            .map(Into::into)
+            .collect();
+        let process = ProcessView {
+            name: "python3",
+            exe: Some(std::path::Path::new("/usr/bin/python3")),
+            cmd: &cmd,
+            environ: &[],
+        };
+        let hashes = ExeHashCache::default();
+
+        let by_path = ProcessMatcher::with_kind(
+            "/usr/bin/python3".to_string(),
+            "exact".to_string(),
+            AppRuleKind::ExePath,
+        )
+        .unwrap();
+        assert!(by_path.matches_process(&process, &hashes));
+
+        // Exact paths are case-sensitive and not normalized like names
+        let by_path = ProcessMatcher::with_kind(
+            "/usr/bin/Python3".to_string(),
+            "exact".to_string(),
+            AppRuleKind::ExePath,
+        )
+        .unwrap();
+        assert!(!by_path.matches_process(&process, &hashes));
+
+        let by_cmdline = ProcessMatcher::with_kind(
+            r"game\.py".to_string(),
+            "regex".to_string(),
+            AppRuleKind::Cmdline,
+        )
+        .unwrap();
+        assert!(by_cmdline.matches_process(&process, &hashes));
+
+        // The interpreter's name alone does not reveal the script
+        let by_name = ProcessMatcher::new("game".to_string(), "exact".to_string()).unwrap();
+        assert!(!by_name.matches_process(&process, &hashes));
+    }
+
+    #[tokio::test]
+    async fn test_exe_hash_rule_survives_renaming() {
+        let dir = std::env::temp_dir().join(format!("focusflow-exe-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("renamed-game");
+        std::fs::write(&exe, b"hello").unwrap();
+
+        let process = ProcessView {
+            name: "renamed-game",
+            exe: Some(&exe),
+            cmd: &[],
+            environ: &[],
+        };
+        let mut hashes = ExeHashCache::default();
+        hashes.load([exe.clone()]).await;
+
+        let by_hash = ProcessMatcher::with_kind(
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string(),
+            "exact".to_string(),
+            AppRuleKind::ExeHash,
+        )
+        .unwrap();
+        assert!(by_hash.matches_process(&process, &hashes));
+
+        std::fs::write(&exe, b"other binary").unwrap();
+        hashes.load([exe.clone()]).await;
+        assert!(!by_hash.matches_process(&process, &hashes));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,675 @@
+// blocking/rules.rs - Website rule parsing and matching
+//
+// Website rules are plain strings so they can be stored in blocked_items,
+// categories and sessions alongside ordinary domains:
+//
+//   example.com          example.com and all of its subdomains
+//   *.example.com        subdomains of example.com, but not example.com itself
+//   youtube.com/shorts   only URLs under that path (host and its subdomains)
+//   /^ads?\d*\./         regular expression, matched against the host (and
+//                        against host + path when a full URL is checked)
+//   !music.youtube.com   exception: any rule can be prefixed with `!` to allow
+//                        matching names or URLs; exceptions win over blocks
+//   *                    every website; combined with exceptions this is an
+//                        allow-list (see `blocking::allowlist`)
+//
+// A list of rules is compiled once into a WebsiteMatcher, which is shared by the
+// DNS fallback, the DNS sinkhole and the hosts file writer.
+
+use crate::{blocking::dns::DomainCheckResult, Error, Result};
+use regex::{RegexSet, RegexSetBuilder};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// What a website rule matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleTarget {
+    /// A domain and all of its subdomains
+    Domain(String),
+    /// Subdomains of a domain, but not the domain itself
+    Wildcard(String),
+    /// URLs below `path` on a host (which may itself be a `*.` wildcard)
+    Path { host: String, path: String },
+    /// Regular expression
+    Regex(String),
+    /// Every website
+    All,
+}
+
+/// A single parsed website rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebsiteRule {
+    pub target: RuleTarget,
+    /// Allow-list exception rather than a block
+    pub exception: bool,
+}
+
+impl WebsiteRule {
+    /// Parse a rule in the string syntax described at the top of this module
+    pub fn parse(rule: &str) -> Result<Self> {
+        let rule = rule.trim();
+
+        if rule.contains('\0') {
+            return Err(Error::InvalidInput(
+                "Website rule contains invalid characters".to_string(),
+            ));
+        }
+
+        let (exception, rule) = match rule.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, rule),
+        };
+
+        if rule.len() > 2 && rule.starts_with('/') && rule.ends_with('/') {
+            return Self::regex(&rule[1..rule.len() - 1], exception);
+        }
+
+        if rule == "*" {
+            return Ok(Self {
+                target: RuleTarget::All,
+                exception,
+            });
+        }
+
+        let rule = rule.to_lowercase();
+        let rule = strip_scheme(&rule);
+
+        let target = match rule.split_once('/') {
+            Some((host, path)) => {
+                validate_host(host)?;
+                let path = path.split(['?', '#']).next().unwrap_or_default();
+                let path = format!("/{}", path.trim_end_matches('/'));
+                if path == "/" {
+                    // "example.com/" is just the domain
+                    host_target(host)
+                } else {
+                    RuleTarget::Path {
+                        host: host.to_string(),
+                        path,
+                    }
+                }
+            }
+            None => {
+                validate_host(rule)?;
+                host_target(rule)
+            }
+        };
+
+        Ok(Self { target, exception })
+    }
+
+    /// Build a rule from a blocked_items row, honouring its match_type
+    ///
+    /// `regex` values may be bare patterns; `contains` matches the value
+    /// anywhere in the host (or host + path).
+    pub fn from_blocked_item(value: &str, match_type: &str) -> Result<Self> {
+        let value = value.trim();
+        let (exception, pattern) = match value.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+
+        match match_type {
+            "regex" if !(pattern.len() > 2 && pattern.starts_with('/') && pattern.ends_with('/')) => {
+                Self::regex(pattern, exception)
+            }
+            "contains" => Self::regex(&regex::escape(&pattern.to_lowercase()), exception),
+            _ => Self::parse(value),
+        }
+    }
+
+    fn regex(pattern: &str, exception: bool) -> Result<Self> {
+        if pattern.is_empty() || pattern.len() > 1024 {
+            return Err(Error::InvalidInput(
+                "Regex rule must be between 1 and 1024 characters".to_string(),
+            ));
+        }
+
+        regex::Regex::new(pattern).map_err(|e| {
+            Error::InvalidInput(format!("Invalid regex pattern '{}': {}", pattern, e))
+        })?;
+
+        Ok(Self {
+            target: RuleTarget::Regex(pattern.to_string()),
+            exception,
+        })
+    }
+
+    /// Match type recorded for this rule ("exact", "wildcard", "path", "regex" or "allowlist")
+    pub fn match_type(&self) -> &'static str {
+        match self.target {
+            RuleTarget::Domain(_) => "exact",
+            RuleTarget::Wildcard(_) => "wildcard",
+            RuleTarget::Path { .. } => "path",
+            RuleTarget::Regex(_) => "regex",
+            RuleTarget::All => "allowlist",
+        }
+    }
+}
+
+impl fmt::Display for WebsiteRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.exception {
+            f.write_str("!")?;
+        }
+        match &self.target {
+            RuleTarget::Domain(domain) => f.write_str(domain),
+            RuleTarget::Wildcard(domain) => write!(f, "*.{}", domain),
+            RuleTarget::Path { host, path } => write!(f, "{}{}", host, path),
+            RuleTarget::Regex(pattern) => write!(f, "/{}/", pattern),
+            RuleTarget::All => f.write_str("*"),
+        }
+    }
+}
+
+fn host_target(host: &str) -> RuleTarget {
+    match host.strip_prefix("*.") {
+        Some(domain) => RuleTarget::Wildcard(domain.to_string()),
+        None => RuleTarget::Domain(host.to_string()),
+    }
+}
+
+fn strip_scheme(rule: &str) -> &str {
+    rule.strip_prefix("http://")
+        .or_else(|| rule.strip_prefix("https://"))
+        .unwrap_or(rule)
+}
+
+/// Validate a host name, optionally prefixed with `*.`
+fn validate_host(host: &str) -> Result<()> {
+    let domain = host.strip_prefix("*.").unwrap_or(host);
+
+    if domain.is_empty() {
+        return Err(Error::InvalidInput("Domain cannot be empty".to_string()));
+    }
+
+    // Max 253 chars per DNS spec
+    if domain.len() > 253 {
+        return Err(Error::InvalidInput(
+            "Domain name is too long (max 253 characters)".to_string(),
+        ));
+    }
+
+    if !domain.contains('.') {
+        return Err(Error::InvalidInput(
+            "Invalid domain format. Domain must contain at least one dot (e.g., 'example.com')".to_string(),
+        ));
+    }
+
+    if domain.contains(':') {
+        return Err(Error::InvalidInput(
+            "Invalid domain format. Use 'example.com' without a port".to_string(),
+        ));
+    }
+
+    if !domain.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.') {
+        return Err(Error::InvalidInput(
+            "Domain contains invalid characters. Only letters, numbers, hyphens, and dots are allowed".to_string(),
+        ));
+    }
+
+    if domain.starts_with('.') || domain.ends_with('.') || domain.starts_with('-') || domain.ends_with('-') {
+        return Err(Error::InvalidInput(
+            "Domain cannot start or end with a dot or hyphen".to_string(),
+        ));
+    }
+
+    if domain.contains("..") {
+        return Err(Error::InvalidInput(
+            "Domain cannot contain consecutive dots".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Rules of one polarity (blocks or exceptions), indexed for lookup
+#[derive(Debug, Clone, Default)]
+struct RuleSet {
+    domains: HashSet<String>,
+    wildcards: HashSet<String>,
+    /// Path prefixes keyed by host (which may start with "*.")
+    paths: HashMap<String, Vec<String>>,
+    regexes: Option<RegexSet>,
+    /// A `*` rule is present
+    all: bool,
+}
+
+/// A rule that matched, as reported to callers
+struct RuleMatch {
+    rule: String,
+    match_type: &'static str,
+}
+
+impl RuleSet {
+    fn build(rules: &[&WebsiteRule]) -> Self {
+        let mut set = Self::default();
+        let mut patterns = Vec::new();
+
+        for rule in rules {
+            match &rule.target {
+                RuleTarget::Domain(domain) => {
+                    set.domains.insert(domain.clone());
+                }
+                RuleTarget::Wildcard(domain) => {
+                    set.wildcards.insert(domain.clone());
+                }
+                RuleTarget::Path { host, path } => {
+                    set.paths.entry(host.clone()).or_default().push(path.clone());
+                }
+                RuleTarget::Regex(pattern) => patterns.push(pattern.clone()),
+                RuleTarget::All => set.all = true,
+            }
+        }
+
+        if !patterns.is_empty() {
+            // Patterns were validated one by one; only the combined size limit can fail here
+            match RegexSetBuilder::new(&patterns).case_insensitive(true).build() {
+                Ok(regexes) => set.regexes = Some(regexes),
+                Err(e) => tracing::warn!("Ignoring {} website regex rules: {}", patterns.len(), e),
+            }
+        }
+
+        set
+    }
+
+    /// Match a host name (without path)
+    fn match_host(&self, host: &str) -> Option<RuleMatch> {
+        self.match_host_rules(host).or_else(|| self.match_all())
+    }
+
+    fn match_host_rules(&self, host: &str) -> Option<RuleMatch> {
+        if self.domains.contains(host) {
+            return Some(RuleMatch {
+                rule: host.to_string(),
+                match_type: "exact",
+            });
+        }
+
+        // api.example.com -> example.com -> com
+        for parent in parent_domains(host) {
+            if self.domains.contains(parent) {
+                return Some(RuleMatch {
+                    rule: parent.to_string(),
+                    match_type: "subdomain",
+                });
+            }
+            if self.wildcards.contains(parent) {
+                return Some(RuleMatch {
+                    rule: format!("*.{}", parent),
+                    match_type: "wildcard",
+                });
+            }
+        }
+
+        self.match_regex(host)
+    }
+
+    /// Match a host and path
+    fn match_url(&self, host: &str, path: &str) -> Option<RuleMatch> {
+        if let Some(found) = self.match_host_rules(host) {
+            return Some(found);
+        }
+
+        if !self.paths.is_empty() {
+            // Rules for the host itself, then for each parent domain and its wildcard
+            let keys = std::iter::once(host.to_string()).chain(
+                parent_domains(host).flat_map(|parent| [parent.to_string(), format!("*.{}", parent)]),
+            );
+
+            for key in keys {
+                if let Some(prefixes) = self.paths.get(&key) {
+                    if let Some(prefix) = prefixes.iter().find(|prefix| path_has_prefix(path, prefix)) {
+                        return Some(RuleMatch {
+                            rule: format!("{}{}", key, prefix),
+                            match_type: "path",
+                        });
+                    }
+                }
+            }
+        }
+
+        self.match_regex(&format!("{}{}", host, path))
+            .or_else(|| self.match_all())
+    }
+
+    fn match_all(&self) -> Option<RuleMatch> {
+        self.all.then(|| RuleMatch {
+            rule: "*".to_string(),
+            match_type: "allowlist",
+        })
+    }
+
+    fn match_regex(&self, target: &str) -> Option<RuleMatch> {
+        let regexes = self.regexes.as_ref()?;
+        let index = regexes.matches(target).into_iter().next()?;
+
+        Some(RuleMatch {
+            rule: format!("/{}/", regexes.patterns()[index]),
+            match_type: "regex",
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.domains.len()
+            + self.wildcards.len()
+            + self.paths.values().map(Vec::len).sum::<usize>()
+            + self.regexes.as_ref().map_or(0, RegexSet::len)
+            + usize::from(self.all)
+    }
+}
+
+/// Parent domains of a host, nearest first (excluding the host itself)
+fn parent_domains(host: &str) -> impl Iterator<Item = &str> {
+    host.match_indices('.').map(move |(i, _)| &host[i + 1..])
+}
+
+/// Whether `path` is `prefix` or lies below it
+fn path_has_prefix(path: &str, prefix: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'),
+        None => false,
+    }
+}
+
+/// Compiled website rules
+#[derive(Debug, Clone, Default)]
+pub struct WebsiteMatcher {
+    blocks: RuleSet,
+    exceptions: RuleSet,
+    /// Canonical form of every valid rule, in input order
+    rules: Vec<String>,
+}
+
+impl WebsiteMatcher {
+    /// Compile a list of rules, skipping (and logging) invalid ones
+    pub fn compile<S: AsRef<str>>(rules: &[S]) -> Self {
+        let mut parsed: Vec<WebsiteRule> = Vec::new();
+
+        for rule in rules {
+            match WebsiteRule::parse(rule.as_ref()) {
+                Ok(rule) => {
+                    if !parsed.contains(&rule) {
+                        parsed.push(rule);
+                    }
+                }
+                Err(e) => tracing::warn!("Skipping invalid website rule '{}': {}", rule.as_ref(), e),
+            }
+        }
+
+        let (exceptions, blocks): (Vec<&WebsiteRule>, Vec<&WebsiteRule>) =
+            parsed.iter().partition(|rule| rule.exception);
+
+        Self {
+            blocks: RuleSet::build(&blocks),
+            exceptions: RuleSet::build(&exceptions),
+            rules: parsed.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Canonical form of every rule
+    pub fn rules(&self) -> &[String] {
+        &self.rules
+    }
+
+    /// Number of block (non-exception) rules
+    pub fn block_rule_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check a host name, as seen by DNS
+    ///
+    /// Path rules never block a whole host.
+    pub fn check_domain(&self, domain: &str) -> DomainCheckResult {
+        // Fully qualified names from DNS queries may carry a trailing dot
+        let host = domain.trim().trim_end_matches('.').to_lowercase();
+
+        if let Some(exception) = self.exceptions.match_host(&host) {
+            return allowed_by(exception);
+        }
+
+        check_result(self.blocks.match_host(&host))
+    }
+
+    /// Check a full URL, including path rules
+    pub fn check_url(&self, url: &str) -> DomainCheckResult {
+        let Some((host, path)) = split_url(url) else {
+            return check_result(None);
+        };
+
+        if let Some(exception) = self.exceptions.match_url(&host, &path) {
+            return allowed_by(exception);
+        }
+
+        check_result(self.blocks.match_url(&host, &path))
+    }
+
+    /// Domains that can be blocked through the hosts file
+    ///
+    /// The hosts file only maps exact names, so wildcard, path and regex rules
+    /// are left to the DNS-level blockers, and excepted domains are dropped.
+    pub fn hosts_domains(&self) -> Vec<String> {
+        let mut domains: Vec<String> = self
+            .blocks
+            .domains
+            .iter()
+            .filter(|domain| self.exceptions.match_host(domain).is_none())
+            .cloned()
+            .collect();
+        domains.sort();
+        domains
+    }
+}
+
+fn check_result(found: Option<RuleMatch>) -> DomainCheckResult {
+    match found {
+        Some(found) => DomainCheckResult {
+            blocked: true,
+            matched_domain: Some(found.rule),
+            match_type: Some(found.match_type.to_string()),
+        },
+        None => DomainCheckResult {
+            blocked: false,
+            matched_domain: None,
+            match_type: None,
+        },
+    }
+}
+
+fn allowed_by(exception: RuleMatch) -> DomainCheckResult {
+    DomainCheckResult {
+        blocked: false,
+        matched_domain: Some(format!("!{}", exception.rule)),
+        match_type: Some("exception".to_string()),
+    }
+}
+
+/// Split a URL (or bare domain) into lowercase host and path
+fn split_url(url: &str) -> Option<(String, String)> {
+    let url = url.trim().to_lowercase();
+    let rest = match url.find("://") {
+        Some(i) => &url[i + 3..],
+        None => url.strip_prefix("//").unwrap_or(&url),
+    };
+
+    let (authority, path) = match rest.find(['/', '?', '#']) {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = host.split(':').next().unwrap_or(host).trim_end_matches('.');
+    if host.is_empty() {
+        return None;
+    }
+
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let path = if path.is_empty() { "/" } else { path };
+
+    Some((host.to_string(), path.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(rules: &[&str]) -> WebsiteMatcher {
+        WebsiteMatcher::compile(rules)
+    }
+
+    #[test]
+    fn test_parse_rule_kinds() {
+        assert_eq!(
+            WebsiteRule::parse("Example.COM").unwrap().target,
+            RuleTarget::Domain("example.com".to_string())
+        );
+        assert_eq!(
+            WebsiteRule::parse("*.example.com").unwrap().target,
+            RuleTarget::Wildcard("example.com".to_string())
+        );
+        assert_eq!(
+            WebsiteRule::parse("https://youtube.com/shorts/").unwrap().target,
+            RuleTarget::Path {
+                host: "youtube.com".to_string(),
+                path: "/shorts".to_string()
+            }
+        );
+        assert_eq!(
+            WebsiteRule::parse("/^ads?\\d*\\./").unwrap().target,
+            RuleTarget::Regex("^ads?\\d*\\.".to_string())
+        );
+
+        let exception = WebsiteRule::parse("!music.youtube.com").unwrap();
+        assert!(exception.exception);
+        assert_eq!(exception.to_string(), "!music.youtube.com");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_rules() {
+        assert!(WebsiteRule::parse("").is_err());
+        assert!(WebsiteRule::parse("localhost").is_err());
+        assert!(WebsiteRule::parse("example.com:8080").is_err());
+        assert!(WebsiteRule::parse("exa mple.com").is_err());
+        assert!(WebsiteRule::parse("/[unclosed/").is_err());
+        assert!(WebsiteRule::parse("!").is_err());
+    }
+
+    #[test]
+    fn test_from_blocked_item_match_types() {
+        let regex = WebsiteRule::from_blocked_item("^news\\.", "regex").unwrap();
+        assert_eq!(regex.to_string(), "/^news\\./");
+
+        let contains = WebsiteRule::from_blocked_item("casino", "contains").unwrap();
+        assert_eq!(contains.match_type(), "regex");
+
+        let exact = WebsiteRule::from_blocked_item("reddit.com", "exact").unwrap();
+        assert_eq!(exact.to_string(), "reddit.com");
+    }
+
+    #[test]
+    fn test_domain_and_wildcard_matching() {
+        let m = matcher(&["reddit.com", "*.example.com"]);
+
+        assert_eq!(m.check_domain("reddit.com").match_type.as_deref(), Some("exact"));
+        assert_eq!(m.check_domain("old.reddit.com.").match_type.as_deref(), Some("subdomain"));
+        assert!(!m.check_domain("notreddit.com").blocked);
+
+        assert_eq!(m.check_domain("cdn.example.com").match_type.as_deref(), Some("wildcard"));
+        assert!(!m.check_domain("example.com").blocked);
+    }
+
+    #[test]
+    fn test_path_rules_only_apply_to_urls() {
+        let m = matcher(&["youtube.com/shorts", "reddit.com/r/all"]);
+
+        assert!(!m.check_domain("youtube.com").blocked);
+        assert!(!m.check_url("https://www.youtube.com/watch?v=1").blocked);
+        assert!(!m.check_url("https://youtube.com/shortsfeed").blocked);
+
+        let result = m.check_url("https://m.youtube.com/shorts/abc?feature=share");
+        assert!(result.blocked);
+        assert_eq!(result.matched_domain.as_deref(), Some("youtube.com/shorts"));
+        assert_eq!(result.match_type.as_deref(), Some("path"));
+
+        assert!(m.check_url("reddit.com/r/all").blocked);
+        assert!(!m.check_url("reddit.com/r/rust").blocked);
+    }
+
+    #[test]
+    fn test_regex_rules() {
+        let m = matcher(&["/^(www\\.)?casino/", "/reddit\\.com/r/(all|popular)/"]);
+
+        assert_eq!(m.check_domain("casino-royale.net").match_type.as_deref(), Some("regex"));
+        assert!(m.check_domain("WWW.CASINO.COM").blocked);
+        assert!(!m.check_domain("reddit.com").blocked);
+        assert!(m.check_url("https://reddit.com/r/popular").blocked);
+    }
+
+    #[test]
+    fn test_exceptions_win_over_blocks() {
+        let m = matcher(&["youtube.com", "!music.youtube.com", "*.google.com", "!/^docs\\./"]);
+
+        assert!(m.check_domain("youtube.com").blocked);
+        assert!(m.check_domain("www.youtube.com").blocked);
+
+        let allowed = m.check_domain("music.youtube.com");
+        assert!(!allowed.blocked);
+        assert_eq!(allowed.matched_domain.as_deref(), Some("!music.youtube.com"));
+        assert_eq!(allowed.match_type.as_deref(), Some("exception"));
+
+        assert!(m.check_domain("mail.google.com").blocked);
+        assert!(!m.check_domain("docs.google.com").blocked);
+    }
+
+    #[test]
+    fn test_path_exceptions() {
+        let m = matcher(&["reddit.com", "!reddit.com/r/rust"]);
+
+        assert!(m.check_url("https://reddit.com/r/all").blocked);
+        assert!(!m.check_url("https://www.reddit.com/r/rust/comments/1").blocked);
+        // DNS cannot see paths, so the host stays blocked
+        assert!(m.check_domain("reddit.com").blocked);
+    }
+
+    #[test]
+    fn test_catch_all_with_exceptions_is_an_allowlist() {
+        let m = matcher(&["*", "!github.com", "!docs.rs/std"]);
+
+        let blocked = m.check_domain("news.ycombinator.com");
+        assert!(blocked.blocked);
+        assert_eq!(blocked.matched_domain.as_deref(), Some("*"));
+        assert_eq!(blocked.match_type.as_deref(), Some("allowlist"));
+
+        assert!(!m.check_domain("api.github.com").blocked);
+        assert!(m.check_url("https://docs.rs/serde").blocked);
+        assert!(!m.check_url("https://docs.rs/std/vec").blocked);
+        assert!(m.hosts_domains().is_empty());
+    }
+
+    #[test]
+    fn test_hosts_domains() {
+        let m = matcher(&[
+            "reddit.com",
+            "music.youtube.com",
+            "!music.youtube.com",
+            "*.example.com",
+            "youtube.com/shorts",
+            "/casino/",
+            "not a domain",
+        ]);
+
+        assert_eq!(m.hosts_domains(), vec!["reddit.com".to_string()]);
+        assert_eq!(m.rules().len(), 6);
+        assert_eq!(m.block_rule_count(), 5);
+    }
+
+    #[test]
+    fn test_split_url() {
+        assert_eq!(
+            split_url("HTTPS://user@Example.com:8443/Path/?q=1#top"),
+            Some(("example.com".to_string(), "/path/".to_string()))
+        );
+        assert_eq!(split_url("example.com"), Some(("example.com".to_string(), "/".to_string())));
+        assert_eq!(split_url("https://"), None);
+    }
+}
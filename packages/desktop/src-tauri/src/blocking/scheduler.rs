@@ -0,0 +1,480 @@
+// blocking/scheduler.rs - Recurring blocking schedule enforcement
+//
+// Blocking schedules are stored as weekly windows (day_of_week + HH:MM start/end)
+// in the `blocking_schedules` table. This module periodically evaluates the
+// enabled schedules against local time and turns blocking on/off accordingly.
+//
+// A window whose end time is earlier than its start time crosses midnight:
+// "Friday 22:00-02:00" is active from Friday 22:00 until Saturday 02:00.
+
+use crate::{
+    blocking::{
+        blocklist::{self, EffectiveBlocklist},
+        hosts,
+    },
+    db::queries::{self, FocusProfile},
+    state::AppState,
+    system::{dimming, notification_control},
+    Result,
+};
+use chrono::{Datelike, Local, NaiveDateTime, Timelike};
+use tauri::Emitter;
+use tokio::time::{interval, Duration};
+
+/// How often schedules are re-evaluated
+const SCHEDULER_INTERVAL_SECS: u64 = 30;
+
+/// Focus profile applied by a blocking schedule while its window is open
+///
+/// The profile's apps and websites are part of the effective blocklist only
+/// while it is recorded here, and the dimming and DND the schedule turned on
+/// are undone from this record, so editing the profile mid-window changes
+/// neither.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScheduleProfile {
+    pub schedule_id: i64,
+    pub profile_id: i64,
+    pub blocked_apps: Vec<String>,
+    pub blocked_websites: Vec<String>,
+    /// Whether the schedule turned on screen dimming
+    pub dimmed: bool,
+    /// Whether the schedule paused notifications
+    pub notifications_paused: bool,
+}
+
+/// A blocking schedule window normalized for fast evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleWindow {
+    pub id: i64,
+    /// 0 = Sunday, 6 = Saturday
+    pub day_of_week: u32,
+    /// Minutes since midnight
+    pub start_minute: u32,
+    /// Minutes since midnight (may be <= start_minute for overnight windows)
+    pub end_minute: u32,
+    /// Focus profile applied while the window is active
+    pub profile_id: Option<i64>,
+}
+
+impl ScheduleWindow {
+    /// Build a window from stored values, returning None for malformed rows
+    pub fn new(id: i64, day_of_week: i32, start_time: &str, end_time: &str) -> Option<Self> {
+        if !(0..=6).contains(&day_of_week) {
+            return None;
+        }
+
+        Some(Self {
+            id,
+            day_of_week: day_of_week as u32,
+            start_minute: parse_minutes(start_time)?,
+            end_minute: parse_minutes(end_time)?,
+            profile_id: None,
+        })
+    }
+
+    /// Whether the window wraps past midnight into the following day
+    pub fn crosses_midnight(&self) -> bool {
+        self.end_minute <= self.start_minute
+    }
+
+    /// Check if the window is active at the given weekday (0 = Sunday) and minute of day
+    ///
+    /// A window with identical start and end times is treated as a full 24 hours.
+    pub fn is_active_at(&self, day_of_week: u32, minute: u32) -> bool {
+        if !self.crosses_midnight() {
+            return day_of_week == self.day_of_week
+                && minute >= self.start_minute
+                && minute < self.end_minute;
+        }
+
+        let next_day = (self.day_of_week + 1) % 7;
+
+        (day_of_week == self.day_of_week && minute >= self.start_minute)
+            || (day_of_week == next_day && minute < self.end_minute)
+    }
+
+    /// Check if the window is active at a local date/time
+    pub fn is_active_at_datetime(&self, at: NaiveDateTime) -> bool {
+        let day = at.weekday().num_days_from_sunday();
+        let minute = at.hour() * 60 + at.minute();
+        self.is_active_at(day, minute)
+    }
+}
+
+/// Parse "HH:MM" into minutes since midnight
+pub(crate) fn parse_minutes(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+
+    if hours >= 24 || minutes >= 60 {
+        return None;
+    }
+
+    Some(hours * 60 + minutes)
+}
+
+/// Find the first schedule window active at the given local time
+pub fn find_active_window(windows: &[ScheduleWindow], at: NaiveDateTime) -> Option<&ScheduleWindow> {
+    windows.iter().find(|w| w.is_active_at_datetime(at))
+}
+
+/// Load enabled schedules from the database as evaluation windows
+async fn load_windows(state: &AppState) -> Result<Vec<ScheduleWindow>> {
+    let schedules = queries::get_enabled_blocking_schedules(state.pool()).await?;
+
+    Ok(schedules
+        .iter()
+        .filter_map(|s| {
+            let window = ScheduleWindow::new(s.id, s.day_of_week, &s.start_time, &s.end_time)
+                .map(|w| ScheduleWindow {
+                    profile_id: s.profile_id,
+                    ..w
+                });
+            if window.is_none() {
+                tracing::warn!("Skipping malformed blocking schedule {}", s.id);
+            }
+            window
+        })
+        .collect())
+}
+
+/// Start the blocking schedule loop
+///
+/// Runs alongside the process monitoring loop. When an enabled schedule window
+/// opens, blocking is enabled with the current website blocklist and a
+/// `schedule-started` event is emitted. When it closes, blocking that was turned
+/// on by the schedule is turned off again and `schedule-ended` is emitted.
+/// Blocking that belongs to an active focus session is never torn down here.
+pub async fn start_scheduler_loop(state: AppState) -> Result<()> {
+    let mut interval = interval(Duration::from_secs(SCHEDULER_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let windows = match load_windows(&state).await {
+            Ok(windows) => windows,
+            Err(e) => {
+                tracing::error!("Failed to load blocking schedules: {}", e);
+                continue;
+            }
+        };
+
+        let now = Local::now().naive_local();
+        let active_window = find_active_window(&windows, now).cloned();
+
+        let current_schedule = {
+            let blocking_state = state.blocking_state.read().await;
+            blocking_state.schedule_id
+        };
+
+        match (current_schedule, active_window) {
+            (None, Some(window)) => {
+                if let Err(e) = start_schedule(&state, &window).await {
+                    tracing::error!("Failed to start blocking schedule {}: {}", window.id, e);
+                }
+            }
+            (Some(id), Some(window)) if id != window.id => {
+                // Back-to-back windows: hand over without toggling blocking off
+                let owns_blocking = {
+                    let mut blocking_state = state.blocking_state.write().await;
+                    blocking_state.schedule_id = Some(window.id);
+                    blocking_state.schedule_owns_blocking
+                };
+                release_schedule_profile(&state).await;
+                if owns_blocking {
+                    apply_schedule_profile(&state, &window).await;
+                }
+                if let Err(e) = blocklist::refresh_website_blocking(&state).await {
+                    tracing::error!("Failed to refresh blocking for schedule {}: {}", window.id, e);
+                }
+                emit_schedule_event(&state, "schedule-ended", id);
+                emit_schedule_event(&state, "schedule-started", window.id);
+            }
+            (Some(id), None) => {
+                if let Err(e) = end_schedule(&state, id).await {
+                    tracing::error!("Failed to end blocking schedule {}: {}", id, e);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Enable blocking for a schedule window that just opened
+async fn start_schedule(state: &AppState, window: &ScheduleWindow) -> Result<()> {
+    let already_enabled = {
+        let blocking_state = state.blocking_state.read().await;
+        blocking_state.enabled
+    };
+
+    // A session or manual toggle already owns blocking; claim the window without
+    // overriding its blocklist, and leave blocking to its owner when the window closes
+    if already_enabled {
+        {
+            let mut blocking_state = state.blocking_state.write().await;
+            blocking_state.schedule_id = Some(window.id);
+            blocking_state.schedule_owns_blocking = false;
+        }
+
+        tracing::info!("Blocking schedule {} started (blocking already on)", window.id);
+        emit_schedule_event(state, "schedule-started", window.id);
+        return Ok(());
+    }
+
+    apply_schedule_profile(state, window).await;
+
+    let domains = EffectiveBlocklist::load(state).await?.domains();
+
+    {
+        let mut blocking_state = state.blocking_state.write().await;
+        blocking_state.enable();
+        blocking_state.update_blocked_websites(domains.clone());
+        blocking_state.schedule_id = Some(window.id);
+        blocking_state.schedule_owns_blocking = true;
+    }
+
+    if let Err(e) = hosts::update_hosts_file(state, &domains).await {
+        tracing::warn!("Hosts file update failed: {}, DNS fallback active", e);
+    }
+
+    tracing::info!(
+        "Blocking schedule {} started ({} websites)",
+        window.id,
+        domains.len()
+    );
+
+    emit_schedule_event(state, "schedule-started", window.id);
+
+    Ok(())
+}
+
+/// Disable blocking for a schedule window that just closed
+async fn end_schedule(state: &AppState, schedule_id: i64) -> Result<()> {
+    // A focus session or nuclear option started during the window keeps its blocking,
+    // as does blocking that was already on when the window opened
+    let session_active = {
+        let active_session = state.active_session.read().await;
+        active_session.is_some()
+    };
+    let keep_blocking = session_active
+        || state.nuclear_option_state.read().await.active
+        || !state.blocking_state.read().await.schedule_owns_blocking;
+
+    {
+        let mut blocking_state = state.blocking_state.write().await;
+        blocking_state.schedule_id = None;
+        blocking_state.schedule_owns_blocking = false;
+
+        if !keep_blocking {
+            blocking_state.disable();
+            blocking_state.update_blocked_websites(Vec::new());
+        }
+    }
+
+    let had_profile = release_schedule_profile(state).await;
+
+    if !keep_blocking {
+        if let Err(e) = hosts::clear_hosts_file(state).await {
+            tracing::warn!("Failed to clear hosts file: {}", e);
+        }
+    } else if had_profile {
+        // Blocking stays on for its owner, without the profile's items
+        blocklist::refresh_website_blocking(state).await?;
+    }
+
+    tracing::info!("Blocking schedule {} ended", schedule_id);
+
+    emit_schedule_event(state, "schedule-ended", schedule_id);
+
+    Ok(())
+}
+
+/// Current version of a schedule's profile, logging instead of failing the schedule
+async fn load_schedule_profile(state: &AppState, profile_id: i64) -> Option<FocusProfile> {
+    match queries::get_focus_profile(state.pool(), profile_id).await {
+        Ok(Some(profile)) => Some(FocusProfile::from(profile)),
+        Ok(None) => {
+            tracing::warn!("Schedule profile {} no longer exists", profile_id);
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Schedule profile {} unavailable: {}", profile_id, e);
+            None
+        }
+    }
+}
+
+/// Apply a window's focus profile and record what was applied
+///
+/// The profile's items join the effective blocklist; dimming and DND are only
+/// turned on (and later off) when nothing else had them on already.
+async fn apply_schedule_profile(state: &AppState, window: &ScheduleWindow) {
+    let Some(profile_id) = window.profile_id else {
+        return;
+    };
+    let Some(profile) = load_schedule_profile(state, profile_id).await else {
+        return;
+    };
+
+    let mut applied = ScheduleProfile {
+        schedule_id: window.id,
+        profile_id: profile.id,
+        blocked_apps: profile.blocked_apps.clone(),
+        blocked_websites: profile.blocked_websites.clone(),
+        ..Default::default()
+    };
+
+    if profile.enable_dimming && !state.dimming_state.read().await.enabled {
+        match dimming::enable_dimming_internal(state, &state.app_handle, profile.dimming_opacity, true, None).await {
+            Ok(()) => applied.dimmed = true,
+            Err(e) => tracing::warn!("Failed to enable screen dimming for schedule: {}", e),
+        }
+    }
+
+    if profile.pause_notifications && !state.notification_control_state.read().await.paused {
+        match notification_control::pause_notifications_internal(state, &state.app_handle, None).await {
+            Ok(_) => applied.notifications_paused = true,
+            Err(e) => tracing::warn!("Failed to pause notifications for schedule: {}", e),
+        }
+    }
+
+    *state.schedule_profile.write().await = Some(applied);
+    blocklist::invalidate();
+}
+
+/// Drop the applied profile's items and undo the dimming and DND it turned on
+///
+/// Dimming or DND taken over by a focus session since is left to the session.
+/// Returns whether a profile was applied.
+async fn release_schedule_profile(state: &AppState) -> bool {
+    let Some(applied) = state.schedule_profile.write().await.take() else {
+        return false;
+    };
+    blocklist::invalidate();
+
+    let dimming_is_ours = {
+        let dimming = state.dimming_state.read().await;
+        dimming.enabled && dimming.session_id.is_none()
+    };
+    if applied.dimmed && dimming_is_ours {
+        if let Err(e) = dimming::force_disable_dimming(state, &state.app_handle).await {
+            tracing::warn!("Failed to disable screen dimming: {}", e);
+        }
+    }
+
+    let notifications_are_ours = {
+        let notifications = state.notification_control_state.read().await;
+        notifications.paused && notifications.session_id.as_deref() == Some("manual")
+    };
+    if applied.notifications_paused && notifications_are_ours {
+        if let Err(e) = notification_control::force_resume_notifications(state, &state.app_handle).await {
+            tracing::warn!("Failed to resume notifications: {}", e);
+        }
+    }
+
+    true
+}
+
+fn emit_schedule_event(state: &AppState, event: &str, schedule_id: i64) {
+    if let Err(e) = state.app_handle.emit(
+        event,
+        serde_json::json!({
+            "scheduleId": schedule_id,
+        }),
+    ) {
+        tracing::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_minutes() {
+        assert_eq!(parse_minutes("00:00"), Some(0));
+        assert_eq!(parse_minutes("09:30"), Some(570));
+        assert_eq!(parse_minutes("23:59"), Some(1439));
+        assert_eq!(parse_minutes("24:00"), None);
+        assert_eq!(parse_minutes("12:60"), None);
+        assert_eq!(parse_minutes("noon"), None);
+    }
+
+    #[test]
+    fn test_rejects_invalid_day() {
+        assert!(ScheduleWindow::new(1, 7, "09:00", "12:00").is_none());
+        assert!(ScheduleWindow::new(1, -1, "09:00", "12:00").is_none());
+    }
+
+    #[test]
+    fn test_same_day_window() {
+        // Monday 09:00-12:00
+        let window = ScheduleWindow::new(1, 1, "09:00", "12:00").unwrap();
+        assert!(!window.crosses_midnight());
+
+        assert!(window.is_active_at(1, 9 * 60));
+        assert!(window.is_active_at(1, 11 * 60 + 59));
+        assert!(!window.is_active_at(1, 12 * 60));
+        assert!(!window.is_active_at(1, 8 * 60 + 59));
+        assert!(!window.is_active_at(2, 10 * 60));
+    }
+
+    #[test]
+    fn test_window_crossing_midnight() {
+        // Friday 22:00 - Saturday 02:00
+        let window = ScheduleWindow::new(1, 5, "22:00", "02:00").unwrap();
+        assert!(window.crosses_midnight());
+
+        assert!(window.is_active_at(5, 22 * 60));
+        assert!(window.is_active_at(5, 23 * 60 + 59));
+        assert!(window.is_active_at(6, 0));
+        assert!(window.is_active_at(6, 60 + 59));
+        assert!(!window.is_active_at(6, 2 * 60));
+        assert!(!window.is_active_at(5, 21 * 60));
+        assert!(!window.is_active_at(5, 60)); // Friday early morning belongs to Thursday's window
+    }
+
+    #[test]
+    fn test_saturday_window_wraps_to_sunday() {
+        let window = ScheduleWindow::new(1, 6, "23:00", "01:00").unwrap();
+        assert!(window.is_active_at(0, 30));
+        assert!(!window.is_active_at(1, 30));
+    }
+
+    #[test]
+    fn test_identical_start_end_is_full_day() {
+        let window = ScheduleWindow::new(1, 3, "08:00", "08:00").unwrap();
+        assert!(window.is_active_at(3, 8 * 60));
+        assert!(window.is_active_at(3, 23 * 60));
+        assert!(window.is_active_at(4, 7 * 60 + 59));
+        assert!(!window.is_active_at(4, 8 * 60));
+    }
+
+    #[test]
+    fn test_find_active_window_weekdays() {
+        // "9-12 weekdays"
+        let windows: Vec<ScheduleWindow> = (1..=5)
+            .map(|day| ScheduleWindow::new(day as i64, day, "09:00", "12:00").unwrap())
+            .collect();
+
+        // 2026-10-12 is a Monday
+        let monday_morning = at(2026, 10, 12, 10, 15);
+        assert_eq!(find_active_window(&windows, monday_morning).map(|w| w.id), Some(1));
+
+        let monday_afternoon = at(2026, 10, 12, 13, 0);
+        assert!(find_active_window(&windows, monday_afternoon).is_none());
+
+        // 2026-10-17 is a Saturday
+        let saturday_morning = at(2026, 10, 17, 10, 0);
+        assert!(find_active_window(&windows, saturday_morning).is_none());
+    }
+}
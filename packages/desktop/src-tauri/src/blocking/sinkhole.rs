@@ -0,0 +1,1006 @@
+// blocking/sinkhole.rs - Local DNS sinkhole resolver
+//
+// An unprivileged website blocking backend: a small DNS forwarder listening on a
+// local port (UDP and TCP). Queries for blocklisted names are answered directly
+// with NXDOMAIN or a null address; everything else is relayed to an upstream
+// resolver. The system (or browser) resolver has to be pointed at the listen
+// address for it to take effect, e.g. systemd-resolved's `DNS=127.0.0.1:5354`.
+//
+// With DoH protection on, the Firefox canary domain always gets NXDOMAIN, the
+// only answer (besides no addresses) that keeps Firefox off DoH.
+//
+// Only the small subset of the DNS wire format needed to read the question and
+// synthesize answers is implemented; forwarded traffic is relayed untouched.
+
+use crate::{
+    blocking::{allowlist, budget, dns::DnsBlockingFallback, doh},
+    db::queries,
+    state::AppState,
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration, Instant};
+
+/// user_settings key holding the persisted sinkhole configuration
+pub const SINKHOLE_SETTINGS_KEY: &str = "dns_sinkhole";
+
+/// How long a TCP client may stay idle between queries
+const TCP_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the sinkhole blocklist is synced from the blocking state
+const BLOCKLIST_SYNC_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Repeated queries for the same name within this window count as one block attempt
+const BLOCK_ATTEMPT_DEDUPE_WINDOW: Duration = Duration::from_secs(60);
+
+/// TTL of synthesized answers, kept short so unblocking takes effect quickly
+const BLOCKED_ANSWER_TTL: u32 = 60;
+
+/// Largest UDP message accepted (EDNS0 payload size commonly advertised)
+const MAX_UDP_MESSAGE: usize = 4096;
+
+/// Number of top blocked domains reported in statistics
+const TOP_BLOCKED_LIMIT: usize = 10;
+
+const HEADER_LEN: usize = 12;
+const QTYPE_A: u16 = 1;
+const QTYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+const RCODE_FORMERR: u8 = 1;
+const RCODE_SERVFAIL: u8 = 2;
+const RCODE_NXDOMAIN: u8 = 3;
+
+/// How blocked names are answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SinkholeResponse {
+    /// Name does not exist
+    NxDomain,
+    /// 0.0.0.0 for A and :: for AAAA queries, no data for other types
+    NullAddress,
+}
+
+/// Sinkhole resolver configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SinkholeConfig {
+    /// Start the sinkhole automatically when the app launches
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local address to listen on (UDP and TCP)
+    pub listen_addr: SocketAddr,
+    /// Upstream resolver for names that are not blocked
+    pub upstream: SocketAddr,
+    /// Answer given for blocked names
+    pub response: SinkholeResponse,
+    /// How long to wait for the upstream resolver
+    pub upstream_timeout_ms: u64,
+}
+
+impl Default for SinkholeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            // 5353 is taken by mDNS on most systems
+            listen_addr: SocketAddr::from(([127, 0, 0, 1], 5354)),
+            upstream: SocketAddr::from(([1, 1, 1, 1], 53)),
+            response: SinkholeResponse::NullAddress,
+            upstream_timeout_ms: 3000,
+        }
+    }
+}
+
+impl SinkholeConfig {
+    /// Reject configurations that would loop or never answer
+    pub fn validate(&self) -> Result<()> {
+        if self.upstream == self.listen_addr {
+            return Err(Error::Validation(
+                "Upstream resolver cannot be the sinkhole itself".to_string(),
+            ));
+        }
+        if self.upstream.port() == 0 {
+            return Err(Error::Validation("Upstream resolver port is required".to_string()));
+        }
+        if !(100..=30_000).contains(&self.upstream_timeout_ms) {
+            return Err(Error::Validation(
+                "Upstream timeout must be between 100 and 30000 ms".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A query answered by the sinkhole instead of the upstream resolver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedQuery {
+    pub domain: String,
+    pub matched_domain: Option<String>,
+    pub match_type: Option<String>,
+    pub query_type: u16,
+}
+
+/// Per-query statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SinkholeStats {
+    pub running: bool,
+    pub listen_addr: Option<String>,
+    pub upstream: Option<String>,
+    pub total_queries: u64,
+    pub blocked_queries: u64,
+    pub forwarded_queries: u64,
+    pub failed_queries: u64,
+    /// Most frequently blocked names with their query counts
+    pub top_blocked: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    total: AtomicU64,
+    blocked: AtomicU64,
+    forwarded: AtomicU64,
+    failed: AtomicU64,
+    blocked_by_domain: Mutex<HashMap<String, u64>>,
+}
+
+/// State shared by the listener tasks
+struct Shared {
+    config: SinkholeConfig,
+    blocklist: RwLock<DnsBlockingFallback>,
+    /// Answer the DoH canary domain with NXDOMAIN
+    answer_canary: AtomicBool,
+    counters: Counters,
+    on_blocked: Option<mpsc::UnboundedSender<BlockedQuery>>,
+    on_forwarded: Option<mpsc::UnboundedSender<String>>,
+}
+
+impl Shared {
+    fn update_blocklist(&self, domains: Vec<String>, enabled: bool) {
+        let mut blocklist = self.blocklist.write().unwrap_or_else(|e| e.into_inner());
+        blocklist.update_blocklist(domains);
+        if enabled {
+            blocklist.enable();
+        } else {
+            blocklist.disable();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Transport {
+    Udp,
+    Tcp,
+}
+
+/// A running sinkhole resolver
+///
+/// The listener tasks stop when the sinkhole is stopped or dropped.
+pub struct DnsSinkhole {
+    shared: Arc<Shared>,
+    local_addr: SocketAddr,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl DnsSinkhole {
+    /// Bind the UDP and TCP listeners and start answering queries
+    ///
+    /// Blocked queries are reported on `on_blocked` and the names of forwarded
+    /// queries on `on_forwarded` when senders are given.
+    pub async fn start(
+        config: SinkholeConfig,
+        on_blocked: Option<mpsc::UnboundedSender<BlockedQuery>>,
+        on_forwarded: Option<mpsc::UnboundedSender<String>>,
+    ) -> Result<Self> {
+        config.validate()?;
+
+        let udp = UdpSocket::bind(config.listen_addr).await.map_err(|e| {
+            Error::System(format!("Failed to bind DNS sinkhole on {}: {}", config.listen_addr, e))
+        })?;
+        // Bind TCP on the port UDP actually got (listen_addr may use port 0)
+        let local_addr = udp.local_addr()?;
+        let tcp = TcpListener::bind(local_addr).await.map_err(|e| {
+            Error::System(format!("Failed to bind DNS sinkhole on {}/tcp: {}", local_addr, e))
+        })?;
+
+        let shared = Arc::new(Shared {
+            config,
+            blocklist: RwLock::new(DnsBlockingFallback::new()),
+            answer_canary: AtomicBool::new(false),
+            counters: Counters::default(),
+            on_blocked,
+            on_forwarded,
+        });
+
+        let tasks = vec![
+            tokio::spawn(serve_udp(shared.clone(), Arc::new(udp))),
+            tokio::spawn(serve_tcp(shared.clone(), tcp)),
+        ];
+
+        tracing::info!(
+            "DNS sinkhole listening on {} (upstream {})",
+            local_addr,
+            shared.config.upstream
+        );
+
+        Ok(Self {
+            shared,
+            local_addr,
+            tasks,
+        })
+    }
+
+    /// Address the sinkhole is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn config(&self) -> &SinkholeConfig {
+        &self.shared.config
+    }
+
+    /// Replace the blocked names; nothing is blocked while `enabled` is false
+    pub fn update_blocklist(&self, domains: Vec<String>, enabled: bool) {
+        self.shared.update_blocklist(domains, enabled);
+    }
+
+    /// Answer the DoH canary domain with NXDOMAIN, whatever the response mode
+    pub fn set_answer_canary(&self, answer: bool) {
+        self.shared.answer_canary.store(answer, Ordering::Relaxed);
+    }
+
+    /// Current query statistics
+    pub fn stats(&self) -> SinkholeStats {
+        let counters = &self.shared.counters;
+
+        let mut top_blocked: Vec<(String, u64)> = counters
+            .blocked_by_domain
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(domain, count)| (domain.clone(), *count))
+            .collect();
+        top_blocked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_blocked.truncate(TOP_BLOCKED_LIMIT);
+
+        SinkholeStats {
+            running: true,
+            listen_addr: Some(self.local_addr.to_string()),
+            upstream: Some(self.shared.config.upstream.to_string()),
+            total_queries: counters.total.load(Ordering::Relaxed),
+            blocked_queries: counters.blocked.load(Ordering::Relaxed),
+            forwarded_queries: counters.forwarded.load(Ordering::Relaxed),
+            failed_queries: counters.failed.load(Ordering::Relaxed),
+            top_blocked,
+        }
+    }
+
+    /// Stop the listeners
+    pub fn stop(self) {
+        tracing::info!("DNS sinkhole on {} stopped", self.local_addr);
+    }
+}
+
+impl Drop for DnsSinkhole {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn serve_udp(shared: Arc<Shared>, socket: Arc<UdpSocket>) {
+    let mut buf = vec![0u8; MAX_UDP_MESSAGE];
+
+    loop {
+        let (len, peer) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(e) => {
+                // ICMP errors from earlier replies surface here on some platforms
+                tracing::debug!("DNS sinkhole UDP receive error: {}", e);
+                continue;
+            }
+        };
+
+        let query = buf[..len].to_vec();
+        let shared = shared.clone();
+        let socket = socket.clone();
+
+        tokio::spawn(async move {
+            if let Some(response) = handle_query(&shared, &query, Transport::Udp).await {
+                if let Err(e) = socket.send_to(&response, peer).await {
+                    tracing::debug!("DNS sinkhole failed to reply to {}: {}", peer, e);
+                }
+            }
+        });
+    }
+}
+
+async fn serve_tcp(shared: Arc<Shared>, listener: TcpListener) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tracing::warn!("DNS sinkhole TCP accept error: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve_tcp_connection(&shared, stream).await {
+                tracing::debug!("DNS sinkhole TCP connection from {} closed: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Answer length-prefixed queries until the client disconnects or goes idle
+async fn serve_tcp_connection(shared: &Shared, mut stream: TcpStream) -> std::io::Result<()> {
+    loop {
+        let query = match timeout(TCP_IDLE_TIMEOUT, read_tcp_message(&mut stream)).await {
+            Ok(Ok(Some(query))) => query,
+            Ok(Ok(None)) | Err(_) => return Ok(()),
+            Ok(Err(e)) => return Err(e),
+        };
+
+        if let Some(response) = handle_query(shared, &query, Transport::Tcp).await {
+            write_tcp_message(&mut stream, &response).await?;
+        }
+    }
+}
+
+/// Read one length-prefixed DNS message; None on a clean EOF
+async fn read_tcp_message(stream: &mut TcpStream) -> std::io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 2];
+    match stream.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let mut message = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut message).await?;
+    Ok(Some(message))
+}
+
+async fn write_tcp_message(stream: &mut TcpStream, message: &[u8]) -> std::io::Result<()> {
+    let mut framed = Vec::with_capacity(message.len() + 2);
+    framed.extend_from_slice(&(message.len() as u16).to_be_bytes());
+    framed.extend_from_slice(message);
+    stream.write_all(&framed).await
+}
+
+/// Answer a single query, either from the blocklist or via the upstream
+async fn handle_query(shared: &Shared, query: &[u8], transport: Transport) -> Option<Vec<u8>> {
+    let counters = &shared.counters;
+    counters.total.fetch_add(1, Ordering::Relaxed);
+
+    let question = match parse_query(query) {
+        Some(question) => question,
+        None => {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+            return error_response(query, None, RCODE_FORMERR);
+        }
+    };
+
+    // A null address would still count as resolving for Firefox
+    if shared.answer_canary.load(Ordering::Relaxed) && question.name == doh::CANARY_DOMAIN {
+        return Some(blocked_response(query, &question, SinkholeResponse::NxDomain));
+    }
+
+    let check = {
+        let blocklist = shared.blocklist.read().unwrap_or_else(|e| e.into_inner());
+        blocklist.is_domain_blocked(&question.name)
+    };
+
+    if check.blocked {
+        counters.blocked.fetch_add(1, Ordering::Relaxed);
+        *counters
+            .blocked_by_domain
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(question.name.clone())
+            .or_insert(0) += 1;
+
+        tracing::debug!("DNS sinkhole blocked {} ({:?})", question.name, check.match_type);
+
+        if let Some(sender) = &shared.on_blocked {
+            let _ = sender.send(BlockedQuery {
+                domain: question.name.clone(),
+                matched_domain: check.matched_domain,
+                match_type: check.match_type,
+                query_type: question.qtype,
+            });
+        }
+
+        return Some(blocked_response(query, &question, shared.config.response));
+    }
+
+    let upstream_timeout = Duration::from_millis(shared.config.upstream_timeout_ms);
+    let forwarded = match transport {
+        Transport::Udp => forward_udp(shared.config.upstream, query, upstream_timeout).await,
+        Transport::Tcp => forward_tcp(shared.config.upstream, query, upstream_timeout).await,
+    };
+
+    match forwarded {
+        Ok(response) => {
+            counters.forwarded.fetch_add(1, Ordering::Relaxed);
+            if let Some(sender) = &shared.on_forwarded {
+                let _ = sender.send(question.name.clone());
+            }
+            Some(response)
+        }
+        Err(e) => {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!("DNS sinkhole upstream query for {} failed: {}", question.name, e);
+            error_response(query, Some(&question), RCODE_SERVFAIL)
+        }
+    }
+}
+
+/// Relay a query to the upstream resolver over UDP
+async fn forward_udp(upstream: SocketAddr, query: &[u8], limit: Duration) -> Result<Vec<u8>> {
+    let bind_addr: SocketAddr = if upstream.is_ipv4() {
+        SocketAddr::from(([0, 0, 0, 0], 0))
+    } else {
+        SocketAddr::from(([0u16; 8], 0))
+    };
+
+    let socket = UdpSocket::bind(bind_addr).await?;
+    socket.connect(upstream).await?;
+    socket.send(query).await?;
+
+    let deadline = Instant::now() + limit;
+    let mut buf = vec![0u8; MAX_UDP_MESSAGE];
+
+    loop {
+        let len = tokio::time::timeout_at(deadline, socket.recv(&mut buf))
+            .await
+            .map_err(|_| Error::System(format!("Upstream resolver {} timed out", upstream)))??;
+
+        // Ignore stray datagrams that do not answer our query
+        if len >= HEADER_LEN && buf[..2] == query[..2] {
+            return Ok(buf[..len].to_vec());
+        }
+    }
+}
+
+/// Relay a query to the upstream resolver over TCP
+async fn forward_tcp(upstream: SocketAddr, query: &[u8], limit: Duration) -> Result<Vec<u8>> {
+    let exchange = async {
+        let mut stream = TcpStream::connect(upstream).await?;
+        write_tcp_message(&mut stream, query).await?;
+        read_tcp_message(&mut stream).await?.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "upstream closed connection")
+        })
+    };
+
+    timeout(limit, exchange)
+        .await
+        .map_err(|_| Error::System(format!("Upstream resolver {} timed out", upstream)))?
+        .map_err(Error::from)
+}
+
+/// The first question of a DNS query
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DnsQuestion {
+    /// Lowercase name without the trailing dot
+    name: String,
+    qtype: u16,
+    qclass: u16,
+    /// Offset just past the question in the original message
+    end: usize,
+}
+
+/// Parse the header and first question of a query
+///
+/// Returns None for responses, non-standard opcodes and malformed messages.
+fn parse_query(message: &[u8]) -> Option<DnsQuestion> {
+    if message.len() < HEADER_LEN {
+        return None;
+    }
+
+    let flags = u16::from_be_bytes([message[2], message[3]]);
+    let is_response = flags & 0x8000 != 0;
+    let opcode = (flags >> 11) & 0x0f;
+    let qdcount = u16::from_be_bytes([message[4], message[5]]);
+    if is_response || opcode != 0 || qdcount == 0 {
+        return None;
+    }
+
+    let mut labels = Vec::new();
+    let mut pos = HEADER_LEN;
+    loop {
+        let len = *message.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        // Compression pointers and extended label types never appear in a query's first question
+        if len > 63 {
+            return None;
+        }
+        let label = message.get(pos..pos + len)?;
+        labels.push(String::from_utf8_lossy(label).to_lowercase());
+        pos += len;
+    }
+
+    let fixed = message.get(pos..pos + 4)?;
+    let name = labels.join(".");
+    if name.len() > 253 {
+        return None;
+    }
+
+    Some(DnsQuestion {
+        name,
+        qtype: u16::from_be_bytes([fixed[0], fixed[1]]),
+        qclass: u16::from_be_bytes([fixed[2], fixed[3]]),
+        end: pos + 4,
+    })
+}
+
+/// Response header echoing the query ID, opcode and RD bit
+fn response_header(query: &[u8], rcode: u8, qdcount: u16, ancount: u16) -> Vec<u8> {
+    let query_flags = u16::from_be_bytes([query[2], query[3]]);
+    // QR | opcode + RD from the query | RA | rcode
+    let flags = 0x8000 | (query_flags & 0x7900) | 0x0080 | u16::from(rcode & 0x0f);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(&query[..2]);
+    header.extend_from_slice(&flags.to_be_bytes());
+    header.extend_from_slice(&qdcount.to_be_bytes());
+    header.extend_from_slice(&ancount.to_be_bytes());
+    header.extend_from_slice(&[0, 0, 0, 0]);
+    header
+}
+
+/// Synthesize the answer for a blocked name
+fn blocked_response(query: &[u8], question: &DnsQuestion, mode: SinkholeResponse) -> Vec<u8> {
+    let null_address: Option<&[u8]> = match (mode, question.qclass, question.qtype) {
+        (SinkholeResponse::NullAddress, CLASS_IN, QTYPE_A) => Some(&[0u8; 4]),
+        (SinkholeResponse::NullAddress, CLASS_IN, QTYPE_AAAA) => Some(&[0u8; 16]),
+        _ => None,
+    };
+
+    let rcode = match mode {
+        SinkholeResponse::NxDomain => RCODE_NXDOMAIN,
+        // Other record types get an empty NOERROR answer
+        SinkholeResponse::NullAddress => 0,
+    };
+
+    let mut response = response_header(query, rcode, 1, u16::from(null_address.is_some()));
+    response.extend_from_slice(&query[HEADER_LEN..question.end]);
+
+    if let Some(address) = null_address {
+        // Name is a pointer back to the question
+        response.extend_from_slice(&[0xc0, HEADER_LEN as u8]);
+        response.extend_from_slice(&question.qtype.to_be_bytes());
+        response.extend_from_slice(&question.qclass.to_be_bytes());
+        response.extend_from_slice(&BLOCKED_ANSWER_TTL.to_be_bytes());
+        response.extend_from_slice(&(address.len() as u16).to_be_bytes());
+        response.extend_from_slice(address);
+    }
+
+    response
+}
+
+/// Error answer; None if the message is too short to even echo its ID
+fn error_response(query: &[u8], question: Option<&DnsQuestion>, rcode: u8) -> Option<Vec<u8>> {
+    if query.len() < HEADER_LEN {
+        return None;
+    }
+
+    let mut response = response_header(query, rcode, u16::from(question.is_some()), 0);
+    if let Some(question) = question {
+        response.extend_from_slice(&query[HEADER_LEN..question.end]);
+    }
+    Some(response)
+}
+
+// ============================================================================
+// App integration
+// ============================================================================
+
+/// Load the persisted sinkhole configuration
+pub async fn load_config(state: &AppState) -> Result<SinkholeConfig> {
+    let stored = queries::get_user_setting(state.pool(), SINKHOLE_SETTINGS_KEY).await?;
+
+    Ok(match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid DNS sinkhole settings: {}", e);
+            SinkholeConfig::default()
+        }),
+        None => SinkholeConfig::default(),
+    })
+}
+
+/// Persist the sinkhole configuration
+pub async fn save_config(state: &AppState, config: &SinkholeConfig) -> Result<()> {
+    let json = serde_json::to_string(config)?;
+    queries::set_user_setting(state.pool(), SINKHOLE_SETTINGS_KEY, &json).await
+}
+
+/// Start the sinkhole for the app, replacing a running instance
+///
+/// Blocked queries are recorded as website block attempts, forwarded names
+/// count against website time budgets, and the blocklist
+/// follows the blocking state until the sinkhole is stopped.
+pub async fn start_for_app(state: &AppState, config: SinkholeConfig) -> Result<SinkholeStats> {
+    // Release the port before rebinding
+    stop_for_app(state).await;
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let (forwarded_sender, forwarded_receiver) = mpsc::unbounded_channel();
+    let sinkhole = DnsSinkhole::start(config, Some(sender), Some(forwarded_sender)).await?;
+    let stats = sinkhole.stats();
+    let shared = Arc::downgrade(&sinkhole.shared);
+
+    {
+        let mut running = state.dns_sinkhole.write().await;
+        *running = Some(sinkhole);
+    }
+
+    tokio::spawn(record_blocked_queries(state.clone(), receiver));
+    tokio::spawn(budget::track_website_queries(state.clone(), forwarded_receiver));
+    tokio::spawn(follow_blocking_state(state.clone(), shared));
+
+    Ok(stats)
+}
+
+/// Stop the app's sinkhole, if running
+pub async fn stop_for_app(state: &AppState) {
+    let sinkhole = {
+        let mut running = state.dns_sinkhole.write().await;
+        running.take()
+    };
+
+    if let Some(sinkhole) = sinkhole {
+        sinkhole.stop();
+    }
+}
+
+/// Start the sinkhole at launch if it was left enabled
+pub async fn restore_for_app(state: &AppState) -> Result<()> {
+    let config = load_config(state).await?;
+    if config.enabled {
+        start_for_app(state, config).await?;
+    }
+    Ok(())
+}
+
+/// Keep the sinkhole blocklist in step with the blocking state
+///
+/// Includes the web allow-list of an active session or Focus Time block and
+/// the DoH canary setting, and exits once the sinkhole it was started for is
+/// stopped.
+async fn follow_blocking_state(state: AppState, shared: Weak<Shared>) {
+    let mut interval = tokio::time::interval(BLOCKLIST_SYNC_INTERVAL);
+    let mut last_seen: Option<(bool, Vec<String>)> = None;
+
+    loop {
+        interval.tick().await;
+
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+
+        shared
+            .answer_canary
+            .store(doh::active_settings().block_canary, Ordering::Relaxed);
+
+        let current = allowlist::effective_website_rules(&state).await;
+
+        if last_seen.as_ref() != Some(&current) {
+            shared.update_blocklist(current.1.clone(), current.0);
+            last_seen = Some(current);
+        }
+    }
+}
+
+/// Record blocked queries in block_attempts
+///
+/// Resolvers and browsers retry aggressively, so each name is recorded at
+/// most once per dedupe window.
+async fn record_blocked_queries(state: AppState, mut receiver: mpsc::UnboundedReceiver<BlockedQuery>) {
+    let mut last_recorded: HashMap<String, Instant> = HashMap::new();
+
+    while let Some(blocked) = receiver.recv().await {
+        let now = Instant::now();
+        last_recorded.retain(|_, at| now.duration_since(*at) < BLOCK_ATTEMPT_DEDUPE_WINDOW);
+        if last_recorded.contains_key(&blocked.domain) {
+            continue;
+        }
+        last_recorded.insert(blocked.domain.clone(), now);
+
+        let session_id = {
+            let active_session = state.active_session.read().await;
+            active_session.as_ref().map(|s| s.id.clone())
+        };
+        let user_id = state.get_user_id().await;
+
+        if let Err(e) = queries::record_block_attempt(
+            state.pool(),
+            "website",
+            &blocked.domain,
+            session_id.as_deref(),
+            user_id.as_deref(),
+        )
+        .await
+        {
+            tracing::warn!("Failed to record DNS block attempt: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_query(id: u16, name: &str, qtype: u16) -> Vec<u8> {
+        let mut query = Vec::new();
+        query.extend_from_slice(&id.to_be_bytes());
+        query.extend_from_slice(&0x0100u16.to_be_bytes()); // RD
+        query.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+        for label in name.split('.') {
+            query.push(label.len() as u8);
+            query.extend_from_slice(label.as_bytes());
+        }
+        query.push(0);
+        query.extend_from_slice(&qtype.to_be_bytes());
+        query.extend_from_slice(&CLASS_IN.to_be_bytes());
+        query
+    }
+
+    fn rcode(response: &[u8]) -> u8 {
+        response[3] & 0x0f
+    }
+
+    fn answer_count(response: &[u8]) -> u16 {
+        u16::from_be_bytes([response[6], response[7]])
+    }
+
+    /// Answer data of the single answer record
+    fn answer_rdata(response: &[u8], query_len: usize) -> &[u8] {
+        // pointer (2) + type (2) + class (2) + ttl (4) + rdlength (2)
+        &response[query_len + 12..]
+    }
+
+    /// Upstream that answers every query with 93.184.216.34 (UDP and TCP)
+    async fn stub_upstream() -> SocketAddr {
+        fn answer(query: &[u8]) -> Vec<u8> {
+            let question = parse_query(query).expect("stub received invalid query");
+            let mut response = response_header(query, 0, 1, 1);
+            response.extend_from_slice(&query[HEADER_LEN..question.end]);
+            response.extend_from_slice(&[0xc0, 0x0c, 0, 1, 0, 1, 0, 0, 0, 30, 0, 4, 93, 184, 216, 34]);
+            response
+        }
+
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = udp.local_addr().unwrap();
+        let tcp = TcpListener::bind(addr).await.unwrap();
+
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 512];
+            while let Ok((len, peer)) = udp.recv_from(&mut buf).await {
+                let _ = udp.send_to(&answer(&buf[..len]), peer).await;
+            }
+        });
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = tcp.accept().await {
+                if let Ok(Some(query)) = read_tcp_message(&mut stream).await {
+                    let _ = write_tcp_message(&mut stream, &answer(&query)).await;
+                }
+            }
+        });
+
+        addr
+    }
+
+    async fn start_sinkhole(
+        upstream: SocketAddr,
+        response: SinkholeResponse,
+        blocklist: &[&str],
+    ) -> (DnsSinkhole, mpsc::UnboundedReceiver<BlockedQuery>) {
+        let config = SinkholeConfig {
+            enabled: true,
+            listen_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            upstream,
+            response,
+            upstream_timeout_ms: 500,
+        };
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let sinkhole = DnsSinkhole::start(config, Some(sender), None).await.unwrap();
+        sinkhole.update_blocklist(blocklist.iter().map(|d| d.to_string()).collect(), true);
+        (sinkhole, receiver)
+    }
+
+    async fn udp_exchange(server: SocketAddr, query: &[u8]) -> Vec<u8> {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket.send_to(query, server).await.unwrap();
+        let mut buf = vec![0u8; 512];
+        let len = timeout(Duration::from_secs(2), socket.recv(&mut buf))
+            .await
+            .expect("no reply from sinkhole")
+            .unwrap();
+        buf[..len].to_vec()
+    }
+
+    async fn tcp_exchange(server: SocketAddr, query: &[u8]) -> Vec<u8> {
+        let mut stream = TcpStream::connect(server).await.unwrap();
+        write_tcp_message(&mut stream, query).await.unwrap();
+        timeout(Duration::from_secs(2), read_tcp_message(&mut stream))
+            .await
+            .expect("no reply from sinkhole")
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = build_query(0xbeef, "News.Example.com", QTYPE_AAAA);
+        let question = parse_query(&query).unwrap();
+
+        assert_eq!(question.name, "news.example.com");
+        assert_eq!(question.qtype, QTYPE_AAAA);
+        assert_eq!(question.qclass, CLASS_IN);
+        assert_eq!(question.end, query.len());
+    }
+
+    #[test]
+    fn test_parse_query_rejects_malformed() {
+        let query = build_query(1, "example.com", QTYPE_A);
+
+        assert!(parse_query(&query[..8]).is_none());
+        assert!(parse_query(&query[..query.len() - 2]).is_none());
+
+        let mut response = query.clone();
+        response[2] |= 0x80;
+        assert!(parse_query(&response).is_none());
+
+        let mut pointer = query;
+        pointer[HEADER_LEN] = 0xc0;
+        assert!(parse_query(&pointer).is_none());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(SinkholeConfig::default().validate().is_ok());
+
+        let looped = SinkholeConfig {
+            upstream: SinkholeConfig::default().listen_addr,
+            ..SinkholeConfig::default()
+        };
+        assert!(looped.validate().is_err());
+
+        let no_timeout = SinkholeConfig {
+            upstream_timeout_ms: 0,
+            ..SinkholeConfig::default()
+        };
+        assert!(no_timeout.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blocked_name_gets_null_address() {
+        let upstream = stub_upstream().await;
+        let (sinkhole, mut blocked) =
+            start_sinkhole(upstream, SinkholeResponse::NullAddress, &["reddit.com"]).await;
+
+        let query = build_query(7, "reddit.com", QTYPE_A);
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(&response[..2], &[0, 7]);
+        assert_eq!(rcode(&response), 0);
+        assert_eq!(answer_count(&response), 1);
+        assert_eq!(answer_rdata(&response, query.len()), &[0, 0, 0, 0]);
+
+        let query = build_query(8, "www.reddit.com", QTYPE_AAAA);
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(answer_rdata(&response, query.len()), &[0u8; 16]);
+
+        let event = blocked.recv().await.unwrap();
+        assert_eq!(event.domain, "reddit.com");
+        assert_eq!(event.match_type.as_deref(), Some("exact"));
+    }
+
+    #[tokio::test]
+    async fn test_subdomain_gets_nxdomain() {
+        let upstream = stub_upstream().await;
+        let (sinkhole, _blocked) =
+            start_sinkhole(upstream, SinkholeResponse::NxDomain, &["youtube.com"]).await;
+
+        let query = build_query(9, "m.youtube.com", QTYPE_A);
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+
+        assert_eq!(rcode(&response), RCODE_NXDOMAIN);
+        assert_eq!(answer_count(&response), 0);
+        // Question is echoed back
+        assert_eq!(&response[HEADER_LEN..], &query[HEADER_LEN..]);
+    }
+
+    #[tokio::test]
+    async fn test_canary_gets_nxdomain_in_null_address_mode() {
+        let upstream = stub_upstream().await;
+        let (sinkhole, _blocked) = start_sinkhole(upstream, SinkholeResponse::NullAddress, &[]).await;
+        let query = build_query(11, doh::CANARY_DOMAIN, QTYPE_A);
+
+        // Relayed until DoH protection asks for it
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(answer_count(&response), 1);
+
+        sinkhole.set_answer_canary(true);
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(rcode(&response), RCODE_NXDOMAIN);
+        assert_eq!(answer_count(&response), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_blocks_subdomains_only() {
+        let upstream = stub_upstream().await;
+        let (sinkhole, _blocked) =
+            start_sinkhole(upstream, SinkholeResponse::NxDomain, &["*.example.com"]).await;
+
+        let response = udp_exchange(sinkhole.local_addr(), &build_query(1, "cdn.example.com", QTYPE_A)).await;
+        assert_eq!(rcode(&response), RCODE_NXDOMAIN);
+
+        let query = build_query(2, "example.com", QTYPE_A);
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(rcode(&response), 0);
+        assert_eq!(answer_rdata(&response, query.len()), &[93, 184, 216, 34]);
+    }
+
+    #[tokio::test]
+    async fn test_unblocked_names_are_forwarded() {
+        let upstream = stub_upstream().await;
+        let (sinkhole, _blocked) =
+            start_sinkhole(upstream, SinkholeResponse::NullAddress, &["reddit.com"]).await;
+
+        let query = build_query(0x1234, "docs.rs", QTYPE_A);
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(&response[..2], &[0x12, 0x34]);
+        assert_eq!(answer_rdata(&response, query.len()), &[93, 184, 216, 34]);
+
+        let response = tcp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(answer_rdata(&response, query.len()), &[93, 184, 216, 34]);
+
+        let response = tcp_exchange(sinkhole.local_addr(), &build_query(3, "reddit.com", QTYPE_A)).await;
+        assert_eq!(answer_count(&response), 1);
+
+        let stats = sinkhole.stats();
+        assert_eq!(stats.total_queries, 3);
+        assert_eq!(stats.forwarded_queries, 2);
+        assert_eq!(stats.blocked_queries, 1);
+        assert_eq!(stats.top_blocked, vec![("reddit.com".to_string(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_blocklist_forwards_everything() {
+        let upstream = stub_upstream().await;
+        let (sinkhole, _blocked) =
+            start_sinkhole(upstream, SinkholeResponse::NxDomain, &["reddit.com"]).await;
+        sinkhole.update_blocklist(vec!["reddit.com".to_string()], false);
+
+        let query = build_query(4, "reddit.com", QTYPE_A);
+        let response = udp_exchange(sinkhole.local_addr(), &query).await;
+        assert_eq!(answer_rdata(&response, query.len()), &[93, 184, 216, 34]);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_upstream_gets_servfail() {
+        // Bound but never answers
+        let silent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (sinkhole, _blocked) =
+            start_sinkhole(silent.local_addr().unwrap(), SinkholeResponse::NxDomain, &[]).await;
+
+        let response = udp_exchange(sinkhole.local_addr(), &build_query(5, "docs.rs", QTYPE_A)).await;
+        assert_eq!(rcode(&response), RCODE_SERVFAIL);
+        assert_eq!(sinkhole.stats().failed_queries, 1);
+    }
+}
@@ -0,0 +1,461 @@
+// blocking/termination.rs - Process-tree aware, graceful termination
+//
+// Multi-process apps (browsers, Electron apps like Discord or Slack) respawn
+// or leave helpers behind when only the main PID is killed. Termination
+// therefore covers the whole process tree plus the process groups it leads,
+// asks politely first (SIGTERM, so apps can save their state) and only sends
+// SIGKILL to what is still running after a configurable timeout. Process
+// groups entirely owned by the tree are signalled once instead of per PID.
+
+use crate::{db::queries, Error, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, Signal, System, UpdateKind};
+
+/// user_settings key of the persisted termination settings
+pub const TERMINATION_SETTINGS_KEY: &str = "process_termination";
+
+/// Longest graceful timeout that can be configured
+pub const MAX_GRACEFUL_TIMEOUT_MS: u64 = 60_000;
+
+/// How long killed processes get to disappear after SIGKILL
+const KILL_WAIT: Duration = Duration::from_millis(1000);
+
+/// How often exits are polled while waiting
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Termination settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TerminationSettings {
+    /// How long apps get to exit after SIGTERM before they are killed
+    pub graceful_timeout_ms: u64,
+}
+
+impl Default for TerminationSettings {
+    fn default() -> Self {
+        Self {
+            graceful_timeout_ms: 5000,
+        }
+    }
+}
+
+impl TerminationSettings {
+    pub fn graceful_timeout(&self) -> Duration {
+        Duration::from_millis(self.graceful_timeout_ms.min(MAX_GRACEFUL_TIMEOUT_MS))
+    }
+}
+
+/// Load the persisted termination settings
+pub async fn load_settings(pool: &SqlitePool) -> Result<TerminationSettings> {
+    let stored = queries::get_user_setting(pool, TERMINATION_SETTINGS_KEY).await?;
+
+    Ok(match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid termination settings: {}", e);
+            TerminationSettings::default()
+        }),
+        None => TerminationSettings::default(),
+    })
+}
+
+/// Persist the termination settings
+pub async fn save_settings(pool: &SqlitePool, settings: &TerminationSettings) -> Result<()> {
+    if settings.graceful_timeout_ms > MAX_GRACEFUL_TIMEOUT_MS {
+        return Err(Error::Validation(format!(
+            "Graceful timeout cannot exceed {} ms",
+            MAX_GRACEFUL_TIMEOUT_MS
+        )));
+    }
+
+    let json = serde_json::to_string(settings)?;
+    queries::set_user_setting(pool, TERMINATION_SETTINGS_KEY, &json).await
+}
+
+/// What happened to each process of a terminated tree
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminationOutcome {
+    pub root_pid: u32,
+    /// Processes that exited
+    pub killed: Vec<u32>,
+    /// Processes still running after SIGKILL
+    pub survived: Vec<u32>,
+    /// Processes left alone (system or other users' processes)
+    pub protected: Vec<u32>,
+    /// Whether SIGKILL was needed after the graceful timeout
+    pub forced: bool,
+}
+
+impl TerminationOutcome {
+    /// Summary recorded in block_attempts: "killed", "survived" or "protected"
+    pub fn status(&self) -> &'static str {
+        if !self.survived.is_empty() {
+            "survived"
+        } else if self.killed.is_empty() {
+            "protected"
+        } else {
+            "killed"
+        }
+    }
+}
+
+/// Minimal process information needed to plan a termination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ProcessNode {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    /// Process group, where the platform has them
+    pub pgid: Option<u32>,
+    /// Whether the process may be terminated at all
+    pub allowed: bool,
+}
+
+/// Signals to send for one termination round
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TerminationPlan {
+    /// Process groups signalled as a whole
+    pub groups: Vec<u32>,
+    /// Processes signalled individually
+    pub pids: Vec<u32>,
+    /// Processes that must not be signalled
+    pub protected: Vec<u32>,
+}
+
+impl TerminationPlan {
+    /// Every process the plan signals, directly or through its group
+    pub fn targets(&self, nodes: &[ProcessNode]) -> Vec<u32> {
+        let mut targets: Vec<u32> = nodes
+            .iter()
+            .filter(|node| node.pgid.is_some_and(|pgid| self.groups.contains(&pgid)))
+            .map(|node| node.pid)
+            .chain(self.pids.iter().copied())
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        targets
+    }
+}
+
+/// Work out what to signal to terminate `root` and everything it started
+///
+/// The tree is the root, its descendants, and the members of process groups
+/// led by a process of the tree (helpers re-parented to init keep their group).
+/// A group is signalled once when its leader is in the tree and every member
+/// may be terminated; everything else is signalled per PID.
+pub(crate) fn plan_termination(root: u32, nodes: &[ProcessNode]) -> TerminationPlan {
+    let mut tree: HashSet<u32> = HashSet::from([root]);
+
+    // Grow until stable: descendants, then members of groups led from the tree
+    loop {
+        let before = tree.len();
+        for node in nodes {
+            let in_tree_parent = node.parent.is_some_and(|parent| tree.contains(&parent));
+            let in_tree_group = node.pgid.is_some_and(|pgid| tree.contains(&pgid));
+            if in_tree_parent || in_tree_group {
+                tree.insert(node.pid);
+            }
+        }
+        if tree.len() == before {
+            break;
+        }
+    }
+
+    let mut plan = TerminationPlan::default();
+    let mut by_group: BTreeMap<Option<u32>, Vec<&ProcessNode>> = BTreeMap::new();
+    for node in nodes.iter().filter(|node| tree.contains(&node.pid)) {
+        by_group.entry(node.pgid).or_default().push(node);
+    }
+
+    for (pgid, members) in by_group {
+        let whole_group = pgid.is_some_and(|pgid| {
+            tree.contains(&pgid)
+                && nodes
+                    .iter()
+                    .filter(|node| node.pgid == Some(pgid))
+                    .all(|node| tree.contains(&node.pid) && node.allowed)
+        });
+
+        match pgid {
+            Some(pgid) if whole_group => plan.groups.push(pgid),
+            _ => {
+                for node in members {
+                    if node.allowed {
+                        plan.pids.push(node.pid);
+                    } else {
+                        plan.protected.push(node.pid);
+                    }
+                }
+            }
+        }
+    }
+
+    plan.pids.sort_unstable();
+    plan.protected.sort_unstable();
+    plan
+}
+
+/// Snapshot the running processes for planning
+fn snapshot(system: &mut System, allowed: &dyn Fn(&sysinfo::Process) -> bool) -> Vec<ProcessNode> {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_user(UpdateKind::OnlyIfNotSet),
+    );
+
+    system
+        .processes()
+        .iter()
+        .filter(|(_, process)| !is_gone(process))
+        .map(|(pid, process)| ProcessNode {
+            pid: pid.as_u32(),
+            parent: process.parent().map(|parent| parent.as_u32()),
+            pgid: process_group(pid.as_u32()),
+            allowed: allowed(process),
+        })
+        .collect()
+}
+
+/// The processes of `root`'s tree that `allowed` lets us signal, root first
+///
+/// Used by policies that signal a whole app without terminating it, e.g.
+/// pausing it with SIGSTOP.
+pub(crate) fn process_tree(root: u32, allowed: &dyn Fn(&sysinfo::Process) -> bool) -> Result<Vec<u32>> {
+    let mut system = System::new();
+    let nodes = snapshot(&mut system, allowed);
+
+    if !nodes.iter().any(|node| node.pid == root) {
+        return Err(Error::ProcessNotFound(format!("PID {}", root)));
+    }
+
+    let plan = plan_termination(root, &nodes);
+    let mut tree = vec![root];
+    tree.extend(plan.targets(&nodes).into_iter().filter(|pid| *pid != root));
+    Ok(tree)
+}
+
+fn is_gone(process: &sysinfo::Process) -> bool {
+    matches!(process.status(), ProcessStatus::Zombie | ProcessStatus::Dead)
+}
+
+#[cfg(unix)]
+fn process_group(pid: u32) -> Option<u32> {
+    // SAFETY: getpgid only reads kernel state for the given pid
+    let pgid = unsafe { libc::getpgid(pid as libc::pid_t) };
+    (pgid > 0).then_some(pgid as u32)
+}
+
+#[cfg(not(unix))]
+fn process_group(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Send a signal to every target of the plan
+fn signal_plan(system: &System, plan: &TerminationPlan, signal: Signal) {
+    #[cfg(unix)]
+    for pgid in &plan.groups {
+        let signum = match signal {
+            Signal::Kill => libc::SIGKILL,
+            _ => libc::SIGTERM,
+        };
+        // SAFETY: killpg has no memory safety requirements
+        if unsafe { libc::killpg(*pgid as libc::pid_t, signum) } != 0 {
+            tracing::debug!(
+                "Failed to signal process group {}: {}",
+                pgid,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    for pid in &plan.pids {
+        if let Some(process) = system.process(Pid::from_u32(*pid)) {
+            // Platforms without SIGTERM (Windows) can only kill outright
+            if process.kill_with(signal).is_none() {
+                process.kill();
+            }
+        }
+    }
+}
+
+/// Wait until the given processes exited, returning the ones still running
+async fn wait_for_exit(system: &mut System, targets: &[u32], timeout: Duration) -> Vec<u32> {
+    let pids: Vec<Pid> = targets.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        system.refresh_processes_specifics(ProcessesToUpdate::Some(&pids), true, ProcessRefreshKind::nothing());
+        let running: Vec<u32> = pids
+            .iter()
+            .filter(|pid| system.process(**pid).is_some_and(|process| !is_gone(process)))
+            .map(|pid| pid.as_u32())
+            .collect();
+
+        if running.is_empty() || tokio::time::Instant::now() >= deadline {
+            return running;
+        }
+        tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+    }
+}
+
+/// Terminate a process and its tree: SIGTERM, then SIGKILL after `timeout`
+///
+/// `allowed` decides which processes may be terminated; the others are
+/// reported as protected and left alone.
+pub async fn terminate_tree(
+    root: u32,
+    timeout: Duration,
+    allowed: &(dyn Fn(&sysinfo::Process) -> bool + Sync),
+) -> Result<TerminationOutcome> {
+    let mut system = System::new();
+    let nodes = snapshot(&mut system, allowed);
+
+    if !nodes.iter().any(|node| node.pid == root) {
+        return Err(Error::ProcessNotFound(format!("PID {}", root)));
+    }
+
+    let plan = plan_termination(root, &nodes);
+    let targets = plan.targets(&nodes);
+    let mut outcome = TerminationOutcome {
+        root_pid: root,
+        protected: plan.protected.clone(),
+        ..Default::default()
+    };
+
+    if targets.is_empty() {
+        return Ok(outcome);
+    }
+
+    tracing::debug!(
+        "Terminating tree of PID {}: groups {:?}, pids {:?}, protected {:?}",
+        root,
+        plan.groups,
+        plan.pids,
+        plan.protected
+    );
+
+    signal_plan(&system, &plan, Signal::Term);
+    let mut running = wait_for_exit(&mut system, &targets, timeout).await;
+
+    if !running.is_empty() {
+        outcome.forced = true;
+        let survivors = TerminationPlan {
+            groups: Vec::new(),
+            pids: running.clone(),
+            protected: Vec::new(),
+        };
+        signal_plan(&system, &survivors, Signal::Kill);
+        running = wait_for_exit(&mut system, &running, KILL_WAIT).await;
+    }
+
+    outcome.killed = targets.into_iter().filter(|pid| !running.contains(pid)).collect();
+    outcome.survived = running;
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(pid: u32, parent: u32, pgid: u32) -> ProcessNode {
+        ProcessNode {
+            pid,
+            parent: Some(parent),
+            pgid: Some(pgid),
+            allowed: true,
+        }
+    }
+
+    #[test]
+    fn test_plan_signals_owned_group_once() {
+        let nodes = vec![
+            node(1, 0, 1),
+            node(100, 1, 100),   // app, group leader
+            node(101, 100, 100), // renderer
+            node(102, 101, 100), // grandchild
+            node(150, 1, 100),   // helper re-parented to init, same group
+            node(200, 1, 200),   // unrelated
+        ];
+
+        let plan = plan_termination(100, &nodes);
+        assert_eq!(plan.groups, vec![100]);
+        assert!(plan.pids.is_empty());
+        assert_eq!(plan.targets(&nodes), vec![100, 101, 102, 150]);
+    }
+
+    #[test]
+    fn test_plan_skips_shared_groups() {
+        // The app was started without its own group (it shares the launcher's)
+        let nodes = vec![
+            node(10, 1, 10),   // launcher, leads the group
+            node(100, 10, 10), // blocked app
+            node(101, 100, 10),
+            node(11, 10, 10), // sibling app, must survive
+        ];
+
+        let plan = plan_termination(100, &nodes);
+        assert!(plan.groups.is_empty());
+        assert_eq!(plan.pids, vec![100, 101]);
+        assert_eq!(plan.targets(&nodes), vec![100, 101]);
+    }
+
+    #[test]
+    fn test_plan_reports_protected() {
+        let mut nodes = vec![node(100, 1, 100), node(101, 100, 100), node(102, 100, 100)];
+        nodes[2].allowed = false;
+
+        let plan = plan_termination(100, &nodes);
+        // A protected member prevents signalling the whole group
+        assert!(plan.groups.is_empty());
+        assert_eq!(plan.pids, vec![100, 101]);
+        assert_eq!(plan.protected, vec![102]);
+    }
+
+    #[test]
+    fn test_outcome_status() {
+        let mut outcome = TerminationOutcome {
+            root_pid: 1,
+            killed: vec![1],
+            ..Default::default()
+        };
+        assert_eq!(outcome.status(), "killed");
+
+        outcome.survived = vec![2];
+        assert_eq!(outcome.status(), "survived");
+
+        let outcome = TerminationOutcome {
+            root_pid: 1,
+            protected: vec![1],
+            ..Default::default()
+        };
+        assert_eq!(outcome.status(), "protected");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_terminate_tree_kills_children() {
+        // A shell with a child that ignores SIGTERM, in its own process group
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg("trap '' TERM; sleep 30 & wait")
+            .spawn()
+            .unwrap();
+        let root = child.id();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let outcome = terminate_tree(root, Duration::from_millis(300), &|_| true).await.unwrap();
+        child.wait().unwrap();
+
+        assert!(outcome.killed.contains(&root));
+        assert!(outcome.killed.len() >= 2, "child sleep should be killed: {:?}", outcome);
+        assert!(outcome.survived.is_empty());
+        assert!(outcome.forced);
+        assert_eq!(outcome.status(), "killed");
+
+        let err = terminate_tree(u32::MAX - 1, Duration::ZERO, &|_| true).await.unwrap_err();
+        assert!(matches!(err, Error::ProcessNotFound(_)));
+    }
+}
@@ -1,11 +1,12 @@
 // commands/analytics.rs - Analytics and productivity metrics
 
 use crate::{
-    db::queries::{self, DailyAnalytics},
+    db::queries::{self, DailyAnalytics, DailyInterruption, ProjectFocusTime, TagFocusTime, WebsiteVisitTotals},
     state::AppState,
     Result,
 };
 use serde::Serialize;
+use sqlx::SqlitePool;
 use tauri::State;
 
 #[derive(Debug, Serialize)]
@@ -16,6 +17,24 @@ pub struct DailyStatsResponse {
     pub sessions_completed: i64,
     pub sessions_abandoned: i64,
     pub productivity_score: f64,
+    pub by_project: Vec<ProjectBreakdown>,
+    pub by_tag: Vec<TagBreakdown>,
+}
+
+/// Focus time of a project; sessions without a project have no ID or name
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectBreakdown {
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub focus_minutes: i64,
+    pub sessions: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagBreakdown {
+    pub tag: String,
+    pub focus_minutes: i64,
+    pub sessions: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -33,6 +52,28 @@ pub struct WeeklyTotals {
     pub average_daily_focus: f64,
     pub best_day: Option<String>,
     pub productivity_trend: f64,
+    pub by_project: Vec<ProjectBreakdown>,
+    pub by_tag: Vec<TagBreakdown>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InterruptionStatsResponse {
+    pub start_date: String,
+    pub end_date: String,
+    /// Average interruption score (0-100) of the scored sessions
+    pub average_score: Option<f64>,
+    pub sessions_scored: i64,
+    pub pauses: i64,
+    pub extensions: i64,
+    pub early_ends: i64,
+    pub daily: Vec<DailyInterruption>,
+    pub top_reasons: Vec<InterruptionReason>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InterruptionReason {
+    pub reason: String,
+    pub count: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -69,7 +110,11 @@ pub async fn get_daily_stats(
         analytics.sessions_abandoned,
     );
 
+    let breakdowns = Breakdowns::load(state.pool(), &target_date, &target_date).await?;
+
     Ok(DailyStatsResponse {
+        by_project: breakdowns.projects(Some(&analytics.date)),
+        by_tag: breakdowns.tags(Some(&analytics.date)),
         date: analytics.date,
         total_focus_minutes: analytics.total_focus_seconds / 60,
         total_break_minutes: analytics.total_break_seconds / 60,
@@ -96,6 +141,7 @@ pub async fn get_weekly_stats(
         &end_str,
     )
     .await?;
+    let breakdowns = Breakdowns::load(state.pool(), &start_str, &end_str).await?;
 
     let mut daily_stats = Vec::new();
     let mut total_focus_minutes = 0i64;
@@ -123,6 +169,8 @@ pub async fn get_weekly_stats(
         total_sessions += day_analytics.sessions_completed + day_analytics.sessions_abandoned;
 
         daily_stats.push(DailyStatsResponse {
+            by_project: breakdowns.projects(Some(&day_analytics.date)),
+            by_tag: breakdowns.tags(Some(&day_analytics.date)),
             date: day_analytics.date,
             total_focus_minutes: focus_minutes,
             total_break_minutes: day_analytics.total_break_seconds / 60,
@@ -151,6 +199,8 @@ pub async fn get_weekly_stats(
             average_daily_focus,
             best_day: best_day.map(|(date, _)| date),
             productivity_trend,
+            by_project: breakdowns.projects(None),
+            by_tag: breakdowns.tags(None),
         },
     })
 }
@@ -168,6 +218,7 @@ pub async fn get_date_range_stats(
         &end_date,
     )
     .await?;
+    let breakdowns = Breakdowns::load(state.pool(), &start_date, &end_date).await?;
 
     let daily_stats: Vec<DailyStatsResponse> = analytics
         .into_iter()
@@ -179,6 +230,8 @@ pub async fn get_date_range_stats(
             );
 
             DailyStatsResponse {
+                by_project: breakdowns.projects(Some(&day_analytics.date)),
+                by_tag: breakdowns.tags(Some(&day_analytics.date)),
                 date: day_analytics.date,
                 total_focus_minutes: day_analytics.total_focus_seconds / 60,
                 total_break_minutes: day_analytics.total_break_seconds / 60,
@@ -192,6 +245,58 @@ pub async fn get_date_range_stats(
     Ok(daily_stats)
 }
 
+/// Get pauses, extensions, early ends and interruption scores for a date range
+#[tauri::command]
+pub async fn get_interruption_stats(
+    start_date: String,
+    end_date: String,
+    state: State<'_, AppState>,
+) -> Result<InterruptionStatsResponse> {
+    let daily = queries::get_daily_interruption_scores(state.pool(), &start_date, &end_date).await?;
+    let counts = queries::get_session_event_counts(state.pool(), &start_date, &end_date).await?;
+    let top_reasons =
+        queries::get_top_interruption_reasons(state.pool(), &start_date, &end_date, 5).await?;
+
+    let count = |event_type: &str| {
+        counts
+            .iter()
+            .find(|(t, _)| t == event_type)
+            .map(|(_, n)| *n)
+            .unwrap_or(0)
+    };
+
+    let sessions_scored: i64 = daily.iter().map(|d| d.sessions).sum();
+    let average_score = (sessions_scored > 0).then(|| {
+        daily.iter().map(|d| d.average_score * d.sessions as f64).sum::<f64>() / sessions_scored as f64
+    });
+
+    Ok(InterruptionStatsResponse {
+        start_date,
+        end_date,
+        average_score,
+        sessions_scored,
+        pauses: count("pause"),
+        extensions: count("extend"),
+        early_ends: count("end_early"),
+        daily,
+        top_reasons: top_reasons
+            .into_iter()
+            .map(|(reason, count)| InterruptionReason { reason, count })
+            .collect(),
+    })
+}
+
+/// Get the most visited websites reported by the browser extension
+#[tauri::command]
+pub async fn get_website_visits(
+    start_date: String,
+    end_date: String,
+    limit: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<WebsiteVisitTotals>> {
+    queries::get_top_website_visits(state.pool(), &start_date, &end_date, limit.unwrap_or(20).clamp(1, 200)).await
+}
+
 /// Get current productivity score with grade
 #[tauri::command]
 pub async fn get_productivity_score(
@@ -261,6 +366,82 @@ pub async fn get_productivity_score(
 // Helper Functions
 // ============================================================================
 
+/// Per-day focus time by project and tag for a date range
+struct Breakdowns {
+    projects: Vec<ProjectFocusTime>,
+    tags: Vec<TagFocusTime>,
+}
+
+impl Breakdowns {
+    async fn load(pool: &SqlitePool, start_date: &str, end_date: &str) -> Result<Self> {
+        Ok(Self {
+            projects: queries::get_project_focus_time(pool, start_date, end_date).await?,
+            tags: queries::get_tag_focus_time(pool, start_date, end_date).await?,
+        })
+    }
+
+    /// Project totals for one day, or for the whole range when `day` is None
+    fn projects(&self, day: Option<&str>) -> Vec<ProjectBreakdown> {
+        let mut totals: Vec<(ProjectBreakdown, i64)> = Vec::new();
+
+        for row in self.projects.iter().filter(|row| day.is_none() || day == Some(row.day.as_str())) {
+            match totals.iter_mut().find(|(total, _)| total.project_id == row.project_id) {
+                Some((total, seconds)) => {
+                    *seconds += row.focus_seconds;
+                    total.sessions += row.sessions;
+                }
+                None => totals.push((
+                    ProjectBreakdown {
+                        project_id: row.project_id,
+                        project_name: row.project_name.clone(),
+                        focus_minutes: 0,
+                        sessions: row.sessions,
+                    },
+                    row.focus_seconds,
+                )),
+            }
+        }
+
+        finish_totals(totals, |total, minutes| total.focus_minutes = minutes)
+    }
+
+    /// Tag totals for one day, or for the whole range when `day` is None
+    fn tags(&self, day: Option<&str>) -> Vec<TagBreakdown> {
+        let mut totals: Vec<(TagBreakdown, i64)> = Vec::new();
+
+        for row in self.tags.iter().filter(|row| day.is_none() || day == Some(row.day.as_str())) {
+            match totals.iter_mut().find(|(total, _)| total.tag == row.tag) {
+                Some((total, seconds)) => {
+                    *seconds += row.focus_seconds;
+                    total.sessions += row.sessions;
+                }
+                None => totals.push((
+                    TagBreakdown {
+                        tag: row.tag.clone(),
+                        focus_minutes: 0,
+                        sessions: row.sessions,
+                    },
+                    row.focus_seconds,
+                )),
+            }
+        }
+
+        finish_totals(totals, |total, minutes| total.focus_minutes = minutes)
+    }
+}
+
+/// Convert summed seconds to minutes and sort by most focus time first
+fn finish_totals<T>(mut totals: Vec<(T, i64)>, set_minutes: impl Fn(&mut T, i64)) -> Vec<T> {
+    totals.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+    totals
+        .into_iter()
+        .map(|(mut total, seconds)| {
+            set_minutes(&mut total, seconds / 60);
+            total
+        })
+        .collect()
+}
+
 /// Calculate productivity score (0-100) based on focus time and session completion
 fn calculate_productivity_score(
     focus_seconds: i64,
@@ -304,3 +485,75 @@ fn calculate_trend(daily_stats: &[DailyStatsResponse]) -> f64 {
 
     second_half - first_half
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_row(day: &str, project_id: Option<i64>, focus_seconds: i64) -> ProjectFocusTime {
+        ProjectFocusTime {
+            day: day.to_string(),
+            project_id,
+            project_name: project_id.map(|id| format!("Project {}", id)),
+            focus_seconds,
+            sessions: 1,
+        }
+    }
+
+    #[test]
+    fn test_project_breakdown() {
+        let breakdowns = Breakdowns {
+            projects: vec![
+                project_row("2026-10-12", Some(1), 1500),
+                project_row("2026-10-12", None, 600),
+                project_row("2026-10-13", Some(2), 3000),
+                project_row("2026-10-13", Some(1), 1530),
+            ],
+            tags: Vec::new(),
+        };
+
+        let day = breakdowns.projects(Some("2026-10-12"));
+        assert_eq!(day.len(), 2);
+        assert_eq!(day[0].project_id, Some(1));
+        assert_eq!(day[0].focus_minutes, 25);
+        assert_eq!(day[1].project_id, None);
+
+        let week = breakdowns.projects(None);
+        assert_eq!(week[0].project_id, Some(1));
+        assert_eq!(week[0].focus_minutes, 50);
+        assert_eq!(week[0].sessions, 2);
+        assert_eq!(week[1].project_id, Some(2));
+        assert_eq!(week[2].project_id, None);
+    }
+
+    #[test]
+    fn test_tag_breakdown() {
+        let breakdowns = Breakdowns {
+            projects: Vec::new(),
+            tags: vec![
+                TagFocusTime {
+                    day: "2026-10-12".to_string(),
+                    tag: "writing".to_string(),
+                    focus_seconds: 1200,
+                    sessions: 1,
+                },
+                TagFocusTime {
+                    day: "2026-10-13".to_string(),
+                    tag: "writing".to_string(),
+                    focus_seconds: 1200,
+                    sessions: 2,
+                },
+            ],
+        };
+
+        assert_eq!(
+            breakdowns.tags(None),
+            vec![TagBreakdown {
+                tag: "writing".to_string(),
+                focus_minutes: 40,
+                sessions: 3,
+            }]
+        );
+        assert!(breakdowns.tags(Some("2026-10-14")).is_empty());
+    }
+}
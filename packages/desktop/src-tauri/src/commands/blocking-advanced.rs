@@ -2,7 +2,15 @@
 //
 // Implements schedule-based blocking, categories, strict mode, nuclear option, and statistics
 
-use crate::{db::queries, state::AppState, Error, Result};
+use crate::{
+    blocking::{
+        blocklist, import,
+        nuclear::{self, MonotonicStamp},
+    },
+    db::queries::{self, BlockingCategory, BlockingSchedule},
+    state::{AppState, NuclearOptionState},
+    Error, Result,
+};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -11,23 +19,13 @@ use tauri::State;
 // Blocking Schedules
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
-pub struct BlockingSchedule {
-    pub id: i64,
-    pub user_id: Option<String>,
-    pub day_of_week: i32,
-    pub start_time: String,
-    pub end_time: String,
-    pub enabled: bool,
-    pub created_at: String,
-    pub updated_at: String,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct CreateScheduleRequest {
     pub day_of_week: i32,
     pub start_time: String,
     pub end_time: String,
+    #[serde(default)]
+    pub profile_id: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,6 +34,21 @@ pub struct UpdateScheduleRequest {
     pub enabled: Option<bool>,
     pub start_time: Option<String>,
     pub end_time: Option<String>,
+    #[serde(default)]
+    pub profile_id: Option<i64>,
+    /// Stop applying a focus profile during this schedule
+    #[serde(default)]
+    pub clear_profile: bool,
+}
+
+/// Make sure a schedule's profile exists before referencing it
+async fn ensure_profile_exists(state: &AppState, profile_id: Option<i64>) -> Result<()> {
+    if let Some(profile_id) = profile_id {
+        if queries::get_focus_profile(state.pool(), profile_id).await?.is_none() {
+            return Err(Error::NotFound(format!("Focus profile {} not found", profile_id)));
+        }
+    }
+    Ok(())
 }
 
 /// Create a new blocking schedule
@@ -58,15 +71,18 @@ pub async fn create_blocking_schedule(
         ));
     }
 
+    ensure_profile_exists(&state, request.profile_id).await?;
+
     let result = sqlx::query(
         r#"
-        INSERT INTO blocking_schedules (day_of_week, start_time, end_time)
-        VALUES (?, ?, ?)
+        INSERT INTO blocking_schedules (day_of_week, start_time, end_time, profile_id)
+        VALUES (?, ?, ?, ?)
         "#,
     )
     .bind(request.day_of_week)
     .bind(&request.start_time)
     .bind(&request.end_time)
+    .bind(request.profile_id)
     .execute(state.pool())
     .await?;
 
@@ -122,6 +138,8 @@ pub async fn update_blocking_schedule(
         }
     }
 
+    ensure_profile_exists(&state, request.profile_id).await?;
+
     let mut query = String::from("UPDATE blocking_schedules SET updated_at = CURRENT_TIMESTAMP");
     let mut bindings: Vec<String> = Vec::new();
 
@@ -140,6 +158,13 @@ pub async fn update_blocking_schedule(
         bindings.push(end_time);
     }
 
+    if request.clear_profile {
+        query.push_str(", profile_id = NULL");
+    } else if let Some(profile_id) = request.profile_id {
+        query.push_str(", profile_id = ?");
+        bindings.push(profile_id.to_string());
+    }
+
     query.push_str(" WHERE id = ?");
 
     let mut sql_query = sqlx::query(&query);
@@ -172,17 +197,6 @@ pub async fn delete_blocking_schedule(id: i64, state: State<'_, AppState>) -> Re
 // Blocking Categories
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
-pub struct BlockingCategory {
-    pub id: i64,
-    pub name: String,
-    pub description: Option<String>,
-    pub items: String, // JSON array stored as string
-    pub enabled: bool,
-    pub created_at: String,
-    pub updated_at: String,
-}
-
 #[derive(Debug, Serialize)]
 pub struct CategoryResponse {
     pub id: i64,
@@ -277,6 +291,8 @@ pub async fn create_blocking_category(
 
     let items: Vec<String> = serde_json::from_str(&category.items)?;
 
+    blocklist::refresh_website_blocking(&state).await?;
+
     tracing::info!("Created blocking category: {}", request.name);
 
     Ok(CategoryResponse {
@@ -296,6 +312,10 @@ pub async fn update_blocking_category(
     request: UpdateCategoryRequest,
     state: State<'_, AppState>,
 ) -> Result<()> {
+    if request.items.is_some() || request.enabled == Some(false) {
+        nuclear::ensure_unlocked(&state, "change blocking categories").await?;
+    }
+
     let mut query = String::from("UPDATE blocking_categories SET updated_at = CURRENT_TIMESTAMP");
     let mut bindings: Vec<(String, String)> = Vec::new();
 
@@ -330,6 +350,9 @@ pub async fn update_blocking_category(
 
     sql_query.execute(state.pool()).await?;
 
+    // Category items or enabled state may have changed the effective blocklist
+    blocklist::refresh_website_blocking(&state).await?;
+
     tracing::info!("Updated blocking category: {}", request.id);
 
     Ok(())
@@ -342,6 +365,10 @@ pub async fn toggle_blocking_category(
     enabled: bool,
     state: State<'_, AppState>,
 ) -> Result<()> {
+    if !enabled {
+        nuclear::ensure_unlocked(&state, "disable blocking categories").await?;
+    }
+
     sqlx::query(
         r#"
         UPDATE blocking_categories
@@ -354,11 +381,71 @@ pub async fn toggle_blocking_category(
     .execute(state.pool())
     .await?;
 
+    blocklist::refresh_website_blocking(&state).await?;
+
     tracing::info!("Toggled category {} to {}", id, enabled);
 
     Ok(())
 }
 
+// ============================================================================
+// Blocklist Import
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ImportBlocklistRequest {
+    /// http(s) URL or absolute path of a hosts file, domain list or Adblock list
+    pub source: String,
+    /// Category to import into; created if it does not exist
+    pub category_name: String,
+    /// Keep the list up to date in the background
+    #[serde(default)]
+    pub subscribe: bool,
+    pub refresh_interval_hours: Option<i64>,
+}
+
+/// Import a blocklist into a blocking category
+#[tauri::command]
+pub async fn import_blocklist(
+    request: ImportBlocklistRequest,
+    state: State<'_, AppState>,
+) -> Result<import::ImportReport> {
+    let refresh_interval_hours = request.subscribe.then(|| {
+        request
+            .refresh_interval_hours
+            .unwrap_or(import::DEFAULT_REFRESH_INTERVAL_HOURS)
+    });
+
+    import::import_list(&state, &request.source, &request.category_name, refresh_interval_hours).await
+}
+
+/// Get all blocklist subscriptions
+#[tauri::command]
+pub async fn get_blocklist_subscriptions(
+    state: State<'_, AppState>,
+) -> Result<Vec<queries::BlocklistSubscription>> {
+    queries::get_blocklist_subscriptions(state.pool()).await
+}
+
+/// Fetch a subscribed blocklist now and report what changed
+#[tauri::command]
+pub async fn refresh_blocklist_subscription(
+    id: i64,
+    state: State<'_, AppState>,
+) -> Result<import::ImportReport> {
+    import::refresh_subscription(&state, id).await
+}
+
+/// Stop refreshing a blocklist, optionally removing its domains from the category
+#[tauri::command]
+pub async fn delete_blocklist_subscription(
+    id: i64,
+    remove_domains: bool,
+    state: State<'_, AppState>,
+) -> Result<()> {
+    import::remove_subscription(&state, id, remove_domains).await
+}
+
 // ============================================================================
 // Strict Mode
 // ============================================================================
@@ -377,10 +464,15 @@ pub async fn enable_strict_mode(
     session_id: String,
     state: State<'_, AppState>,
 ) -> Result<()> {
-    let mut strict_mode = state.strict_mode_state.write().await;
-    strict_mode.enabled = true;
-    strict_mode.session_id = Some(session_id.clone());
-    strict_mode.started_at = Some(Utc::now().to_rfc3339());
+    {
+        let mut strict_mode = state.strict_mode_state.write().await;
+        strict_mode.enabled = true;
+        strict_mode.session_id = Some(session_id.clone());
+        strict_mode.started_at = Some(Utc::now().to_rfc3339());
+    }
+
+    // Persist so quitting the app doesn't escape strict mode
+    state.checkpoint_session().await;
 
     tracing::info!("Strict mode enabled for session: {}", session_id);
 
@@ -390,6 +482,8 @@ pub async fn enable_strict_mode(
 /// Disable strict mode (only if session has ended)
 #[tauri::command]
 pub async fn disable_strict_mode(state: State<'_, AppState>) -> Result<()> {
+    nuclear::ensure_unlocked(&state, "disable strict mode").await?;
+
     let strict_mode = state.strict_mode_state.read().await;
 
     // Check if there's an active session
@@ -407,10 +501,14 @@ pub async fn disable_strict_mode(state: State<'_, AppState>) -> Result<()> {
 
     drop(strict_mode); // Release read lock
 
-    let mut strict_mode = state.strict_mode_state.write().await;
-    strict_mode.enabled = false;
-    strict_mode.session_id = None;
-    strict_mode.started_at = None;
+    {
+        let mut strict_mode = state.strict_mode_state.write().await;
+        strict_mode.enabled = false;
+        strict_mode.session_id = None;
+        strict_mode.started_at = None;
+    }
+
+    state.checkpoint_session().await;
 
     tracing::info!("Strict mode disabled");
 
@@ -456,88 +554,71 @@ pub struct ActivateNuclearOptionRequest {
 }
 
 /// Activate nuclear option (irreversible lockdown)
+///
+/// The lockdown persists across restarts and ignores system clock changes;
+/// it cannot be shortened or cancelled once started.
 #[tauri::command]
 pub async fn activate_nuclear_option(
     request: ActivateNuclearOptionRequest,
     state: State<'_, AppState>,
 ) -> Result<NuclearOption> {
-    // Validate duration
-    if ![5, 10, 15, 30, 60].contains(&request.duration_minutes) {
-        return Err(Error::InvalidInput(
-            "Duration must be 5, 10, 15, 30, or 60 minutes".to_string(),
-        ));
-    }
+    nuclear::validate_duration(request.duration_minutes)?;
+
+    let response = {
+        let mut nuclear_state = state.nuclear_option_state.write().await;
 
-    let started_at = Utc::now();
-    let ends_at = started_at + chrono::Duration::minutes(request.duration_minutes as i64);
+        // Re-activating could be used to shorten a running lockdown
+        if nuclear_state.active {
+            return Err(Error::InvalidInput(
+                "Nuclear option is already active".to_string(),
+            ));
+        }
+
+        nuclear_state.activate(request.duration_minutes, Utc::now(), MonotonicStamp::now());
+        nuclear_option_response(&nuclear_state)
+    };
 
-    let mut nuclear_state = state.nuclear_option_state.write().await;
-    nuclear_state.active = true;
-    nuclear_state.duration_minutes = request.duration_minutes;
-    nuclear_state.started_at = Some(started_at);
-    nuclear_state.ends_at = Some(ends_at);
+    nuclear::apply_lockdown_blocking(&state).await?;
+
+    // Persist so restarting the app doesn't lift the lockdown
+    state.checkpoint_session().await;
 
     tracing::warn!(
         "Nuclear option activated for {} minutes",
         request.duration_minutes
     );
 
-    let remaining = (ends_at - Utc::now()).num_seconds();
-
-    Ok(NuclearOption {
-        active: true,
-        duration_minutes: request.duration_minutes,
-        started_at: Some(started_at.to_rfc3339()),
-        ends_at: Some(ends_at.to_rfc3339()),
-        remaining_seconds: Some(remaining),
-    })
+    Ok(response)
 }
 
 /// Get nuclear option state
 #[tauri::command]
 pub async fn get_nuclear_option_state(state: State<'_, AppState>) -> Result<NuclearOption> {
-    let nuclear_state = state.nuclear_option_state.read().await;
-
-    if nuclear_state.active {
-        if let Some(ends_at) = nuclear_state.ends_at {
-            let now = Utc::now();
-
-            // Check if nuclear option has expired
-            if now >= ends_at {
-                drop(nuclear_state); // Release read lock
-                let mut nuclear_state = state.nuclear_option_state.write().await;
-                nuclear_state.active = false;
-                nuclear_state.started_at = None;
-                nuclear_state.ends_at = None;
-
-                return Ok(NuclearOption {
-                    active: false,
-                    duration_minutes: 0,
-                    started_at: None,
-                    ends_at: None,
-                    remaining_seconds: None,
-                });
-            }
-
-            let remaining = (ends_at - now).num_seconds();
-
-            return Ok(NuclearOption {
-                active: true,
-                duration_minutes: nuclear_state.duration_minutes,
-                started_at: nuclear_state.started_at.map(|dt| dt.to_rfc3339()),
-                ends_at: Some(ends_at.to_rfc3339()),
-                remaining_seconds: Some(remaining),
-            });
-        }
+    // Project the countdown to now without mutating; the monitoring loop owns expiry
+    let mut nuclear_state = state.nuclear_option_state.read().await.clone();
+    nuclear_state.advance(Utc::now(), MonotonicStamp::now());
+
+    Ok(nuclear_option_response(&nuclear_state))
+}
+
+fn nuclear_option_response(nuclear_state: &NuclearOptionState) -> NuclearOption {
+    if !nuclear_state.active {
+        return NuclearOption {
+            active: false,
+            duration_minutes: 0,
+            started_at: None,
+            ends_at: None,
+            remaining_seconds: None,
+        };
     }
 
-    Ok(NuclearOption {
-        active: false,
-        duration_minutes: 0,
-        started_at: None,
-        ends_at: None,
-        remaining_seconds: None,
-    })
+    NuclearOption {
+        active: true,
+        duration_minutes: nuclear_state.duration_minutes,
+        started_at: nuclear_state.started_at.map(|dt| dt.to_rfc3339()),
+        ends_at: nuclear_state.ends_at.map(|dt| dt.to_rfc3339()),
+        remaining_seconds: Some(nuclear_state.remaining_seconds()),
+    }
 }
 
 // ============================================================================
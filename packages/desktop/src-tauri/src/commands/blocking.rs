@@ -1,8 +1,21 @@
 // commands/blocking.rs - App and website blocking management
 
 use crate::{
-    blocking::{capabilities, dns, hosts},
+    blocking::{
+        allowlist,
+        blocklist::{self, EffectiveBlocklist},
+        app_rules::AppRuleKind,
+        capabilities, dns,
+        doh::{self, DohSettings},
+        enforcement::{self, DelayedProcess, EnforcementPolicy},
+        hosts,
+        interstitial::{self, BlockedPageSettings, BlockedPageStatus},
+        nuclear, rules,
+        sinkhole::{self, SinkholeConfig, SinkholeStats},
+        termination::{self, TerminationSettings},
+    },
     db::queries,
+    extension::manifest::{self, ExtensionIds, ManifestInstall},
     state::AppState,
     Error, Result,
 };
@@ -10,8 +23,19 @@ use serde::{Deserialize, Serialize};
 use tauri::State;
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AddBlockedItemRequest {
     pub value: String,
+    /// "regex" or "contains" to treat the value as a pattern (website rules,
+    /// and app rules matching the executable path or command line)
+    #[serde(default)]
+    pub match_type: Option<String>,
+    /// Apps only: what happens when the app is detected (defaults to kill)
+    #[serde(default)]
+    pub enforcement: Option<EnforcementPolicy>,
+    /// Apps only: what the rule matches (defaults to the process name)
+    #[serde(default)]
+    pub rule_kind: Option<AppRuleKind>,
 }
 
 #[derive(Debug, Serialize)]
@@ -28,9 +52,61 @@ pub async fn add_blocked_app(
     request: AddBlockedItemRequest,
     state: State<'_, AppState>,
 ) -> Result<()> {
-    // Validate process name format - enhanced security
-    let app_name = request.value.trim();
+    let rule_kind = request.rule_kind.unwrap_or_default();
+    let (app_name, match_type) = match rule_kind {
+        AppRuleKind::Name => {
+            let app_name = request.value.trim();
+            validate_process_name(app_name)?;
+            (app_name.to_string(), "exact")
+        }
+        AppRuleKind::ExePath | AppRuleKind::Cmdline => {
+            let match_type = match request.match_type.as_deref().map(str::to_lowercase).as_deref() {
+                None | Some("exact") => "exact",
+                Some("contains") => "contains",
+                Some("regex") => {
+                    regex::Regex::new(request.value.trim())
+                        .map_err(|e| Error::InvalidInput(format!("Invalid regex pattern: {}", e)))?;
+                    "regex"
+                }
+                Some(other) => {
+                    return Err(Error::InvalidInput(format!("Unsupported match type: {}", other)));
+                }
+            };
+            (rule_kind.normalize_value(&request.value, match_type)?, match_type)
+        }
+        AppRuleKind::ExeHash | AppRuleKind::DesktopId => {
+            (rule_kind.normalize_value(&request.value, "exact")?, "exact")
+        }
+    };
+    let app_name = app_name.as_str();
+
+    let enforcement = request.enforcement.unwrap_or_default();
+    if enforcement != EnforcementPolicy::Kill {
+        nuclear::ensure_unlocked(&state, "soften app enforcement").await?;
+    }
 
+    queries::insert_blocked_app_rule(
+        state.pool(),
+        app_name,
+        match_type,
+        rule_kind.as_str(),
+        enforcement.as_str(),
+    )
+    .await?;
+    blocklist::invalidate();
+
+    tracing::info!(
+        "Added blocked app: {} ({}, {})",
+        app_name,
+        rule_kind.as_str(),
+        enforcement.as_str()
+    );
+
+    Ok(())
+}
+
+/// Validate a process name rule - enhanced security
+fn validate_process_name(app_name: &str) -> Result<()> {
     if app_name.is_empty() {
         return Err(Error::InvalidInput(
             "Process name cannot be empty".to_string(),
@@ -66,20 +142,93 @@ pub async fn add_blocked_app(
         ));
     }
 
-    queries::insert_blocked_item(state.pool(), "app", app_name).await?;
+    Ok(())
+}
 
-    tracing::info!("Added blocked app: {}", app_name);
+/// Change what happens when a blocked app is detected
+#[tauri::command]
+pub async fn set_blocked_app_enforcement(
+    value: String,
+    enforcement: EnforcementPolicy,
+    state: State<'_, AppState>,
+) -> Result<()> {
+    if enforcement != EnforcementPolicy::Kill {
+        nuclear::ensure_unlocked(&state, "soften app enforcement").await?;
+    }
+
+    let updated =
+        queries::set_blocked_item_enforcement(state.pool(), "app", value.trim(), enforcement.as_str()).await?;
+    if !updated {
+        return Err(Error::NotFound(format!("App '{}' is not blocked", value)));
+    }
+    blocklist::invalidate();
+
+    tracing::info!("Set enforcement of {} to {}", value, enforcement.as_str());
 
     Ok(())
 }
 
+/// Blocked apps currently paused by the delay policy
+#[tauri::command]
+pub async fn get_delayed_processes(state: State<'_, AppState>) -> Result<Vec<DelayedProcess>> {
+    let delayed = state.delayed_processes.read().await;
+    Ok(delayed.iter().filter(|d| !d.resumed).cloned().collect())
+}
+
+/// Continue a paused app
+///
+/// Needs a typed reason until the countdown has elapsed; the reason is saved
+/// to the trigger journal under `trigger_type` (default "other").
+#[tauri::command]
+pub async fn resume_delayed_process(
+    id: String,
+    reason: Option<String>,
+    trigger_type: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<DelayedProcess> {
+    enforcement::resume_delayed_process(&state, &id, reason.as_deref(), trigger_type.as_deref()).await
+}
+
+/// Close a paused app instead of continuing it
+#[tauri::command]
+pub async fn close_delayed_process(id: String, state: State<'_, AppState>) -> Result<()> {
+    enforcement::close_delayed_process(&state, &id).await
+}
+
+/// How blocked apps are terminated
+#[tauri::command]
+pub async fn get_termination_settings(state: State<'_, AppState>) -> Result<TerminationSettings> {
+    termination::load_settings(state.pool()).await
+}
+
+/// Change how long blocked apps get to exit before they are killed
+#[tauri::command]
+pub async fn set_termination_settings(
+    settings: TerminationSettings,
+    state: State<'_, AppState>,
+) -> Result<TerminationSettings> {
+    let current = termination::load_settings(state.pool()).await?;
+    if settings.graceful_timeout_ms > current.graceful_timeout_ms {
+        nuclear::ensure_unlocked(&state, "extend the termination timeout").await?;
+    }
+
+    termination::save_settings(state.pool(), &settings).await?;
+
+    tracing::info!("Set graceful termination timeout to {} ms", settings.graceful_timeout_ms);
+
+    Ok(settings)
+}
+
 /// Remove an application from the block list
 #[tauri::command]
 pub async fn remove_blocked_app(
     request: AddBlockedItemRequest,
     state: State<'_, AppState>,
 ) -> Result<()> {
+    nuclear::ensure_unlocked(&state, "remove blocked apps").await?;
+
     queries::remove_blocked_item(state.pool(), "app", &request.value).await?;
+    blocklist::invalidate();
 
     tracing::info!("Removed blocked app: {}", request.value);
 
@@ -88,81 +237,30 @@ pub async fn remove_blocked_app(
 
 /// Add a website to the block list
 ///
-/// Domain should be in format: "example.com" or "www.example.com"
+/// Accepts a domain ("example.com") or any website rule: "*.example.com",
+/// "youtube.com/shorts", "/regex/" or an "!music.youtube.com" exception.
+/// A `matchType` of "regex" or "contains" treats the value as a pattern.
 #[tauri::command]
 pub async fn add_blocked_website(
     request: AddBlockedItemRequest,
     state: State<'_, AppState>,
 ) -> Result<()> {
-    // Validate domain format
-    let domain = request.value.trim().to_lowercase();
-
-    // Check for empty domain
-    if domain.is_empty() {
-        return Err(Error::InvalidInput(
-            "Domain cannot be empty".to_string(),
-        ));
-    }
-
-    // Check domain length (max 255 chars per DNS spec)
-    if domain.len() > 253 {
-        return Err(Error::InvalidInput(
-            "Domain name is too long (max 253 characters)".to_string(),
-        ));
-    }
-
-    // Check for at least one dot (e.g., "example.com")
-    if !domain.contains('.') {
-        return Err(Error::InvalidInput(
-            "Invalid domain format. Domain must contain at least one dot (e.g., 'example.com')".to_string(),
-        ));
-    }
-
-    // Check for protocol or path characters
-    if domain.contains('/') || domain.contains(':') {
-        return Err(Error::InvalidInput(
-            "Invalid domain format. Use 'example.com' without protocol or path".to_string(),
-        ));
-    }
-
-    // Check for invalid characters (only alphanumeric, hyphens, and dots allowed)
-    let valid_domain = domain.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
-    if !valid_domain {
-        return Err(Error::InvalidInput(
-            "Domain contains invalid characters. Only letters, numbers, hyphens, and dots are allowed".to_string(),
-        ));
+    let rule = match request.match_type.as_deref() {
+        Some(match_type) => rules::WebsiteRule::from_blocked_item(&request.value, match_type)?,
+        None => rules::WebsiteRule::parse(&request.value)?,
+    };
+    // Exceptions weaken blocking
+    if rule.exception {
+        nuclear::ensure_unlocked(&state, "add website exceptions").await?;
     }
 
-    // Check that domain doesn't start or end with hyphen or dot
-    if domain.starts_with('.') || domain.ends_with('.') || domain.starts_with('-') || domain.ends_with('-') {
-        return Err(Error::InvalidInput(
-            "Domain cannot start or end with a dot or hyphen".to_string(),
-        ));
-    }
+    let domain = rule.to_string();
+    let match_type = if rule.match_type() == "regex" { "regex" } else { "exact" };
 
-    queries::insert_blocked_item(state.pool(), "website", &domain).await?;
+    queries::insert_blocked_item_with_match_type(state.pool(), "website", &domain, match_type).await?;
 
     // Apply hosts file blocking if session is active
-    let blocking_enabled = {
-        let blocking_state = state.blocking_state.read().await;
-        blocking_state.enabled
-    };
-
-    if blocking_enabled {
-        let all_websites = queries::get_blocked_items(state.pool(), Some("website")).await?;
-        let domains: Vec<String> = all_websites.iter().map(|item| item.value.clone()).collect();
-
-        // Update in-memory state for DNS fallback
-        {
-            let mut blocking_state = state.blocking_state.write().await;
-            blocking_state.update_blocked_websites(domains.clone());
-        }
-
-        // Try to update hosts file (may fail without privileges)
-        if let Err(e) = hosts::update_hosts_file(&domains).await {
-            tracing::warn!("Hosts file update failed: {}, DNS fallback active", e);
-        }
-    }
+    blocklist::refresh_website_blocking(&state).await?;
 
     tracing::info!("Added blocked website: {}", domain);
 
@@ -175,31 +273,20 @@ pub async fn remove_blocked_website(
     request: AddBlockedItemRequest,
     state: State<'_, AppState>,
 ) -> Result<()> {
-    let domain = request.value.trim().to_lowercase();
+    nuclear::ensure_unlocked(&state, "remove blocked websites").await?;
+
+    // Rules are stored in canonical form; fall back to the raw value for legacy rows
+    let domain = match request.match_type.as_deref() {
+        Some(match_type) => rules::WebsiteRule::from_blocked_item(&request.value, match_type),
+        None => rules::WebsiteRule::parse(&request.value),
+    }
+    .map(|rule| rule.to_string())
+    .unwrap_or_else(|_| request.value.trim().to_lowercase());
 
     queries::remove_blocked_item(state.pool(), "website", &domain).await?;
 
     // Update hosts file
-    let blocking_enabled = {
-        let blocking_state = state.blocking_state.read().await;
-        blocking_state.enabled
-    };
-
-    if blocking_enabled {
-        let all_websites = queries::get_blocked_items(state.pool(), Some("website")).await?;
-        let domains: Vec<String> = all_websites.iter().map(|item| item.value.clone()).collect();
-
-        // Update in-memory state for DNS fallback
-        {
-            let mut blocking_state = state.blocking_state.write().await;
-            blocking_state.update_blocked_websites(domains.clone());
-        }
-
-        // Try to update hosts file (may fail without privileges)
-        if let Err(e) = hosts::update_hosts_file(&domains).await {
-            tracing::warn!("Hosts file update failed: {}, DNS fallback active", e);
-        }
-    }
+    blocklist::refresh_website_blocking(&state).await?;
 
     tracing::info!("Removed blocked website: {}", domain);
 
@@ -233,8 +320,11 @@ pub async fn toggle_blocking(
     enable: bool,
     state: State<'_, AppState>,
 ) -> Result<()> {
-    let websites = queries::get_blocked_items(state.pool(), Some("website")).await?;
-    let domains: Vec<String> = websites.iter().map(|item| item.value.clone()).collect();
+    if !enable {
+        nuclear::ensure_unlocked(&state, "disable blocking").await?;
+    }
+
+    let domains = EffectiveBlocklist::load(&state).await?.domains();
 
     {
         let mut blocking_state = state.blocking_state.write().await;
@@ -249,7 +339,7 @@ pub async fn toggle_blocking(
 
     if enable {
         // Try to apply hosts file blocking (requires elevated privileges)
-        match hosts::update_hosts_file(&domains).await {
+        match hosts::update_hosts_file(&state, &domains).await {
             Ok(_) => {
                 tracing::info!("Hosts file blocking enabled");
             }
@@ -260,7 +350,7 @@ pub async fn toggle_blocking(
         }
     } else {
         // Clear website blocking
-        if let Err(e) = hosts::clear_hosts_file().await {
+        if let Err(e) = hosts::clear_hosts_file(&state).await {
             tracing::warn!("Failed to clear hosts file: {}", e);
         }
     }
@@ -270,6 +360,17 @@ pub async fn toggle_blocking(
     Ok(())
 }
 
+/// Preview the effective blocklist
+///
+/// Resolves manual items and enabled blocking categories into the websites and
+/// process names that blocking applies to, listing where each entry came from.
+#[tauri::command]
+pub async fn get_effective_blocklist(
+    state: State<'_, AppState>,
+) -> Result<EffectiveBlocklist> {
+    EffectiveBlocklist::load(&state).await
+}
+
 // ============================================================================
 // DNS Fallback Commands (for frontend-based blocking)
 // ============================================================================
@@ -283,12 +384,13 @@ pub async fn toggle_blocking(
 pub async fn get_blocked_domains(
     state: State<'_, AppState>,
 ) -> Result<dns::BlockedDomainsResponse> {
+    let (enabled, domains) = allowlist::effective_website_rules(&state).await;
     let blocking_state = state.blocking_state.read().await;
 
     let response = dns::BlockedDomainsResponse {
-        domains: blocking_state.blocked_websites.clone(),
-        enabled: blocking_state.enabled,
-        count: blocking_state.blocked_websites.len(),
+        count: domains.len(),
+        domains,
+        enabled,
         last_updated: blocking_state
             .last_check
             .unwrap_or_else(chrono::Utc::now)
@@ -308,9 +410,9 @@ pub async fn check_domain_blocked(
     record_attempt: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<dns::DomainCheckResult> {
-    let blocking_state = state.blocking_state.read().await;
+    let rules = allowlist::compiled_website_rules(&state).await;
 
-    if !blocking_state.enabled {
+    if !rules.enabled {
         return Ok(dns::DomainCheckResult {
             blocked: false,
             matched_domain: None,
@@ -318,17 +420,10 @@ pub async fn check_domain_blocked(
         });
     }
 
-    // Create temporary fallback instance to use domain checking logic
-    let mut fallback = dns::DnsBlockingFallback::new();
-    fallback.update_blocklist(blocking_state.blocked_websites.clone());
-    fallback.enable();
-
-    let result = fallback.is_domain_blocked(&domain);
+    let result = rules.matcher.check_domain(&domain);
 
     // Record the block attempt if requested and blocked
     if record_attempt.unwrap_or(false) && result.blocked {
-        drop(blocking_state); // Release the lock before async operation
-
         let session_id = {
             let active_session = state.active_session.read().await;
             active_session.as_ref().map(|s| s.id.clone())
@@ -357,7 +452,7 @@ pub async fn check_domain_blocked(
 
 /// Check if a URL is blocked
 ///
-/// Convenience endpoint that extracts domain from URL and checks if blocked
+/// Like `check_domain_blocked`, but path rules (e.g. "youtube.com/shorts") apply
 /// When record_attempt is true, this will log the block attempt to the database
 #[tauri::command]
 pub async fn check_url_blocked(
@@ -365,9 +460,16 @@ pub async fn check_url_blocked(
     record_attempt: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<dns::DomainCheckResult> {
-    let blocking_state = state.blocking_state.read().await;
+    check_url(&state, &url, record_attempt.unwrap_or(false)).await
+}
 
-    if !blocking_state.enabled {
+/// Check a URL against the website rules currently in force
+///
+/// Shared by `check_url_blocked` and the browser extension bridge.
+pub(crate) async fn check_url(state: &AppState, url: &str, record_attempt: bool) -> Result<dns::DomainCheckResult> {
+    let rules = allowlist::compiled_website_rules(state).await;
+
+    if !rules.enabled {
         return Ok(dns::DomainCheckResult {
             blocked: false,
             matched_domain: None,
@@ -376,20 +478,14 @@ pub async fn check_url_blocked(
     }
 
     // Extract domain from URL
-    let domain = dns::DnsBlockingFallback::extract_domain_from_url(&url)
+    let domain = dns::DnsBlockingFallback::extract_domain_from_url(url)
         .ok_or_else(|| Error::InvalidInput("Invalid URL format".to_string()))?;
 
-    // Create temporary fallback instance to use domain checking logic
-    let mut fallback = dns::DnsBlockingFallback::new();
-    fallback.update_blocklist(blocking_state.blocked_websites.clone());
-    fallback.enable();
-
-    let result = fallback.is_domain_blocked(&domain);
+    // URL checks include path rules
+    let result = rules.matcher.check_url(url);
 
     // Record the block attempt if requested and blocked
-    if record_attempt.unwrap_or(false) && result.blocked {
-        drop(blocking_state); // Release the lock before async operation
-
+    if record_attempt && result.blocked {
         let session_id = {
             let active_session = state.active_session.read().await;
             active_session.as_ref().map(|s| s.id.clone())
@@ -421,12 +517,12 @@ pub async fn check_url_blocked(
 pub async fn get_blocking_stats(
     state: State<'_, AppState>,
 ) -> Result<dns::BlockingStats> {
-    let blocking_state = state.blocking_state.read().await;
+    let (enabled, rules) = allowlist::effective_website_rules(&state).await;
 
     let mut fallback = dns::DnsBlockingFallback::new();
-    fallback.update_blocklist(blocking_state.blocked_websites.clone());
+    fallback.update_blocklist(rules);
 
-    if blocking_state.enabled {
+    if enabled {
         fallback.enable();
     }
 
@@ -452,7 +548,7 @@ pub async fn get_blocking_stats(
 ///   "hosts_file_path": "/etc/hosts",
 ///   "process_termination_available": true,
 ///   "recommended_method": "process_termination",
-///   "available_methods": ["process_termination", "frontend_only"],
+///   "available_methods": ["process_termination", "dns_sinkhole", "frontend_only"],
 ///   "limitations": [
 ///     "Hosts file at /etc/hosts is not writable. Website blocking requires elevated privileges."
 ///   ],
@@ -460,8 +556,11 @@ pub async fn get_blocking_stats(
 /// }
 /// ```
 #[tauri::command]
-pub async fn get_blocking_capabilities() -> Result<capabilities::BlockingCapabilities> {
-    let caps = capabilities::check_capabilities().await;
+pub async fn get_blocking_capabilities(
+    state: State<'_, AppState>,
+) -> Result<capabilities::BlockingCapabilities> {
+    let sinkhole_running = state.dns_sinkhole.read().await.is_some();
+    let caps = capabilities::check_capabilities(sinkhole_running).await;
 
     if !caps.hosts_file_writable {
         tracing::warn!(
@@ -529,3 +628,155 @@ pub async fn check_hosts_file_permissions() -> Result<bool> {
 
     Ok(writable)
 }
+
+/// List the FocusFlow backups of the hosts file, newest first
+#[tauri::command]
+pub async fn list_hosts_backups() -> Result<Vec<hosts::HostsBackup>> {
+    hosts::list_hosts_backups().await
+}
+
+/// Restore the hosts file from a backup
+///
+/// Active blocking entries are re-applied on top of the restored file.
+/// Refused while the nuclear option is active.
+#[tauri::command]
+pub async fn restore_hosts_backup(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<hosts::HostsBackup>> {
+    nuclear::ensure_unlocked(&state, "restore the hosts file").await?;
+
+    hosts::restore_hosts_backup(&state, &name).await?;
+
+    hosts::list_hosts_backups().await
+}
+
+// ============================================================================
+// DNS-over-HTTPS Protection
+// ============================================================================
+
+/// Get which DNS-over-HTTPS domains are added to the hosts block
+#[tauri::command]
+pub async fn get_doh_settings(state: State<'_, AppState>) -> Result<DohSettings> {
+    doh::load_settings(&state).await
+}
+
+/// Choose which DNS-over-HTTPS domains are added to the hosts block
+///
+/// A running block is rewritten right away. Turning protection off is refused
+/// while the nuclear option is active.
+#[tauri::command]
+pub async fn set_doh_settings(
+    settings: DohSettings,
+    state: State<'_, AppState>,
+) -> Result<DohSettings> {
+    doh::save_settings(&state, settings).await?;
+    Ok(settings)
+}
+
+// ============================================================================
+// Blocked Page
+// ============================================================================
+
+/// Whether the local "site blocked" page is being served, and on which addresses
+#[tauri::command]
+pub async fn get_blocked_page_status(state: State<'_, AppState>) -> Result<BlockedPageStatus> {
+    interstitial::status(&state).await
+}
+
+/// Turn the local "site blocked" page on or off
+///
+/// Enabling binds the port right away; if that fails (port 80 usually needs
+/// privileges) the error is returned and the settings are left unchanged.
+#[tauri::command]
+pub async fn set_blocked_page_settings(
+    settings: BlockedPageSettings,
+    state: State<'_, AppState>,
+) -> Result<BlockedPageStatus> {
+    if settings.enabled {
+        interstitial::start_for_app(&state, settings.port).await?;
+    } else {
+        interstitial::stop_for_app(&state).await;
+    }
+
+    interstitial::save_settings(&state, &settings).await?;
+
+    interstitial::status(&state).await
+}
+
+// ============================================================================
+// Browser Extension
+// ============================================================================
+
+/// Register the native messaging host with the user's browsers
+///
+/// Only the given extensions may start the host. Browsers without a profile
+/// directory are skipped.
+#[tauri::command]
+pub async fn install_browser_extension_host(ids: ExtensionIds) -> Result<Vec<ManifestInstall>> {
+    let host_path = manifest::host_binary_path()?;
+    manifest::install(&host_path, &ids, &manifest::default_targets()?)
+}
+
+/// Remove the native messaging host registrations
+#[tauri::command]
+pub async fn uninstall_browser_extension_host() -> Result<Vec<String>> {
+    manifest::uninstall(&manifest::default_targets()?)
+}
+
+// ============================================================================
+// DNS Sinkhole
+// ============================================================================
+
+/// Start the local DNS sinkhole resolver
+///
+/// Uses the given configuration, or the saved one if omitted. The sinkhole is
+/// restarted automatically on the next launch until it is stopped. Blocked
+/// names only resolve to the sinkhole once the system resolver points at
+/// `listenAddr`.
+#[tauri::command]
+pub async fn start_dns_sinkhole(
+    config: Option<SinkholeConfig>,
+    state: State<'_, AppState>,
+) -> Result<SinkholeStats> {
+    let mut config = match config {
+        Some(config) => config,
+        None => sinkhole::load_config(&state).await?,
+    };
+    config.validate()?;
+    config.enabled = true;
+
+    let stats = sinkhole::start_for_app(&state, config.clone()).await?;
+    sinkhole::save_config(&state, &config).await?;
+
+    Ok(stats)
+}
+
+/// Stop the local DNS sinkhole resolver
+#[tauri::command]
+pub async fn stop_dns_sinkhole(state: State<'_, AppState>) -> Result<()> {
+    nuclear::ensure_unlocked(&state, "stop the DNS sinkhole").await?;
+
+    sinkhole::stop_for_app(&state).await;
+
+    let mut config = sinkhole::load_config(&state).await?;
+    config.enabled = false;
+    sinkhole::save_config(&state, &config).await
+}
+
+/// Get the saved DNS sinkhole configuration
+#[tauri::command]
+pub async fn get_dns_sinkhole_config(state: State<'_, AppState>) -> Result<SinkholeConfig> {
+    sinkhole::load_config(&state).await
+}
+
+/// Get DNS sinkhole query statistics
+#[tauri::command]
+pub async fn get_dns_sinkhole_stats(state: State<'_, AppState>) -> Result<SinkholeStats> {
+    let running = state.dns_sinkhole.read().await;
+
+    Ok(running
+        .as_ref()
+        .map(|sinkhole| sinkhole.stats())
+        .unwrap_or_default())
+}
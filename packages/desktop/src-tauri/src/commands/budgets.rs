@@ -0,0 +1,220 @@
+// commands/budgets.rs - Daily time budgets for apps and websites
+
+use crate::{
+    blocking::{
+        blocklist,
+        budget::{self, BudgetSettings, BudgetStatus, MAX_DAILY_LIMIT_MINUTES},
+        nuclear,
+    },
+    db::queries,
+    state::AppState,
+    Error, Result,
+};
+use serde::Deserialize;
+use tauri::State;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTimeBudgetRequest {
+    /// "app" or "website"
+    pub item_type: String,
+    pub value: String,
+    pub daily_limit_minutes: i64,
+    /// Weekdays the budget applies (0 = Sunday); every day when omitted
+    #[serde(default)]
+    pub days_of_week: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTimeBudgetRequest {
+    pub id: i64,
+    pub daily_limit_minutes: Option<i64>,
+    pub days_of_week: Option<Vec<u32>>,
+    pub enabled: Option<bool>,
+}
+
+fn validate_limit(daily_limit_minutes: i64) -> Result<()> {
+    if !(1..=MAX_DAILY_LIMIT_MINUTES).contains(&daily_limit_minutes) {
+        return Err(Error::InvalidInput(format!(
+            "Daily limit must be between 1 and {} minutes",
+            MAX_DAILY_LIMIT_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// Sorted, deduplicated weekdays as stored JSON
+fn days_json(days: &[u32]) -> Result<String> {
+    if days.is_empty() || days.iter().any(|d| *d > 6) {
+        return Err(Error::InvalidInput(
+            "Days must be between 0 (Sunday) and 6 (Saturday), with at least one day".to_string(),
+        ));
+    }
+
+    let mut days = days.to_vec();
+    days.sort_unstable();
+    days.dedup();
+    Ok(serde_json::to_string(&days)?)
+}
+
+async fn find_status(state: &AppState, id: i64) -> Result<BudgetStatus> {
+    budget::get_statuses(state.pool())
+        .await?
+        .into_iter()
+        .find(|status| status.id == id)
+        .ok_or_else(|| Error::NotFound(format!("Time budget {} not found", id)))
+}
+
+/// All time budgets with today's usage and remaining time
+#[tauri::command]
+pub async fn get_time_budgets(state: State<'_, AppState>) -> Result<Vec<BudgetStatus>> {
+    budget::get_statuses(state.pool()).await
+}
+
+/// Remaining budget for an app or website, if it has one
+#[tauri::command]
+pub async fn get_remaining_budget(
+    item_type: String,
+    value: String,
+    state: State<'_, AppState>,
+) -> Result<Option<BudgetStatus>> {
+    let value = budget::normalize_value(&item_type, &value)?;
+
+    Ok(budget::get_statuses(state.pool())
+        .await?
+        .into_iter()
+        .find(|status| status.item_type == item_type && status.value.eq_ignore_ascii_case(&value)))
+}
+
+/// Create a daily time budget
+#[tauri::command]
+pub async fn create_time_budget(
+    request: CreateTimeBudgetRequest,
+    state: State<'_, AppState>,
+) -> Result<BudgetStatus> {
+    let value = budget::normalize_value(&request.item_type, &request.value)?;
+    validate_limit(request.daily_limit_minutes)?;
+    let days = days_json(request.days_of_week.as_deref().unwrap_or(&[0, 1, 2, 3, 4, 5, 6]))?;
+
+    let existing = queries::get_time_budgets(state.pool()).await?;
+    if existing
+        .iter()
+        .any(|b| b.item_type == request.item_type && b.value.eq_ignore_ascii_case(&value))
+    {
+        return Err(Error::Validation(format!("{} already has a time budget", value)));
+    }
+
+    let id = queries::insert_time_budget(
+        state.pool(),
+        &request.item_type,
+        &value,
+        request.daily_limit_minutes,
+        &days,
+    )
+    .await?;
+    blocklist::invalidate();
+
+    tracing::info!(
+        "Created time budget for {} {}: {} minutes",
+        request.item_type,
+        value,
+        request.daily_limit_minutes
+    );
+
+    find_status(&state, id).await
+}
+
+/// Change the limit, days or enabled flag of a time budget
+///
+/// Loosening a budget (more time, fewer days, disabling it) is refused while
+/// the nuclear option is active.
+#[tauri::command]
+pub async fn update_time_budget(
+    request: UpdateTimeBudgetRequest,
+    state: State<'_, AppState>,
+) -> Result<BudgetStatus> {
+    let current = queries::get_time_budget(state.pool(), request.id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Time budget {} not found", request.id)))?;
+    let current_days = budget::parse_days(&current.days_of_week);
+
+    let daily_limit_minutes = request.daily_limit_minutes.unwrap_or(current.daily_limit_minutes);
+    validate_limit(daily_limit_minutes)?;
+    let days = request.days_of_week.unwrap_or_else(|| current_days.clone());
+    let enabled = request.enabled.unwrap_or(current.enabled);
+
+    let loosened = daily_limit_minutes > current.daily_limit_minutes
+        || current_days.iter().any(|day| !days.contains(day))
+        || (current.enabled && !enabled);
+    if loosened {
+        nuclear::ensure_unlocked(&state, "loosen time budgets").await?;
+    }
+
+    queries::update_time_budget(state.pool(), request.id, daily_limit_minutes, &days_json(&days)?, enabled).await?;
+    blocklist::invalidate();
+
+    find_status(&state, request.id).await
+}
+
+/// Delete a time budget
+#[tauri::command]
+pub async fn delete_time_budget(id: i64, state: State<'_, AppState>) -> Result<()> {
+    nuclear::ensure_unlocked(&state, "delete time budgets").await?;
+
+    if !queries::delete_time_budget(state.pool(), id).await? {
+        return Err(Error::NotFound(format!("Time budget {} not found", id)));
+    }
+    blocklist::invalidate();
+
+    tracing::info!("Deleted time budget {}", id);
+
+    Ok(())
+}
+
+/// Day boundary and warning thresholds shared by all budgets
+#[tauri::command]
+pub async fn get_budget_settings(state: State<'_, AppState>) -> Result<BudgetSettings> {
+    budget::load_settings(state.pool()).await
+}
+
+/// Change the day boundary or warning thresholds
+///
+/// Moving the day boundary can start a new budget day early, so it is refused
+/// while the nuclear option is active.
+#[tauri::command]
+pub async fn set_budget_settings(
+    settings: BudgetSettings,
+    state: State<'_, AppState>,
+) -> Result<BudgetSettings> {
+    settings.validate()?;
+
+    let current = budget::load_settings(state.pool()).await?;
+    if settings.day_starts_at != current.day_starts_at {
+        nuclear::ensure_unlocked(&state, "move the budget day boundary").await?;
+    }
+
+    budget::save_settings(state.pool(), &settings).await?;
+    blocklist::invalidate();
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_json() {
+        assert_eq!(days_json(&[5, 1, 3, 1]).unwrap(), "[1,3,5]");
+        assert!(days_json(&[]).is_err());
+        assert!(days_json(&[7]).is_err());
+    }
+
+    #[test]
+    fn test_validate_limit() {
+        assert!(validate_limit(30).is_ok());
+        assert!(validate_limit(0).is_err());
+        assert!(validate_limit(MAX_DAILY_LIMIT_MINUTES + 1).is_err());
+    }
+}
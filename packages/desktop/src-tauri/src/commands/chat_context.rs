@@ -59,9 +59,19 @@ pub fn build_context_prompt(
         prompt.push_str(&format!("- Most common distraction: {}\n", trigger));
     }
     prompt.push_str(&format!(
-        "- Average session length: {} minutes\n\n",
+        "- Average session length: {} minutes\n",
         user_context.average_session_minutes
     ));
+    if let Some(score) = user_context.average_interruption_score {
+        prompt.push_str(&format!(
+            "- Interruption score this week: {:.0}/100 (higher means more pauses and early ends)\n",
+            score
+        ));
+    }
+    if let Some(ref reason) = user_context.top_interruption_reason {
+        prompt.push_str(&format!("- Most common interruption reason: {}\n", reason));
+    }
+    prompt.push('\n');
 
     // Add recent conversation context
     if !recent_messages.is_empty() {
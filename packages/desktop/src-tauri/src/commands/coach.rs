@@ -14,6 +14,7 @@ use crate::ai::{
 };
 use crate::commands::chat_context;
 use crate::commands::chat_history::{self, MessageRole};
+use crate::db::queries;
 use crate::{AppState, Error, Result};
 use chrono::Datelike;
 use serde::{Deserialize, Serialize};
@@ -67,6 +68,12 @@ pub struct UserContext {
     pub current_streak_days: i32,
     pub top_trigger: Option<String>,
     pub average_session_minutes: i32,
+    /// Average interruption score (0-100) of the last 7 days
+    #[serde(default)]
+    pub average_interruption_score: Option<f32>,
+    /// Most common reason given for pauses and early ends
+    #[serde(default)]
+    pub top_interruption_reason: Option<String>,
 }
 
 /// Get a coaching response based on user input and context
@@ -623,6 +630,25 @@ async fn get_user_context(state: &State<'_, AppState>) -> Result<UserContext> {
     .fetch_optional(state.pool())
     .await?;
 
+    // Get interruption patterns from the session event log
+    let week_start = (chrono::Utc::now() - chrono::Duration::days(6)).date_naive().to_string();
+    let interruption_scores =
+        queries::get_daily_interruption_scores(state.pool(), &week_start, &today).await?;
+    let scored_sessions: i64 = interruption_scores.iter().map(|d| d.sessions).sum();
+    let average_interruption_score = (scored_sessions > 0).then(|| {
+        (interruption_scores
+            .iter()
+            .map(|d| d.average_score * d.sessions as f64)
+            .sum::<f64>()
+            / scored_sessions as f64) as f32
+    });
+    let top_interruption_reason =
+        queries::get_top_interruption_reasons(state.pool(), &week_start, &today, 1)
+            .await?
+            .into_iter()
+            .next()
+            .map(|(reason, _)| reason);
+
     // Calculate streak (simplified)
     let streak: i32 = sqlx::query_scalar(
         "WITH RECURSIVE streak_days AS (
@@ -648,6 +674,8 @@ async fn get_user_context(state: &State<'_, AppState>) -> Result<UserContext> {
         current_streak_days: streak,
         top_trigger,
         average_session_minutes: avg_duration.unwrap_or(25.0) as i32,
+        average_interruption_score,
+        top_interruption_reason,
     })
 }
 
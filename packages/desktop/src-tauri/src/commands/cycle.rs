@@ -0,0 +1,671 @@
+// commands/cycle.rs - Pomodoro cycle engine
+//
+// Chains focus sessions and breaks: focus, short break, focus, ... with a long
+// break after every N completed focus sessions. When the timer of the cycle's
+// current session completes, the session is ended as completed and the next
+// phase either starts on its own or waits for the user, depending on the
+// auto-start settings. Breaks are regular `SessionType::Break` sessions, which
+// relax blocking. Every change is broadcast as a `cycle-state` event.
+
+use crate::{
+    commands::focus::{self, StartSessionRequest},
+    db::queries,
+    state::{AppState, SessionType},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{Emitter, State};
+
+/// user_settings key of the persisted cycle settings
+pub const CYCLE_SETTINGS_KEY: &str = "pomodoro_cycle";
+
+/// Pomodoro cycle settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CycleSettings {
+    pub focus_minutes: i32,
+    pub short_break_minutes: i32,
+    pub long_break_minutes: i32,
+    /// A long break follows every this many completed focus sessions
+    pub long_break_every: u32,
+    /// Start breaks as soon as a focus session completes
+    pub auto_start_breaks: bool,
+    /// Start the next focus session as soon as a break completes
+    pub auto_start_focus: bool,
+}
+
+impl Default for CycleSettings {
+    fn default() -> Self {
+        Self {
+            focus_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+            auto_start_breaks: true,
+            auto_start_focus: false,
+        }
+    }
+}
+
+impl CycleSettings {
+    pub fn validate(&self) -> Result<()> {
+        for (name, minutes) in [
+            ("Focus", self.focus_minutes),
+            ("Short break", self.short_break_minutes),
+            ("Long break", self.long_break_minutes),
+        ] {
+            if !(1..=480).contains(&minutes) {
+                return Err(Error::Validation(format!(
+                    "{} length must be between 1 minute and 8 hours",
+                    name
+                )));
+            }
+        }
+
+        if !(1..=12).contains(&self.long_break_every) {
+            return Err(Error::Validation(
+                "Long break interval must be between 1 and 12 focus sessions".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Length of a phase in minutes
+    pub fn phase_minutes(&self, phase: CyclePhase) -> i32 {
+        match phase {
+            CyclePhase::Focus => self.focus_minutes,
+            CyclePhase::ShortBreak => self.short_break_minutes,
+            CyclePhase::LongBreak => self.long_break_minutes,
+        }
+    }
+
+    /// Whether the given phase starts without waiting for the user
+    pub fn auto_starts(&self, phase: CyclePhase) -> bool {
+        match phase {
+            CyclePhase::Focus => self.auto_start_focus,
+            CyclePhase::ShortBreak | CyclePhase::LongBreak => self.auto_start_breaks,
+        }
+    }
+}
+
+/// Load the persisted cycle settings
+pub async fn load_settings(pool: &SqlitePool) -> Result<CycleSettings> {
+    let stored = queries::get_user_setting(pool, CYCLE_SETTINGS_KEY).await?;
+
+    Ok(match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid cycle settings: {}", e);
+            CycleSettings::default()
+        }),
+        None => CycleSettings::default(),
+    })
+}
+
+/// Persist the cycle settings
+pub async fn save_settings(pool: &SqlitePool, settings: &CycleSettings) -> Result<()> {
+    settings.validate()?;
+
+    let json = serde_json::to_string(settings)?;
+    queries::set_user_setting(pool, CYCLE_SETTINGS_KEY, &json).await
+}
+
+/// Phase of a Pomodoro cycle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CyclePhase {
+    #[default]
+    Focus,
+    ShortBreak,
+    LongBreak,
+}
+
+impl CyclePhase {
+    fn session_type(self) -> SessionType {
+        match self {
+            CyclePhase::Focus => SessionType::Focus,
+            CyclePhase::ShortBreak | CyclePhase::LongBreak => SessionType::Break,
+        }
+    }
+}
+
+/// Phase that follows `phase`
+///
+/// `completed_focus` is the number of focus sessions completed so far in the
+/// cycle, including the one that just ended.
+pub fn next_phase(phase: CyclePhase, completed_focus: u32, long_break_every: u32) -> CyclePhase {
+    match phase {
+        CyclePhase::Focus if completed_focus > 0 && completed_focus % long_break_every.max(1) == 0 => {
+            CyclePhase::LongBreak
+        }
+        CyclePhase::Focus => CyclePhase::ShortBreak,
+        CyclePhase::ShortBreak | CyclePhase::LongBreak => CyclePhase::Focus,
+    }
+}
+
+/// Runtime state of the Pomodoro cycle
+///
+/// Checkpointed with the session so the cycle survives a restart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CycleState {
+    pub active: bool,
+    pub phase: CyclePhase,
+    /// Focus sessions completed since the cycle started
+    pub completed_focus: u32,
+    /// The current phase is waiting for the user to start it
+    pub awaiting_start: bool,
+    /// Session running the current phase
+    pub session_id: Option<String>,
+    /// Settings captured when the cycle started
+    pub settings: CycleSettings,
+    /// Blocklists, profile, dimming and DND used for every focus phase
+    pub focus_request: Option<StartSessionRequest>,
+}
+
+impl CycleState {
+    /// Move on to the next phase after the current one ended
+    ///
+    /// A focus phase that was cut short doesn't count towards the long break
+    /// and is always followed by a short break.
+    pub fn advance(&mut self, completed: bool) {
+        let completed_focus = self.phase == CyclePhase::Focus && completed;
+        if completed_focus {
+            self.completed_focus += 1;
+        }
+
+        self.phase = if self.phase == CyclePhase::Focus && !completed_focus {
+            CyclePhase::ShortBreak
+        } else {
+            next_phase(self.phase, self.completed_focus, self.settings.long_break_every)
+        };
+        self.session_id = None;
+        self.awaiting_start = !self.settings.auto_starts(self.phase);
+    }
+
+    /// Cycle to carry over from a checkpoint after a restart
+    ///
+    /// `resumed` is the checkpointed session that was resumed and `expired` the
+    /// one that ran out while the app was down. A cycle whose phase expired moves
+    /// on to the next phase and waits for the user; a cycle whose session is
+    /// neither is dropped.
+    pub fn restored(mut self, resumed: Option<&str>, expired: Option<&str>) -> CycleState {
+        if !self.active {
+            return CycleState::default();
+        }
+
+        match self.session_id.as_deref() {
+            Some(id) if resumed == Some(id) => self,
+            Some(id) if expired == Some(id) => {
+                self.advance(false);
+                self.awaiting_start = true;
+                self
+            }
+            None if self.awaiting_start && resumed.is_none() => self,
+            _ => CycleState::default(),
+        }
+    }
+
+    /// Session request for the current phase
+    fn phase_request(&self) -> StartSessionRequest {
+        let minutes = self.settings.phase_minutes(self.phase);
+
+        match (self.phase, &self.focus_request) {
+            (CyclePhase::Focus, Some(request)) => StartSessionRequest {
+                planned_duration_minutes: minutes,
+                session_type: SessionType::Focus,
+                ..request.clone()
+            },
+            _ => StartSessionRequest::new(minutes, self.phase.session_type()),
+        }
+    }
+
+    pub fn payload(&self) -> CycleStatePayload {
+        let every = self.settings.long_break_every.max(1);
+
+        CycleStatePayload {
+            active: self.active,
+            phase: self.phase,
+            phase_minutes: self.settings.phase_minutes(self.phase),
+            completed_focus_sessions: self.completed_focus,
+            cycle_position: self.completed_focus % every,
+            long_break_every: every,
+            awaiting_start: self.awaiting_start,
+            session_id: self.session_id.clone(),
+        }
+    }
+}
+
+/// Payload of the `cycle-state` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleStatePayload {
+    pub active: bool,
+    pub phase: CyclePhase,
+    pub phase_minutes: i32,
+    pub completed_focus_sessions: u32,
+    /// Completed focus sessions since the last long break
+    pub cycle_position: u32,
+    pub long_break_every: u32,
+    pub awaiting_start: bool,
+    pub session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartCycleRequest {
+    /// Focus profile used for every focus phase
+    #[serde(default)]
+    pub profile_id: Option<i64>,
+    #[serde(default)]
+    pub blocked_apps: Vec<String>,
+    #[serde(default)]
+    pub blocked_websites: Vec<String>,
+    #[serde(default)]
+    pub allowed_websites: Vec<String>,
+    #[serde(default)]
+    pub enable_dimming: bool,
+    #[serde(default = "default_dimming_opacity")]
+    pub dimming_opacity: f32,
+    #[serde(default)]
+    pub pause_notifications: bool,
+    /// Project, task and tags recorded on every focus phase
+    #[serde(default)]
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub task_title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+fn default_dimming_opacity() -> f32 {
+    0.7
+}
+
+/// Broadcast the cycle state and checkpoint it with the session
+async fn emit_cycle_state(state: &AppState) {
+    let payload = state.cycle_state.read().await.payload();
+    state.checkpoint_session().await;
+
+    if let Err(e) = state.app_handle.emit("cycle-state", &payload) {
+        tracing::warn!("Failed to emit cycle-state: {}", e);
+    }
+}
+
+/// Start the session for the cycle's current phase
+async fn start_phase(state: &AppState) -> Result<()> {
+    let request = state.cycle_state.read().await.phase_request();
+    let response = focus::start_session(request, state, state.app_handle.clone()).await?;
+
+    // The cycle schedules its own breaks
+    if let Some(sender) = state.break_reminder_cancellation.write().await.take() {
+        let _ = sender.send(());
+    }
+
+    {
+        let mut cycle = state.cycle_state.write().await;
+        cycle.session_id = Some(response.id);
+        cycle.awaiting_start = false;
+    }
+    emit_cycle_state(state).await;
+
+    Ok(())
+}
+
+/// End the cycle's current phase and move on to the next one
+///
+/// The next phase starts right away when `start_next` is set or its auto-start
+/// setting is on.
+async fn finish_phase(
+    state: &AppState,
+    completed: bool,
+    start_next: bool,
+) -> Result<focus::SessionResponse> {
+    let reason = (!completed).then_some("Skipped Pomodoro phase");
+    let ended = focus::end_session(completed, reason, state).await?;
+
+    let auto_start = {
+        let mut cycle = state.cycle_state.write().await;
+        cycle.advance(completed);
+        start_next || !cycle.awaiting_start
+    };
+
+    if auto_start {
+        start_phase(state).await?;
+    } else {
+        emit_cycle_state(state).await;
+    }
+
+    Ok(ended)
+}
+
+/// Stop the Pomodoro cycle without touching the running session
+pub(crate) async fn stop_cycle(state: &AppState) {
+    let was_active = {
+        let mut cycle = state.cycle_state.write().await;
+        std::mem::take(&mut *cycle).active
+    };
+
+    if was_active {
+        tracing::info!("Pomodoro cycle stopped");
+        emit_cycle_state(state).await;
+    }
+}
+
+/// Whether completing `session_id` is up to the cycle
+///
+/// True for the session running the current phase, and while a phase is
+/// being completed (claimed, with the next one not started yet).
+pub(crate) async fn owns_completion(state: &AppState, session_id: &str) -> bool {
+    let cycle = state.cycle_state.read().await;
+    cycle.active
+        && match cycle.session_id.as_deref() {
+            Some(id) => id == session_id,
+            None => !cycle.awaiting_start,
+        }
+}
+
+/// End the cycle's current phase as completed and move on to the next one
+///
+/// Both the timer loop and the frontend's `timer-completed` handler get here;
+/// the first call claims the phase and later ones are rejected.
+pub(crate) async fn complete_phase(
+    state: &AppState,
+    session_id: &str,
+) -> Result<focus::SessionResponse> {
+    {
+        let mut cycle = state.cycle_state.write().await;
+        if !cycle.active || cycle.session_id.as_deref() != Some(session_id) {
+            return Err(Error::InvalidSession(
+                "Pomodoro phase already ended".to_string(),
+            ));
+        }
+        cycle.session_id = None;
+    }
+
+    match finish_phase(state, true, false).await {
+        Ok(ended) => Ok(ended),
+        Err(e) => {
+            tracing::warn!("Pomodoro cycle could not move to the next phase: {}", e);
+            stop_cycle(state).await;
+            Err(e)
+        }
+    }
+}
+
+/// React to a session's timer running out (called by the timer loop)
+pub(crate) async fn on_timer_completed(state: &AppState, session_id: &str) {
+    if !owns_completion(state, session_id).await {
+        return;
+    }
+
+    if let Err(e) = complete_phase(state, session_id).await {
+        tracing::debug!("Pomodoro phase {} not completed by the timer: {}", session_id, e);
+    }
+}
+
+/// Pomodoro cycle settings
+#[tauri::command]
+pub async fn get_cycle_settings(state: State<'_, AppState>) -> Result<CycleSettings> {
+    load_settings(state.pool()).await
+}
+
+/// Change the Pomodoro cycle settings (applies to the next cycle)
+#[tauri::command]
+pub async fn set_cycle_settings(
+    settings: CycleSettings,
+    state: State<'_, AppState>,
+) -> Result<CycleSettings> {
+    save_settings(state.pool(), &settings).await?;
+    Ok(settings)
+}
+
+/// Current Pomodoro cycle state (for initial sync when a window opens)
+#[tauri::command]
+pub async fn get_cycle_state(state: State<'_, AppState>) -> Result<CycleStatePayload> {
+    Ok(state.cycle_state.read().await.payload())
+}
+
+/// Start a Pomodoro cycle with its first focus session
+#[tauri::command]
+pub async fn start_pomodoro_cycle(
+    request: StartCycleRequest,
+    state: State<'_, AppState>,
+) -> Result<CycleStatePayload> {
+    if state.cycle_state.read().await.active {
+        return Err(Error::InvalidSession("A Pomodoro cycle is already running".to_string()));
+    }
+
+    let settings = load_settings(state.pool()).await?;
+    let focus_request = StartSessionRequest {
+        planned_duration_minutes: settings.focus_minutes,
+        session_type: SessionType::Focus,
+        blocked_apps: request.blocked_apps,
+        blocked_websites: request.blocked_websites,
+        allowed_websites: request.allowed_websites,
+        enable_dimming: request.enable_dimming,
+        dimming_opacity: request.dimming_opacity,
+        pause_notifications: request.pause_notifications,
+        profile_id: request.profile_id,
+        project_id: request.project_id,
+        task_title: request.task_title,
+        tags: request.tags,
+    };
+
+    {
+        let mut cycle = state.cycle_state.write().await;
+        *cycle = CycleState {
+            active: true,
+            settings,
+            focus_request: Some(focus_request),
+            ..CycleState::default()
+        };
+    }
+
+    if let Err(e) = start_phase(&state).await {
+        *state.cycle_state.write().await = CycleState::default();
+        return Err(e);
+    }
+
+    tracing::info!("Pomodoro cycle started");
+
+    Ok(state.cycle_state.read().await.payload())
+}
+
+/// Start the phase the cycle is waiting on
+#[tauri::command]
+pub async fn start_next_cycle_phase(state: State<'_, AppState>) -> Result<CycleStatePayload> {
+    {
+        let cycle = state.cycle_state.read().await;
+        if !cycle.active || !cycle.awaiting_start {
+            return Err(Error::InvalidSession(
+                "No Pomodoro phase is waiting to start".to_string(),
+            ));
+        }
+    }
+
+    start_phase(&state).await?;
+
+    Ok(state.cycle_state.read().await.payload())
+}
+
+/// End the running phase early and go straight to the next one
+#[tauri::command]
+pub async fn skip_cycle_phase(state: State<'_, AppState>) -> Result<CycleStatePayload> {
+    {
+        let cycle = state.cycle_state.read().await;
+        if !cycle.active || cycle.session_id.is_none() {
+            return Err(Error::InvalidSession(
+                "No Pomodoro phase is running".to_string(),
+            ));
+        }
+    }
+
+    finish_phase(&state, false, true).await?;
+
+    Ok(state.cycle_state.read().await.payload())
+}
+
+/// Stop the Pomodoro cycle, ending its running session
+#[tauri::command]
+pub async fn stop_pomodoro_cycle(state: State<'_, AppState>) -> Result<()> {
+    let running_session = {
+        let cycle = state.cycle_state.read().await;
+        if !cycle.active {
+            return Err(Error::InvalidSession("No Pomodoro cycle is running".to_string()));
+        }
+        cycle.session_id.clone()
+    };
+
+    stop_cycle(&state).await;
+
+    let still_running = state
+        .active_session
+        .read()
+        .await
+        .as_ref()
+        .is_some_and(|session| Some(&session.id) == running_session.as_ref());
+    if still_running {
+        focus::end_session(false, Some("Stopped Pomodoro cycle"), &state).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(settings: CycleSettings) -> CycleState {
+        CycleState {
+            active: true,
+            settings,
+            ..CycleState::default()
+        }
+    }
+
+    #[test]
+    fn test_next_phase() {
+        assert_eq!(next_phase(CyclePhase::Focus, 1, 4), CyclePhase::ShortBreak);
+        assert_eq!(next_phase(CyclePhase::Focus, 4, 4), CyclePhase::LongBreak);
+        assert_eq!(next_phase(CyclePhase::Focus, 8, 4), CyclePhase::LongBreak);
+        assert_eq!(next_phase(CyclePhase::ShortBreak, 1, 4), CyclePhase::Focus);
+        assert_eq!(next_phase(CyclePhase::LongBreak, 4, 4), CyclePhase::Focus);
+        assert_eq!(next_phase(CyclePhase::Focus, 1, 1), CyclePhase::LongBreak);
+    }
+
+    #[test]
+    fn test_full_cycle() {
+        let mut state = cycle(CycleSettings {
+            long_break_every: 2,
+            ..CycleSettings::default()
+        });
+
+        let mut phases = Vec::new();
+        for _ in 0..5 {
+            state.advance(true);
+            phases.push(state.phase);
+        }
+
+        assert_eq!(
+            phases,
+            vec![
+                CyclePhase::ShortBreak,
+                CyclePhase::Focus,
+                CyclePhase::LongBreak,
+                CyclePhase::Focus,
+                CyclePhase::ShortBreak,
+            ]
+        );
+        assert_eq!(state.completed_focus, 3);
+        assert_eq!(state.payload().cycle_position, 1);
+    }
+
+    #[test]
+    fn test_auto_start() {
+        let mut state = cycle(CycleSettings::default());
+
+        state.advance(true);
+        assert_eq!(state.phase, CyclePhase::ShortBreak);
+        assert!(!state.awaiting_start);
+
+        state.advance(true);
+        assert_eq!(state.phase, CyclePhase::Focus);
+        assert!(state.awaiting_start);
+    }
+
+    #[test]
+    fn test_skipped_focus_does_not_count() {
+        let mut state = cycle(CycleSettings {
+            long_break_every: 1,
+            ..CycleSettings::default()
+        });
+
+        state.advance(false);
+        assert_eq!(state.phase, CyclePhase::ShortBreak);
+        assert_eq!(state.completed_focus, 0);
+    }
+
+    #[test]
+    fn test_phase_request() {
+        let mut state = cycle(CycleSettings::default());
+        state.focus_request = Some(StartSessionRequest {
+            blocked_apps: vec!["slack".to_string()],
+            ..StartSessionRequest::for_profile(3)
+        });
+
+        let focus = state.phase_request();
+        assert_eq!(focus.planned_duration_minutes, 25);
+        assert_eq!(focus.profile_id, Some(3));
+        assert_eq!(focus.blocked_apps, vec!["slack"]);
+
+        state.advance(true);
+        let rest = state.phase_request();
+        assert!(matches!(rest.session_type, SessionType::Break));
+        assert_eq!(rest.planned_duration_minutes, 5);
+        assert!(rest.blocked_apps.is_empty());
+        assert_eq!(rest.profile_id, None);
+    }
+
+    #[test]
+    fn test_restored_from_checkpoint() {
+        let mut state = cycle(CycleSettings::default());
+        state.session_id = Some("focus-1".to_string());
+
+        let json = serde_json::to_string(&state).unwrap();
+        let checkpointed: CycleState = serde_json::from_str(&json).unwrap();
+
+        // The resumed session keeps running the current phase
+        let resumed = checkpointed.clone().restored(Some("focus-1"), None);
+        assert!(resumed.active);
+        assert_eq!(resumed.session_id.as_deref(), Some("focus-1"));
+
+        // An expired phase moves on and waits for the user
+        let expired = checkpointed.clone().restored(None, Some("focus-1"));
+        assert!(expired.active);
+        assert_eq!(expired.phase, CyclePhase::ShortBreak);
+        assert!(expired.awaiting_start);
+        assert_eq!(expired.session_id, None);
+
+        // Any other session isn't part of the cycle
+        assert!(!checkpointed.restored(Some("other"), None).active);
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        assert!(CycleSettings::default().validate().is_ok());
+        assert!(CycleSettings {
+            focus_minutes: 0,
+            ..CycleSettings::default()
+        }
+        .validate()
+        .is_err());
+        assert!(CycleSettings {
+            long_break_every: 0,
+            ..CycleSettings::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
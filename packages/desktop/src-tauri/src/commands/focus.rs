@@ -1,10 +1,20 @@
 // commands/focus.rs - Focus session management commands
 
 use crate::{
-    blocking::hosts,
-    commands::timer,
-    db::queries::{self, Session},
-    state::{ActiveSession, AppState, SessionType, TimerState},
+    blocking::{
+        blocklist, hosts,
+        nuclear::{self, MonotonicStamp},
+        rules,
+    },
+    commands::{
+        cycle,
+        profiles,
+        projects::SessionMetadata,
+        session_events::{self, SessionEventType},
+        timer,
+    },
+    db::queries::{self, FocusProfile, Session, SessionFilter},
+    state::{ActiveSession, AppState, SessionCheckpoint, SessionType, StrictModeState, TimerState},
     system::notifications::NotificationManager,
     system::tray::{update_tray_icon, TrayIconState},
     Error, Result,
@@ -12,13 +22,20 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager, State};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartSessionRequest {
+    /// Falls back to the profile's duration when 0 and a profile is given
+    #[serde(default)]
     pub planned_duration_minutes: i32,
     pub session_type: SessionType,
+    #[serde(default)]
     pub blocked_apps: Vec<String>,
+    #[serde(default)]
     pub blocked_websites: Vec<String>,
+    /// Only these websites resolve during the session (allow-list mode)
+    #[serde(default)]
+    pub allowed_websites: Vec<String>,
     /// Enable screen dimming overlay during focus mode
     #[serde(default)]
     pub enable_dimming: bool,
@@ -28,12 +45,79 @@ pub struct StartSessionRequest {
     /// Pause system notifications during focus mode
     #[serde(default)]
     pub pause_notifications: bool,
+    /// Focus profile whose settings are merged into this request
+    #[serde(default)]
+    pub profile_id: Option<i64>,
+    /// Project the session's focus time counts towards
+    #[serde(default)]
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub task_title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 fn default_dimming_opacity() -> f32 {
     0.7
 }
 
+impl StartSessionRequest {
+    /// A session with nothing blocked and no extras
+    pub fn new(planned_duration_minutes: i32, session_type: SessionType) -> Self {
+        Self {
+            planned_duration_minutes,
+            session_type,
+            blocked_apps: Vec::new(),
+            blocked_websites: Vec::new(),
+            allowed_websites: Vec::new(),
+            enable_dimming: false,
+            dimming_opacity: default_dimming_opacity(),
+            pause_notifications: false,
+            profile_id: None,
+            project_id: None,
+            task_title: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// A focus session using only the settings of a profile
+    pub fn for_profile(profile_id: i64) -> Self {
+        Self {
+            profile_id: Some(profile_id),
+            ..Self::new(0, SessionType::Focus)
+        }
+    }
+
+    /// Merge a profile into the request
+    ///
+    /// An explicit duration wins; lists are combined and dimming/DND are
+    /// enabled if either side asks for them.
+    fn apply_profile(&mut self, profile: &FocusProfile) {
+        if self.planned_duration_minutes <= 0 {
+            self.planned_duration_minutes = profile.planned_duration_minutes;
+        }
+
+        let merge = |items: &mut Vec<String>, extra: &[String]| {
+            for item in extra {
+                if !items.iter().any(|existing| existing.eq_ignore_ascii_case(item)) {
+                    items.push(item.clone());
+                }
+            }
+        };
+        merge(&mut self.blocked_apps, &profile.blocked_apps);
+        merge(&mut self.blocked_websites, &profile.blocked_websites);
+        merge(&mut self.allowed_websites, &profile.allowed_websites);
+
+        if profile.enable_dimming {
+            if !self.enable_dimming {
+                self.dimming_opacity = profile.dimming_opacity;
+            }
+            self.enable_dimming = true;
+        }
+        self.pause_notifications |= profile.pause_notifications;
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionResponse {
@@ -61,6 +145,24 @@ pub async fn start_focus_session(
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<SessionResponse> {
+    start_session(request, &state, app_handle).await
+}
+
+/// Start a focus session outside of a command (tray menu, schedules)
+pub(crate) async fn start_session(
+    mut request: StartSessionRequest,
+    state: &AppState,
+    app_handle: tauri::AppHandle,
+) -> Result<SessionResponse> {
+    // Merge the profile's settings, remembering which version was used
+    let profile = match request.profile_id {
+        Some(profile_id) => Some(profiles::load_profile(state.pool(), profile_id).await?),
+        None => None,
+    };
+    if let Some(profile) = &profile {
+        request.apply_profile(profile);
+    }
+
     // Validate duration
     if request.planned_duration_minutes <= 0 {
         return Err(Error::Validation("Duration must be positive".into()));
@@ -74,6 +176,15 @@ pub async fn start_focus_session(
 
     // Validate and sanitize blocked websites (prevent injection attacks)
     validate_blocked_websites(&request.blocked_websites)?;
+    validate_blocked_websites(&request.allowed_websites)?;
+
+    let metadata = SessionMetadata {
+        project_id: request.project_id,
+        task_title: request.task_title.clone(),
+        tags: request.tags.clone(),
+    }
+    .validated(state.pool())
+    .await?;
 
     // SECURITY FIX: Hold write lock during entire check-and-set operation
     // This prevents TOCTOU race condition where multiple sessions could be started simultaneously
@@ -112,12 +223,16 @@ pub async fn start_focus_session(
     };
 
     // Create new session
-    let session = ActiveSession::new(
+    let mut session = ActiveSession::new(
         request.planned_duration_minutes,
         request.session_type.clone(),
         request.blocked_apps.clone(),
         request.blocked_websites.clone(),
     );
+    session.allowed_websites = request.allowed_websites.clone();
+    session.project_id = metadata.project_id;
+    session.task_title = metadata.task_title.clone();
+    session.tags = metadata.tags.clone();
 
     // Insert into database
     queries::insert_session(
@@ -133,6 +248,14 @@ pub async fn start_focus_session(
     )
     .await?;
 
+    if !metadata.is_empty() {
+        metadata.save(state.pool(), &session.id).await?;
+    }
+
+    if let Some(profile) = &profile {
+        queries::set_session_profile(state.pool(), &session.id, profile.id, profile.version).await?;
+    }
+
     // Batch insert all blocked items in a single query to avoid N+1 queries
     let mut blocked_items = Vec::new();
     for app in &request.blocked_apps {
@@ -143,18 +266,30 @@ pub async fn start_focus_session(
     }
     if !blocked_items.is_empty() {
         queries::insert_blocked_items_batch(state.pool(), blocked_items).await?;
+        blocklist::invalidate();
     }
 
+    // Breaks relax blocking; anything already enforcing it (nuclear option,
+    // schedules) is left alone
+    let relax_blocking = matches!(request.session_type, SessionType::Break);
+
+    // Session websites plus everything else on the effective blocklist
+    let blocked_websites = if relax_blocking {
+        Vec::new()
+    } else {
+        blocklist::with_session_websites(state, &request.blocked_websites).await?
+    };
+
     // Enable blocking and update state
-    {
+    if !relax_blocking {
         let mut blocking = state.blocking_state.write().await;
         blocking.enable();
-        blocking.update_blocked_websites(request.blocked_websites.clone());
+        blocking.update_blocked_websites(blocked_websites.clone());
     }
 
     // Update hosts file with blocked websites (may fail without privileges)
-    if !request.blocked_websites.is_empty() {
-        if let Err(e) = hosts::update_hosts_file(&request.blocked_websites).await {
+    if !blocked_websites.is_empty() {
+        if let Err(e) = hosts::update_hosts_file(state, &blocked_websites).await {
             tracing::warn!("Failed to update hosts file: {}, DNS fallback active", e);
             // Don't fail the session start if hosts file update fails
             // DNS fallback will still work for frontend-based blocking
@@ -182,19 +317,22 @@ pub async fn start_focus_session(
 
     // Start the backend timer broadcast loop and store cancellation sender
     {
-        let cancel_tx = timer::start_timer_loop(state.app_handle.clone(), (*state).clone());
+        let cancel_tx = timer::start_timer_loop(state.app_handle.clone(), state.clone());
         let mut timer_cancellation = state.timer_cancellation.write().await;
         *timer_cancellation = Some(cancel_tx);
     }
 
+    // Persist the session so it survives a crash or restart
+    state.checkpoint_session().await;
+
     // Enable screen dimming if requested
     if request.enable_dimming {
-        let dimming_state = (*state).clone();
+        let dimming_state = state.clone();
         let dimming_handle = app_handle.clone();
         let opacity = request.dimming_opacity;
         let session_id = response.id.clone();
         tauri::async_runtime::spawn(async move {
-            if let Err(e) = super::dimming::enable_dimming_internal(
+            if let Err(e) = crate::system::dimming::enable_dimming_internal(
                 &dimming_state,
                 &dimming_handle,
                 opacity,
@@ -208,11 +346,11 @@ pub async fn start_focus_session(
 
     // Pause notifications if requested
     if request.pause_notifications {
-        let notification_state = (*state).clone();
+        let notification_state = state.clone();
         let notification_handle = app_handle.clone();
         let session_id = response.id.clone();
         tauri::async_runtime::spawn(async move {
-            match super::notification_control::pause_notifications_internal(
+            match crate::system::notification_control::pause_notifications_internal(
                 &notification_state,
                 &notification_handle,
                 Some(session_id),
@@ -293,10 +431,45 @@ pub async fn start_focus_session(
 }
 
 /// End the current focus session
+///
+/// `session_id` names the session the caller means to end, so a late
+/// `timer-completed` can't end the session that replaced it.
 #[tauri::command]
 pub async fn end_focus_session(
     completed: bool,
+    reason: Option<String>,
+    session_id: Option<String>,
     state: State<'_, AppState>,
+) -> Result<SessionResponse> {
+    let reason = session_events::normalize_reason(reason)?;
+
+    let active_id = state.active_session.read().await.as_ref().map(|s| s.id.clone());
+    if session_id.is_some() && session_id != active_id {
+        return Err(Error::InvalidSession("Session already ended".to_string()));
+    }
+
+    // A Pomodoro phase that ran its course is completed by the cycle, which
+    // moves on to the next phase
+    if let Some(id) = active_id.filter(|_| completed) {
+        if cycle::owns_completion(&state, &id).await {
+            return cycle::complete_phase(&state, &id).await;
+        }
+    }
+
+    // Ending a session early also ends any Pomodoro cycle it belongs to
+    cycle::stop_cycle(&state).await;
+
+    end_session(completed, reason.as_deref(), &state).await
+}
+
+/// End the current session outside of a command (Pomodoro phase changes)
+///
+/// Sessions abandoned or ended before their planned time are logged as ending
+/// early, with the optional reason.
+pub(crate) async fn end_session(
+    completed: bool,
+    reason: Option<&str>,
+    state: &AppState,
 ) -> Result<SessionResponse> {
     let session = {
         let mut active = state.active_session.write().await;
@@ -305,6 +478,21 @@ pub async fn end_focus_session(
         })?
     };
 
+    // Effective focused time, read before the timer state is reset
+    let timer_snapshot = state.timer_state.read().await.clone();
+    let elapsed_seconds = timer_snapshot.calculate_elapsed(session.start_time);
+    let remaining_seconds = session.planned_duration_minutes as i64 * 60 - elapsed_seconds;
+    if !completed || remaining_seconds > 0 {
+        session_events::record(
+            state.pool(),
+            &session.id,
+            SessionEventType::EndEarly,
+            reason,
+            Some((remaining_seconds.max(0) / 60) as i32),
+        )
+        .await;
+    }
+
     // Stop the timer loop and reset timer state
     {
         let mut timer_state = state.timer_state.write().await;
@@ -313,7 +501,7 @@ pub async fn end_focus_session(
     }
 
     // Cancel the timer broadcast loop
-    timer::stop_timer_loop(&state).await;
+    timer::stop_timer_loop(state).await;
 
     // Cancel break reminder loop if it was started
     {
@@ -326,28 +514,55 @@ pub async fn end_focus_session(
 
     let end_time = chrono::Utc::now();
 
+    // Time spent away from the machine doesn't count towards the session
+    let idle_seconds = timer_snapshot.idle_seconds_at(end_time);
+    let duration = ((end_time - session.start_time).num_seconds() - idle_seconds).max(0);
+
     // Update database
-    queries::end_session(state.pool(), &session.id, end_time, completed).await?;
+    queries::end_session_with_duration(state.pool(), &session.id, end_time, duration, completed).await?;
 
-    // Disable blocking and clear state
+    if let Err(e) =
+        session_events::finalize(state.pool(), &session.id, session.planned_duration_minutes, end_time).await
     {
-        let mut blocking = state.blocking_state.write().await;
-        blocking.disable();
-        blocking.update_blocked_websites(Vec::new());
+        tracing::warn!("Failed to score interruptions of session {}: {}", session.id, e);
     }
 
-    // Clear hosts file to remove website blocking
-    if let Err(e) = hosts::clear_hosts_file().await {
-        tracing::warn!("Failed to clear hosts file: {}", e);
-        // Don't fail the session end if hosts file clearing fails
+    // A running nuclear option keeps blocking in force past the session
+    let nuclear_active = state.nuclear_option_state.read().await.active;
+
+    if !nuclear_active {
+        // An open schedule window keeps blocking; it releases it when it closes
+        let schedule_open = {
+            let mut blocking = state.blocking_state.write().await;
+            let schedule_open = blocking.schedule_id.is_some();
+            if schedule_open {
+                blocking.schedule_owns_blocking = true;
+            } else {
+                blocking.disable();
+                blocking.update_blocked_websites(Vec::new());
+            }
+            schedule_open
+        };
+
+        if schedule_open {
+            if let Err(e) = blocklist::refresh_website_blocking(state).await {
+                tracing::warn!("Failed to hand website blocking back to the schedule: {}", e);
+            }
+        } else if let Err(e) = hosts::clear_hosts_file(state).await {
+            // Don't fail the session end if hosts file clearing fails
+            tracing::warn!("Failed to clear hosts file: {}", e);
+        }
     }
 
+    // Drop the persisted session so it isn't resumed on next launch
+    state.checkpoint_session().await;
+
     // Disable screen dimming if it was enabled
     {
         let dimming_state = state.dimming_state.read().await;
         if dimming_state.enabled {
             drop(dimming_state);
-            if let Err(e) = super::dimming::force_disable_dimming(&state, &state.app_handle).await {
+            if let Err(e) = crate::system::dimming::force_disable_dimming(state, &state.app_handle).await {
                 tracing::warn!("Failed to disable screen dimming: {}", e);
             }
         }
@@ -359,7 +574,7 @@ pub async fn end_focus_session(
         if notification_state.paused {
             let was_system_dnd_active = notification_state.system_dnd_enabled;
             drop(notification_state);
-            match super::notification_control::force_resume_notifications(&state, &state.app_handle).await {
+            match crate::system::notification_control::force_resume_notifications(state, &state.app_handle).await {
                 Ok(Some(result)) if !result.success && was_system_dnd_active => {
                     // Only warn if we had actually enabled DND and failed to disable it
                     tracing::warn!(
@@ -387,7 +602,6 @@ pub async fn end_focus_session(
 
     // Update analytics
     let date = end_time.format("%Y-%m-%d").to_string();
-    let duration = session.elapsed_seconds();
 
     let (focus_seconds, break_seconds) = match session.session_type {
         SessionType::Focus => (duration, 0),
@@ -408,7 +622,7 @@ pub async fn end_focus_session(
     // Check achievements if session was completed
     // Note: Running inline rather than spawned to avoid state lifetime issues
     if completed {
-        if let Err(e) = super::achievements::check_achievements(session.id.clone(), state.clone()).await {
+        if let Err(e) = super::achievements::check_achievements(session.id.clone(), state.app_handle.state::<AppState>()).await {
             tracing::warn!("Failed to check achievements: {}", e);
         }
     }
@@ -434,6 +648,204 @@ pub async fn end_focus_session(
     })
 }
 
+/// Restore a checkpointed session after an app restart or crash
+///
+/// Called from `AppState::new`. A session with time remaining is resumed: state
+/// is rehydrated, blocking and hosts entries are re-applied and the timer loop is
+/// restarted. A session whose planned duration ran out while the app was down is
+/// ended as abandoned, crediting only the focus time up to the last checkpoint.
+/// Stale hosts entries are removed when nothing needs them anymore.
+pub async fn restore_checkpointed_session(state: &AppState) -> Result<()> {
+    let row = queries::load_session_checkpoint(state.pool()).await?;
+
+    let (checkpoint, checkpointed_at) = match row {
+        Some(row) => match serde_json::from_str::<SessionCheckpoint>(&row.data) {
+            Ok(checkpoint) => (checkpoint, row.updated_at),
+            Err(e) => {
+                tracing::warn!("Discarding unreadable session checkpoint: {}", e);
+                (SessionCheckpoint::default(), row.updated_at)
+            }
+        },
+        None => (SessionCheckpoint::default(), chrono::Utc::now()),
+    };
+
+    let now = chrono::Utc::now();
+
+    // Nuclear option keeps counting down across restarts; the monitoring loop re-applies its blocking
+    let mut nuclear_option = checkpoint.nuclear_option.clone();
+    let nuclear_outcome = nuclear_option.advance(now, MonotonicStamp::now());
+    let nuclear_active = nuclear_option.active;
+    if nuclear_active {
+        tracing::info!(
+            "Restored nuclear option ({}s remaining)",
+            nuclear_option.remaining_seconds()
+        );
+        let mut nuclear_state = state.nuclear_option_state.write().await;
+        *nuclear_state = nuclear_option;
+    }
+    if let Some(skew_seconds) = nuclear_outcome.clock_skew_seconds {
+        nuclear::record_clock_tamper(state, skew_seconds).await;
+    }
+
+    let mut resumed = None;
+    let mut expired = None;
+
+    if let Some(session) = checkpoint.active_session {
+        let already_ended = queries::get_session(state.pool(), &session.id)
+            .await?
+            .map_or(true, |s| s.end_time.is_some());
+
+        let timer_state = checkpoint.timer_state;
+        let planned_seconds = session.planned_duration_minutes as i64 * 60;
+
+        if already_ended {
+            tracing::info!("Checkpointed session {} already ended, discarding", session.id);
+        } else if timer_state.elapsed_at(session.start_time, now) >= planned_seconds {
+            reconcile_expired_session(state, &session, &timer_state, checkpointed_at).await?;
+            expired = Some(session.id);
+        } else {
+            resumed = Some(session.id.clone());
+            resume_session(state, session, timer_state, checkpoint.strict_mode).await;
+        }
+    }
+
+    let cycle = checkpoint.cycle.restored(resumed.as_deref(), expired.as_deref());
+    if cycle.active {
+        tracing::info!("Restored Pomodoro cycle ({:?} phase)", cycle.phase);
+        let mut cycle_state = state.cycle_state.write().await;
+        *cycle_state = cycle;
+    }
+
+    // Remove hosts entries left behind by a crash
+    if resumed.is_none() && !nuclear_active {
+        match hosts::has_focusflow_entries().await {
+            Ok(true) => {
+                if let Err(e) = hosts::clear_hosts_file(state).await {
+                    tracing::warn!("Failed to clear stale hosts entries: {}", e);
+                } else {
+                    tracing::info!("Cleared stale hosts entries from previous run");
+                }
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to check hosts file for stale entries: {}", e),
+        }
+    }
+
+    state.checkpoint_session().await;
+
+    Ok(())
+}
+
+/// Rehydrate an interrupted session that still has time remaining
+async fn resume_session(
+    state: &AppState,
+    session: ActiveSession,
+    timer_state: TimerState,
+    strict_mode: StrictModeState,
+) {
+    let session_id = session.id.clone();
+    // Breaks relax blocking, as in `start_session`
+    let relax_blocking = matches!(session.session_type, SessionType::Break);
+    let blocked_websites = if relax_blocking {
+        Vec::new()
+    } else {
+        blocklist::with_session_websites(state, &session.blocked_websites)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load the blocklist: {}", e);
+                session.blocked_websites.clone()
+            })
+    };
+
+    {
+        let mut active = state.active_session.write().await;
+        *active = Some(session);
+    }
+
+    {
+        let mut timer = state.timer_state.write().await;
+        *timer = TimerState {
+            is_running: true,
+            ..timer_state
+        };
+    }
+
+    if strict_mode.session_id.as_deref() == Some(session_id.as_str()) {
+        let mut strict = state.strict_mode_state.write().await;
+        *strict = strict_mode;
+    }
+
+    // Re-enable blocking; the monitoring loop picks this up on its next tick
+    if !relax_blocking {
+        {
+            let mut blocking = state.blocking_state.write().await;
+            blocking.enable();
+            blocking.update_blocked_websites(blocked_websites.clone());
+        }
+
+        if let Err(e) = hosts::update_hosts_file(state, &blocked_websites).await {
+            tracing::warn!("Failed to re-apply hosts file: {}, DNS fallback active", e);
+        }
+    }
+
+    {
+        let cancel_tx = timer::start_timer_loop(state.app_handle.clone(), state.clone());
+        let mut timer_cancellation = state.timer_cancellation.write().await;
+        *timer_cancellation = Some(cancel_tx);
+    }
+
+    tracing::info!("Resumed focus session {} after restart", session_id);
+}
+
+/// End a session that expired while the app wasn't running
+///
+/// The session is recorded as abandoned with the focus time known to have
+/// elapsed by the last checkpoint, capped at the planned duration.
+async fn reconcile_expired_session(
+    state: &AppState,
+    session: &ActiveSession,
+    timer_state: &TimerState,
+    checkpointed_at: chrono::DateTime<chrono::Utc>,
+) -> Result<()> {
+    let planned_seconds = session.planned_duration_minutes as i64 * 60;
+    let duration = timer_state
+        .elapsed_at(session.start_time, checkpointed_at)
+        .clamp(0, planned_seconds);
+
+    queries::end_session_with_duration(state.pool(), &session.id, checkpointed_at, duration, false)
+        .await?;
+    if let Err(e) =
+        session_events::finalize(state.pool(), &session.id, session.planned_duration_minutes, checkpointed_at)
+            .await
+    {
+        tracing::warn!("Failed to score interruptions of session {}: {}", session.id, e);
+    }
+
+    let (focus_seconds, break_seconds) = match session.session_type {
+        SessionType::Focus => (duration, 0),
+        SessionType::Break => (0, duration),
+        SessionType::Custom => (duration, 0),
+    };
+
+    queries::upsert_daily_analytics(
+        state.pool(),
+        &checkpointed_at.format("%Y-%m-%d").to_string(),
+        focus_seconds,
+        break_seconds,
+        0,
+        1,
+    )
+    .await?;
+
+    tracing::info!(
+        "Session {} expired while app was closed, recorded as abandoned ({}s)",
+        session.id,
+        duration
+    );
+
+    Ok(())
+}
+
 /// Get the currently active session if any
 #[tauri::command]
 pub async fn get_active_session(
@@ -444,15 +856,20 @@ pub async fn get_active_session(
 }
 
 /// Get session history for a date range
+///
+/// Optionally limited to one project and/or tag.
 #[tauri::command]
 pub async fn get_session_history(
     days: i64,
+    project_id: Option<i64>,
+    tag: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<Vec<Session>> {
     let end = chrono::Utc::now();
     let start = end - chrono::Duration::days(days);
 
-    let sessions = queries::get_sessions_in_range(state.pool(), start, end).await?;
+    let filter = SessionFilter { project_id, tag };
+    let sessions = queries::get_sessions_filtered(state.pool(), start, end, &filter).await?;
 
     Ok(sessions)
 }
@@ -489,9 +906,12 @@ pub async fn toggle_session_pause(
 #[tauri::command]
 pub async fn extend_session(
     additional_minutes: i32,
+    reason: Option<String>,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<i32> {
+    let reason = session_events::normalize_reason(reason)?;
+
     // Validate input
     if additional_minutes <= 0 {
         return Err(Error::InvalidSession(
@@ -528,6 +948,17 @@ pub async fn extend_session(
         );
     }
 
+    state.checkpoint_session().await;
+
+    session_events::record(
+        state.pool(),
+        &session_id,
+        SessionEventType::Extend,
+        reason.as_deref(),
+        Some(additional_minutes),
+    )
+    .await;
+
     // Broadcast to ALL windows using app-level emit
     if let Err(e) = app_handle.emit(
         "session-extended",
@@ -602,7 +1033,7 @@ pub async fn get_todays_session_count(
 /// - Null bytes (injection attacks)
 /// - Path traversal attempts
 /// - Shell metacharacters
-fn validate_blocked_apps(apps: &[String]) -> Result<()> {
+pub(crate) fn validate_blocked_apps(apps: &[String]) -> Result<()> {
     for app in apps {
         let trimmed = app.trim();
 
@@ -648,71 +1079,56 @@ fn validate_blocked_apps(apps: &[String]) -> Result<()> {
 
 /// Validate blocked websites list for security
 ///
-/// Prevents:
+/// Each entry must be a valid website rule (see `blocking::rules`), which rejects:
 /// - Empty strings
 /// - Null bytes (injection attacks)
-/// - Invalid domain formats
-/// - Path traversal attempts
-fn validate_blocked_websites(websites: &[String]) -> Result<()> {
+/// - Invalid domain formats and regex patterns
+pub(crate) fn validate_blocked_websites(websites: &[String]) -> Result<()> {
     for website in websites {
-        let domain = website.trim().to_lowercase();
-
-        // Reject empty strings
-        if domain.is_empty() {
-            return Err(Error::InvalidInput(
-                "Domain cannot be empty".to_string(),
-            ));
-        }
-
-        // Reject null bytes (prevent injection)
-        if domain.contains('\0') {
-            return Err(Error::InvalidInput(
-                "Domain contains invalid characters".to_string(),
-            ));
-        }
-
-        // Check domain length (max 253 chars per DNS spec)
-        if domain.len() > 253 {
-            return Err(Error::InvalidInput(
-                "Domain name is too long (max 253 characters)".to_string(),
-            ));
-        }
-
-        // Require at least one dot (e.g., "example.com")
-        if !domain.contains('.') {
-            return Err(Error::InvalidInput(
-                "Invalid domain format. Domain must contain at least one dot (e.g., 'example.com')".to_string(),
-            ));
-        }
-
-        // Reject protocol or path characters
-        if domain.contains('/') || domain.contains(':') {
-            return Err(Error::InvalidInput(
-                "Invalid domain format. Use 'example.com' without protocol or path".to_string(),
-            ));
-        }
-
-        // Only allow alphanumeric, hyphens, and dots
-        if !domain.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.') {
-            return Err(Error::InvalidInput(
-                "Domain contains invalid characters. Only letters, numbers, hyphens, and dots are allowed".to_string(),
-            ));
-        }
-
-        // Reject domains starting or ending with hyphen or dot
-        if domain.starts_with('.') || domain.ends_with('.') || domain.starts_with('-') || domain.ends_with('-') {
-            return Err(Error::InvalidInput(
-                "Domain cannot start or end with a dot or hyphen".to_string(),
-            ));
-        }
-
-        // Reject consecutive dots (invalid DNS)
-        if domain.contains("..") {
-            return Err(Error::InvalidInput(
-                "Domain cannot contain consecutive dots".to_string(),
-            ));
-        }
+        rules::WebsiteRule::parse(website)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_profile_merges_settings() {
+        let profile = FocusProfile {
+            id: 1,
+            version: 2,
+            name: "Deep Work".to_string(),
+            planned_duration_minutes: 90,
+            blocked_apps: vec!["Slack".to_string()],
+            blocked_websites: vec!["twitter.com".to_string()],
+            allowed_websites: vec![],
+            enable_dimming: true,
+            dimming_opacity: 0.4,
+            pause_notifications: true,
+        };
+
+        let mut request = StartSessionRequest::for_profile(1);
+        request.blocked_apps = vec!["slack".to_string(), "discord".to_string()];
+        request.apply_profile(&profile);
+
+        assert_eq!(request.planned_duration_minutes, 90);
+        assert_eq!(request.blocked_apps, vec!["slack", "discord"]);
+        assert_eq!(request.blocked_websites, vec!["twitter.com"]);
+        assert!(request.enable_dimming);
+        assert_eq!(request.dimming_opacity, 0.4);
+        assert!(request.pause_notifications);
+
+        // An explicit duration and dimming choice win over the profile
+        let mut request = StartSessionRequest::for_profile(1);
+        request.planned_duration_minutes = 25;
+        request.enable_dimming = true;
+        request.dimming_opacity = 0.9;
+        request.apply_profile(&profile);
+
+        assert_eq!(request.planned_duration_minutes, 25);
+        assert_eq!(request.dimming_opacity, 0.9);
+    }
+}
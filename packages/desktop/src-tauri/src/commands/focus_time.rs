@@ -87,6 +87,17 @@ pub async fn get_allowed_apps(
     Ok(focus_state.allowed_apps.clone())
 }
 
+/// Get the list of websites allowed during the current Focus Time
+///
+/// Empty when the Focus Time has no web allow-list.
+#[tauri::command]
+pub async fn get_allowed_sites(
+    state: State<'_, AppState>,
+) -> Result<Vec<String>> {
+    let focus_state = state.focus_time_state.read().await;
+    Ok(focus_state.allowed_sites.clone())
+}
+
 /// Request for overriding Focus Time apps
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
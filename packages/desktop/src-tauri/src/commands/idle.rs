@@ -0,0 +1,523 @@
+// commands/idle.rs - Auto-pause on idle and screen lock
+//
+// While a session runs, the idle monitor is polled every few seconds. When the
+// user has been idle for the configured number of minutes, or locks the
+// screen, the timer is paused as of the moment they were last active so time
+// away never counts as focus time. When they come back the frontend is
+// prompted to resume (or the session resumes on its own, if configured).
+
+use crate::{
+    commands::session_events::{self, SessionEventType},
+    db::queries,
+    state::{AppState, SessionType, TimerState},
+    system::{
+        idle::{IdleMonitor, IdleSample},
+        tray::{update_tray_icon, TrayIconState},
+    },
+    Error, Result,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{Emitter, State};
+use tokio::time::{interval, Duration};
+
+pub const IDLE_SETTINGS_KEY: &str = "idle_detection";
+
+/// How often the idle monitor is polled while a session runs
+const IDLE_POLL_INTERVAL_SECS: u64 = 15;
+
+/// Idle detection settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IdleSettings {
+    pub enabled: bool,
+    /// Pause after this many minutes without input
+    pub auto_pause_after_minutes: u32,
+    /// Pause as soon as the screen is locked
+    pub pause_on_lock: bool,
+    /// Resume without asking when the user comes back
+    pub auto_resume: bool,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auto_pause_after_minutes: 5,
+            pause_on_lock: true,
+            auto_resume: false,
+        }
+    }
+}
+
+impl IdleSettings {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=120).contains(&self.auto_pause_after_minutes) {
+            return Err(Error::Validation(
+                "Idle timeout must be between 1 and 120 minutes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Load the idle settings, falling back to defaults
+pub async fn load_settings(pool: &SqlitePool) -> Result<IdleSettings> {
+    let stored = queries::get_user_setting(pool, IDLE_SETTINGS_KEY).await?;
+
+    Ok(match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid idle settings: {}", e);
+            IdleSettings::default()
+        }),
+        None => IdleSettings::default(),
+    })
+}
+
+/// Persist the idle settings
+pub async fn save_settings(pool: &SqlitePool, settings: &IdleSettings) -> Result<()> {
+    settings.validate()?;
+
+    let json = serde_json::to_string(settings)?;
+    queries::set_user_setting(pool, IDLE_SETTINGS_KEY, &json).await
+}
+
+/// Why a session was auto-paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IdleReason {
+    Idle,
+    Locked,
+}
+
+impl IdleReason {
+    fn label(self) -> &'static str {
+        match self {
+            IdleReason::Idle => "Away from computer",
+            IdleReason::Locked => "Screen locked",
+        }
+    }
+}
+
+/// What to do with the running session after a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleAction {
+    None,
+    /// Pause the timer as of `since`
+    Pause {
+        since: DateTime<Utc>,
+        reason: IdleReason,
+    },
+    /// The user is back from an idle pause
+    Returned,
+}
+
+/// Decide how a sample affects the running session
+///
+/// Manual pauses are left alone; only pauses started here end in `Returned`.
+pub fn decide(
+    settings: &IdleSettings,
+    sample: &IdleSample,
+    timer: &TimerState,
+    session_start: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> IdleAction {
+    if !settings.enabled || !timer.is_running {
+        return IdleAction::None;
+    }
+
+    if timer.is_paused {
+        return if timer.idle_paused && !sample.idle && !sample.locked {
+            IdleAction::Returned
+        } else {
+            IdleAction::None
+        };
+    }
+
+    let last_active = sample
+        .idle_since
+        .filter(|_| sample.idle)
+        .unwrap_or(now)
+        .max(session_start);
+
+    if sample.locked && settings.pause_on_lock {
+        return IdleAction::Pause {
+            since: last_active,
+            reason: IdleReason::Locked,
+        };
+    }
+
+    let timeout = chrono::Duration::minutes(settings.auto_pause_after_minutes as i64);
+    if sample.idle && now - last_active >= timeout {
+        return IdleAction::Pause {
+            since: last_active,
+            reason: IdleReason::Idle,
+        };
+    }
+
+    IdleAction::None
+}
+
+/// What a poll did to the running session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleTransition {
+    /// The timer was paused as of `since`
+    Paused {
+        since: DateTime<Utc>,
+        reason: IdleReason,
+    },
+    /// The user came back after `away_seconds`; `resumed` if the timer was too
+    Returned { away_seconds: i64, resumed: bool },
+}
+
+/// State the idle loop keeps between polls
+#[derive(Debug, Default)]
+pub struct IdleTracker {
+    /// Session the user was already prompted about, so the prompt fires once
+    prompted_session: Option<String>,
+}
+
+impl IdleTracker {
+    /// Apply a sample to the running session's timer
+    pub fn apply(
+        &mut self,
+        settings: &IdleSettings,
+        sample: &IdleSample,
+        timer: &mut TimerState,
+        session_id: &str,
+        session_start: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<IdleTransition> {
+        match decide(settings, sample, timer, session_start, now) {
+            IdleAction::None => None,
+            IdleAction::Pause { since, reason } => {
+                timer.pause_for_idle(since);
+                self.prompted_session = None;
+                Some(IdleTransition::Paused {
+                    since: timer.paused_at.unwrap_or(since),
+                    reason,
+                })
+            }
+            IdleAction::Returned => {
+                if self.prompted_session.as_deref() == Some(session_id) && !settings.auto_resume {
+                    return None;
+                }
+                self.prompted_session = Some(session_id.to_string());
+
+                let away_seconds = timer
+                    .paused_at
+                    .map(|since| (now - since).num_seconds().max(0))
+                    .unwrap_or(0);
+                if settings.auto_resume {
+                    timer.resume();
+                }
+                Some(IdleTransition::Returned {
+                    away_seconds,
+                    resumed: settings.auto_resume,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlePausedPayload {
+    pub session_id: String,
+    pub reason: IdleReason,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleReturnedPayload {
+    pub session_id: String,
+    pub away_seconds: i64,
+    /// Whether the session was already resumed (`autoResume`)
+    pub resumed: bool,
+}
+
+/// Poll the idle monitor and pause or resume the running session
+///
+/// Runs for the lifetime of the app; polling is skipped while no session is
+/// active.
+pub async fn start_idle_loop(state: AppState, monitor: IdleMonitor) {
+    let mut ticker = interval(Duration::from_secs(IDLE_POLL_INTERVAL_SECS));
+    let mut tracker = IdleTracker::default();
+    let mut monitor_failing = false;
+
+    loop {
+        ticker.tick().await;
+
+        let Some(session) = state.active_session.read().await.clone() else {
+            continue;
+        };
+
+        let settings = match load_settings(state.pool()).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::warn!("Failed to load idle settings: {}", e);
+                continue;
+            }
+        };
+        if !settings.enabled {
+            continue;
+        }
+
+        let sample = match monitor.sample().await {
+            Ok(sample) => {
+                monitor_failing = false;
+                sample
+            }
+            Err(e) => {
+                if !monitor_failing {
+                    tracing::warn!("Failed to read idle state: {}", e);
+                    monitor_failing = true;
+                }
+                continue;
+            }
+        };
+
+        let transition = {
+            let mut timer = state.timer_state.write().await;
+            tracker.apply(&settings, &sample, &mut timer, &session.id, session.start_time, Utc::now())
+        };
+
+        match transition {
+            None => {}
+            Some(IdleTransition::Paused { since, reason }) => {
+                session_paused(&state, &session.id, since, reason).await;
+            }
+            Some(IdleTransition::Returned { away_seconds, resumed }) => {
+                user_returned(&state, &session.id, &session.session_type, away_seconds, resumed).await;
+            }
+        }
+    }
+}
+
+async fn session_paused(state: &AppState, session_id: &str, since: DateTime<Utc>, reason: IdleReason) {
+    state.checkpoint_session().await;
+
+    session_events::record_at(
+        state.pool(),
+        session_id,
+        SessionEventType::IdlePause,
+        since,
+        Some(reason.label()),
+        None,
+    )
+    .await;
+
+    update_tray_icon(&state.app_handle, TrayIconState::Paused);
+    tracing::info!("Session {} auto-paused ({:?})", session_id, reason);
+
+    let payload = IdlePausedPayload {
+        session_id: session_id.to_string(),
+        reason,
+        since,
+    };
+    if let Err(e) = state.app_handle.emit("idle-paused", &payload) {
+        tracing::error!("Failed to emit idle-paused: {}", e);
+    }
+}
+
+async fn user_returned(
+    state: &AppState,
+    session_id: &str,
+    session_type: &SessionType,
+    away_seconds: i64,
+    resumed: bool,
+) {
+    if resumed {
+        state.checkpoint_session().await;
+        session_events::record(state.pool(), session_id, SessionEventType::Resume, None, None).await;
+
+        let tray_state = match session_type {
+            SessionType::Break => TrayIconState::Break,
+            SessionType::Focus | SessionType::Custom => TrayIconState::Focus,
+        };
+        update_tray_icon(&state.app_handle, tray_state);
+        tracing::info!("Session {} auto-resumed after {}s away", session_id, away_seconds);
+    }
+
+    let payload = IdleReturnedPayload {
+        session_id: session_id.to_string(),
+        away_seconds,
+        resumed,
+    };
+    if let Err(e) = state.app_handle.emit("idle-returned", &payload) {
+        tracing::error!("Failed to emit idle-returned: {}", e);
+    }
+}
+
+/// Get the idle detection settings
+#[tauri::command]
+pub async fn get_idle_settings(state: State<'_, AppState>) -> Result<IdleSettings> {
+    load_settings(state.pool()).await
+}
+
+/// Change the idle detection settings (applies from the next poll)
+#[tauri::command]
+pub async fn set_idle_settings(
+    settings: IdleSettings,
+    state: State<'_, AppState>,
+) -> Result<IdleSettings> {
+    save_settings(state.pool(), &settings).await?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn active() -> IdleSample {
+        IdleSample::default()
+    }
+
+    fn idle_since(since: DateTime<Utc>) -> IdleSample {
+        IdleSample {
+            idle: true,
+            idle_since: Some(since),
+            locked: false,
+        }
+    }
+
+    #[test]
+    fn test_idle_loop_pauses_and_prompts_once() {
+        let settings = IdleSettings::default();
+        let start = Utc::now() - Duration::minutes(30);
+        let now = start + Duration::minutes(20);
+        let mut tracker = IdleTracker::default();
+        let mut timer = TimerState::new_running();
+
+        // Idle for less than the timeout: keep counting
+        let sample = idle_since(now - Duration::minutes(2));
+        assert_eq!(tracker.apply(&settings, &sample, &mut timer, "s", start, now), None);
+        assert!(!timer.is_paused);
+
+        // Past the timeout: pause as of when the user was last active
+        let sample = idle_since(now - Duration::minutes(6));
+        assert_eq!(
+            tracker.apply(&settings, &sample, &mut timer, "s", start, now),
+            Some(IdleTransition::Paused {
+                since: now - Duration::minutes(6),
+                reason: IdleReason::Idle,
+            })
+        );
+        assert!(timer.is_paused && timer.idle_paused);
+
+        // Back at the keyboard: prompt once and leave resuming to the user
+        let later = now + Duration::minutes(1);
+        assert_eq!(
+            tracker.apply(&settings, &active(), &mut timer, "s", start, later),
+            Some(IdleTransition::Returned {
+                away_seconds: 7 * 60,
+                resumed: false,
+            })
+        );
+        assert_eq!(tracker.apply(&settings, &active(), &mut timer, "s", start, later), None);
+        assert!(timer.is_paused);
+
+        // Once the user resumes, a new idle stretch prompts again
+        timer.resume();
+        let sample = idle_since(later);
+        let back = later + Duration::minutes(10);
+        assert!(matches!(
+            tracker.apply(&settings, &sample, &mut timer, "s", start, back),
+            Some(IdleTransition::Paused { .. })
+        ));
+        assert!(matches!(
+            tracker.apply(&settings, &active(), &mut timer, "s", start, back),
+            Some(IdleTransition::Returned { resumed: false, .. })
+        ));
+    }
+
+    #[test]
+    fn test_idle_loop_auto_resumes() {
+        let settings = IdleSettings {
+            auto_resume: true,
+            ..IdleSettings::default()
+        };
+        let start = Utc::now() - Duration::minutes(30);
+        let now = Utc::now();
+        let mut tracker = IdleTracker::default();
+        let mut timer = TimerState::new_running();
+
+        let locked = IdleSample {
+            locked: true,
+            ..IdleSample::default()
+        };
+        assert!(matches!(
+            tracker.apply(&settings, &locked, &mut timer, "s", start, now),
+            Some(IdleTransition::Paused {
+                reason: IdleReason::Locked,
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            tracker.apply(&settings, &active(), &mut timer, "s", start, now),
+            Some(IdleTransition::Returned { resumed: true, .. })
+        ));
+        assert!(!timer.is_paused && !timer.idle_paused);
+    }
+
+    #[test]
+    fn test_lock_pauses_immediately() {
+        let settings = IdleSettings::default();
+        let start = Utc::now() - Duration::minutes(10);
+        let now = Utc::now();
+        let locked = IdleSample {
+            locked: true,
+            ..IdleSample::default()
+        };
+        let timer = TimerState::new_running();
+
+        assert_eq!(
+            decide(&settings, &locked, &timer, start, now),
+            IdleAction::Pause {
+                since: now,
+                reason: IdleReason::Locked,
+            }
+        );
+
+        let settings = IdleSettings {
+            pause_on_lock: false,
+            ..IdleSettings::default()
+        };
+        assert_eq!(decide(&settings, &locked, &timer, start, now), IdleAction::None);
+    }
+
+    #[test]
+    fn test_manual_pause_and_session_start_are_respected() {
+        let settings = IdleSettings::default();
+        let start = Utc::now() - Duration::minutes(3);
+        let now = Utc::now() + Duration::minutes(10);
+
+        // Idle since before the session started: pause at the start
+        let mut timer = TimerState::new_running();
+        assert_eq!(
+            decide(&settings, &idle_since(start - Duration::hours(1)), &timer, start, now),
+            IdleAction::Pause {
+                since: start,
+                reason: IdleReason::Idle,
+            }
+        );
+
+        // A manual pause never turns into a resume prompt
+        timer.pause();
+        assert_eq!(decide(&settings, &active(), &timer, start, now), IdleAction::None);
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        assert!(IdleSettings::default().validate().is_ok());
+        let bad = IdleSettings {
+            auto_pause_after_minutes: 0,
+            ..IdleSettings::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+}
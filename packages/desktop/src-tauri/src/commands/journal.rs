@@ -2,6 +2,7 @@
 
 use crate::{AppState, Result};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use tauri::State;
 
 /// Trigger types based on Indistractable framework
@@ -72,6 +73,16 @@ pub struct TriggerInsight {
 pub async fn create_journal_entry(
     state: State<'_, AppState>,
     request: CreateJournalEntryRequest,
+) -> Result<JournalEntry> {
+    insert_journal_entry(state.pool(), request).await
+}
+
+/// Insert a journal entry and update trigger patterns
+///
+/// Shared by the journal UI and by reasons typed to unblock a delayed app.
+pub(crate) async fn insert_journal_entry(
+    pool: &SqlitePool,
+    request: CreateJournalEntryRequest,
 ) -> Result<JournalEntry> {
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
@@ -80,7 +91,7 @@ pub async fn create_journal_entry(
     let device_id: Option<String> = sqlx::query_scalar(
         "SELECT value FROM user_settings WHERE key = 'device_id'"
     )
-    .fetch_optional(state.pool())
+    .fetch_optional(pool)
     .await?;
 
     sqlx::query(
@@ -98,11 +109,11 @@ pub async fn create_journal_entry(
     .bind(&now)
     .bind(&device_id)
     .bind(&now)
-    .execute(state.pool())
+    .execute(pool)
     .await?;
 
     // Update trigger patterns for analytics
-    update_trigger_patterns(&state, &request.trigger_type).await?;
+    update_trigger_patterns(pool, &request.trigger_type).await?;
 
     Ok(JournalEntry {
         id,
@@ -187,26 +198,45 @@ pub async fn get_recent_journal_entries(
 /// Get trigger pattern insights
 #[tauri::command]
 pub async fn get_trigger_insights(state: State<'_, AppState>) -> Result<Vec<TriggerInsight>> {
-    // Get frequency by trigger type
-    let insights: Vec<TriggerInsight> = sqlx::query_as::<_, (String, i32)>(
+    // Frequency by trigger type, with peak times learned from every journaled
+    // trigger (including reasons typed to continue a delayed app)
+    let insights = sqlx::query_as::<_, (String, i32, Option<i32>, Option<i32>)>(
         r#"
-        SELECT trigger_type, COUNT(*) as frequency
-        FROM journal_entries
-        WHERE deleted = 0 AND created_at >= datetime('now', '-30 days')
-        GROUP BY trigger_type
+        WITH hours AS (
+            SELECT trigger_type, hour_of_day,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY trigger_type ORDER BY SUM(frequency) DESC, hour_of_day
+                   ) AS row_rank
+            FROM trigger_patterns
+            WHERE user_id = 'local'
+            GROUP BY trigger_type, hour_of_day
+        ),
+        days AS (
+            SELECT trigger_type, day_of_week,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY trigger_type ORDER BY SUM(frequency) DESC, day_of_week
+                   ) AS row_rank
+            FROM trigger_patterns
+            WHERE user_id = 'local'
+            GROUP BY trigger_type, day_of_week
+        )
+        SELECT j.trigger_type, COUNT(*) as frequency, MAX(h.hour_of_day), MAX(d.day_of_week)
+        FROM journal_entries j
+        LEFT JOIN hours h ON h.trigger_type = j.trigger_type AND h.row_rank = 1
+        LEFT JOIN days d ON d.trigger_type = j.trigger_type AND d.row_rank = 1
+        WHERE j.deleted = 0 AND j.created_at >= datetime('now', '-30 days')
+        GROUP BY j.trigger_type
         ORDER BY frequency DESC
         "#
     )
     .fetch_all(state.pool())
     .await?
     .into_iter()
-    .map(|(trigger_type, frequency)| {
-        TriggerInsight {
-            trigger_type,
-            frequency,
-            peak_hour: None,
-            peak_day: None,
-        }
+    .map(|(trigger_type, frequency, peak_hour, peak_day)| TriggerInsight {
+        trigger_type,
+        frequency,
+        peak_hour,
+        peak_day,
     })
     .collect();
 
@@ -257,7 +287,7 @@ pub struct PeakTimes {
 }
 
 /// Update trigger patterns for analytics
-async fn update_trigger_patterns(state: &State<'_, AppState>, trigger_type: &str) -> Result<()> {
+async fn update_trigger_patterns(pool: &SqlitePool, trigger_type: &str) -> Result<()> {
     let now = chrono::Utc::now();
     let hour = now.hour() as i32;
     let day = now.weekday().num_days_from_sunday() as i32;
@@ -274,7 +304,7 @@ async fn update_trigger_patterns(state: &State<'_, AppState>, trigger_type: &str
     .bind(trigger_type)
     .bind(hour)
     .bind(day)
-    .execute(state.pool())
+    .execute(pool)
     .await?;
 
     Ok(())
@@ -6,19 +6,25 @@ pub mod ai_providers;
 pub mod analytics;
 pub mod auth;
 pub mod blocking;
+pub mod budgets;
 pub mod calendar;
 pub mod chat_context;
 pub mod chat_history;
 pub mod coach;
 pub mod credentials;
+pub mod cycle;
 pub mod dimming;
 pub mod focus;
 pub mod focus_time;
+pub mod idle;
 pub mod journal;
 pub mod llm;
 pub mod notification_control;
 pub mod onboarding;
 pub mod permissions;
+pub mod profiles;
+pub mod projects;
+pub mod session_events;
 pub mod streaks;
 pub mod sync;
 pub mod team;
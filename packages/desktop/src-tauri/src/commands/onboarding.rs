@@ -1,6 +1,6 @@
 // commands/onboarding.rs - Tauri commands for onboarding flow
 
-use crate::{Result, AppState};
+use crate::{blocking::blocklist, Result, AppState};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize)]
@@ -105,7 +105,7 @@ pub async fn complete_onboarding(
             r#"
             INSERT INTO blocked_items (item_type, value, enabled)
             VALUES ('app', ?, 1)
-            ON CONFLICT(item_type, value) DO UPDATE SET enabled = 1
+            ON CONFLICT(item_type, value, match_type, rule_kind) DO UPDATE SET enabled = 1
             "#,
         )
         .bind(app)
@@ -119,7 +119,7 @@ pub async fn complete_onboarding(
             r#"
             INSERT INTO blocked_items (item_type, value, enabled)
             VALUES ('website', ?, 1)
-            ON CONFLICT(item_type, value) DO UPDATE SET enabled = 1
+            ON CONFLICT(item_type, value, match_type, rule_kind) DO UPDATE SET enabled = 1
             "#,
         )
         .bind(website)
@@ -152,6 +152,8 @@ pub async fn complete_onboarding(
     // Commit transaction
     tx.commit().await?;
 
+    blocklist::refresh_website_blocking(&state).await?;
+
     tracing::info!(
         "Onboarding completed for user: {}, apps: {}, websites: {}",
         data.user_name,
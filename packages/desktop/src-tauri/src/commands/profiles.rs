@@ -0,0 +1,249 @@
+// commands/profiles.rs - Named focus profiles
+//
+// A profile bundles the blocklists, dimming, DND and duration of a focus
+// session under a name. Every edit saves a new version so past sessions keep
+// pointing at the settings they actually ran with.
+
+use crate::{
+    commands::focus::{validate_blocked_apps, validate_blocked_websites},
+    db::queries::{self, FocusProfile, FocusProfileVersion},
+    state::AppState,
+    system::tray,
+    Error, Result,
+};
+use serde::Deserialize;
+use tauri::State;
+
+const MAX_PROFILE_NAME_LENGTH: usize = 100;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveProfileRequest {
+    pub name: String,
+    pub planned_duration_minutes: i32,
+    #[serde(default)]
+    pub blocked_apps: Vec<String>,
+    #[serde(default)]
+    pub blocked_websites: Vec<String>,
+    #[serde(default)]
+    pub allowed_websites: Vec<String>,
+    #[serde(default)]
+    pub enable_dimming: bool,
+    #[serde(default = "default_dimming_opacity")]
+    pub dimming_opacity: f32,
+    #[serde(default)]
+    pub pause_notifications: bool,
+}
+
+fn default_dimming_opacity() -> f32 {
+    0.7
+}
+
+impl SaveProfileRequest {
+    /// Validate the request and convert it to a version row
+    fn into_version(self) -> Result<FocusProfileVersion> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() || name.len() > MAX_PROFILE_NAME_LENGTH {
+            return Err(Error::InvalidInput(format!(
+                "Profile name must be between 1 and {} characters",
+                MAX_PROFILE_NAME_LENGTH
+            )));
+        }
+        if !(1..=480).contains(&self.planned_duration_minutes) {
+            return Err(Error::Validation(
+                "Duration must be between 1 minute and 8 hours".into(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.dimming_opacity) {
+            return Err(Error::InvalidInput(
+                "Dimming opacity must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+
+        validate_blocked_apps(&self.blocked_apps)?;
+        validate_blocked_websites(&self.blocked_websites)?;
+        validate_blocked_websites(&self.allowed_websites)?;
+
+        Ok(FocusProfileVersion {
+            profile_id: 0,
+            version: 0,
+            name,
+            planned_duration_minutes: self.planned_duration_minutes,
+            blocked_apps: serde_json::to_string(&trimmed(self.blocked_apps))?,
+            blocked_websites: serde_json::to_string(&trimmed(self.blocked_websites))?,
+            allowed_websites: serde_json::to_string(&trimmed(self.allowed_websites))?,
+            enable_dimming: self.enable_dimming,
+            dimming_opacity: self.dimming_opacity as f64,
+            pause_notifications: self.pause_notifications,
+        })
+    }
+}
+
+fn trimmed(items: Vec<String>) -> Vec<String> {
+    items.into_iter().map(|item| item.trim().to_string()).collect()
+}
+
+/// Load the current version of a profile
+pub(crate) async fn load_profile(pool: &sqlx::SqlitePool, id: i64) -> Result<FocusProfile> {
+    queries::get_focus_profile(pool, id)
+        .await?
+        .map(FocusProfile::from)
+        .ok_or_else(|| Error::NotFound(format!("Focus profile {} not found", id)))
+}
+
+async fn ensure_unique_name(state: &AppState, name: &str, except: Option<i64>) -> Result<()> {
+    let profiles = queries::get_focus_profiles(state.pool()).await?;
+    if profiles
+        .iter()
+        .any(|p| Some(p.profile_id) != except && p.name.eq_ignore_ascii_case(name))
+    {
+        return Err(Error::Validation(format!(
+            "A focus profile named \"{}\" already exists",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Push the current profile names to the tray's "Start with Profile" menu
+pub(crate) async fn refresh_tray_profiles(state: &AppState) {
+    match queries::get_focus_profiles(state.pool()).await {
+        Ok(profiles) => tray::set_tray_profiles(
+            &state.app_handle,
+            profiles.into_iter().map(|p| (p.profile_id, p.name)).collect(),
+        ),
+        Err(e) => tracing::warn!("Failed to load focus profiles for tray: {}", e),
+    }
+}
+
+/// List all focus profiles
+#[tauri::command]
+pub async fn get_focus_profiles(state: State<'_, AppState>) -> Result<Vec<FocusProfile>> {
+    Ok(queries::get_focus_profiles(state.pool())
+        .await?
+        .into_iter()
+        .map(FocusProfile::from)
+        .collect())
+}
+
+/// Get the current version of a focus profile
+#[tauri::command]
+pub async fn get_focus_profile(id: i64, state: State<'_, AppState>) -> Result<FocusProfile> {
+    load_profile(state.pool(), id).await
+}
+
+/// Get every saved version of a focus profile, newest first
+#[tauri::command]
+pub async fn get_focus_profile_versions(
+    id: i64,
+    state: State<'_, AppState>,
+) -> Result<Vec<FocusProfile>> {
+    let versions = queries::get_focus_profile_versions(state.pool(), id).await?;
+    if versions.is_empty() {
+        return Err(Error::NotFound(format!("Focus profile {} not found", id)));
+    }
+
+    Ok(versions.into_iter().map(FocusProfile::from).collect())
+}
+
+/// Create a focus profile
+#[tauri::command]
+pub async fn create_focus_profile(
+    request: SaveProfileRequest,
+    state: State<'_, AppState>,
+) -> Result<FocusProfile> {
+    let profile = request.into_version()?;
+    ensure_unique_name(&state, &profile.name, None).await?;
+
+    let id = queries::insert_focus_profile(state.pool(), &profile).await?;
+    tracing::info!("Created focus profile {} ({})", id, profile.name);
+
+    refresh_tray_profiles(&state).await;
+    load_profile(state.pool(), id).await
+}
+
+/// Save new settings for a focus profile
+///
+/// The edit becomes a new version; sessions already started with the profile
+/// keep referring to the version they used.
+#[tauri::command]
+pub async fn update_focus_profile(
+    id: i64,
+    request: SaveProfileRequest,
+    state: State<'_, AppState>,
+) -> Result<FocusProfile> {
+    let profile = request.into_version()?;
+    ensure_unique_name(&state, &profile.name, Some(id)).await?;
+
+    let version = queries::insert_focus_profile_version(state.pool(), id, &profile)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Focus profile {} not found", id)))?;
+    tracing::info!("Saved focus profile {} version {}", id, version);
+
+    refresh_tray_profiles(&state).await;
+    load_profile(state.pool(), id).await
+}
+
+/// Delete a focus profile
+///
+/// Its versions are kept for session history, and schedules that used it fall
+/// back to the regular blocklist.
+#[tauri::command]
+pub async fn delete_focus_profile(id: i64, state: State<'_, AppState>) -> Result<()> {
+    if !queries::archive_focus_profile(state.pool(), id).await? {
+        return Err(Error::NotFound(format!("Focus profile {} not found", id)));
+    }
+    tracing::info!("Deleted focus profile {}", id);
+
+    refresh_tray_profiles(&state).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> SaveProfileRequest {
+        SaveProfileRequest {
+            name: "  Deep Work ".to_string(),
+            planned_duration_minutes: 90,
+            blocked_apps: vec!["slack".to_string()],
+            blocked_websites: vec![" twitter.com".to_string()],
+            allowed_websites: vec![],
+            enable_dimming: true,
+            dimming_opacity: 0.5,
+            pause_notifications: true,
+        }
+    }
+
+    #[test]
+    fn test_into_version_round_trip() {
+        let version = request().into_version().unwrap();
+        assert_eq!(version.name, "Deep Work");
+
+        let profile = FocusProfile::from(version);
+        assert_eq!(profile.blocked_apps, vec!["slack"]);
+        assert_eq!(profile.blocked_websites, vec!["twitter.com"]);
+        assert!(profile.allowed_websites.is_empty());
+        assert_eq!(profile.dimming_opacity, 0.5);
+    }
+
+    #[test]
+    fn test_into_version_validation() {
+        let mut bad = request();
+        bad.name = "   ".to_string();
+        assert!(bad.into_version().is_err());
+
+        let mut bad = request();
+        bad.planned_duration_minutes = 0;
+        assert!(bad.into_version().is_err());
+
+        let mut bad = request();
+        bad.dimming_opacity = 1.5;
+        assert!(bad.into_version().is_err());
+
+        let mut bad = request();
+        bad.blocked_apps = vec!["../evil".to_string()];
+        assert!(bad.into_version().is_err());
+    }
+}
@@ -0,0 +1,321 @@
+// commands/projects.rs - Projects, tasks and tags on focus sessions
+//
+// Sessions can be attributed to a project, given a task title and tagged,
+// either when they start or afterwards. Analytics break focus time down by
+// project and tag.
+
+use crate::{
+    db::queries::{self, Project, TagUsage},
+    state::AppState,
+    Error, Result,
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use tauri::State;
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_TASK_TITLE_LENGTH: usize = 200;
+const MAX_TAG_LENGTH: usize = 50;
+const MAX_TAGS_PER_SESSION: usize = 10;
+
+/// Project, task title and tags of a session
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMetadata {
+    #[serde(default)]
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub task_title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl SessionMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.project_id.is_none() && self.task_title.is_none() && self.tags.is_empty()
+    }
+
+    /// Trim and check the task title and tags (without touching the database)
+    fn normalized(self) -> Result<Self> {
+        let task_title = self
+            .task_title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+        if task_title.as_ref().is_some_and(|t| t.chars().count() > MAX_TASK_TITLE_LENGTH) {
+            return Err(Error::InvalidInput(format!(
+                "Task title cannot exceed {} characters",
+                MAX_TASK_TITLE_LENGTH
+            )));
+        }
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags {
+            let tag = tag.trim().trim_start_matches('#').trim().to_string();
+            if tag.is_empty() || tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                continue;
+            }
+            if tag.chars().count() > MAX_TAG_LENGTH {
+                return Err(Error::InvalidInput(format!(
+                    "Tags cannot exceed {} characters",
+                    MAX_TAG_LENGTH
+                )));
+            }
+            tags.push(tag);
+        }
+        if tags.len() > MAX_TAGS_PER_SESSION {
+            return Err(Error::InvalidInput(format!(
+                "A session can have at most {} tags",
+                MAX_TAGS_PER_SESSION
+            )));
+        }
+
+        Ok(Self {
+            project_id: self.project_id,
+            task_title,
+            tags,
+        })
+    }
+
+    /// Normalize the metadata and make sure its project can take new sessions
+    pub async fn validated(self, pool: &SqlitePool) -> Result<Self> {
+        let metadata = self.normalized()?;
+
+        if let Some(project_id) = metadata.project_id {
+            match queries::get_project(pool, project_id).await? {
+                Some(project) if !project.archived => {}
+                Some(project) => {
+                    return Err(Error::Validation(format!(
+                        "Project \"{}\" is archived",
+                        project.name
+                    )))
+                }
+                None => return Err(Error::NotFound(format!("Project {} not found", project_id))),
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Store the metadata on a session
+    pub async fn save(&self, pool: &SqlitePool, session_id: &str) -> Result<bool> {
+        queries::set_session_metadata(
+            pool,
+            session_id,
+            self.project_id,
+            self.task_title.as_deref(),
+            &self.tags,
+        )
+        .await
+    }
+}
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(Error::InvalidInput(format!(
+            "Project name must be between 1 and {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_color(color: Option<String>) -> Result<Option<String>> {
+    match color {
+        Some(color) => {
+            let valid = color.len() == 7
+                && color.starts_with('#')
+                && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+            if !valid {
+                return Err(Error::InvalidInput(
+                    "Color must be a hex value like #4f46e5".to_string(),
+                ));
+            }
+            Ok(Some(color.to_ascii_lowercase()))
+        }
+        None => Ok(None),
+    }
+}
+
+async fn ensure_unique_name(pool: &SqlitePool, name: &str, except: Option<i64>) -> Result<()> {
+    let projects = queries::get_projects(pool, true).await?;
+    if projects
+        .iter()
+        .any(|p| Some(p.id) != except && p.name.eq_ignore_ascii_case(name))
+    {
+        return Err(Error::Validation(format!(
+            "A project named \"{}\" already exists",
+            name
+        )));
+    }
+    Ok(())
+}
+
+async fn find_project(pool: &SqlitePool, id: i64) -> Result<Project> {
+    queries::get_project(pool, id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Project {} not found", id)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateProjectRequest {
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProjectRequest {
+    pub id: i64,
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub archived: Option<bool>,
+}
+
+/// List projects (archived ones only when asked)
+#[tauri::command]
+pub async fn get_projects(
+    include_archived: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Project>> {
+    queries::get_projects(state.pool(), include_archived.unwrap_or(false)).await
+}
+
+/// Create a project
+#[tauri::command]
+pub async fn create_project(
+    request: CreateProjectRequest,
+    state: State<'_, AppState>,
+) -> Result<Project> {
+    let name = validate_name(&request.name)?;
+    let color = validate_color(request.color)?;
+    ensure_unique_name(state.pool(), &name, None).await?;
+
+    let id = queries::insert_project(state.pool(), &name, color.as_deref()).await?;
+    tracing::info!("Created project {} ({})", id, name);
+
+    find_project(state.pool(), id).await
+}
+
+/// Rename, recolor, archive or restore a project
+///
+/// Archived projects keep their sessions but can't be picked for new ones.
+#[tauri::command]
+pub async fn update_project(
+    request: UpdateProjectRequest,
+    state: State<'_, AppState>,
+) -> Result<Project> {
+    let current = find_project(state.pool(), request.id).await?;
+
+    let name = match request.name {
+        Some(name) => validate_name(&name)?,
+        None => current.name,
+    };
+    ensure_unique_name(state.pool(), &name, Some(request.id)).await?;
+    let color = match request.color {
+        Some(color) => validate_color(Some(color))?,
+        None => current.color,
+    };
+    let archived = request.archived.unwrap_or(current.archived);
+
+    queries::update_project(state.pool(), request.id, &name, color.as_deref(), archived).await?;
+
+    find_project(state.pool(), request.id).await
+}
+
+/// List tags with how many sessions use each
+#[tauri::command]
+pub async fn get_tags(state: State<'_, AppState>) -> Result<Vec<TagUsage>> {
+    queries::get_tags(state.pool()).await
+}
+
+/// Delete a tag from every session
+#[tauri::command]
+pub async fn delete_tag(id: i64, state: State<'_, AppState>) -> Result<()> {
+    if !queries::delete_tag(state.pool(), id).await? {
+        return Err(Error::NotFound(format!("Tag {} not found", id)));
+    }
+    Ok(())
+}
+
+/// Set the project, task title and tags of a session, running or finished
+#[tauri::command]
+pub async fn update_session_metadata(
+    session_id: String,
+    metadata: SessionMetadata,
+    state: State<'_, AppState>,
+) -> Result<()> {
+    let metadata = metadata.validated(state.pool()).await?;
+
+    if !metadata.save(state.pool(), &session_id).await? {
+        return Err(Error::NotFound(format!("Session {} not found", session_id)));
+    }
+
+    // Keep the running session (and its checkpoint) in sync
+    let is_active = {
+        let mut active = state.active_session.write().await;
+        match active.as_mut().filter(|session| session.id == session_id) {
+            Some(session) => {
+                session.project_id = metadata.project_id;
+                session.task_title = metadata.task_title.clone();
+                session.tags = metadata.tags.clone();
+                true
+            }
+            None => false,
+        }
+    };
+    if is_active {
+        state.checkpoint_session().await;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_metadata() {
+        let metadata = SessionMetadata {
+            project_id: Some(1),
+            task_title: Some("  ".to_string()),
+            tags: vec![
+                " #writing".to_string(),
+                "Writing".to_string(),
+                "".to_string(),
+                "review".to_string(),
+            ],
+        }
+        .normalized()
+        .unwrap();
+
+        assert_eq!(metadata.task_title, None);
+        assert_eq!(metadata.tags, vec!["writing", "review"]);
+    }
+
+    #[test]
+    fn test_normalized_metadata_limits() {
+        let too_many = SessionMetadata {
+            tags: (0..=MAX_TAGS_PER_SESSION).map(|i| format!("tag{}", i)).collect(),
+            ..SessionMetadata::default()
+        };
+        assert!(too_many.normalized().is_err());
+
+        let long_title = SessionMetadata {
+            task_title: Some("x".repeat(MAX_TASK_TITLE_LENGTH + 1)),
+            ..SessionMetadata::default()
+        };
+        assert!(long_title.normalized().is_err());
+    }
+
+    #[test]
+    fn test_validate_color() {
+        assert_eq!(validate_color(Some("#4F46E5".to_string())).unwrap(), Some("#4f46e5".to_string()));
+        assert_eq!(validate_color(None).unwrap(), None);
+        assert!(validate_color(Some("blue".to_string())).is_err());
+        assert!(validate_color(Some("#12345".to_string())).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+// commands/session_events.rs - Pause and interruption log
+//
+// Every pause, resume, extension and early end of a session is logged with a
+// timestamp and an optional reason. When the session ends the log is boiled
+// down to an interruption score from 0 (uninterrupted) to 100, which feeds
+// analytics and the AI coach. Pauses for idle time, screen lock and sleep are
+// logged too, but aren't scored.
+
+use crate::{
+    db::queries::{self, SessionEvent},
+    state::AppState,
+    Error, Result,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::State;
+
+const MAX_REASON_LENGTH: usize = 200;
+
+/// Kind of session event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEventType {
+    Pause,
+    /// Paused by idle detection, screen lock or sleep
+    IdlePause,
+    Resume,
+    Extend,
+    EndEarly,
+}
+
+impl SessionEventType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionEventType::Pause => "pause",
+            SessionEventType::IdlePause => "idle_pause",
+            SessionEventType::Resume => "resume",
+            SessionEventType::Extend => "extend",
+            SessionEventType::EndEarly => "end_early",
+        }
+    }
+}
+
+/// Trim a user-supplied reason, dropping empty ones
+pub fn normalize_reason(reason: Option<String>) -> Result<Option<String>> {
+    let reason = reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+
+    if reason.as_ref().is_some_and(|r| r.chars().count() > MAX_REASON_LENGTH) {
+        return Err(Error::InvalidInput(format!(
+            "Reason cannot exceed {} characters",
+            MAX_REASON_LENGTH
+        )));
+    }
+
+    Ok(reason)
+}
+
+/// Log an event for a session
+///
+/// Failures are logged rather than returned so a broken log never blocks
+/// pausing or ending a session.
+pub async fn record(
+    pool: &SqlitePool,
+    session_id: &str,
+    event_type: SessionEventType,
+    reason: Option<&str>,
+    minutes: Option<i32>,
+) {
+    record_at(pool, session_id, event_type, Utc::now(), reason, minutes).await
+}
+
+/// Log an event that happened at a given time (e.g. a backdated idle pause)
+pub async fn record_at(
+    pool: &SqlitePool,
+    session_id: &str,
+    event_type: SessionEventType,
+    occurred_at: DateTime<Utc>,
+    reason: Option<&str>,
+    minutes: Option<i32>,
+) {
+    if let Err(e) = queries::insert_session_event(
+        pool,
+        session_id,
+        event_type.as_str(),
+        occurred_at,
+        reason,
+        minutes,
+    )
+    .await
+    {
+        tracing::warn!(
+            "Failed to record {} event for session {}: {}",
+            event_type.as_str(),
+            session_id,
+            e
+        );
+    }
+}
+
+/// What a session's event log adds up to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterruptionSummary {
+    pub pauses: u32,
+    pub paused_seconds: i64,
+    pub extensions: u32,
+    pub extended_minutes: i64,
+    pub ended_early: bool,
+}
+
+/// Summarize a session's events, counting a pause still open at `end_time`
+///
+/// Idle pauses only mark time away: they aren't interruptions, so neither
+/// they nor the time they last are counted.
+pub fn summarize(events: &[SessionEvent], end_time: DateTime<Utc>) -> InterruptionSummary {
+    let mut summary = InterruptionSummary::default();
+    // Start of the open pause, if it counts as an interruption
+    let mut paused_at: Option<Option<DateTime<Utc>>> = None;
+
+    for event in events {
+        match event.event_type.as_str() {
+            "pause" => {
+                summary.pauses += 1;
+                paused_at.get_or_insert(Some(event.occurred_at));
+            }
+            "idle_pause" => {
+                paused_at.get_or_insert(None);
+            }
+            "resume" => {
+                if let Some(Some(start)) = paused_at.take() {
+                    summary.paused_seconds += (event.occurred_at - start).num_seconds().max(0);
+                }
+            }
+            "extend" => {
+                summary.extensions += 1;
+                summary.extended_minutes += event.minutes.unwrap_or(0) as i64;
+            }
+            "end_early" => summary.ended_early = true,
+            _ => {}
+        }
+    }
+
+    if let Some(Some(start)) = paused_at {
+        summary.paused_seconds += (end_time - start).num_seconds().max(0);
+    }
+
+    summary
+}
+
+/// Interruption score from 0 (uninterrupted) to 100
+///
+/// Each pause costs 10 points and each extension 5, time spent paused up to
+/// 30 points relative to the planned length, and ending early 20 points.
+pub fn interruption_score(summary: &InterruptionSummary, planned_duration_minutes: i32) -> f64 {
+    let planned_seconds = (planned_duration_minutes.max(1) as f64) * 60.0;
+    let paused_share = (summary.paused_seconds as f64 / planned_seconds * 50.0).min(30.0);
+
+    let score = summary.pauses as f64 * 10.0
+        + summary.extensions as f64 * 5.0
+        + paused_share
+        + if summary.ended_early { 20.0 } else { 0.0 };
+
+    (score.clamp(0.0, 100.0) * 10.0).round() / 10.0
+}
+
+/// Compute and store the interruption score of a session that just ended
+pub async fn finalize(
+    pool: &SqlitePool,
+    session_id: &str,
+    planned_duration_minutes: i32,
+    end_time: DateTime<Utc>,
+) -> Result<f64> {
+    let events = queries::get_session_events(pool, session_id).await?;
+    let score = interruption_score(&summarize(&events, end_time), planned_duration_minutes);
+
+    queries::set_session_interruption_score(pool, session_id, score).await?;
+
+    Ok(score)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEventsResponse {
+    pub events: Vec<SessionEvent>,
+    pub summary: InterruptionSummary,
+    /// Stored score for finished sessions, live estimate for the running one
+    pub interruption_score: f64,
+}
+
+/// Event log and interruption score of a session
+#[tauri::command]
+pub async fn get_session_events(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<SessionEventsResponse> {
+    let session = queries::get_session(state.pool(), &session_id)
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("Session {} not found", session_id)))?;
+
+    let events = queries::get_session_events(state.pool(), &session_id).await?;
+    let summary = summarize(&events, session.end_time.unwrap_or_else(Utc::now));
+    let interruption_score = session
+        .interruption_score
+        .unwrap_or_else(|| self::interruption_score(&summary, session.planned_duration_minutes));
+
+    Ok(SessionEventsResponse {
+        events,
+        summary,
+        interruption_score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(event_type: &str, minute: i64, minutes: Option<i32>) -> SessionEvent {
+        SessionEvent {
+            id: minute,
+            session_id: "s".to_string(),
+            event_type: event_type.to_string(),
+            occurred_at: Utc.with_ymd_and_hms(2026, 10, 12, 9, 0, 0).unwrap()
+                + chrono::Duration::minutes(minute),
+            reason: None,
+            minutes,
+        }
+    }
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 12, 9, 0, 0).unwrap() + chrono::Duration::minutes(minute)
+    }
+
+    #[test]
+    fn test_summarize() {
+        let events = vec![
+            event("pause", 5, None),
+            event("resume", 8, None),
+            event("extend", 20, Some(10)),
+            event("pause", 30, None),
+        ];
+
+        let summary = summarize(&events, at(32));
+        assert_eq!(summary.pauses, 2);
+        assert_eq!(summary.paused_seconds, 5 * 60);
+        assert_eq!(summary.extensions, 1);
+        assert_eq!(summary.extended_minutes, 10);
+        assert!(!summary.ended_early);
+    }
+
+    #[test]
+    fn test_idle_pauses_are_not_interruptions() {
+        let events = vec![
+            event("idle_pause", 5, None),
+            event("resume", 15, None),
+            event("pause", 20, None),
+            event("resume", 22, None),
+            event("idle_pause", 25, None),
+        ];
+
+        let summary = summarize(&events, at(40));
+        assert_eq!(summary.pauses, 1);
+        assert_eq!(summary.paused_seconds, 2 * 60);
+        assert_eq!(interruption_score(&summary, 25), 14.0);
+    }
+
+    #[test]
+    fn test_interruption_score() {
+        assert_eq!(interruption_score(&InterruptionSummary::default(), 25), 0.0);
+
+        let summary = InterruptionSummary {
+            pauses: 1,
+            paused_seconds: 150,
+            ended_early: true,
+            ..InterruptionSummary::default()
+        };
+        // 10 (pause) + 5 (10% of the session paused) + 20 (ended early)
+        assert_eq!(interruption_score(&summary, 25), 35.0);
+
+        let summary = InterruptionSummary {
+            pauses: 12,
+            ..InterruptionSummary::default()
+        };
+        assert_eq!(interruption_score(&summary, 25), 100.0);
+    }
+
+    #[test]
+    fn test_normalize_reason() {
+        assert_eq!(normalize_reason(None).unwrap(), None);
+        assert_eq!(normalize_reason(Some("  ".to_string())).unwrap(), None);
+        assert_eq!(
+            normalize_reason(Some(" phone call ".to_string())).unwrap(),
+            Some("phone call".to_string())
+        );
+        assert!(normalize_reason(Some("x".repeat(MAX_REASON_LENGTH + 1))).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 // commands/sync.rs - Data export/import for backup and sync
 
 use crate::{
+    blocking::blocklist,
     db::queries::{self, BlockedItem, Session},
     state::AppState,
     Result,
@@ -27,7 +28,8 @@ pub async fn export_data(
     // Get all sessions (last 90 days)
     let end = chrono::Utc::now();
     let start = end - chrono::Duration::days(90);
-    let sessions = queries::get_sessions_in_range(state.pool(), start, end).await?;
+    let mut sessions = queries::get_sessions_in_range(state.pool(), start, end).await?;
+    queries::attach_session_tags(state.pool(), &mut sessions).await?;
 
     // Get all blocked items
     let blocked_items = queries::get_blocked_items(state.pool(), None).await?;
@@ -106,6 +108,18 @@ pub async fn import_data(
                 .await?;
             }
 
+            // Project IDs are local to each install; task titles and tags carry over
+            if session.task_title.is_some() || !session.tags.is_empty() {
+                queries::set_session_metadata(
+                    state.pool(),
+                    &session.id,
+                    None,
+                    session.task_title.as_deref(),
+                    &session.tags,
+                )
+                .await?;
+            }
+
             stats.sessions_imported += 1;
         } else {
             stats.sessions_skipped += 1;
@@ -123,6 +137,7 @@ pub async fn import_data(
 
         stats.blocked_items_imported += 1;
     }
+    blocklist::invalidate();
 
     tracing::info!(
         "Import completed: {} sessions, {} blocked items",
@@ -7,7 +7,7 @@ use crate::trailbase::{
     models::{Member, MemberRole, Team as TrailBaseTeam},
     sync::{SyncOperationType, SyncQueue},
 };
-use crate::{AppState, Error, Result};
+use crate::{blocking::blocklist, AppState, Error, Result};
 use chrono::{DateTime, Datelike, Utc};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -1046,6 +1046,10 @@ pub async fn sync_team_blocklist(
         }
     }
 
+    if added_count > 0 {
+        blocklist::refresh_website_blocking(&state).await?;
+    }
+
     tracing::info!("Synced {} items from team blocklist", added_count);
 
     Ok(added_count)
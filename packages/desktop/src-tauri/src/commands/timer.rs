@@ -4,6 +4,10 @@
 // ensuring perfect synchronization between main timer and mini-timer windows.
 
 use crate::{
+    commands::{
+        cycle,
+        session_events::{self, SessionEventType},
+    },
     state::AppState,
     system::tray::{update_tray_icon, TrayIconState},
     Error, Result,
@@ -18,6 +22,9 @@ use tracing::{debug, error, info, trace};
 /// Static flag to ensure only one timer loop runs at a time
 static TIMER_LOOP_RUNNING: AtomicBool = AtomicBool::new(false);
 
+/// How often (in ticks) the running session is checkpointed to the database
+const CHECKPOINT_INTERVAL_TICKS: u32 = 30;
+
 /// Payload broadcast to all windows on every timer tick
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -55,6 +62,9 @@ pub fn start_timer_loop(app_handle: AppHandle, state: AppState) -> oneshot::Send
     tokio::spawn(async move {
         let mut tick_interval = interval(Duration::from_secs(1));
         let mut idle_ticks = 0u32;
+        let mut session_ticks = 0u32;
+        // Session whose completion was already handed to the Pomodoro cycle
+        let mut completed_session: Option<String> = None;
 
         loop {
             tokio::select! {
@@ -89,6 +99,12 @@ pub fn start_timer_loop(app_handle: AppHandle, state: AppState) -> oneshot::Send
                     // Reset idle counter when we have a session
                     idle_ticks = 0;
 
+                    // Periodically checkpoint so a crash loses at most a few seconds of progress
+                    session_ticks += 1;
+                    if session_ticks % CHECKPOINT_INTERVAL_TICKS == 0 {
+                        state.checkpoint_session().await;
+                    }
+
                     // Only broadcast if timer is running
                     if !timer_state.is_running {
                         trace!("Timer state is_running=false, skipping broadcast");
@@ -139,6 +155,15 @@ pub fn start_timer_loop(app_handle: AppHandle, state: AppState) -> oneshot::Send
                         if let Err(e) = app_handle.emit("timer-completed", &payload) {
                             error!("Failed to emit timer-completed: {}", e);
                         }
+
+                        // Let the Pomodoro cycle move on, once per session
+                        if completed_session.as_deref() != Some(session.id.as_str()) {
+                            completed_session = Some(session.id.clone());
+                            let cycle_state = state.clone();
+                            tokio::spawn(async move {
+                                cycle::on_timer_completed(&cycle_state, &session.id).await;
+                            });
+                        }
                     }
                 }
             }
@@ -171,13 +196,20 @@ pub async fn stop_timer_loop(state: &AppState) {
 }
 
 /// Toggle pause/resume for the current session
+///
+/// The optional reason is kept in the session's interruption log.
 #[tauri::command]
-pub async fn toggle_timer_pause(state: State<'_, AppState>) -> Result<bool> {
+pub async fn toggle_timer_pause(
+    reason: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<bool> {
+    let reason = session_events::normalize_reason(reason)?;
+
     // Verify there's an active session and get its type
-    let session_type = {
+    let (session_id, session_type) = {
         let active = state.active_session.read().await;
         match active.as_ref() {
-            Some(session) => session.session_type.clone(),
+            Some(session) => (session.id.clone(), session.session_type.clone()),
             None => {
                 return Err(Error::InvalidSession(
                     "No active session to toggle".to_string(),
@@ -192,6 +224,15 @@ pub async fn toggle_timer_pause(state: State<'_, AppState>) -> Result<bool> {
         timer_state.is_paused
     };
 
+    state.checkpoint_session().await;
+
+    let event_type = if is_paused {
+        SessionEventType::Pause
+    } else {
+        SessionEventType::Resume
+    };
+    session_events::record(state.pool(), &session_id, event_type, reason.as_deref(), None).await;
+
     // Update tray icon based on pause state
     let tray_state = if is_paused {
         TrayIconState::Paused
@@ -46,6 +46,19 @@ pub async fn run(pool: &SqlitePool) -> Result<()> {
     run_if_needed(pool, 22, "create_team_tables").await?;
     run_if_needed(pool, 23, "create_conversation_sessions_table").await?;
     run_if_needed(pool, 24, "encrypt_sensitive_data").await?;
+    run_if_needed(pool, 25, "create_session_checkpoint_table").await?;
+    run_if_needed(pool, 26, "create_tamper_events_table").await?;
+    run_if_needed(pool, 28, "add_enforcement_to_blocked_items").await?;
+    run_if_needed(pool, 29, "add_rule_kind_to_blocked_items").await?;
+    run_if_needed(pool, 30, "add_outcome_to_block_attempts").await?;
+    run_if_needed(pool, 31, "create_time_budget_tables").await?;
+    run_if_needed(pool, 32, "create_focus_profile_tables").await?;
+    run_if_needed(pool, 33, "create_project_tag_tables").await?;
+    run_if_needed(pool, 34, "create_session_events_table").await?;
+    run_if_needed(pool, 35, "create_website_visits_table").await?;
+    run_if_needed(pool, 36, "create_blocklist_subscriptions_table").await?;
+    run_if_needed(pool, 37, "widen_blocked_items_unique_key").await?;
+    run_if_needed(pool, 38, "add_idle_pause_session_event").await?;
 
     Ok(())
 }
@@ -87,6 +100,19 @@ async fn run_if_needed(pool: &SqlitePool, id: i32, name: &str) -> Result<()> {
             22 => create_team_tables(pool).await?,
             23 => create_conversation_sessions_table(pool).await?,
             24 => encrypt_sensitive_data(pool).await?,
+            25 => create_session_checkpoint_table(pool).await?,
+            26 => create_tamper_events_table(pool).await?,
+            28 => add_enforcement_to_blocked_items(pool).await?,
+            29 => add_rule_kind_to_blocked_items(pool).await?,
+            30 => add_outcome_to_block_attempts(pool).await?,
+            31 => create_time_budget_tables(pool).await?,
+            32 => create_focus_profile_tables(pool).await?,
+            33 => create_project_tag_tables(pool).await?,
+            34 => create_session_events_table(pool).await?,
+            35 => create_website_visits_table(pool).await?,
+            36 => create_blocklist_subscriptions_table(pool).await?,
+            37 => widen_blocked_items_unique_key(pool).await?,
+            38 => add_idle_pause_session_event(pool).await?,
             _ => return Err(crate::Error::Config(format!("Unknown migration id: {}", id))),
         }
 
@@ -1428,3 +1454,469 @@ async fn encrypt_sensitive_data(pool: &SqlitePool) -> Result<()> {
 
     Ok(())
 }
+
+/// Migration 25: Create session_checkpoint table for resuming sessions after a restart
+///
+/// Holds a single row with the serialized in-memory session state (active session,
+/// timer pause accounting, strict mode and nuclear option).
+async fn create_session_checkpoint_table(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS session_checkpoint (
+            id INTEGER PRIMARY KEY CHECK(id = 1),
+            data TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 26: Create tamper_events table for logging attempts to circumvent blocking
+async fn create_tamper_events_table(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tamper_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_type TEXT NOT NULL,
+            details TEXT,
+            detected_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_tamper_events_detected_at
+        ON tamper_events(detected_at)
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 28: Add per-item enforcement policy to blocked_items
+///
+/// Existing items keep the previous behavior of closing the app.
+async fn add_enforcement_to_blocked_items(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        ALTER TABLE blocked_items
+        ADD COLUMN enforcement TEXT NOT NULL DEFAULT 'kill'
+        CHECK(enforcement IN ('kill', 'warn-only', 'delay'))
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 29: What blocked app rules match against
+///
+/// `name` keeps the existing process name matching; the other kinds match the
+/// executable path, the full command line, the executable's SHA-256 or a
+/// Linux desktop entry ID. `match_type` still applies to the chosen target.
+async fn add_rule_kind_to_blocked_items(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        ALTER TABLE blocked_items
+        ADD COLUMN rule_kind TEXT NOT NULL DEFAULT 'name'
+        CHECK(rule_kind IN ('name', 'exe_path', 'cmdline', 'exe_hash', 'desktop_id'))
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 30: Record what happened to blocked apps
+///
+/// `outcome` is killed, survived, protected, warned or delayed;
+/// `termination_result` holds the per-PID termination report as JSON.
+async fn add_outcome_to_block_attempts(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("ALTER TABLE block_attempts ADD COLUMN outcome TEXT")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("ALTER TABLE block_attempts ADD COLUMN termination_result TEXT")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Migration 31: Create tables for daily time budgets per app or website
+///
+/// `days_of_week` is a JSON array (0 = Sunday) of the days a budget applies.
+/// Usage is kept per budget day, which starts at the configured local boundary.
+async fn create_time_budget_tables(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS time_budgets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            item_type TEXT NOT NULL CHECK(item_type IN ('app', 'website')),
+            value TEXT NOT NULL,
+            daily_limit_minutes INTEGER NOT NULL CHECK(daily_limit_minutes > 0),
+            days_of_week TEXT NOT NULL DEFAULT '[0,1,2,3,4,5,6]',
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(item_type, value)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS time_budget_usage (
+            budget_id INTEGER NOT NULL REFERENCES time_budgets(id) ON DELETE CASCADE,
+            day TEXT NOT NULL,
+            used_seconds INTEGER NOT NULL DEFAULT 0,
+            warned_percent INTEGER NOT NULL DEFAULT 0,
+            exhausted_at TEXT,
+            PRIMARY KEY (budget_id, day)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 32: Create versioned focus profiles
+///
+/// Every edit adds a row to focus_profile_versions, and sessions record the
+/// version they started with, so editing a profile never rewrites history.
+/// Deleted profiles are archived for the same reason.
+async fn create_focus_profile_tables(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS focus_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            current_version INTEGER NOT NULL DEFAULT 1,
+            archived BOOLEAN NOT NULL DEFAULT 0,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS focus_profile_versions (
+            profile_id INTEGER NOT NULL REFERENCES focus_profiles(id),
+            version INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            planned_duration_minutes INTEGER NOT NULL,
+            blocked_apps TEXT NOT NULL DEFAULT '[]',
+            blocked_websites TEXT NOT NULL DEFAULT '[]',
+            allowed_websites TEXT NOT NULL DEFAULT '[]',
+            enable_dimming BOOLEAN NOT NULL DEFAULT 0,
+            dimming_opacity REAL NOT NULL DEFAULT 0.7,
+            pause_notifications BOOLEAN NOT NULL DEFAULT 0,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (profile_id, version)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("ALTER TABLE sessions ADD COLUMN profile_id INTEGER")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("ALTER TABLE sessions ADD COLUMN profile_version INTEGER")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("ALTER TABLE blocking_schedules ADD COLUMN profile_id INTEGER")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Migration 33: Create projects and tags for sessions
+///
+/// A session belongs to at most one project and may have a task title and any
+/// number of tags. Names are unique regardless of case.
+async fn create_project_tag_tables(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT,
+            archived BOOLEAN NOT NULL DEFAULT 0,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS session_tags (
+            session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (session_id, tag_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("ALTER TABLE sessions ADD COLUMN project_id INTEGER REFERENCES projects(id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("ALTER TABLE sessions ADD COLUMN task_title TEXT")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_sessions_project ON sessions(project_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag_id)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Migration 34: Create the session event log
+///
+/// Pauses, resumes, extensions and early ends are logged with an optional
+/// reason. `minutes` holds the minutes added by an extension or left when a
+/// session ended early. Each finished session gets an interruption score.
+async fn create_session_events_table(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS session_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            event_type TEXT NOT NULL CHECK (event_type IN ('pause', 'resume', 'extend', 'end_early')),
+            occurred_at TEXT NOT NULL,
+            reason TEXT,
+            minutes INTEGER,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_session_events_session ON session_events(session_id, occurred_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("ALTER TABLE sessions ADD COLUMN interruption_score REAL")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Migration 35: Create the website visit log
+///
+/// Visited domains reported by the browser extension, totalled per day.
+/// `focus_seconds` is the part of `active_seconds` spent during a focus session.
+async fn create_website_visits_table(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS website_visits (
+            date TEXT NOT NULL,
+            domain TEXT NOT NULL,
+            visits INTEGER NOT NULL DEFAULT 0,
+            active_seconds INTEGER NOT NULL DEFAULT 0,
+            focus_seconds INTEGER NOT NULL DEFAULT 0,
+            last_visited_at TEXT NOT NULL,
+            PRIMARY KEY (date, domain)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 36: Create blocklist subscriptions
+///
+/// Lists imported from a URL or file into a blocking category and refreshed
+/// periodically. `domains` is the JSON array of domains the list added to the
+/// category, the only ones a refresh may remove again.
+async fn create_blocklist_subscriptions_table(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS blocklist_subscriptions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            category_id INTEGER NOT NULL REFERENCES blocking_categories(id) ON DELETE CASCADE,
+            source TEXT NOT NULL,
+            refresh_interval_hours INTEGER NOT NULL DEFAULT 24,
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            domains TEXT NOT NULL DEFAULT '[]',
+            last_refreshed_at TEXT,
+            last_error TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (category_id, source)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 37: Let blocked app rules of different kinds share a value
+///
+/// A name rule and an exe_path rule (or an exact and a regex rule) for the
+/// same value are different rules, so the unique key includes match_type and
+/// rule_kind. SQLite cannot change a table constraint in place, so the table
+/// is rebuilt.
+async fn widen_blocked_items_unique_key(pool: &SqlitePool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE blocked_items_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            item_type TEXT NOT NULL CHECK(item_type IN ('app', 'website')),
+            value TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            user_id TEXT,
+            device_id TEXT,
+            synced_at TEXT,
+            last_modified TEXT DEFAULT CURRENT_TIMESTAMP,
+            deleted BOOLEAN DEFAULT 0,
+            match_type TEXT NOT NULL DEFAULT 'exact'
+                CHECK(match_type IN ('exact', 'contains', 'regex')),
+            enforcement TEXT NOT NULL DEFAULT 'kill'
+                CHECK(enforcement IN ('kill', 'warn-only', 'delay')),
+            rule_kind TEXT NOT NULL DEFAULT 'name'
+                CHECK(rule_kind IN ('name', 'exe_path', 'cmdline', 'exe_hash', 'desktop_id')),
+            UNIQUE(item_type, value, match_type, rule_kind)
+        )
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO blocked_items_new (
+            id, item_type, value, enabled, created_at, user_id, device_id,
+            synced_at, last_modified, deleted, match_type, enforcement, rule_kind
+        )
+        SELECT
+            id, item_type, value, enabled, created_at, user_id, device_id,
+            synced_at, last_modified, deleted, match_type, enforcement, rule_kind
+        FROM blocked_items
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DROP TABLE blocked_items").execute(&mut *tx).await?;
+    sqlx::query("ALTER TABLE blocked_items_new RENAME TO blocked_items")
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_blocked_items_type_enabled
+        ON blocked_items(item_type, enabled)
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Migration 38: Allow idle pauses in the session event log
+///
+/// Pauses for idle time, screen lock and sleep get their own event type so
+/// they aren't scored as interruptions. SQLite can't alter a CHECK constraint,
+/// so the table is rebuilt.
+async fn add_idle_pause_session_event(pool: &SqlitePool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE session_events_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            event_type TEXT NOT NULL
+                CHECK (event_type IN ('pause', 'idle_pause', 'resume', 'extend', 'end_early')),
+            occurred_at TEXT NOT NULL,
+            reason TEXT,
+            minutes INTEGER,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO session_events_new (id, session_id, event_type, occurred_at, reason, minutes, created_at)
+        SELECT id, session_id, event_type, occurred_at, reason, minutes, created_at
+        FROM session_events
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DROP TABLE session_events").execute(&mut *tx).await?;
+    sqlx::query("ALTER TABLE session_events_new RENAME TO session_events")
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_session_events_session ON session_events(session_id, occurred_at)",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
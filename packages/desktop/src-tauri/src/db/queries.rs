@@ -17,6 +17,81 @@ pub struct Session {
     pub session_type: String,
     pub completed: bool,
     pub notes: Option<String>,
+    pub project_id: Option<i64>,
+    pub task_title: Option<String>,
+    /// 0 (uninterrupted) to 100, set when the session ends
+    pub interruption_score: Option<f64>,
+    /// Filled in by `attach_session_tags`
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Project that sessions can be attributed to
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    pub archived: bool,
+}
+
+/// Tag with the number of sessions using it
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUsage {
+    pub id: i64,
+    pub name: String,
+    pub session_count: i64,
+}
+
+/// Pause, resume, extension or early end of a session
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEvent {
+    pub id: i64,
+    pub session_id: String,
+    /// "pause", "idle_pause", "resume", "extend" or "end_early"
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    /// Minutes added by an extension, or left when the session ended early
+    pub minutes: Option<i32>,
+}
+
+/// Average interruption score of the sessions that ended on one day
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DailyInterruption {
+    pub day: String,
+    pub average_score: f64,
+    pub sessions: i64,
+}
+
+/// Filters for session history
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    pub project_id: Option<i64>,
+    pub tag: Option<String>,
+}
+
+/// Focus time of one project (or of unassigned sessions) on one day
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ProjectFocusTime {
+    pub day: String,
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub focus_seconds: i64,
+    pub sessions: i64,
+}
+
+/// Focus time of one tag on one day
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TagFocusTime {
+    pub day: String,
+    pub tag: String,
+    pub focus_seconds: i64,
+    pub sessions: i64,
 }
 
 /// Blocked item database model
@@ -28,6 +103,12 @@ pub struct BlockedItem {
     pub enabled: bool,
     #[serde(default = "default_match_type")]
     pub match_type: String,
+    /// What happens when a blocked app is detected ("kill", "warn-only" or "delay")
+    #[serde(default = "default_enforcement")]
+    pub enforcement: String,
+    /// What an app rule matches ("name", "exe_path", "cmdline", "exe_hash" or "desktop_id")
+    #[serde(default = "default_rule_kind")]
+    pub rule_kind: String,
 }
 
 /// Default match type for backward compatibility
@@ -35,6 +116,147 @@ fn default_match_type() -> String {
     "exact".to_string()
 }
 
+/// Default enforcement for backward compatibility
+fn default_enforcement() -> String {
+    "kill".to_string()
+}
+
+/// Default rule kind for backward compatibility
+fn default_rule_kind() -> String {
+    "name".to_string()
+}
+
+/// Daily time budget database model
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TimeBudget {
+    pub id: i64,
+    pub item_type: String,
+    pub value: String,
+    pub daily_limit_minutes: i64,
+    /// JSON array of weekdays the budget applies (0 = Sunday)
+    pub days_of_week: String,
+    pub enabled: bool,
+}
+
+/// Time used against a budget on one budget day
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TimeBudgetUsage {
+    pub budget_id: i64,
+    pub day: String,
+    pub used_seconds: i64,
+    /// Highest warning threshold already notified (percent of the limit)
+    pub warned_percent: i64,
+    pub exhausted_at: Option<DateTime<Utc>>,
+}
+
+/// One saved version of a focus profile
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FocusProfileVersion {
+    pub profile_id: i64,
+    pub version: i64,
+    pub name: String,
+    pub planned_duration_minutes: i32,
+    /// JSON arrays
+    pub blocked_apps: String,
+    pub blocked_websites: String,
+    pub allowed_websites: String,
+    pub enable_dimming: bool,
+    pub dimming_opacity: f64,
+    pub pause_notifications: bool,
+}
+
+/// Current version of a focus profile, with its lists parsed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusProfile {
+    pub id: i64,
+    pub version: i64,
+    pub name: String,
+    pub planned_duration_minutes: i32,
+    pub blocked_apps: Vec<String>,
+    pub blocked_websites: Vec<String>,
+    pub allowed_websites: Vec<String>,
+    pub enable_dimming: bool,
+    pub dimming_opacity: f32,
+    pub pause_notifications: bool,
+}
+
+impl From<FocusProfileVersion> for FocusProfile {
+    fn from(row: FocusProfileVersion) -> Self {
+        let parse = |json: &str| serde_json::from_str::<Vec<String>>(json).unwrap_or_default();
+
+        Self {
+            id: row.profile_id,
+            version: row.version,
+            blocked_apps: parse(&row.blocked_apps),
+            blocked_websites: parse(&row.blocked_websites),
+            allowed_websites: parse(&row.allowed_websites),
+            name: row.name,
+            planned_duration_minutes: row.planned_duration_minutes,
+            enable_dimming: row.enable_dimming,
+            dimming_opacity: row.dimming_opacity as f32,
+            pause_notifications: row.pause_notifications,
+        }
+    }
+}
+
+/// Time spent on a website, as reported by the browser extension
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct WebsiteVisitTotals {
+    pub domain: String,
+    pub visits: i64,
+    pub active_seconds: i64,
+    /// Part of `active_seconds` spent during focus sessions
+    pub focus_seconds: i64,
+}
+
+/// A blocklist imported from a URL or file and refreshed periodically
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BlocklistSubscription {
+    pub id: i64,
+    pub category_id: i64,
+    pub category_name: String,
+    pub source: String,
+    pub refresh_interval_hours: i64,
+    pub enabled: bool,
+    /// JSON array of the domains the list added to its category
+    #[serde(skip)]
+    pub domains: String,
+    pub domain_count: i64,
+    pub last_refreshed_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+/// Blocking category; `items` holds domains and app names
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BlockingCategory {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub items: String, // JSON array stored as string
+    pub enabled: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Recurring weekly blocking window
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BlockingSchedule {
+    pub id: i64,
+    pub user_id: Option<String>,
+    pub day_of_week: i32,
+    pub start_time: String,
+    pub end_time: String,
+    pub enabled: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Focus profile applied while the schedule is active
+    pub profile_id: Option<i64>,
+}
+
 /// Daily analytics database model
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct DailyAnalytics {
@@ -100,6 +322,36 @@ pub async fn end_session(
     Ok(())
 }
 
+/// End a session with an explicit duration
+///
+/// Used when reconciling a session that ran out while the app wasn't running,
+/// where wall-clock time since start would overstate the focused time.
+pub async fn end_session_with_duration(
+    pool: &SqlitePool,
+    id: &str,
+    end_time: DateTime<Utc>,
+    actual_duration_seconds: i64,
+    completed: bool,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE sessions
+        SET end_time = ?,
+            actual_duration_seconds = ?,
+            completed = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(end_time)
+    .bind(actual_duration_seconds)
+    .bind(completed)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 /// Update planned duration for an active session (for session extension)
 pub async fn update_session_duration(
     pool: &SqlitePool,
@@ -156,6 +408,75 @@ pub async fn get_sessions_in_range(
     Ok(sessions)
 }
 
+/// Get sessions within date range matching a filter, with their tags
+pub async fn get_sessions_filtered(
+    pool: &SqlitePool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    filter: &SessionFilter,
+) -> Result<Vec<Session>> {
+    let mut query = String::from("SELECT s.* FROM sessions s WHERE s.start_time >= ? AND s.start_time <= ?");
+
+    if filter.project_id.is_some() {
+        query.push_str(" AND s.project_id = ?");
+    }
+    if filter.tag.is_some() {
+        query.push_str(
+            " AND EXISTS (SELECT 1 FROM session_tags st JOIN tags t ON t.id = st.tag_id \
+             WHERE st.session_id = s.id AND t.name = ?)",
+        );
+    }
+    query.push_str(" ORDER BY s.start_time DESC");
+
+    let mut sql_query = sqlx::query_as::<_, Session>(&query).bind(start).bind(end);
+    if let Some(project_id) = filter.project_id {
+        sql_query = sql_query.bind(project_id);
+    }
+    if let Some(tag) = &filter.tag {
+        sql_query = sql_query.bind(tag.trim());
+    }
+
+    let mut sessions = sql_query.fetch_all(pool).await?;
+    attach_session_tags(pool, &mut sessions).await?;
+
+    Ok(sessions)
+}
+
+/// Fill in the tags of the given sessions
+pub async fn attach_session_tags(pool: &SqlitePool, sessions: &mut [Session]) -> Result<()> {
+    if sessions.is_empty() {
+        return Ok(());
+    }
+
+    let placeholders = vec!["?"; sessions.len()].join(", ");
+    let query = format!(
+        r#"
+        SELECT st.session_id, t.name
+        FROM session_tags st
+        JOIN tags t ON t.id = st.tag_id
+        WHERE st.session_id IN ({})
+        ORDER BY t.name COLLATE NOCASE
+        "#,
+        placeholders
+    );
+
+    let mut sql_query = sqlx::query_as::<_, (String, String)>(&query);
+    for session in sessions.iter() {
+        sql_query = sql_query.bind(&session.id);
+    }
+
+    let rows = sql_query.fetch_all(pool).await?;
+    for session in sessions.iter_mut() {
+        session.tags = rows
+            .iter()
+            .filter(|(session_id, _)| *session_id == session.id)
+            .map(|(_, tag)| tag.clone())
+            .collect();
+    }
+
+    Ok(())
+}
+
 /// Count sessions started today (for free tier limit enforcement)
 /// Returns the number of sessions that have been started today, regardless of completion status
 pub async fn count_todays_sessions(pool: &SqlitePool) -> Result<i64> {
@@ -174,6 +495,50 @@ pub async fn count_todays_sessions(pool: &SqlitePool) -> Result<i64> {
     Ok(count.0)
 }
 
+// ============================================================================
+// Session Checkpoint Queries
+// ============================================================================
+
+/// Persisted snapshot of in-memory session state
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SessionCheckpointRow {
+    /// Serialized `SessionCheckpoint` JSON
+    pub data: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Save the session checkpoint, replacing any previous one
+pub async fn save_session_checkpoint(pool: &SqlitePool, data: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO session_checkpoint (id, data, updated_at)
+        VALUES (1, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            data = excluded.data,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(data)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Load the last saved session checkpoint
+pub async fn load_session_checkpoint(pool: &SqlitePool) -> Result<Option<SessionCheckpointRow>> {
+    let row = sqlx::query_as::<_, SessionCheckpointRow>(
+        r#"
+        SELECT data, updated_at FROM session_checkpoint WHERE id = 1
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
 // ============================================================================
 // Blocked Items Queries
 // ============================================================================
@@ -198,13 +563,41 @@ pub async fn insert_blocked_item_with_match_type(
         r#"
         INSERT INTO blocked_items (item_type, value, match_type)
         VALUES (?, ?, ?)
-        ON CONFLICT(item_type, value) DO UPDATE SET enabled = 1, match_type = ?
+        ON CONFLICT(item_type, value, match_type, rule_kind) DO UPDATE SET enabled = 1
         "#,
     )
     .bind(item_type)
     .bind(value)
     .bind(match_type)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Insert or re-enable a blocked app rule with its enforcement policy
+///
+/// Re-adding an existing rule only re-enables it: its enforcement is changed
+/// through `set_blocked_item_enforcement`, which honours the locks, so adding
+/// can never weaken a rule.
+pub async fn insert_blocked_app_rule(
+    pool: &SqlitePool,
+    value: &str,
+    match_type: &str,
+    rule_kind: &str,
+    enforcement: &str,
+) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO blocked_items (item_type, value, match_type, rule_kind, enforcement)
+        VALUES ('app', ?, ?, ?, ?)
+        ON CONFLICT(item_type, value, match_type, rule_kind) DO UPDATE SET enabled = 1
+        "#,
+    )
+    .bind(value)
     .bind(match_type)
+    .bind(rule_kind)
+    .bind(enforcement)
     .execute(pool)
     .await?;
 
@@ -232,6 +625,31 @@ pub async fn remove_blocked_item(
     Ok(())
 }
 
+/// Set the enforcement policy of a blocked item
+///
+/// Returns false if no enabled item matches.
+pub async fn set_blocked_item_enforcement(
+    pool: &SqlitePool,
+    item_type: &str,
+    value: &str,
+    enforcement: &str,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE blocked_items
+        SET enforcement = ?
+        WHERE item_type = ? AND value = ? AND enabled = 1
+        "#,
+    )
+    .bind(enforcement)
+    .bind(item_type)
+    .bind(value)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Get all enabled blocked items of a specific type
 pub async fn get_blocked_items(
     pool: &SqlitePool,
@@ -284,7 +702,7 @@ pub async fn insert_blocked_items_batch(
         query_str.push_str("(?, ?, 'exact')");
     }
 
-    query_str.push_str(" ON CONFLICT(item_type, value) DO UPDATE SET enabled = 1");
+    query_str.push_str(" ON CONFLICT(item_type, value, match_type, rule_kind) DO UPDATE SET enabled = 1");
 
     let mut query = sqlx::query(&query_str);
 
@@ -298,6 +716,109 @@ pub async fn insert_blocked_items_batch(
     Ok(())
 }
 
+// ============================================================================
+// Blocking Schedule Queries
+// ============================================================================
+
+/// Get all enabled blocking schedules
+pub async fn get_enabled_blocking_schedules(
+    pool: &SqlitePool,
+) -> Result<Vec<BlockingSchedule>> {
+    let schedules = sqlx::query_as::<_, BlockingSchedule>(
+        r#"
+        SELECT * FROM blocking_schedules
+        WHERE enabled = 1
+        ORDER BY day_of_week, start_time
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(schedules)
+}
+
+// ============================================================================
+// Blocking Category Queries
+// ============================================================================
+
+/// Get all enabled blocking categories
+pub async fn get_enabled_blocking_categories(
+    pool: &SqlitePool,
+) -> Result<Vec<BlockingCategory>> {
+    let categories = sqlx::query_as::<_, BlockingCategory>(
+        r#"
+        SELECT * FROM blocking_categories
+        WHERE enabled = 1
+        ORDER BY name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(categories)
+}
+
+/// Get a blocking category by id
+pub async fn get_blocking_category(pool: &SqlitePool, id: i64) -> Result<Option<BlockingCategory>> {
+    let category = sqlx::query_as::<_, BlockingCategory>("SELECT * FROM blocking_categories WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(category)
+}
+
+/// Get a blocking category by name (case-insensitive)
+pub async fn get_blocking_category_by_name(pool: &SqlitePool, name: &str) -> Result<Option<BlockingCategory>> {
+    let category = sqlx::query_as::<_, BlockingCategory>(
+        "SELECT * FROM blocking_categories WHERE name = ? COLLATE NOCASE",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(category)
+}
+
+/// Create a blocking category, returning its id
+pub async fn insert_blocking_category(
+    pool: &SqlitePool,
+    name: &str,
+    description: Option<&str>,
+    items_json: &str,
+) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO blocking_categories (name, description, items)
+        VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .bind(items_json)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Replace the items of a blocking category
+pub async fn set_blocking_category_items(pool: &SqlitePool, id: i64, items_json: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE blocking_categories
+        SET items = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(items_json)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 // ============================================================================
 // Analytics Queries
 // ============================================================================
@@ -1025,3 +1546,1003 @@ pub async fn record_block_attempt(
 
     Ok(result.last_insert_rowid())
 }
+
+/// Record a block attempt of an app along with what happened to it
+pub async fn record_block_attempt_with_outcome(
+    pool: &SqlitePool,
+    item_type: &str,
+    item_value: &str,
+    session_id: Option<&str>,
+    user_id: Option<&str>,
+    outcome: &str,
+    termination_result: Option<&str>,
+) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO block_attempts (user_id, item_type, item_value, session_id, outcome, termination_result)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(user_id)
+    .bind(item_type)
+    .bind(item_value)
+    .bind(session_id)
+    .bind(outcome)
+    .bind(termination_result)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+// ============================================================================
+// Time Budget Queries
+// ============================================================================
+
+/// Get all time budgets
+pub async fn get_time_budgets(pool: &SqlitePool) -> Result<Vec<TimeBudget>> {
+    let budgets = sqlx::query_as::<_, TimeBudget>(
+        r#"
+        SELECT id, item_type, value, daily_limit_minutes, days_of_week, enabled
+        FROM time_budgets
+        ORDER BY item_type, value
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(budgets)
+}
+
+/// Get a time budget by ID
+pub async fn get_time_budget(pool: &SqlitePool, id: i64) -> Result<Option<TimeBudget>> {
+    let budget = sqlx::query_as::<_, TimeBudget>(
+        r#"
+        SELECT id, item_type, value, daily_limit_minutes, days_of_week, enabled
+        FROM time_budgets
+        WHERE id = ?
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(budget)
+}
+
+/// Insert a time budget, returning its ID
+pub async fn insert_time_budget(
+    pool: &SqlitePool,
+    item_type: &str,
+    value: &str,
+    daily_limit_minutes: i64,
+    days_of_week: &str,
+) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO time_budgets (item_type, value, daily_limit_minutes, days_of_week)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(item_type)
+    .bind(value)
+    .bind(daily_limit_minutes)
+    .bind(days_of_week)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Update the limit, days and enabled flag of a time budget
+///
+/// Returns false if no budget has the given ID.
+pub async fn update_time_budget(
+    pool: &SqlitePool,
+    id: i64,
+    daily_limit_minutes: i64,
+    days_of_week: &str,
+    enabled: bool,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE time_budgets
+        SET daily_limit_minutes = ?, days_of_week = ?, enabled = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(daily_limit_minutes)
+    .bind(days_of_week)
+    .bind(enabled)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a time budget and its usage
+pub async fn delete_time_budget(pool: &SqlitePool, id: i64) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM time_budgets WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the usage of every budget on a budget day
+pub async fn get_time_budget_usage(pool: &SqlitePool, day: &str) -> Result<Vec<TimeBudgetUsage>> {
+    let usage = sqlx::query_as::<_, TimeBudgetUsage>(
+        r#"
+        SELECT budget_id, day, used_seconds, warned_percent, exhausted_at
+        FROM time_budget_usage
+        WHERE day = ?
+        "#,
+    )
+    .bind(day)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(usage)
+}
+
+/// Add used time to a budget day, returning the updated usage
+pub async fn add_time_budget_usage(
+    pool: &SqlitePool,
+    budget_id: i64,
+    day: &str,
+    seconds: i64,
+) -> Result<TimeBudgetUsage> {
+    sqlx::query(
+        r#"
+        INSERT INTO time_budget_usage (budget_id, day, used_seconds)
+        VALUES (?, ?, ?)
+        ON CONFLICT(budget_id, day) DO UPDATE SET used_seconds = used_seconds + excluded.used_seconds
+        "#,
+    )
+    .bind(budget_id)
+    .bind(day)
+    .bind(seconds)
+    .execute(pool)
+    .await?;
+
+    let usage = sqlx::query_as::<_, TimeBudgetUsage>(
+        r#"
+        SELECT budget_id, day, used_seconds, warned_percent, exhausted_at
+        FROM time_budget_usage
+        WHERE budget_id = ? AND day = ?
+        "#,
+    )
+    .bind(budget_id)
+    .bind(day)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(usage)
+}
+
+/// Record the warning threshold reached and, once the budget is used up, when
+pub async fn update_time_budget_status(
+    pool: &SqlitePool,
+    budget_id: i64,
+    day: &str,
+    warned_percent: i64,
+    exhausted_at: Option<DateTime<Utc>>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE time_budget_usage
+        SET warned_percent = ?, exhausted_at = COALESCE(exhausted_at, ?)
+        WHERE budget_id = ? AND day = ?
+        "#,
+    )
+    .bind(warned_percent)
+    .bind(exhausted_at)
+    .bind(budget_id)
+    .bind(day)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Focus Profile Queries
+// ============================================================================
+
+const FOCUS_PROFILE_VERSION_COLUMNS: &str = "v.profile_id, v.version, v.name, v.planned_duration_minutes, \
+     v.blocked_apps, v.blocked_websites, v.allowed_websites, v.enable_dimming, v.dimming_opacity, \
+     v.pause_notifications";
+
+/// Get the current version of every focus profile that isn't deleted
+pub async fn get_focus_profiles(pool: &SqlitePool) -> Result<Vec<FocusProfileVersion>> {
+    let profiles = sqlx::query_as::<_, FocusProfileVersion>(&format!(
+        r#"
+        SELECT {}
+        FROM focus_profiles p
+        JOIN focus_profile_versions v ON v.profile_id = p.id AND v.version = p.current_version
+        WHERE p.archived = 0
+        ORDER BY v.name COLLATE NOCASE
+        "#,
+        FOCUS_PROFILE_VERSION_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(profiles)
+}
+
+/// Get the current version of a focus profile that isn't deleted
+pub async fn get_focus_profile(pool: &SqlitePool, id: i64) -> Result<Option<FocusProfileVersion>> {
+    let profile = sqlx::query_as::<_, FocusProfileVersion>(&format!(
+        r#"
+        SELECT {}
+        FROM focus_profiles p
+        JOIN focus_profile_versions v ON v.profile_id = p.id AND v.version = p.current_version
+        WHERE p.id = ? AND p.archived = 0
+        "#,
+        FOCUS_PROFILE_VERSION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(profile)
+}
+
+/// Get every version of a focus profile, newest first (deleted profiles included)
+pub async fn get_focus_profile_versions(pool: &SqlitePool, id: i64) -> Result<Vec<FocusProfileVersion>> {
+    let versions = sqlx::query_as::<_, FocusProfileVersion>(&format!(
+        r#"
+        SELECT {}
+        FROM focus_profile_versions v
+        WHERE v.profile_id = ?
+        ORDER BY v.version DESC
+        "#,
+        FOCUS_PROFILE_VERSION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(versions)
+}
+
+/// Insert a version row; `profile.profile_id` and `profile.version` pick the row
+async fn insert_focus_profile_version_row(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    profile: &FocusProfileVersion,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO focus_profile_versions (
+            profile_id, version, name, planned_duration_minutes, blocked_apps, blocked_websites,
+            allowed_websites, enable_dimming, dimming_opacity, pause_notifications
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(profile.profile_id)
+    .bind(profile.version)
+    .bind(&profile.name)
+    .bind(profile.planned_duration_minutes)
+    .bind(&profile.blocked_apps)
+    .bind(&profile.blocked_websites)
+    .bind(&profile.allowed_websites)
+    .bind(profile.enable_dimming)
+    .bind(profile.dimming_opacity)
+    .bind(profile.pause_notifications)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Create a focus profile with its first version, returning the profile ID
+///
+/// `profile_id` and `version` of the given settings are ignored.
+pub async fn insert_focus_profile(pool: &SqlitePool, profile: &FocusProfileVersion) -> Result<i64> {
+    let mut tx = pool.begin().await?;
+
+    let id = sqlx::query("INSERT INTO focus_profiles (current_version) VALUES (1)")
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+    let first = FocusProfileVersion {
+        profile_id: id,
+        version: 1,
+        ..profile.clone()
+    };
+    insert_focus_profile_version_row(&mut tx, &first).await?;
+
+    tx.commit().await?;
+
+    Ok(id)
+}
+
+/// Save new settings as the next version of a focus profile, returning the version
+///
+/// Returns None if the profile doesn't exist or was deleted.
+pub async fn insert_focus_profile_version(
+    pool: &SqlitePool,
+    id: i64,
+    profile: &FocusProfileVersion,
+) -> Result<Option<i64>> {
+    let mut tx = pool.begin().await?;
+
+    let current: Option<(i64,)> =
+        sqlx::query_as("SELECT current_version FROM focus_profiles WHERE id = ? AND archived = 0")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some((current,)) = current else {
+        return Ok(None);
+    };
+
+    let next = FocusProfileVersion {
+        profile_id: id,
+        version: current + 1,
+        ..profile.clone()
+    };
+    insert_focus_profile_version_row(&mut tx, &next).await?;
+
+    sqlx::query(
+        "UPDATE focus_profiles SET current_version = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+    )
+    .bind(next.version)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(next.version))
+}
+
+/// Delete a focus profile, keeping its versions for past sessions
+///
+/// Schedules using the profile fall back to the regular blocklist.
+pub async fn archive_focus_profile(pool: &SqlitePool, id: i64) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        "UPDATE focus_profiles SET archived = 1, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND archived = 0",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE blocking_schedules SET profile_id = NULL WHERE profile_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record which profile version a session was started with
+pub async fn set_session_profile(
+    pool: &SqlitePool,
+    session_id: &str,
+    profile_id: i64,
+    profile_version: i64,
+) -> Result<()> {
+    sqlx::query("UPDATE sessions SET profile_id = ?, profile_version = ? WHERE id = ?")
+        .bind(profile_id)
+        .bind(profile_version)
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Project and Tag Queries
+// ============================================================================
+
+/// Get projects ordered by name, optionally including archived ones
+pub async fn get_projects(pool: &SqlitePool, include_archived: bool) -> Result<Vec<Project>> {
+    let projects = sqlx::query_as::<_, Project>(
+        r#"
+        SELECT id, name, color, archived FROM projects
+        WHERE archived = 0 OR ?
+        ORDER BY name COLLATE NOCASE
+        "#,
+    )
+    .bind(include_archived)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(projects)
+}
+
+/// Get a project by ID
+pub async fn get_project(pool: &SqlitePool, id: i64) -> Result<Option<Project>> {
+    let project = sqlx::query_as::<_, Project>(
+        "SELECT id, name, color, archived FROM projects WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(project)
+}
+
+/// Insert a project, returning its ID
+pub async fn insert_project(pool: &SqlitePool, name: &str, color: Option<&str>) -> Result<i64> {
+    let result = sqlx::query("INSERT INTO projects (name, color) VALUES (?, ?)")
+        .bind(name)
+        .bind(color)
+        .execute(pool)
+        .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Update a project's name, color and archived flag
+pub async fn update_project(
+    pool: &SqlitePool,
+    id: i64,
+    name: &str,
+    color: Option<&str>,
+    archived: bool,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE projects
+        SET name = ?, color = ?, archived = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(name)
+    .bind(color)
+    .bind(archived)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get all tags with how many sessions use them
+pub async fn get_tags(pool: &SqlitePool) -> Result<Vec<TagUsage>> {
+    let tags = sqlx::query_as::<_, TagUsage>(
+        r#"
+        SELECT t.id, t.name, COUNT(st.session_id) AS session_count
+        FROM tags t
+        LEFT JOIN session_tags st ON st.tag_id = t.id
+        GROUP BY t.id
+        ORDER BY t.name COLLATE NOCASE
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tags)
+}
+
+/// Delete a tag, removing it from every session
+pub async fn delete_tag(pool: &SqlitePool, id: i64) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM session_tags WHERE tag_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace the project, task title and tags of a session
+///
+/// Tags are created on first use.
+pub async fn set_session_metadata(
+    pool: &SqlitePool,
+    session_id: &str,
+    project_id: Option<i64>,
+    task_title: Option<&str>,
+    tags: &[String],
+) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query("UPDATE sessions SET project_id = ?, task_title = ? WHERE id = ?")
+        .bind(project_id)
+        .bind(task_title)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("DELETE FROM session_tags WHERE session_id = ?")
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for tag in tags {
+        sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?)")
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO session_tags (session_id, tag_id)
+            SELECT ?, id FROM tags WHERE name = ?
+            "#,
+        )
+        .bind(session_id)
+        .bind(tag)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Focus time per project and day for finished, non-break sessions
+///
+/// Days are the UTC date the session ended, matching `daily_analytics`.
+/// Sessions without a project are reported with a None project.
+pub async fn get_project_focus_time(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<ProjectFocusTime>> {
+    let rows = sqlx::query_as::<_, ProjectFocusTime>(
+        r#"
+        SELECT date(s.end_time) AS day,
+               s.project_id,
+               p.name AS project_name,
+               COALESCE(SUM(s.actual_duration_seconds), 0) AS focus_seconds,
+               COUNT(*) AS sessions
+        FROM sessions s
+        LEFT JOIN projects p ON p.id = s.project_id
+        WHERE s.end_time IS NOT NULL
+          AND s.session_type != 'break'
+          AND date(s.end_time) BETWEEN ? AND ?
+        GROUP BY day, s.project_id
+        ORDER BY day, focus_seconds DESC
+        "#,
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Focus time per tag and day for finished, non-break sessions
+pub async fn get_tag_focus_time(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<TagFocusTime>> {
+    let rows = sqlx::query_as::<_, TagFocusTime>(
+        r#"
+        SELECT date(s.end_time) AS day,
+               t.name AS tag,
+               COALESCE(SUM(s.actual_duration_seconds), 0) AS focus_seconds,
+               COUNT(*) AS sessions
+        FROM sessions s
+        JOIN session_tags st ON st.session_id = s.id
+        JOIN tags t ON t.id = st.tag_id
+        WHERE s.end_time IS NOT NULL
+          AND s.session_type != 'break'
+          AND date(s.end_time) BETWEEN ? AND ?
+        GROUP BY day, t.id
+        ORDER BY day, focus_seconds DESC
+        "#,
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+// ============================================================================
+// Session Event Queries
+// ============================================================================
+
+/// Log a session event
+pub async fn insert_session_event(
+    pool: &SqlitePool,
+    session_id: &str,
+    event_type: &str,
+    occurred_at: DateTime<Utc>,
+    reason: Option<&str>,
+    minutes: Option<i32>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO session_events (session_id, event_type, occurred_at, reason, minutes)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(session_id)
+    .bind(event_type)
+    .bind(occurred_at)
+    .bind(reason)
+    .bind(minutes)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the events of a session in the order they happened
+pub async fn get_session_events(pool: &SqlitePool, session_id: &str) -> Result<Vec<SessionEvent>> {
+    let events = sqlx::query_as::<_, SessionEvent>(
+        r#"
+        SELECT id, session_id, event_type, occurred_at, reason, minutes
+        FROM session_events
+        WHERE session_id = ?
+        ORDER BY occurred_at, id
+        "#,
+    )
+    .bind(session_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
+
+/// Store the interruption score of a finished session
+pub async fn set_session_interruption_score(pool: &SqlitePool, session_id: &str, score: f64) -> Result<()> {
+    sqlx::query("UPDATE sessions SET interruption_score = ? WHERE id = ?")
+        .bind(score)
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Average interruption score per day (UTC date the sessions ended)
+pub async fn get_daily_interruption_scores(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<DailyInterruption>> {
+    let rows = sqlx::query_as::<_, DailyInterruption>(
+        r#"
+        SELECT date(end_time) AS day,
+               AVG(interruption_score) AS average_score,
+               COUNT(*) AS sessions
+        FROM sessions
+        WHERE interruption_score IS NOT NULL
+          AND date(end_time) BETWEEN ? AND ?
+        GROUP BY day
+        ORDER BY day
+        "#,
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Number of events of each type for sessions that ended in a date range
+pub async fn get_session_event_counts(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<(String, i64)>> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT e.event_type, COUNT(*)
+        FROM session_events e
+        JOIN sessions s ON s.id = e.session_id
+        WHERE date(s.end_time) BETWEEN ? AND ?
+        GROUP BY e.event_type
+        "#,
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Most common reasons given for pauses and early ends in a date range
+pub async fn get_top_interruption_reasons(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+    limit: i64,
+) -> Result<Vec<(String, i64)>> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT e.reason, COUNT(*) AS occurrences
+        FROM session_events e
+        JOIN sessions s ON s.id = e.session_id
+        WHERE e.event_type IN ('pause', 'end_early')
+          AND e.reason IS NOT NULL
+          AND date(s.end_time) BETWEEN ? AND ?
+        GROUP BY e.reason COLLATE NOCASE
+        ORDER BY occurrences DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+// ============================================================================
+// Website Visit Queries
+// ============================================================================
+
+/// Add a visit to a domain's totals for a day
+pub async fn add_website_visit(
+    pool: &SqlitePool,
+    date: &str,
+    domain: &str,
+    active_seconds: i64,
+    focus_seconds: i64,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO website_visits (date, domain, visits, active_seconds, focus_seconds, last_visited_at)
+        VALUES (?, ?, 1, ?, ?, ?)
+        ON CONFLICT(date, domain) DO UPDATE SET
+            visits = visits + 1,
+            active_seconds = active_seconds + excluded.active_seconds,
+            focus_seconds = focus_seconds + excluded.focus_seconds,
+            last_visited_at = excluded.last_visited_at
+        "#,
+    )
+    .bind(date)
+    .bind(domain)
+    .bind(active_seconds)
+    .bind(focus_seconds)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Most visited domains in a date range, by time spent
+pub async fn get_top_website_visits(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+    limit: i64,
+) -> Result<Vec<WebsiteVisitTotals>> {
+    let rows = sqlx::query_as::<_, WebsiteVisitTotals>(
+        r#"
+        SELECT domain,
+               SUM(visits) AS visits,
+               SUM(active_seconds) AS active_seconds,
+               SUM(focus_seconds) AS focus_seconds
+        FROM website_visits
+        WHERE date BETWEEN ? AND ?
+        GROUP BY domain
+        ORDER BY active_seconds DESC, visits DESC, domain
+        LIMIT ?
+        "#,
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+// ============================================================================
+// Blocklist Subscription Queries
+// ============================================================================
+
+const BLOCKLIST_SUBSCRIPTION_COLUMNS: &str = r#"
+    SELECT s.id, s.category_id, c.name AS category_name, s.source, s.refresh_interval_hours,
+           s.enabled, s.domains, json_array_length(s.domains) AS domain_count,
+           s.last_refreshed_at, s.last_error, s.created_at
+    FROM blocklist_subscriptions s
+    JOIN blocking_categories c ON c.id = s.category_id
+"#;
+
+/// Get all blocklist subscriptions
+pub async fn get_blocklist_subscriptions(pool: &SqlitePool) -> Result<Vec<BlocklistSubscription>> {
+    let subscriptions = sqlx::query_as::<_, BlocklistSubscription>(&format!(
+        "{} ORDER BY c.name, s.source",
+        BLOCKLIST_SUBSCRIPTION_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(subscriptions)
+}
+
+/// Get a blocklist subscription by id
+pub async fn get_blocklist_subscription(pool: &SqlitePool, id: i64) -> Result<Option<BlocklistSubscription>> {
+    let subscription = sqlx::query_as::<_, BlocklistSubscription>(&format!(
+        "{} WHERE s.id = ?",
+        BLOCKLIST_SUBSCRIPTION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(subscription)
+}
+
+/// Get the subscription importing a source into a category, if any
+pub async fn find_blocklist_subscription(
+    pool: &SqlitePool,
+    category_id: i64,
+    source: &str,
+) -> Result<Option<BlocklistSubscription>> {
+    let subscription = sqlx::query_as::<_, BlocklistSubscription>(&format!(
+        "{} WHERE s.category_id = ? AND s.source = ?",
+        BLOCKLIST_SUBSCRIPTION_COLUMNS
+    ))
+    .bind(category_id)
+    .bind(source)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(subscription)
+}
+
+/// Subscribe a category to a list, or update the interval of an existing subscription
+pub async fn upsert_blocklist_subscription(
+    pool: &SqlitePool,
+    category_id: i64,
+    source: &str,
+    refresh_interval_hours: i64,
+) -> Result<i64> {
+    sqlx::query(
+        r#"
+        INSERT INTO blocklist_subscriptions (category_id, source, refresh_interval_hours)
+        VALUES (?, ?, ?)
+        ON CONFLICT(category_id, source) DO UPDATE SET
+            refresh_interval_hours = excluded.refresh_interval_hours,
+            enabled = 1
+        "#,
+    )
+    .bind(category_id)
+    .bind(source)
+    .bind(refresh_interval_hours)
+    .execute(pool)
+    .await?;
+
+    let (id,): (i64,) = sqlx::query_as(
+        "SELECT id FROM blocklist_subscriptions WHERE category_id = ? AND source = ?",
+    )
+    .bind(category_id)
+    .bind(source)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// Record the domains fetched by a successful refresh
+pub async fn record_blocklist_refresh(pool: &SqlitePool, id: i64, domains_json: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE blocklist_subscriptions
+        SET domains = ?, last_refreshed_at = ?, last_error = NULL
+        WHERE id = ?
+        "#,
+    )
+    .bind(domains_json)
+    .bind(Utc::now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a failed refresh; the previous domains stay in place
+pub async fn record_blocklist_refresh_error(pool: &SqlitePool, id: i64, error: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE blocklist_subscriptions
+        SET last_refreshed_at = ?, last_error = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(Utc::now())
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Delete a blocklist subscription
+pub async fn delete_blocklist_subscription(pool: &SqlitePool, id: i64) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM blocklist_subscriptions WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// ============================================================================
+// User Settings Queries
+// ============================================================================
+
+/// Get a user setting value by key
+pub async fn get_user_setting(pool: &SqlitePool, key: &str) -> Result<Option<String>> {
+    let value: Option<(String,)> = sqlx::query_as("SELECT value FROM user_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(value.map(|(value,)| value))
+}
+
+/// Insert or update a user setting
+pub async fn set_user_setting(pool: &SqlitePool, key: &str, value: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_settings (key, value)
+        VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Tamper Event Queries
+// ============================================================================
+
+/// Record an attempt to circumvent blocking (clock changes, hosts edits, ...)
+pub async fn record_tamper_event(
+    pool: &SqlitePool,
+    event_type: &str,
+    details: &str,
+) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO tamper_events (event_type, details, detected_at)
+        VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(event_type)
+    .bind(details)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
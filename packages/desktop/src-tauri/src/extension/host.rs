@@ -0,0 +1,150 @@
+// extension/host.rs - Native messaging host
+//
+// Started by the browser for each extension connection. Messages from the
+// extension are forwarded to the app's bridge socket as JSON lines, and lines
+// from the app are framed for the browser. The host exits when either side
+// hangs up. Stdout belongs to the protocol, so diagnostics go to stderr.
+
+use super::{
+    default_socket_path, read_native_message, write_native_message, ExtensionMessage, MAX_MESSAGE_BYTES,
+};
+use crate::{Error, Result};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Entry point of the `focusflow-native-host` binary
+pub async fn run() -> Result<()> {
+    let mut stdout = tokio::io::stdout();
+
+    let connected = match default_socket_path() {
+        Ok(socket_path) => UnixStream::connect(&socket_path).await.map_err(|e| {
+            Error::System(format!(
+                "Could not connect to FocusFlow at {}: {}",
+                socket_path.display(),
+                e
+            ))
+        }),
+        Err(e) => Err(e),
+    };
+    let app = match connected {
+        Ok(app) => app,
+        Err(e) => {
+            // Tell the extension before going away, so it can show why
+            let message = ExtensionMessage::Error {
+                id: None,
+                message: "FocusFlow is not running".to_string(),
+            };
+            write_native_message(&mut stdout, &serde_json::to_vec(&message)?).await?;
+            return Err(e);
+        }
+    };
+
+    relay(tokio::io::stdin(), stdout, app).await
+}
+
+/// Shuttle messages between the browser and the app until either side closes
+pub async fn relay<I, O, A>(mut browser_in: I, mut browser_out: O, app: A) -> Result<()>
+where
+    I: AsyncRead + Unpin,
+    O: AsyncWrite + Unpin,
+    A: AsyncRead + AsyncWrite + Unpin,
+{
+    let (app_read, mut app_write) = tokio::io::split(app);
+
+    let to_app = async {
+        while let Some(message) = read_native_message(&mut browser_in).await? {
+            // Re-serializing guarantees a single line; the app validates the content
+            let value: serde_json::Value = match serde_json::from_slice(&message) {
+                Ok(value) => value,
+                Err(e) => {
+                    tracing::warn!("Dropping malformed message from the extension: {}", e);
+                    continue;
+                }
+            };
+
+            let mut line = serde_json::to_string(&value)?;
+            line.push('\n');
+            app_write.write_all(line.as_bytes()).await?;
+        }
+        Ok(())
+    };
+
+    let to_browser = async {
+        let mut reader = BufReader::new(app_read);
+        loop {
+            let mut line = String::new();
+            let read = (&mut reader)
+                .take(MAX_MESSAGE_BYTES as u64 + 1)
+                .read_line(&mut line)
+                .await?;
+            if read == 0 {
+                return Ok(());
+            }
+            if !line.ends_with('\n') {
+                return Err(Error::System("Message from FocusFlow is too large".to_string()));
+            }
+
+            write_native_message(&mut browser_out, line.trim_end().as_bytes()).await?;
+        }
+    };
+
+    tokio::select! {
+        result = to_app => result,
+        result = to_browser => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extension::ExtensionRequest;
+    use tokio::io::{Lines, ReadHalf};
+
+    async fn next_request<R: AsyncRead>(lines: &mut Lines<BufReader<ReadHalf<R>>>) -> ExtensionRequest {
+        let line = lines.next_line().await.unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_relay_forwards_both_ways() {
+        let (mut browser, host_stdin) = tokio::io::duplex(1024);
+        let (host_stdout, mut browser_reader) = tokio::io::duplex(1024);
+        let (host_app, app) = tokio::io::duplex(1024);
+
+        let relay = tokio::spawn(relay(host_stdin, host_stdout, host_app));
+
+        write_native_message(&mut browser, b"{\n\"type\": \"ping\"\n}").await.unwrap();
+        write_native_message(&mut browser, b"not json").await.unwrap();
+        write_native_message(&mut browser, br#"{"type":"visit","domain":"docs.rs"}"#)
+            .await
+            .unwrap();
+
+        let (app_read, mut app_write) = tokio::io::split(app);
+        let mut app_lines = BufReader::new(app_read).lines();
+        assert_eq!(next_request(&mut app_lines).await, ExtensionRequest::Ping);
+        assert_eq!(
+            next_request(&mut app_lines).await,
+            ExtensionRequest::Visit {
+                domain: "docs.rs".to_string(),
+                active_seconds: 0,
+            }
+        );
+
+        app_write
+            .write_all(b"{\"type\":\"pong\",\"protocolVersion\":1,\"appVersion\":\"0.1.0\"}\n")
+            .await
+            .unwrap();
+        let message = read_native_message(&mut browser_reader).await.unwrap().unwrap();
+        assert_eq!(
+            serde_json::from_slice::<ExtensionMessage>(&message).unwrap(),
+            ExtensionMessage::Pong {
+                protocol_version: 1,
+                app_version: "0.1.0".to_string(),
+            }
+        );
+
+        // The browser closing the port ends the host
+        drop(browser);
+        relay.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,392 @@
+// extension/manifest.rs - Native messaging host registration
+//
+// Browsers only start native hosts that are registered with a JSON manifest
+// in a per-browser directory. Chromium-based browsers identify extensions by
+// origin (`allowed_origins`), Firefox by add-on id (`allowed_extensions`).
+// Manifests are installed for the current user into every Linux browser
+// profile directory that exists.
+
+use super::NATIVE_HOST_NAME;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File name of the native host binary, installed next to the app
+pub const HOST_BINARY_NAME: &str = "focusflow-native-host";
+
+/// Which manifest format a browser reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserFamily {
+    Chromium,
+    Firefox,
+}
+
+/// A browser's native messaging host directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestTarget {
+    pub browser: &'static str,
+    pub family: BrowserFamily,
+    /// The browser's own config directory; nothing is installed if it's missing
+    pub browser_dir: PathBuf,
+    pub manifest_dir: PathBuf,
+}
+
+impl ManifestTarget {
+    pub fn manifest_path(&self) -> PathBuf {
+        self.manifest_dir.join(format!("{}.json", NATIVE_HOST_NAME))
+    }
+}
+
+/// Extensions allowed to start the host
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionIds {
+    /// Chrome Web Store style ids (32 letters a-p)
+    #[serde(default)]
+    pub chromium: Vec<String>,
+    /// Firefox add-on ids ("name@example.com" or "{uuid}")
+    #[serde(default)]
+    pub firefox: Vec<String>,
+}
+
+impl ExtensionIds {
+    pub fn validate(&self) -> Result<()> {
+        if self.chromium.is_empty() && self.firefox.is_empty() {
+            return Err(Error::Validation("At least one extension id is required".to_string()));
+        }
+
+        for id in &self.chromium {
+            if id.len() != 32 || !id.bytes().all(|b| (b'a'..=b'p').contains(&b)) {
+                return Err(Error::Validation(format!("Invalid Chromium extension id: {}", id)));
+            }
+        }
+
+        for id in &self.firefox {
+            let valid = !id.is_empty()
+                && id.len() <= 255
+                && !id.chars().any(|c| c.is_whitespace() || c.is_control())
+                && (id.contains('@') || (id.starts_with('{') && id.ends_with('}')));
+            if !valid {
+                return Err(Error::Validation(format!("Invalid Firefox extension id: {}", id)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Native messaging host manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostManifest {
+    pub name: String,
+    pub description: String,
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_origins: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_extensions: Option<Vec<String>>,
+}
+
+impl HostManifest {
+    /// Manifest for a browser family; None if no extension of that family is allowed
+    pub fn for_family(family: BrowserFamily, host_path: &Path, ids: &ExtensionIds) -> Option<Self> {
+        let mut manifest = Self {
+            name: NATIVE_HOST_NAME.to_string(),
+            description: "FocusFlow browser extension bridge".to_string(),
+            path: host_path.display().to_string(),
+            kind: "stdio".to_string(),
+            allowed_origins: None,
+            allowed_extensions: None,
+        };
+
+        match family {
+            BrowserFamily::Chromium if !ids.chromium.is_empty() => {
+                manifest.allowed_origins = Some(
+                    ids.chromium
+                        .iter()
+                        .map(|id| format!("chrome-extension://{}/", id))
+                        .collect(),
+                );
+            }
+            BrowserFamily::Firefox if !ids.firefox.is_empty() => {
+                manifest.allowed_extensions = Some(ids.firefox.clone());
+            }
+            _ => return None,
+        }
+
+        Some(manifest)
+    }
+}
+
+/// Outcome of installing the manifest for one browser
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestInstall {
+    pub browser: String,
+    pub path: String,
+    pub installed: bool,
+    pub error: Option<String>,
+}
+
+/// Linux user-level manifest directories
+///
+/// Chromium-based browsers look under their config directory, Firefox under
+/// `~/.mozilla`.
+pub fn linux_targets(config_dir: &Path, home_dir: &Path) -> Vec<ManifestTarget> {
+    let chromium = |browser: &'static str, dir: &str| {
+        let browser_dir = config_dir.join(dir);
+        ManifestTarget {
+            browser,
+            family: BrowserFamily::Chromium,
+            manifest_dir: browser_dir.join("NativeMessagingHosts"),
+            browser_dir,
+        }
+    };
+
+    vec![
+        chromium("Google Chrome", "google-chrome"),
+        chromium("Google Chrome Beta", "google-chrome-beta"),
+        chromium("Chromium", "chromium"),
+        chromium("Brave", "BraveSoftware/Brave-Browser"),
+        chromium("Microsoft Edge", "microsoft-edge"),
+        chromium("Vivaldi", "vivaldi"),
+        ManifestTarget {
+            browser: "Firefox",
+            family: BrowserFamily::Firefox,
+            browser_dir: home_dir.join(".mozilla"),
+            manifest_dir: home_dir.join(".mozilla/native-messaging-hosts"),
+        },
+    ]
+}
+
+/// Manifest directories of the current user's browsers
+pub fn default_targets() -> Result<Vec<ManifestTarget>> {
+    if !cfg!(target_os = "linux") {
+        return Err(Error::System(
+            "Installing the browser extension host is only supported on Linux".to_string(),
+        ));
+    }
+
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| Error::System("Could not find the home directory".to_string()))?;
+    let config_dir = dirs::config_dir().unwrap_or_else(|| home_dir.join(".config"));
+
+    Ok(linux_targets(&config_dir, &home_dir))
+}
+
+/// The native host binary shipped next to the running executable
+pub fn host_binary_path() -> Result<PathBuf> {
+    let path = std::env::current_exe()?.with_file_name(HOST_BINARY_NAME);
+    if !path.is_file() {
+        return Err(Error::NotFound(format!(
+            "Browser extension host not found at {}",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+/// Write the manifest for every installed browser with an allowed extension
+pub fn install(host_path: &Path, ids: &ExtensionIds, targets: &[ManifestTarget]) -> Result<Vec<ManifestInstall>> {
+    ids.validate()?;
+    if !host_path.is_absolute() {
+        return Err(Error::InvalidInput("The host path must be absolute".to_string()));
+    }
+
+    let mut results = Vec::new();
+
+    for target in targets.iter().filter(|t| t.browser_dir.is_dir()) {
+        let Some(manifest) = HostManifest::for_family(target.family, host_path, ids) else {
+            continue;
+        };
+
+        let path = target.manifest_path();
+        let written = std::fs::create_dir_all(&target.manifest_dir)
+            .map_err(Error::from)
+            .and_then(|_| Ok(serde_json::to_string_pretty(&manifest)?))
+            .and_then(|json| Ok(std::fs::write(&path, json)?));
+
+        if let Err(e) = &written {
+            tracing::warn!("Failed to register extension host for {}: {}", target.browser, e);
+        } else {
+            tracing::info!("Registered extension host for {} at {}", target.browser, path.display());
+        }
+
+        results.push(ManifestInstall {
+            browser: target.browser.to_string(),
+            path: path.display().to_string(),
+            installed: written.is_ok(),
+            error: written.err().map(|e| e.to_string()),
+        });
+    }
+
+    Ok(results)
+}
+
+/// Remove installed manifests, returning the paths removed
+pub fn uninstall(targets: &[ManifestTarget]) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+
+    for target in targets {
+        let path = target.manifest_path();
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+            removed.push(path.display().to_string());
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Installer command line of the host binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallCommand {
+    Install(ExtensionIds),
+    Uninstall,
+}
+
+impl InstallCommand {
+    /// Parse `--install [--chromium-extension <id>]... [--firefox-extension <id>]...`
+    /// or `--uninstall`; None when the host was started by a browser
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Option<Result<Self>> {
+        let mut args = args.into_iter();
+
+        match args.next().as_deref() {
+            Some("--uninstall") => Some(Ok(Self::Uninstall)),
+            Some("--install") => Some(Self::parse_install(args)),
+            _ => None,
+        }
+    }
+
+    fn parse_install(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut ids = ExtensionIds::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| Error::InvalidInput(format!("{} requires a value", name)))
+            };
+
+            match arg.as_str() {
+                "--chromium-extension" => ids.chromium.push(value("--chromium-extension")?),
+                "--firefox-extension" => ids.firefox.push(value("--firefox-extension")?),
+                other => return Err(Error::InvalidInput(format!("Unknown argument: {}", other))),
+            }
+        }
+
+        Ok(Self::Install(ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME_ID: &str = "abcdefghijklmnopabcdefghijklmnop";
+
+    fn temp_home() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("focusflow-manifest-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_extension_id_validation() {
+        let ids = |chromium: &[&str], firefox: &[&str]| ExtensionIds {
+            chromium: chromium.iter().map(|s| s.to_string()).collect(),
+            firefox: firefox.iter().map(|s| s.to_string()).collect(),
+        };
+
+        assert!(ids(&[CHROME_ID], &["focusflow@example.com"]).validate().is_ok());
+        assert!(ids(&[], &["{8f3a1c2e-0000-4000-8000-000000000000}"]).validate().is_ok());
+        assert!(ids(&[], &[]).validate().is_err());
+        assert!(ids(&["ABCDEFGHIJKLMNOPABCDEFGHIJKLMNOP"], &[]).validate().is_err());
+        assert!(ids(&["abc"], &[]).validate().is_err());
+        assert!(ids(&[], &["not an id"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_manifest_per_family() {
+        let ids = ExtensionIds {
+            chromium: vec![CHROME_ID.to_string()],
+            firefox: Vec::new(),
+        };
+        let host = Path::new("/opt/focusflow/focusflow-native-host");
+
+        let manifest = HostManifest::for_family(BrowserFamily::Chromium, host, &ids).unwrap();
+        let json = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(json["name"], NATIVE_HOST_NAME);
+        assert_eq!(json["type"], "stdio");
+        assert_eq!(json["path"], "/opt/focusflow/focusflow-native-host");
+        assert_eq!(json["allowed_origins"][0], format!("chrome-extension://{}/", CHROME_ID));
+        assert!(json.get("allowed_extensions").is_none());
+
+        assert!(HostManifest::for_family(BrowserFamily::Firefox, host, &ids).is_none());
+    }
+
+    #[test]
+    fn test_install_only_for_present_browsers() {
+        let home = temp_home();
+        let config = home.join(".config");
+        std::fs::create_dir_all(config.join("chromium")).unwrap();
+        std::fs::create_dir_all(home.join(".mozilla")).unwrap();
+
+        let targets = linux_targets(&config, &home);
+        let ids = ExtensionIds {
+            chromium: vec![CHROME_ID.to_string()],
+            firefox: vec!["focusflow@example.com".to_string()],
+        };
+        let host = home.join("bin").join(HOST_BINARY_NAME);
+
+        let results = install(&host, &ids, &targets).unwrap();
+        let browsers: Vec<&str> = results.iter().map(|r| r.browser.as_str()).collect();
+        assert_eq!(browsers, vec!["Chromium", "Firefox"]);
+        assert!(results.iter().all(|r| r.installed));
+
+        let firefox_manifest = home
+            .join(".mozilla/native-messaging-hosts")
+            .join(format!("{}.json", NATIVE_HOST_NAME));
+        let manifest: HostManifest =
+            serde_json::from_str(&std::fs::read_to_string(&firefox_manifest).unwrap()).unwrap();
+        assert_eq!(manifest.allowed_extensions, Some(vec!["focusflow@example.com".to_string()]));
+        assert!(!config.join("google-chrome").exists());
+
+        assert_eq!(uninstall(&targets).unwrap().len(), 2);
+        assert!(!firefox_manifest.exists());
+
+        assert!(install(Path::new("relative/host"), &ids, &targets).is_err());
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn test_install_command_from_args() {
+        assert!(InstallCommand::from_args(Vec::new()).is_none());
+        // Chromium passes the caller's origin, Firefox the manifest path and add-on id
+        assert!(InstallCommand::from_args([format!("chrome-extension://{}/", CHROME_ID)]).is_none());
+
+        let command = InstallCommand::from_args(
+            ["--install", "--chromium-extension", CHROME_ID, "--firefox-extension", "a@b"].map(String::from),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            command,
+            InstallCommand::Install(ExtensionIds {
+                chromium: vec![CHROME_ID.to_string()],
+                firefox: vec!["a@b".to_string()],
+            })
+        );
+
+        assert_eq!(
+            InstallCommand::from_args(["--uninstall"].map(String::from)).unwrap().unwrap(),
+            InstallCommand::Uninstall
+        );
+        assert!(InstallCommand::from_args(["--install", "--chromium-extension"].map(String::from))
+            .unwrap()
+            .is_err());
+    }
+}
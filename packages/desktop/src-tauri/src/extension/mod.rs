@@ -0,0 +1,211 @@
+// extension/mod.rs - Browser extension bridge
+//
+// The hosts file can only block whole domains. A browser extension can block
+// individual paths and tabs, but it needs the app's rules and session state.
+// Browsers talk to native code through "native messaging": they start a host
+// program (`focusflow-native-host`, see `host`) and exchange JSON messages on
+// its stdin/stdout, each prefixed with its length as a native-endian u32.
+//
+// The host is a thin relay. It connects to the running app over a per-user
+// Unix socket (`server`) and forwards messages as newline-delimited JSON, so
+// URL checks use the app's live rules and session changes are pushed to the
+// extension as they happen. `manifest` registers the host with the browsers.
+
+#[cfg(unix)]
+pub mod host;
+pub mod manifest;
+#[cfg(unix)]
+pub(crate) mod server;
+
+use crate::{state::SessionType, Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Protocol version reported in `ExtensionMessage::Pong`
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Name the host is registered under with the browsers
+pub const NATIVE_HOST_NAME: &str = "com.focusflow.native_host";
+
+/// Largest message exchanged; browsers refuse host messages over 1 MB
+pub const MAX_MESSAGE_BYTES: usize = 1024 * 1024;
+
+/// Messages sent by the extension
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExtensionRequest {
+    /// Check that the app is reachable
+    Ping,
+    /// Check whether a URL is blocked right now
+    CheckUrl {
+        /// Echoed in the answer so checks can be matched up
+        #[serde(default)]
+        id: Option<u64>,
+        url: String,
+        /// Log a block attempt when the URL is blocked
+        #[serde(default)]
+        record_attempt: bool,
+    },
+    /// A visit to a domain, with the seconds the tab was in front
+    Visit {
+        domain: String,
+        #[serde(default)]
+        active_seconds: u64,
+    },
+}
+
+/// Messages sent to the extension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExtensionMessage {
+    #[serde(rename_all = "camelCase")]
+    Pong {
+        protocol_version: u32,
+        app_version: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    UrlCheck {
+        id: Option<u64>,
+        url: String,
+        blocked: bool,
+        matched_domain: Option<String>,
+        match_type: Option<String>,
+    },
+    /// Sent on connect and whenever the session or the rules change
+    SessionState(SessionSnapshot),
+    Error {
+        id: Option<u64>,
+        message: String,
+    },
+}
+
+/// What the extension needs to know about the running session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    pub active: bool,
+    pub session_id: Option<String>,
+    pub session_type: Option<SessionType>,
+    pub task_title: Option<String>,
+    pub paused: bool,
+    /// When a running session is due to end
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Time left in a paused session
+    pub remaining_seconds: Option<i64>,
+    /// Website blocking is in force
+    pub blocking_enabled: bool,
+    pub focus_time_active: bool,
+    /// Changes whenever the website rules change; re-check open tabs then
+    pub rules_version: u64,
+}
+
+/// Default extension bridge socket path
+///
+/// Lives in a private directory under the user's runtime directory, which
+/// only the user can reach; without one the bridge isn't available.
+pub fn default_socket_path() -> Result<PathBuf> {
+    let runtime_dir = dirs::runtime_dir().ok_or_else(|| {
+        Error::System("No user runtime directory (XDG_RUNTIME_DIR) for the extension bridge".to_string())
+    })?;
+
+    Ok(runtime_dir.join("focusflow").join("extension.sock"))
+}
+
+/// Read one length-prefixed native message; `None` when the browser hung up
+pub async fn read_native_message<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u32::from_ne_bytes(len) as usize;
+    if len > MAX_MESSAGE_BYTES {
+        return Err(Error::InvalidInput(format!("Native message of {} bytes is too large", len)));
+    }
+
+    let mut message = vec![0u8; len];
+    reader.read_exact(&mut message).await?;
+    Ok(Some(message))
+}
+
+/// Write one length-prefixed native message
+pub async fn write_native_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &[u8]) -> Result<()> {
+    if message.len() > MAX_MESSAGE_BYTES {
+        return Err(Error::InvalidInput(format!(
+            "Native message of {} bytes is too large",
+            message.len()
+        )));
+    }
+
+    writer.write_all(&(message.len() as u32).to_ne_bytes()).await?;
+    writer.write_all(message).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_wire_format() {
+        let request: ExtensionRequest =
+            serde_json::from_str(r#"{"type":"check_url","id":7,"url":"https://youtube.com/shorts/x"}"#).unwrap();
+        assert_eq!(
+            request,
+            ExtensionRequest::CheckUrl {
+                id: Some(7),
+                url: "https://youtube.com/shorts/x".to_string(),
+                record_attempt: false,
+            }
+        );
+
+        let request: ExtensionRequest = serde_json::from_str(r#"{"type":"visit","domain":"docs.rs"}"#).unwrap();
+        assert_eq!(
+            request,
+            ExtensionRequest::Visit {
+                domain: "docs.rs".to_string(),
+                active_seconds: 0,
+            }
+        );
+
+        assert!(serde_json::from_str::<ExtensionRequest>(r#"{"type":"unblock","url":"x"}"#).is_err());
+
+        let json = serde_json::to_value(ExtensionMessage::SessionState(SessionSnapshot {
+            active: true,
+            rules_version: 3,
+            ..SessionSnapshot::default()
+        }))
+        .unwrap();
+        assert_eq!(json["type"], "session_state");
+        assert_eq!(json["active"], true);
+        assert_eq!(json["rulesVersion"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_native_message_framing() {
+        let (mut browser, mut host) = tokio::io::duplex(64);
+
+        write_native_message(&mut browser, br#"{"type":"ping"}"#).await.unwrap();
+        drop(browser);
+
+        let message = read_native_message(&mut host).await.unwrap().unwrap();
+        assert_eq!(message, br#"{"type":"ping"}"#);
+        assert_eq!(read_native_message(&mut host).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_native_message_is_rejected() {
+        let (mut browser, mut host) = tokio::io::duplex(64);
+        browser
+            .write_all(&((MAX_MESSAGE_BYTES + 1) as u32).to_ne_bytes())
+            .await
+            .unwrap();
+
+        assert!(read_native_message(&mut host).await.is_err());
+    }
+}
@@ -0,0 +1,510 @@
+// extension/server.rs - App side of the browser extension bridge
+//
+// Listens on the per-user bridge socket while the app runs. Every native host
+// connection gets the session state right away and again whenever it changes.
+// URL checks go through the same rules as `check_url_blocked`, and visits are
+// added to the website visit log for analytics.
+
+use super::{default_socket_path, ExtensionMessage, ExtensionRequest, SessionSnapshot, MAX_MESSAGE_BYTES, PROTOCOL_VERSION};
+use crate::{
+    blocking::{allowlist, dns::DomainCheckResult},
+    commands,
+    db::queries,
+    state::{AppState, SessionType},
+    Error, Result,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, watch};
+use tokio::time::Duration;
+
+/// How often the session snapshot is refreshed
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most time a single visit may add (guards against stuck tab timers)
+const MAX_VISIT_SECONDS: u64 = 60 * 60;
+
+/// What the bridge needs from the rest of the app
+#[async_trait]
+pub trait ExtensionBackend: Send + Sync {
+    /// Check a URL against the rules in force
+    async fn check_url(&self, url: &str, record_attempt: bool) -> Result<DomainCheckResult>;
+
+    /// Add a visit to the website visit log
+    async fn record_visit(&self, domain: &str, active_seconds: u64) -> Result<()>;
+}
+
+/// Bind the bridge socket, replacing a stale one
+///
+/// Only the current user may connect: the socket is created inside a
+/// directory only they can enter, so it is never reachable before its own
+/// permissions are tightened. A socket another instance still serves is left
+/// alone.
+pub fn bind(socket_path: &Path) -> Result<UnixListener> {
+    if let Some(dir) = socket_path.parent() {
+        std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+
+    if socket_path.exists() {
+        if std::os::unix::net::UnixStream::connect(socket_path).is_ok() {
+            return Err(Error::System(format!(
+                "The extension bridge at {} is already served by another FocusFlow instance",
+                socket_path.display()
+            )));
+        }
+        std::fs::remove_file(socket_path)?;
+    }
+
+    let listener = UnixListener::bind(socket_path)?;
+    std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600))?;
+
+    Ok(listener)
+}
+
+/// Accept and serve native host connections until the listener fails
+pub async fn serve(
+    listener: UnixListener,
+    backend: Arc<dyn ExtensionBackend>,
+    snapshots: watch::Receiver<SessionSnapshot>,
+) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let backend = backend.clone();
+        let snapshots = snapshots.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, backend, snapshots).await {
+                tracing::debug!("Extension connection ended with error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: UnixStream,
+    backend: Arc<dyn ExtensionBackend>,
+    mut snapshots: watch::Receiver<SessionSnapshot>,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+
+    // Lines are read on their own task: `read_line` can't be raced against state changes
+    let (lines_sender, mut lines) = mpsc::channel(16);
+    let read_task = tokio::spawn(read_lines(reader, lines_sender));
+
+    let result = async {
+        let snapshot = snapshots.borrow_and_update().clone();
+        send(&mut writer, &ExtensionMessage::SessionState(snapshot)).await?;
+
+        loop {
+            tokio::select! {
+                line = lines.recv() => {
+                    let line = match line {
+                        Some(Ok(line)) => line,
+                        Some(Err(e)) => {
+                            let message = ExtensionMessage::Error { id: None, message: e.to_string() };
+                            return send(&mut writer, &message).await;
+                        }
+                        None => return Ok(()),
+                    };
+
+                    if let Some(response) = handle_line(&line, backend.as_ref()).await {
+                        send(&mut writer, &response).await?;
+                    }
+                }
+                changed = snapshots.changed() => {
+                    if changed.is_err() {
+                        return Ok(());
+                    }
+                    let snapshot = snapshots.borrow_and_update().clone();
+                    send(&mut writer, &ExtensionMessage::SessionState(snapshot)).await?;
+                }
+            }
+        }
+    }
+    .await;
+
+    read_task.abort();
+    result
+}
+
+/// Forward request lines until the host disconnects or sends an oversized line
+async fn read_lines(reader: OwnedReadHalf, lines: mpsc::Sender<Result<String>>) {
+    let mut reader = BufReader::new(reader);
+
+    loop {
+        let mut line = String::new();
+        let read = (&mut reader)
+            .take(MAX_MESSAGE_BYTES as u64 + 1)
+            .read_line(&mut line)
+            .await;
+
+        let item = match read {
+            Ok(0) => return,
+            Ok(_) if !line.ends_with('\n') => Err(Error::InvalidInput("Request too large".to_string())),
+            Ok(_) => Ok(line),
+            Err(e) => Err(e.into()),
+        };
+        let stop = item.is_err();
+
+        if lines.send(item).await.is_err() || stop {
+            return;
+        }
+    }
+}
+
+async fn send(writer: &mut OwnedWriteHalf, message: &ExtensionMessage) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+/// Answer one request line; visits are not answered unless they fail
+async fn handle_line(line: &str, backend: &dyn ExtensionBackend) -> Option<ExtensionMessage> {
+    let request = match serde_json::from_str::<ExtensionRequest>(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(ExtensionMessage::Error {
+                id: None,
+                message: format!("Malformed request: {}", e),
+            })
+        }
+    };
+
+    match request {
+        ExtensionRequest::Ping => Some(ExtensionMessage::Pong {
+            protocol_version: PROTOCOL_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        }),
+        ExtensionRequest::CheckUrl { id, url, record_attempt } => {
+            Some(match backend.check_url(&url, record_attempt).await {
+                Ok(result) => ExtensionMessage::UrlCheck {
+                    id,
+                    url,
+                    blocked: result.blocked,
+                    matched_domain: result.matched_domain,
+                    match_type: result.match_type,
+                },
+                Err(e) => ExtensionMessage::Error {
+                    id,
+                    message: e.to_string(),
+                },
+            })
+        }
+        ExtensionRequest::Visit { domain, active_seconds } => {
+            match backend.record_visit(&domain, active_seconds.min(MAX_VISIT_SECONDS)).await {
+                Ok(()) => None,
+                Err(e) => Some(ExtensionMessage::Error {
+                    id: None,
+                    message: e.to_string(),
+                }),
+            }
+        }
+    }
+}
+
+/// Lowercase host name without "www." or a trailing dot
+fn normalize_domain(domain: &str) -> Result<String> {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    let domain = domain.strip_prefix("www.").unwrap_or(&domain).to_string();
+
+    let valid = !domain.is_empty()
+        && domain.len() <= 253
+        && domain.contains('.')
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid {
+        return Err(Error::InvalidInput(format!("Invalid domain: {}", domain)));
+    }
+
+    Ok(domain)
+}
+
+// ============================================================================
+// App integration
+// ============================================================================
+
+/// Backend answering from the app state
+struct AppBackend {
+    state: AppState,
+}
+
+#[async_trait]
+impl ExtensionBackend for AppBackend {
+    async fn check_url(&self, url: &str, record_attempt: bool) -> Result<DomainCheckResult> {
+        commands::blocking::check_url(&self.state, url, record_attempt).await
+    }
+
+    async fn record_visit(&self, domain: &str, active_seconds: u64) -> Result<()> {
+        let domain = normalize_domain(domain)?;
+
+        let focusing = {
+            let active_session = self.state.active_session.read().await;
+            let timer = self.state.timer_state.read().await;
+            active_session
+                .as_ref()
+                .is_some_and(|s| s.session_type != SessionType::Break && timer.is_running && !timer.is_paused)
+        };
+
+        let active_seconds = active_seconds as i64;
+        let focus_seconds = if focusing { active_seconds } else { 0 };
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+
+        queries::add_website_visit(self.state.pool(), &today, &domain, active_seconds, focus_seconds).await
+    }
+}
+
+/// Session state as the extension sees it
+pub async fn snapshot(state: &AppState) -> SessionSnapshot {
+    let rules = allowlist::compiled_website_rules(state).await;
+
+    let mut snapshot = SessionSnapshot {
+        blocking_enabled: rules.enabled,
+        focus_time_active: state.focus_time_state.read().await.active,
+        rules_version: rules.version,
+        ..SessionSnapshot::default()
+    };
+
+    let session = state.active_session.read().await.clone();
+    if let Some(session) = session {
+        let timer = state.timer_state.read().await.clone();
+        let planned_seconds = session.planned_duration_minutes as i64 * 60;
+
+        snapshot.paused = timer.is_paused;
+        if timer.is_paused {
+            let elapsed = timer.elapsed_at(session.start_time, Utc::now());
+            snapshot.remaining_seconds = Some((planned_seconds - elapsed).max(0));
+        } else {
+            // Stays put while the session runs, so it only changes on pause/resume
+            snapshot.ends_at = Some(
+                session.start_time
+                    + chrono::Duration::seconds(planned_seconds + timer.pause_elapsed_seconds),
+            );
+        }
+
+        snapshot.active = true;
+        snapshot.session_id = Some(session.id);
+        snapshot.session_type = Some(session.session_type);
+        snapshot.task_title = session.task_title;
+    }
+
+    snapshot
+}
+
+/// Publish the session state whenever it changes
+async fn follow_session_state(state: AppState, sender: watch::Sender<SessionSnapshot>) {
+    let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current = snapshot(&state).await;
+        sender.send_if_modified(|published| {
+            if *published == current {
+                return false;
+            }
+            *published = current;
+            true
+        });
+    }
+}
+
+/// Serve the extension bridge for the lifetime of the app
+pub async fn run_for_app(state: AppState) -> Result<()> {
+    let socket_path = default_socket_path()?;
+    let listener = bind(&socket_path)?;
+
+    let (sender, receiver) = watch::channel(snapshot(&state).await);
+    tokio::spawn(follow_session_state(state.clone(), sender));
+
+    tracing::info!("Browser extension bridge listening on {}", socket_path.display());
+
+    serve(listener, Arc::new(AppBackend { state }), receiver).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::rules::WebsiteMatcher;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+    use tokio::io::Lines;
+
+    /// Backend blocking a fixed rule list and remembering visits
+    struct FakeBackend {
+        matcher: WebsiteMatcher,
+        visits: Mutex<Vec<(String, u64)>>,
+    }
+
+    #[async_trait]
+    impl ExtensionBackend for FakeBackend {
+        async fn check_url(&self, url: &str, _record_attempt: bool) -> Result<DomainCheckResult> {
+            Ok(self.matcher.check_url(url))
+        }
+
+        async fn record_visit(&self, domain: &str, active_seconds: u64) -> Result<()> {
+            let domain = normalize_domain(domain)?;
+            self.visits.lock().unwrap().push((domain, active_seconds));
+            Ok(())
+        }
+    }
+
+    struct TestBridge {
+        dir: PathBuf,
+        backend: Arc<FakeBackend>,
+        snapshots: watch::Sender<SessionSnapshot>,
+        task: tokio::task::JoinHandle<Result<()>>,
+    }
+
+    impl Drop for TestBridge {
+        fn drop(&mut self) {
+            self.task.abort();
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    impl TestBridge {
+        fn start() -> Self {
+            let dir = std::env::temp_dir().join(format!("focusflow-extension-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let backend = Arc::new(FakeBackend {
+                matcher: WebsiteMatcher::compile(&["youtube.com/shorts", "reddit.com"]),
+                visits: Mutex::new(Vec::new()),
+            });
+            let (snapshots, receiver) = watch::channel(SessionSnapshot::default());
+            let listener = bind(&dir.join("extension.sock")).unwrap();
+            let task = tokio::spawn(serve(listener, backend.clone(), receiver));
+
+            Self {
+                dir,
+                backend,
+                snapshots,
+                task,
+            }
+        }
+
+        async fn connect(&self) -> (Lines<BufReader<OwnedReadHalf>>, OwnedWriteHalf) {
+            let stream = UnixStream::connect(self.dir.join("extension.sock")).await.unwrap();
+            let (reader, writer) = stream.into_split();
+            (BufReader::new(reader).lines(), writer)
+        }
+    }
+
+    async fn next_message(lines: &mut Lines<BufReader<OwnedReadHalf>>) -> ExtensionMessage {
+        let line = lines.next_line().await.unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_url_checks_and_visits() {
+        let bridge = TestBridge::start();
+        let (mut lines, mut writer) = bridge.connect().await;
+
+        // The current state comes first
+        assert_eq!(
+            next_message(&mut lines).await,
+            ExtensionMessage::SessionState(SessionSnapshot::default())
+        );
+
+        writer
+            .write_all(b"{\"type\":\"check_url\",\"id\":1,\"url\":\"https://www.youtube.com/shorts/abc\"}\n")
+            .await
+            .unwrap();
+        match next_message(&mut lines).await {
+            ExtensionMessage::UrlCheck { id, blocked, .. } => {
+                assert_eq!(id, Some(1));
+                assert!(blocked);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        writer
+            .write_all(b"{\"type\":\"check_url\",\"id\":2,\"url\":\"https://youtube.com/watch?v=1\"}\n")
+            .await
+            .unwrap();
+        match next_message(&mut lines).await {
+            ExtensionMessage::UrlCheck { id, blocked, .. } => {
+                assert_eq!(id, Some(2));
+                assert!(!blocked);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        writer
+            .write_all(b"{\"type\":\"visit\",\"domain\":\"WWW.Docs.rs\",\"active_seconds\":99999}\n")
+            .await
+            .unwrap();
+        writer.write_all(b"{\"type\":\"ping\"}\n").await.unwrap();
+        assert!(matches!(next_message(&mut lines).await, ExtensionMessage::Pong { .. }));
+        assert_eq!(
+            *bridge.backend.visits.lock().unwrap(),
+            vec![("docs.rs".to_string(), MAX_VISIT_SECONDS)]
+        );
+
+        writer.write_all(b"{\"type\":\"visit\",\"domain\":\"not a domain\"}\n").await.unwrap();
+        assert!(matches!(next_message(&mut lines).await, ExtensionMessage::Error { .. }));
+
+        writer.write_all(b"garbage\n").await.unwrap();
+        assert!(matches!(next_message(&mut lines).await, ExtensionMessage::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_session_changes_are_pushed() {
+        let bridge = TestBridge::start();
+        let (mut lines, _writer) = bridge.connect().await;
+        next_message(&mut lines).await;
+
+        let started = SessionSnapshot {
+            active: true,
+            session_id: Some("s1".to_string()),
+            blocking_enabled: true,
+            ..SessionSnapshot::default()
+        };
+        bridge.snapshots.send_replace(started.clone());
+
+        assert_eq!(next_message(&mut lines).await, ExtensionMessage::SessionState(started));
+    }
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(normalize_domain(" WWW.GitHub.com. ").unwrap(), "github.com");
+        assert_eq!(normalize_domain("sub.example.co.uk").unwrap(), "sub.example.co.uk");
+        assert!(normalize_domain("localhost").is_err());
+        assert!(normalize_domain("evil.com/path").is_err());
+        assert!(normalize_domain("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bind_keeps_live_socket_and_replaces_stale_one() {
+        let bridge = TestBridge::start();
+        let socket_path = bridge.dir.join("extension.sock");
+
+        // Another instance is serving the socket
+        assert!(bind(&socket_path).is_err());
+        let (mut lines, _writer) = bridge.connect().await;
+        assert_eq!(
+            next_message(&mut lines).await,
+            ExtensionMessage::SessionState(SessionSnapshot::default())
+        );
+
+        // Left behind by an instance that is gone
+        let stale_dir = bridge.dir.join("stale");
+        let stale_path = stale_dir.join("extension.sock");
+        std::fs::create_dir_all(&stale_dir).unwrap();
+        drop(std::os::unix::net::UnixListener::bind(&stale_path).unwrap());
+        assert!(bind(&stale_path).is_ok());
+
+        let mode = std::fs::metadata(&stale_dir).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o700);
+    }
+
+}
@@ -458,6 +458,7 @@ mod focus_time_state_tests {
             duration_minutes: 60,
             allowed_apps: vec![],
             raw_allowed_apps: None,
+            allowed_sites: vec![],
             categories: vec![],
             is_active: true,
             is_upcoming: false,
@@ -509,6 +510,7 @@ mod manager_tests {
             duration_minutes: 60,
             allowed_apps: vec!["Code".to_string(), "Terminal".to_string()],
             raw_allowed_apps: None,
+            allowed_sites: vec![],
             categories: vec!["Coding".to_string()],
             is_active: true,
             is_upcoming: false,
@@ -615,6 +617,7 @@ mod manager_tests {
             duration_minutes: 60,
             allowed_apps: vec![],
             raw_allowed_apps: None,
+            allowed_sites: vec![],
             categories: vec![],
             is_active: false,
             is_upcoming: false,
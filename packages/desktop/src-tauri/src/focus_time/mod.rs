@@ -46,6 +46,9 @@ pub struct FocusTimeState {
     pub added_apps: Vec<String>,
     /// Apps removed during the session (overrides)
     pub removed_apps: Vec<String>,
+    /// Websites allowed during this Focus Time (empty means no web allow-list)
+    #[serde(default)]
+    pub allowed_sites: Vec<String>,
     /// Whether this was manually started (vs auto-triggered by scheduler)
     pub manually_started: bool,
     /// Whether this was ended early by user
@@ -65,6 +68,7 @@ impl FocusTimeState {
             original_allowed_apps: event.allowed_apps.clone(),
             added_apps: Vec::new(),
             removed_apps: Vec::new(),
+            allowed_sites: event.allowed_sites.clone(),
             manually_started: false,
             ended_early: false,
         }
@@ -136,6 +140,9 @@ pub struct FocusTimeEventParsed {
     pub allowed_apps: Vec<String>,
     /// Raw allowed apps string from description (for debugging)
     pub raw_allowed_apps: Option<String>,
+    /// Websites allowed during this Focus Time (web allow-list)
+    #[serde(default)]
+    pub allowed_sites: Vec<String>,
     /// Categories detected in the event
     pub categories: Vec<String>,
     /// Whether this event is currently active
@@ -182,6 +189,7 @@ impl FocusTimeEventParsed {
             } else {
                 Some(config.raw_allowed_apps.join(", "))
             },
+            allowed_sites: config.allowed_sites.clone(),
             categories: config
                 .allowed_categories
                 .iter()
@@ -2,9 +2,10 @@
 //
 // This module provides functionality to:
 // 1. Detect Focus Time events from calendar event titles
-// 2. Parse allowed apps from event descriptions
+// 2. Parse allowed apps and allowed websites from event descriptions
 // 3. Expand app categories to individual app lists
 
+use crate::blocking::rules::WebsiteRule;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
@@ -169,6 +170,9 @@ pub struct FocusTimeConfig {
     pub allowed_categories: Vec<AppCategory>,
     /// Raw allowed apps specified directly (not from categories)
     pub raw_allowed_apps: Vec<String>,
+    /// Websites allowed during this Focus Time (web allow-list, empty for none)
+    #[serde(default)]
+    pub allowed_sites: Vec<String>,
     /// Source event title
     pub source_title: String,
 }
@@ -180,6 +184,7 @@ impl Default for FocusTimeConfig {
             allowed_apps: Vec::new(),
             allowed_categories: Vec::new(),
             raw_allowed_apps: Vec::new(),
+            allowed_sites: Vec::new(),
             source_title: String::new(),
         }
     }
@@ -209,8 +214,10 @@ pub fn parse_focus_time_event(title: &str, description: Option<&str>) -> FocusTi
         return config;
     }
 
-    // Parse allowed apps from description
+    // Parse allowed apps and websites from description
     if let Some(desc) = description {
+        config.allowed_sites = parse_allowed_sites_from_description(desc);
+
         let (categories, apps) = parse_allowed_apps_from_description(desc);
         config.allowed_categories = categories.clone();
         config.raw_allowed_apps = apps.clone();
@@ -242,6 +249,9 @@ pub fn parse_focus_time_event(title: &str, description: Option<&str>) -> FocusTi
 /// - `Allowed: @coding, notion` - Prefixed format
 /// - Line-by-line format with "Apps:" header
 ///
+/// An "Allowed sites:" section (see `parse_allowed_sites_from_description`)
+/// is ignored.
+///
 /// # Arguments
 /// * `description` - The event description text
 ///
@@ -251,6 +261,15 @@ pub fn parse_allowed_apps_from_description(description: &str) -> (Vec<AppCategor
     let mut categories = Vec::new();
     let mut direct_apps = Vec::new();
 
+    let description = match find_sites_section(description) {
+        Some(section) => format!(
+            "{}{}",
+            &description[..section.header_start],
+            &description[section.end..]
+        ),
+        None => description.to_string(),
+    };
+
     // Check if description is empty or whitespace only
     let desc = description.trim();
     if desc.is_empty() {
@@ -293,6 +312,83 @@ pub fn parse_allowed_apps_from_description(description: &str) -> (Vec<AppCategor
     (categories, direct_apps)
 }
 
+/// Parse allowed websites from event description
+///
+/// Looks for a section such as `Allowed sites: github.com, docs.rs` (also
+/// "Allowed websites:", "Websites:" or "Sites:"), running until a blank line
+/// or an apps header. Entries may be any website rule (e.g. `*.rust-lang.org`);
+/// invalid entries are skipped.
+///
+/// # Returns
+/// The allowed website rules, empty if the description has no sites section
+pub fn parse_allowed_sites_from_description(description: &str) -> Vec<String> {
+    let Some(section) = find_sites_section(description) else {
+        return Vec::new();
+    };
+
+    let mut sites: Vec<String> = Vec::new();
+    for item in description[section.content_start..section.end]
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        match WebsiteRule::parse(item) {
+            // Allow-list entries are always allowed, never exceptions of exceptions
+            Ok(rule) if !rule.exception => {
+                let site = rule.to_string();
+                if !sites.contains(&site) {
+                    sites.push(site);
+                }
+            }
+            _ => tracing::debug!("Ignoring invalid allowed site '{}'", item),
+        }
+    }
+
+    sites
+}
+
+/// Headers that start an allowed websites section (longest first)
+const SITE_SECTION_HEADERS: &[&str] = &["allowed websites:", "allowed sites:", "websites:", "sites:"];
+
+/// Headers that start an allowed apps section
+const APP_SECTION_HEADERS: &[&str] = &["allowed:", "apps:", "allow:", "permitted:"];
+
+/// Byte offsets of an allowed websites section
+struct SitesSection {
+    header_start: usize,
+    content_start: usize,
+    end: usize,
+}
+
+fn find_sites_section(description: &str) -> Option<SitesSection> {
+    // ASCII lowercasing keeps byte offsets valid for the original string
+    let desc_lower = description.to_ascii_lowercase();
+
+    let (header_start, header) = SITE_SECTION_HEADERS
+        .iter()
+        .filter_map(|header| desc_lower.find(header).map(|pos| (pos, header)))
+        .min_by_key(|(pos, header)| (*pos, std::cmp::Reverse(header.len())))?;
+    let content_start = header_start + header.len();
+
+    // The section ends at a blank line or a line starting an apps section
+    let remaining = &desc_lower[content_start..];
+    let mut end = remaining.find("\n\n").unwrap_or(remaining.len());
+    let mut offset = 0;
+    for line in remaining.split_inclusive('\n') {
+        if offset > 0 && APP_SECTION_HEADERS.iter().any(|h| line.trim_start().starts_with(h)) {
+            end = end.min(offset);
+            break;
+        }
+        offset += line.len();
+    }
+
+    Some(SitesSection {
+        header_start,
+        content_start,
+        end: content_start + end,
+    })
+}
+
 /// Extract the allowed apps section from a description
 fn extract_allowed_section(description: &str) -> Option<String> {
     let desc_lower = description.to_lowercase();
@@ -509,6 +605,36 @@ mod parser_tests {
         assert!(config.allowed_apps.contains(&"Notion".to_string()));
     }
 
+    #[test]
+    fn test_parse_allowed_sites() {
+        let sites = parse_allowed_sites_from_description("Allowed sites: github.com, docs.rs");
+        assert_eq!(sites, vec!["github.com".to_string(), "docs.rs".to_string()]);
+
+        let sites = parse_allowed_sites_from_description(
+            "Heads down on the parser.\n\nWebsites:\nGitHub.com\n*.rust-lang.org\nnot a site!\n\nNotes",
+        );
+        assert_eq!(sites, vec!["github.com".to_string(), "*.rust-lang.org".to_string()]);
+
+        assert!(parse_allowed_sites_from_description("Allowed: @coding").is_empty());
+    }
+
+    #[test]
+    fn test_sites_section_does_not_leak_into_apps() {
+        let description = "Allowed: @coding, Notion\nAllowed sites: github.com, docs.rs";
+
+        let (categories, apps) = parse_allowed_apps_from_description(description);
+        assert_eq!(categories, vec![AppCategory::Coding]);
+        assert_eq!(apps, vec!["Notion".to_string()]);
+
+        let config = parse_focus_time_event("Focus Time", Some(description));
+        assert_eq!(config.allowed_sites, vec!["github.com".to_string(), "docs.rs".to_string()]);
+
+        // Sites first, apps on the following line
+        let description = "Sites: docs.rs\nApps: Terminal";
+        assert_eq!(parse_allowed_sites_from_description(description), vec!["docs.rs".to_string()]);
+        assert_eq!(parse_allowed_apps_from_description(description).1, vec!["Terminal".to_string()]);
+    }
+
     #[test]
     fn test_empty_description() {
         let config = parse_focus_time_event("Focus Time", Some(""));
@@ -94,6 +94,7 @@ impl FocusTimeSession {
                 allowed_apps,
                 allowed_categories: Vec::new(),
                 raw_allowed_apps: Vec::new(),
+                allowed_sites: Vec::new(),
                 source_title: "Manual Focus Time".to_string(),
             },
         }
@@ -247,6 +248,7 @@ mod session_tests {
             allowed_apps: vec!["Code".to_string(), "Terminal".to_string()],
             allowed_categories: Vec::new(),
             raw_allowed_apps: Vec::new(),
+            allowed_sites: Vec::new(),
             source_title: "Focus Time".to_string(),
         };
 
@@ -336,6 +338,7 @@ mod session_tests {
             allowed_apps: vec!["Terminal".to_string()],
             allowed_categories: Vec::new(),
             raw_allowed_apps: Vec::new(),
+            allowed_sites: Vec::new(),
             source_title: "Deep Work".to_string(),
         };
 
@@ -0,0 +1,238 @@
+// helper/mod.rs - Privileged helper protocol and client
+//
+// Writing the hosts file and terminating other users' processes need root.
+// Instead of running the whole app elevated, a small helper daemon
+// (`focusflow-helper`, see `server`) runs as root and accepts a narrow set of
+// requests over a per-user Unix socket. Requests are newline-delimited JSON;
+// the socket is only accessible to its user, and the helper also checks the
+// peer credentials of each connection against the uid it was installed for.
+
+#[cfg(unix)]
+pub mod server;
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Protocol version reported by `HelperRequest::Status`
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Largest request or response line accepted (a blocklist can be large)
+pub const MAX_MESSAGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// How long the client waits for the helper to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long an availability probe may take
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Requests understood by the helper
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HelperRequest {
+    /// Report the helper version and what it can do
+    Status,
+    /// Replace the requesting user's FocusFlow section of the hosts file
+    ApplyBlocklist { domains: Vec<String> },
+    /// Remove the requesting user's FocusFlow section from the hosts file
+    ClearBlocklist,
+    /// Terminate a process owned by the requesting user
+    KillProcess { pid: u32 },
+}
+
+/// Helper answers, one per request
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HelperResponse {
+    Ok,
+    Status(HelperStatus),
+    Error { error: Error },
+}
+
+/// Helper state reported for `HelperRequest::Status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelperStatus {
+    pub protocol_version: u32,
+    pub helper_version: String,
+    pub hosts_path: String,
+    pub hosts_writable: bool,
+}
+
+/// Helper socket of a user
+///
+/// Every user gets their own helper instance listening on a socket only they
+/// can connect to (see resources/helper/focusflow-helper@.socket).
+pub fn socket_path_for_uid(uid: u32) -> PathBuf {
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from(format!("/var/run/focusflow-helper-{}.sock", uid))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        PathBuf::from(format!("/run/focusflow-helper-{}.sock", uid))
+    }
+}
+
+/// Helper socket of the current user
+pub fn default_socket_path() -> PathBuf {
+    #[cfg(unix)]
+    {
+        // SAFETY: getuid is always successful and has no side effects
+        socket_path_for_uid(unsafe { libc::getuid() })
+    }
+
+    #[cfg(not(unix))]
+    {
+        socket_path_for_uid(0)
+    }
+}
+
+/// Client for the privileged helper
+#[derive(Debug, Clone)]
+pub struct HelperClient {
+    socket_path: PathBuf,
+}
+
+impl Default for HelperClient {
+    fn default() -> Self {
+        Self::at(default_socket_path())
+    }
+}
+
+impl HelperClient {
+    /// Client for a helper listening at a custom socket path
+    pub fn at(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+        }
+    }
+
+    /// Query the helper status, failing if it is not running or incompatible
+    pub async fn status(&self) -> Result<HelperStatus> {
+        match self.request(&HelperRequest::Status).await? {
+            HelperResponse::Status(status) if status.protocol_version == PROTOCOL_VERSION => Ok(status),
+            HelperResponse::Status(status) => Err(Error::System(format!(
+                "Privileged helper speaks protocol {}, expected {}",
+                status.protocol_version, PROTOCOL_VERSION
+            ))),
+            other => Err(unexpected_response(other)),
+        }
+    }
+
+    /// Write hosts-compatible domains to the FocusFlow section of the hosts file
+    pub async fn apply_blocklist(&self, domains: &[String]) -> Result<()> {
+        self.expect_ok(&HelperRequest::ApplyBlocklist {
+            domains: domains.to_vec(),
+        })
+        .await
+    }
+
+    /// Remove the FocusFlow section from the hosts file
+    pub async fn clear_blocklist(&self) -> Result<()> {
+        self.expect_ok(&HelperRequest::ClearBlocklist).await
+    }
+
+    /// Terminate a process owned by the current user
+    pub async fn kill_process(&self, pid: u32) -> Result<()> {
+        self.expect_ok(&HelperRequest::KillProcess { pid }).await
+    }
+
+    async fn expect_ok(&self, request: &HelperRequest) -> Result<()> {
+        match self.request(request).await? {
+            HelperResponse::Ok => Ok(()),
+            other => Err(unexpected_response(other)),
+        }
+    }
+
+    /// Send a single request and wait for its response
+    #[cfg(unix)]
+    pub async fn request(&self, request: &HelperRequest) -> Result<HelperResponse> {
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+        let exchange = async {
+            let mut stream = tokio::net::UnixStream::connect(&self.socket_path).await?;
+
+            let mut line = serde_json::to_string(request)?;
+            line.push('\n');
+            stream.write_all(line.as_bytes()).await?;
+
+            let mut response = String::new();
+            let mut reader = BufReader::new(stream).take(MAX_MESSAGE_BYTES as u64);
+            reader.read_line(&mut response).await?;
+            if response.is_empty() {
+                return Err(Error::System("Privileged helper closed the connection".to_string()));
+            }
+
+            Ok(serde_json::from_str(&response)?)
+        };
+
+        tokio::time::timeout(REQUEST_TIMEOUT, exchange)
+            .await
+            .map_err(|_| Error::System("Privileged helper did not respond".to_string()))?
+    }
+
+    /// Send a single request and wait for its response
+    #[cfg(not(unix))]
+    pub async fn request(&self, _request: &HelperRequest) -> Result<HelperResponse> {
+        Err(Error::System(
+            "The privileged helper is not supported on this platform".to_string(),
+        ))
+    }
+}
+
+fn unexpected_response(response: HelperResponse) -> Error {
+    match response {
+        HelperResponse::Error { error } => error,
+        other => Error::System(format!("Unexpected privileged helper response: {:?}", other)),
+    }
+}
+
+/// Check whether the privileged helper is installed and answering
+pub async fn is_available() -> bool {
+    let client = HelperClient::default();
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, client.status()).await,
+        Ok(Ok(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_wire_format() {
+        let json = serde_json::to_string(&HelperRequest::ApplyBlocklist {
+            domains: vec!["reddit.com".to_string()],
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"type":"apply_blocklist","domains":["reddit.com"]}"#);
+
+        let request: HelperRequest = serde_json::from_str(r#"{"type":"kill_process","pid":42}"#).unwrap();
+        assert_eq!(request, HelperRequest::KillProcess { pid: 42 });
+
+        assert!(serde_json::from_str::<HelperRequest>(r#"{"type":"run_shell","cmd":"id"}"#).is_err());
+    }
+
+    #[test]
+    fn test_error_response_roundtrip() {
+        let json = serde_json::to_string(&HelperResponse::Error {
+            error: Error::PermissionDenied("nope".to_string()),
+        })
+        .unwrap();
+
+        match serde_json::from_str::<HelperResponse>(&json).unwrap() {
+            HelperResponse::Error { error: Error::PermissionDenied(message) } => assert_eq!(message, "nope"),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_helper_is_unavailable() {
+        let client = HelperClient::at(std::env::temp_dir().join("focusflow-no-such-helper.sock"));
+        assert!(client.status().await.is_err());
+    }
+}
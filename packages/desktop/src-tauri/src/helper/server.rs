@@ -0,0 +1,362 @@
+// helper/server.rs - Privileged helper daemon
+//
+// Runs as root (e.g. the `focusflow-helper@<uid>.socket` systemd unit) and
+// serves `HelperRequest`s for one user on a socket owned by that user with
+// mode 0600. Every connection is also authenticated with the kernel-provided
+// peer credentials: only the uid passed with `--allow-uid` is served,
+// processes are only killed when they belong to it, and only its own section
+// of the hosts file is written (see `blocking::hosts`).
+
+use super::{HelperRequest, HelperResponse, HelperStatus, MAX_MESSAGE_BYTES, PROTOCOL_VERSION};
+use crate::{
+    blocking::{hosts::HostsFile, process, rules::WebsiteMatcher},
+    Error, Result,
+};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+/// First file descriptor passed by systemd socket activation
+const SD_LISTEN_FDS_START: i32 = 3;
+
+/// Helper daemon configuration
+#[derive(Debug, Clone)]
+pub struct HelperConfig {
+    pub socket_path: PathBuf,
+    /// The user this helper serves
+    pub allowed_uid: u32,
+    pub hosts_path: PathBuf,
+}
+
+impl HelperConfig {
+    /// Parse command line arguments (`--allow-uid <uid>`, `--socket <path>`, `--hosts <path>`)
+    ///
+    /// The socket defaults to the allowed user's helper socket.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut socket_path = None;
+        let mut allowed_uid = None;
+        let mut hosts_path = crate::blocking::hosts::get_hosts_path();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| Error::InvalidInput(format!("{} requires a value", name)))
+            };
+
+            match arg.as_str() {
+                "--socket" => socket_path = Some(PathBuf::from(value("--socket")?)),
+                "--hosts" => hosts_path = PathBuf::from(value("--hosts")?),
+                "--allow-uid" => {
+                    let uid = value("--allow-uid")?;
+                    let uid = uid
+                        .parse()
+                        .map_err(|_| Error::InvalidInput(format!("Invalid uid: {}", uid)))?;
+                    if allowed_uid.replace(uid).is_some() {
+                        return Err(Error::InvalidInput(
+                            "Only one --allow-uid may be given; run one helper per user".to_string(),
+                        ));
+                    }
+                }
+                other => return Err(Error::InvalidInput(format!("Unknown argument: {}", other))),
+            }
+        }
+
+        let allowed_uid =
+            allowed_uid.ok_or_else(|| Error::InvalidInput("--allow-uid is required".to_string()))?;
+
+        Ok(Self {
+            socket_path: socket_path.unwrap_or_else(|| super::socket_path_for_uid(allowed_uid)),
+            allowed_uid,
+            hosts_path,
+        })
+    }
+}
+
+/// Entry point of the `focusflow-helper` binary
+pub async fn run(config: HelperConfig) -> Result<()> {
+    let listener = match systemd_listener()? {
+        Some(listener) => listener,
+        None => bind(&config.socket_path, config.allowed_uid)?,
+    };
+
+    tracing::info!(
+        "FocusFlow helper listening on {} for uid {}",
+        config.socket_path.display(),
+        config.allowed_uid
+    );
+
+    serve(listener, Arc::new(config)).await
+}
+
+/// Bind the helper socket, replacing a stale one
+///
+/// The socket is owned by the served user and only they can connect.
+pub fn bind(socket_path: &std::path::Path, owner_uid: u32) -> Result<UnixListener> {
+    if socket_path.exists() {
+        std::fs::remove_file(socket_path)?;
+    }
+
+    let listener = UnixListener::bind(socket_path)?;
+    std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600))?;
+
+    // SAFETY: geteuid is always successful and has no side effects
+    if unsafe { libc::geteuid() } != owner_uid {
+        std::os::unix::fs::chown(socket_path, Some(owner_uid), None)?;
+    }
+
+    Ok(listener)
+}
+
+/// Take over the socket passed by systemd socket activation, if any
+fn systemd_listener() -> Result<Option<UnixListener>> {
+    use std::os::unix::io::FromRawFd;
+
+    let for_us = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .is_some_and(|pid| pid == std::process::id());
+    let fds = std::env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|fds| fds.parse::<i32>().ok())
+        .unwrap_or(0);
+
+    if !for_us || fds < 1 {
+        return Ok(None);
+    }
+
+    // SAFETY: systemd hands over ownership of the listening socket at fd 3
+    let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+    listener.set_nonblocking(true)?;
+
+    Ok(Some(UnixListener::from_std(listener)?))
+}
+
+/// Accept and serve connections until the listener fails
+pub async fn serve(listener: UnixListener, config: Arc<HelperConfig>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let config = config.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &config).await {
+                tracing::debug!("Helper connection ended with error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: UnixStream, config: &HelperConfig) -> Result<()> {
+    let peer_uid = stream.peer_cred()?.uid();
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    loop {
+        let mut line = String::new();
+        let read = (&mut reader)
+            .take(MAX_MESSAGE_BYTES as u64)
+            .read_line(&mut line)
+            .await?;
+        if read == 0 {
+            return Ok(());
+        }
+
+        // A line without its newline was cut off at MAX_MESSAGE_BYTES
+        let oversized = !line.ends_with('\n');
+
+        let response = if peer_uid != config.allowed_uid {
+            tracing::warn!("Rejected helper request from unauthorized uid {}", peer_uid);
+            HelperResponse::Error {
+                error: Error::PermissionDenied(format!("uid {} may not use the FocusFlow helper", peer_uid)),
+            }
+        } else if oversized {
+            HelperResponse::Error {
+                error: Error::InvalidInput("Request too large".to_string()),
+            }
+        } else {
+            match serde_json::from_str::<HelperRequest>(&line) {
+                Ok(request) => handle_request(request, peer_uid, config).await,
+                Err(e) => HelperResponse::Error {
+                    error: Error::InvalidInput(format!("Malformed request: {}", e)),
+                },
+            }
+        };
+
+        let mut out = serde_json::to_string(&response)?;
+        out.push('\n');
+        writer.write_all(out.as_bytes()).await?;
+
+        // The rest of an oversized request cannot be framed; drop the connection
+        if oversized {
+            return Ok(());
+        }
+    }
+}
+
+async fn handle_request(request: HelperRequest, peer_uid: u32, config: &HelperConfig) -> HelperResponse {
+    // Each user only ever touches their own section of the shared hosts file
+    let hosts_file = HostsFile::at(&config.hosts_path).for_uid(peer_uid);
+
+    let result = match request {
+        HelperRequest::Status => {
+            return HelperResponse::Status(HelperStatus {
+                protocol_version: PROTOCOL_VERSION,
+                helper_version: env!("CARGO_PKG_VERSION").to_string(),
+                hosts_path: config.hosts_path.display().to_string(),
+                hosts_writable: std::fs::OpenOptions::new()
+                    .write(true)
+                    .open(&config.hosts_path)
+                    .is_ok(),
+            });
+        }
+        HelperRequest::ApplyBlocklist { domains } => {
+            // Only ever write plain, sanitized domains
+            let domains = WebsiteMatcher::compile(&domains).hosts_domains();
+            tracing::info!("Applying {} blocked domains for uid {}", domains.len(), peer_uid);
+            hosts_file.apply(&domains).await
+        }
+        HelperRequest::ClearBlocklist => {
+            tracing::info!("Clearing blocked domains for uid {}", peer_uid);
+            hosts_file.apply(&[]).await
+        }
+        HelperRequest::KillProcess { pid } => {
+            tracing::info!("Terminating PID {} for uid {}", pid, peer_uid);
+            process::terminate_process_for_uid(pid, peer_uid)
+        }
+    };
+
+    match result {
+        Ok(()) => HelperResponse::Ok,
+        Err(error) => HelperResponse::Error { error },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::HelperClient;
+    use std::os::unix::fs::MetadataExt;
+
+    struct TestHelper {
+        dir: PathBuf,
+        client: HelperClient,
+        hosts_path: PathBuf,
+        task: tokio::task::JoinHandle<Result<()>>,
+    }
+
+    impl Drop for TestHelper {
+        fn drop(&mut self) {
+            self.task.abort();
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn current_uid(dir: &std::path::Path) -> u32 {
+        std::fs::metadata(dir).unwrap().uid()
+    }
+
+    fn start_helper(allow_self: bool) -> TestHelper {
+        let dir = std::env::temp_dir().join(format!("focusflow-helper-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let hosts_path = dir.join("hosts");
+        std::fs::write(&hosts_path, "127.0.0.1 localhost\n").unwrap();
+
+        let uid = current_uid(&dir);
+        let config = HelperConfig {
+            socket_path: dir.join("helper.sock"),
+            allowed_uid: if allow_self { uid } else { uid.wrapping_add(1) },
+            hosts_path: hosts_path.clone(),
+        };
+
+        let listener = bind(&config.socket_path, uid).unwrap();
+        let client = HelperClient::at(&config.socket_path);
+        let task = tokio::spawn(serve(listener, Arc::new(config)));
+
+        TestHelper { dir, client, hosts_path, task }
+    }
+
+    #[test]
+    fn test_config_from_args() {
+        let config = HelperConfig::from_args(
+            ["--socket", "/tmp/helper.sock", "--allow-uid", "1000"].map(String::from),
+        )
+        .unwrap();
+        assert_eq!(config.socket_path, PathBuf::from("/tmp/helper.sock"));
+        assert_eq!(config.allowed_uid, 1000);
+
+        let config = HelperConfig::from_args(["--allow-uid", "1000"].map(String::from)).unwrap();
+        assert_eq!(config.socket_path, crate::helper::socket_path_for_uid(1000));
+
+        assert!(HelperConfig::from_args(Vec::new()).is_err());
+        assert!(HelperConfig::from_args(["--allow-uid", "1000", "--allow-uid", "1001"].map(String::from)).is_err());
+        assert!(HelperConfig::from_args(["--allow-uid", "me"].map(String::from)).is_err());
+        assert!(HelperConfig::from_args(["--allow-uid"].map(String::from)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_status_and_blocklist() {
+        let helper = start_helper(true);
+
+        let status = helper.client.status().await.unwrap();
+        assert_eq!(status.protocol_version, PROTOCOL_VERSION);
+        assert!(status.hosts_writable);
+
+        helper
+            .client
+            .apply_blocklist(&["reddit.com".to_string(), "*.evil.com".to_string()])
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(&helper.hosts_path).unwrap();
+        assert!(content.contains("127.0.0.1 reddit.com"));
+        assert!(!content.contains("evil.com"));
+
+        helper.client.clear_blocklist().await.unwrap();
+        let content = std::fs::read_to_string(&helper.hosts_path).unwrap();
+        assert_eq!(content, "127.0.0.1 localhost\n");
+    }
+
+    #[tokio::test]
+    async fn test_clear_keeps_other_users_sections() {
+        let helper = start_helper(true);
+        let other_uid = current_uid(&helper.dir).wrapping_add(1);
+        let other = format!(
+            "127.0.0.1 localhost\n# FocusFlow BLOCK START uid={0}\n127.0.0.1 news.com\n# FocusFlow BLOCK END uid={0}\n",
+            other_uid
+        );
+        std::fs::write(&helper.hosts_path, &other).unwrap();
+
+        helper.client.apply_blocklist(&["reddit.com".to_string()]).await.unwrap();
+        let content = std::fs::read_to_string(&helper.hosts_path).unwrap();
+        assert!(content.contains("127.0.0.1 reddit.com"));
+        assert!(content.contains("127.0.0.1 news.com"));
+
+        helper.client.clear_blocklist().await.unwrap();
+        assert_eq!(std::fs::read_to_string(&helper.hosts_path).unwrap(), other);
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_uid_is_rejected() {
+        let helper = start_helper(false);
+
+        let err = helper.client.status().await.unwrap_err();
+        assert!(matches!(err, Error::PermissionDenied(_)));
+
+        let err = helper.client.clear_blocklist().await.unwrap_err();
+        assert!(matches!(err, Error::PermissionDenied(_)));
+    }
+
+    #[tokio::test]
+    async fn test_kill_process() {
+        let helper = start_helper(true);
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        helper.client.kill_process(child.id()).await.unwrap();
+        assert!(!child.wait().unwrap().success());
+
+        let err = helper.client.kill_process(u32::MAX - 1).await.unwrap_err();
+        assert!(matches!(err, Error::ProcessNotFound(_)));
+    }
+}
@@ -4,7 +4,9 @@ pub mod ai;
 mod commands;
 mod db;
 mod blocking;
+pub mod extension;
 pub mod focus_time;
+pub mod helper;
 mod system;
 mod error;
 mod state;
@@ -58,6 +60,21 @@ pub fn run() {
             // Setup system tray
             system::tray::setup_tray(app)?;
 
+            // Offer saved focus profiles in the tray menu
+            tauri::async_runtime::block_on(commands::profiles::refresh_tray_profiles(&state));
+
+            // Reflect a session resumed from checkpoint in the tray
+            let resumed_session = tauri::async_runtime::block_on(async {
+                state.active_session.read().await.clone()
+            });
+            if let Some(session) = resumed_session {
+                let tray_state = match session.session_type {
+                    state::SessionType::Break => system::tray::TrayIconState::Break,
+                    _ => system::tray::TrayIconState::Focus,
+                };
+                system::tray::update_tray_icon(handle, tray_state);
+            }
+
             // Initialize sync queue
             let sync_state = state.clone();
             tauri::async_runtime::block_on(async {
@@ -83,6 +100,79 @@ pub fn run() {
                 }
             });
 
+            // Start recurring blocking schedule enforcement
+            let scheduler_state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = blocking::scheduler::start_scheduler_loop(scheduler_state).await {
+                    tracing::error!("Blocking scheduler error: {}", e);
+                }
+            });
+
+            // Keep subscribed blocklists up to date
+            let import_state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                blocking::import::start_refresh_loop(import_state).await;
+            });
+
+            // Re-apply hosts file entries removed or edited by other tools
+            let hosts_state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = blocking::hosts::start_integrity_loop(hosts_state).await {
+                    tracing::error!("Hosts integrity check error: {}", e);
+                }
+            });
+
+            // Auto-pause sessions while the user is idle or the screen is locked
+            let idle_state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Some(monitor) = system::idle::default_monitor().await {
+                    commands::idle::start_idle_loop(idle_state, monitor).await;
+                }
+            });
+
+            // Pause sessions across suspend and resync everything on wake
+            let power_state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Some(monitor) = system::power::default_monitor().await {
+                    system::power::start_power_loop(power_state, monitor).await;
+                }
+            });
+
+            // Apply the saved DNS-over-HTTPS protection to the hosts block
+            tauri::async_runtime::block_on(blocking::doh::restore(&state));
+
+            // Restart the DNS sinkhole if it was left enabled
+            let sinkhole_state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = blocking::sinkhole::restore_for_app(&sinkhole_state).await {
+                    tracing::warn!("Failed to start DNS sinkhole: {}", e);
+                }
+            });
+
+            // Serve the "site blocked" page where hosts entries point
+            let blocked_page_state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = blocking::interstitial::restore_for_app(&blocked_page_state).await {
+                    tracing::warn!("Blocked page unavailable: {}", e);
+                }
+            });
+
+            // Answer the browser extension through its native messaging host
+            #[cfg(unix)]
+            {
+                let extension_state = state.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = extension::server::run_for_app(extension_state).await {
+                        tracing::warn!("Browser extension bridge stopped: {}", e);
+                    }
+                });
+            }
+
+            // Restore the system on SIGTERM/SIGINT, logout and system shutdown
+            system::shutdown::spawn_signal_handler(state.clone());
+            #[cfg(target_os = "linux")]
+            system::shutdown::spawn_logind_handler(state.clone());
+
             // Manage state
             app.manage(state);
 
@@ -105,10 +195,17 @@ pub fn run() {
             // Blocking commands
             commands::blocking::add_blocked_app,
             commands::blocking::remove_blocked_app,
+            commands::blocking::set_blocked_app_enforcement,
+            commands::blocking::get_delayed_processes,
+            commands::blocking::resume_delayed_process,
+            commands::blocking::close_delayed_process,
+            commands::blocking::get_termination_settings,
+            commands::blocking::set_termination_settings,
             commands::blocking::add_blocked_website,
             commands::blocking::remove_blocked_website,
             commands::blocking::get_blocked_items,
             commands::blocking::toggle_blocking,
+            commands::blocking::get_effective_blocklist,
 
             // DNS Fallback commands (frontend-based blocking)
             commands::blocking::get_blocked_domains,
@@ -120,6 +217,54 @@ pub fn run() {
             commands::blocking::get_blocking_capabilities,
             commands::blocking::get_elevation_instructions,
             commands::blocking::check_hosts_file_permissions,
+            commands::blocking::list_hosts_backups,
+            commands::blocking::restore_hosts_backup,
+            commands::blocking::get_doh_settings,
+            commands::blocking::set_doh_settings,
+            commands::blocking::start_dns_sinkhole,
+            commands::blocking::stop_dns_sinkhole,
+            commands::blocking::get_dns_sinkhole_config,
+            commands::blocking::get_dns_sinkhole_stats,
+            commands::blocking::get_blocked_page_status,
+            commands::blocking::set_blocked_page_settings,
+            commands::blocking::install_browser_extension_host,
+            commands::blocking::uninstall_browser_extension_host,
+
+            // Daily time budgets
+            commands::budgets::get_time_budgets,
+            commands::budgets::get_remaining_budget,
+            commands::budgets::create_time_budget,
+            commands::budgets::update_time_budget,
+            commands::budgets::delete_time_budget,
+            commands::budgets::get_budget_settings,
+            commands::budgets::set_budget_settings,
+            // Focus profiles
+            commands::profiles::get_focus_profiles,
+            commands::profiles::get_focus_profile,
+            commands::profiles::get_focus_profile_versions,
+            commands::profiles::create_focus_profile,
+            commands::profiles::update_focus_profile,
+            commands::profiles::delete_focus_profile,
+            // Projects, tasks and tags
+            commands::projects::get_projects,
+            commands::projects::create_project,
+            commands::projects::update_project,
+            commands::projects::get_tags,
+            commands::projects::delete_tag,
+            commands::projects::update_session_metadata,
+            // Session event log
+            commands::session_events::get_session_events,
+            // Pomodoro cycles
+            commands::cycle::get_cycle_settings,
+            commands::cycle::set_cycle_settings,
+            commands::cycle::get_cycle_state,
+            commands::cycle::start_pomodoro_cycle,
+            commands::cycle::start_next_cycle_phase,
+            commands::cycle::skip_cycle_phase,
+            commands::cycle::stop_pomodoro_cycle,
+            // Idle and screen-lock auto-pause
+            commands::idle::get_idle_settings,
+            commands::idle::set_idle_settings,
 
             // Comprehensive permission checking
             commands::permissions::check_permissions,
@@ -134,6 +279,10 @@ pub fn run() {
             commands::blocking_advanced::create_blocking_category,
             commands::blocking_advanced::update_blocking_category,
             commands::blocking_advanced::toggle_blocking_category,
+            commands::blocking_advanced::import_blocklist,
+            commands::blocking_advanced::get_blocklist_subscriptions,
+            commands::blocking_advanced::refresh_blocklist_subscription,
+            commands::blocking_advanced::delete_blocklist_subscription,
             commands::blocking_advanced::enable_strict_mode,
             commands::blocking_advanced::disable_strict_mode,
             commands::blocking_advanced::get_strict_mode_state,
@@ -147,6 +296,8 @@ pub fn run() {
             commands::analytics::get_daily_stats,
             commands::analytics::get_weekly_stats,
             commands::analytics::get_date_range_stats,
+            commands::analytics::get_interruption_stats,
+            commands::analytics::get_website_visits,
             commands::analytics::get_productivity_score,
 
             // Auth commands
@@ -187,6 +338,7 @@ pub fn run() {
             commands::focus_time::get_focus_time_events,
             commands::focus_time::get_active_focus_time,
             commands::focus_time::get_allowed_apps,
+            commands::focus_time::get_allowed_sites,
             commands::focus_time::override_focus_time_apps,
             commands::focus_time::end_focus_time_early,
             commands::focus_time::start_focus_time_now,
@@ -338,6 +490,30 @@ pub fn run() {
             commands::tray::set_tray_state,
             commands::tray::get_tray_state,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Hold the exit until the system has been restored
+            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+                if system::shutdown::is_finished() {
+                    return;
+                }
+                api.prevent_exit();
+
+                if !system::shutdown::is_started() {
+                    let app_handle = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        match app_handle.try_state::<AppState>() {
+                            Some(state) => {
+                                system::shutdown::shutdown(&state, system::shutdown::ShutdownReason::AppExit)
+                                    .await;
+                            }
+                            // Setup never got as far as changing the system
+                            None => system::shutdown::mark_finished(),
+                        }
+                        app_handle.exit(0);
+                    });
+                }
+            }
+        });
 }
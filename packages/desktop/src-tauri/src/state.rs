@@ -1,7 +1,13 @@
 // state.rs - Application state management with Arc for thread-safe sharing
 
 use crate::ai::{LlmEngine, ModelConfig};
+use crate::blocking::nuclear::{MonotonicStamp, NUCLEAR_CLOCK_SKEW_TOLERANCE_MS, NUCLEAR_MAX_REBOOT_GAP_MS};
+use crate::blocking::enforcement::DelayedProcess;
+use crate::blocking::interstitial::BlockedPageServer;
+use crate::blocking::scheduler::ScheduleProfile;
+use crate::blocking::sinkhole::DnsSinkhole;
 use crate::commands::auth::{AuthState, PendingOAuthState};
+use crate::commands::cycle::CycleState;
 use crate::focus_time::FocusTimeState;
 use crate::oauth::{google::GoogleCalendar, microsoft::MicrosoftCalendar, Pkce, TokenManager};
 use crate::trailbase::TrailBaseClient;
@@ -30,6 +36,10 @@ pub struct AppState {
     pub active_session: Arc<RwLock<Option<ActiveSession>>>,
     pub timer_state: Arc<RwLock<TimerState>>,
     pub blocking_state: Arc<RwLock<BlockingState>>,
+    /// Domains last written to the system hosts file (empty when nothing is applied)
+    pub applied_hosts_domains: Arc<RwLock<Vec<String>>>,
+    /// Focus profile applied by the active blocking schedule
+    pub schedule_profile: Arc<RwLock<Option<ScheduleProfile>>>,
     pub strict_mode_state: Arc<RwLock<StrictModeState>>,
     pub nuclear_option_state: Arc<RwLock<NuclearOptionState>>,
     pub auth_state: Arc<RwLock<AuthState>>,
@@ -51,6 +61,14 @@ pub struct AppState {
     pub break_reminder_cancellation: Arc<RwLock<Option<oneshot::Sender<()>>>>,
     /// Focus Time state for calendar-based inverse blocking
     pub focus_time_state: Arc<RwLock<FocusTimeState>>,
+    /// Local DNS sinkhole resolver (None when not running)
+    pub dns_sinkhole: Arc<RwLock<Option<DnsSinkhole>>>,
+    /// Local "site blocked" page server (None when not running)
+    pub blocked_page: Arc<RwLock<Option<BlockedPageServer>>>,
+    /// Blocked apps paused by the delay enforcement policy
+    pub delayed_processes: Arc<RwLock<Vec<DelayedProcess>>>,
+    /// Pomodoro cycle chaining focus sessions and breaks
+    pub cycle_state: Arc<RwLock<CycleState>>,
     pub app_handle: tauri::AppHandle,
 }
 
@@ -113,11 +131,13 @@ impl AppState {
         let google_calendar = GoogleCalendar::default(google_client_id);
         let microsoft_calendar = MicrosoftCalendar::default(microsoft_client_id);
 
-        Ok(Self {
+        let state = Self {
             db: Arc::new(db),
             active_session: Arc::new(RwLock::new(None)),
             timer_state: Arc::new(RwLock::new(TimerState::default())),
             blocking_state: Arc::new(RwLock::new(BlockingState::default())),
+            applied_hosts_domains: Arc::new(RwLock::new(Vec::new())),
+            schedule_profile: Arc::new(RwLock::new(None)),
             strict_mode_state: Arc::new(RwLock::new(StrictModeState::default())),
             nuclear_option_state: Arc::new(RwLock::new(NuclearOptionState::default())),
             auth_state: Arc::new(RwLock::new(AuthState::new())),
@@ -133,8 +153,19 @@ impl AppState {
             timer_cancellation: Arc::new(RwLock::new(None)),
             break_reminder_cancellation: Arc::new(RwLock::new(None)),
             focus_time_state: Arc::new(RwLock::new(FocusTimeState::default())),
+            dns_sinkhole: Arc::new(RwLock::new(None)),
+            blocked_page: Arc::new(RwLock::new(None)),
+            delayed_processes: Arc::new(RwLock::new(Vec::new())),
+            cycle_state: Arc::new(RwLock::new(CycleState::default())),
             app_handle,
-        })
+        };
+
+        // Resume (or reconcile) a session interrupted by a crash or restart
+        if let Err(e) = crate::commands::focus::restore_checkpointed_session(&state).await {
+            warn!("Failed to restore checkpointed session: {}", e);
+        }
+
+        Ok(state)
     }
 
     /// Get database connection pool
@@ -159,6 +190,49 @@ impl AppState {
         let mut trailbase_client = self.trailbase_client.write().await;
         *trailbase_client = client;
     }
+
+    /// Snapshot session-related state for persistence
+    pub async fn session_checkpoint(&self) -> SessionCheckpoint {
+        SessionCheckpoint {
+            active_session: self.active_session.read().await.clone(),
+            timer_state: self.timer_state.read().await.clone(),
+            strict_mode: self.strict_mode_state.read().await.clone(),
+            nuclear_option: self.nuclear_option_state.read().await.clone(),
+            cycle: self.cycle_state.read().await.clone(),
+        }
+    }
+
+    /// Persist the current session state so it survives a crash or restart
+    ///
+    /// Failures are logged rather than returned; a missed checkpoint must never
+    /// fail the command that triggered it.
+    pub async fn checkpoint_session(&self) {
+        let checkpoint = self.session_checkpoint().await;
+
+        let data = match serde_json::to_string(&checkpoint) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Failed to serialize session checkpoint: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = crate::db::queries::save_session_checkpoint(self.pool(), &data).await {
+            warn!("Failed to save session checkpoint: {}", e);
+        }
+    }
+}
+
+/// Session state persisted to SQLite and rehydrated on startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionCheckpoint {
+    pub active_session: Option<ActiveSession>,
+    pub timer_state: TimerState,
+    pub strict_mode: StrictModeState,
+    pub nuclear_option: NuclearOptionState,
+    /// Pomodoro cycle the active session belongs to
+    #[serde(default)]
+    pub cycle: CycleState,
 }
 
 /// Represents an active focus session
@@ -170,6 +244,16 @@ pub struct ActiveSession {
     pub session_type: SessionType,
     pub blocked_apps: Vec<String>,
     pub blocked_websites: Vec<String>,
+    /// Websites allowed in allow-list mode (empty means no allow-list)
+    #[serde(default)]
+    pub allowed_websites: Vec<String>,
+    /// Project the session's focus time counts towards
+    #[serde(default)]
+    pub project_id: Option<i64>,
+    #[serde(default)]
+    pub task_title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl ActiveSession {
@@ -186,6 +270,10 @@ impl ActiveSession {
             session_type,
             blocked_apps,
             blocked_websites,
+            allowed_websites: Vec::new(),
+            project_id: None,
+            task_title: None,
+            tags: Vec::new(),
         }
     }
 
@@ -215,6 +303,13 @@ pub struct BlockingState {
     pub blocked_processes: Vec<String>,
     pub blocked_websites: Vec<String>,
     pub last_check: Option<chrono::DateTime<chrono::Utc>>,
+    /// Blocking schedule whose window is active (None for sessions and manual toggles)
+    pub schedule_id: Option<i64>,
+    /// The schedule turned blocking on, rather than finding it already on
+    pub schedule_owns_blocking: bool,
+    /// Bumped whenever `blocked_websites` changes, for caches of compiled rules
+    #[serde(skip)]
+    pub websites_version: u64,
 }
 
 impl BlockingState {
@@ -224,6 +319,8 @@ impl BlockingState {
 
     pub fn disable(&mut self) {
         self.enabled = false;
+        self.schedule_id = None;
+        self.schedule_owns_blocking = false;
     }
 
     pub fn update_last_check(&mut self) {
@@ -231,7 +328,10 @@ impl BlockingState {
     }
 
     pub fn update_blocked_websites(&mut self, websites: Vec<String>) {
-        self.blocked_websites = websites;
+        if self.blocked_websites != websites {
+            self.blocked_websites = websites;
+            self.websites_version += 1;
+        }
     }
 }
 
@@ -244,12 +344,157 @@ pub struct StrictModeState {
 }
 
 /// Nuclear option state - irreversible time-locked blocking
-#[derive(Debug, Clone, Default)]
+///
+/// `ends_at` is informational only; the lockdown is governed by
+/// `remaining_ms`, which is drawn down by monotonic clock readings so that
+/// changing the system clock neither ends nor extends it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NuclearOptionState {
     pub active: bool,
     pub duration_minutes: i32,
     pub started_at: Option<DateTime<Utc>>,
     pub ends_at: Option<DateTime<Utc>>,
+    /// Lockdown time left as of `anchor`
+    #[serde(default)]
+    pub remaining_ms: i64,
+    /// Monotonic clock reading when `remaining_ms` was last updated
+    #[serde(default)]
+    pub anchor: Option<MonotonicStamp>,
+    /// Wall-clock time of the last update, compared against monotonic time to detect tampering
+    #[serde(default)]
+    pub anchor_wall: Option<DateTime<Utc>>,
+}
+
+/// Result of advancing the nuclear option countdown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NuclearAdvance {
+    /// The lockdown ran out during this advance
+    pub expired: bool,
+    /// Wall-clock drift from monotonic time, when beyond tolerance (positive = clock moved forward)
+    pub clock_skew_seconds: Option<i64>,
+}
+
+impl NuclearOptionState {
+    /// Start a lockdown of the given length
+    pub fn activate(&mut self, duration_minutes: i32, now: DateTime<Utc>, stamp: MonotonicStamp) {
+        let remaining_ms = duration_minutes as i64 * 60_000;
+
+        self.active = true;
+        self.duration_minutes = duration_minutes;
+        self.started_at = Some(now);
+        self.ends_at = Some(now + chrono::Duration::milliseconds(remaining_ms));
+        self.remaining_ms = remaining_ms;
+        self.anchor = Some(stamp);
+        self.anchor_wall = Some(now);
+    }
+
+    /// Draw down the remaining time using monotonic clock readings
+    ///
+    /// Within a boot, elapsed time comes from the monotonic clock and any
+    /// disagreement with the wall clock is reported as skew. Across a reboot
+    /// the monotonic clock restarts, so only the wall clock can span the gap.
+    /// It is credited up to the new boot's uptime plus
+    /// `NUCLEAR_MAX_REBOOT_GAP_MS`; a wall clock that went backwards or
+    /// further forward than that is reported as skew.
+    pub fn advance(&mut self, now: DateTime<Utc>, stamp: MonotonicStamp) -> NuclearAdvance {
+        if !self.active {
+            return NuclearAdvance::default();
+        }
+
+        let mut outcome = NuclearAdvance::default();
+
+        match (&self.anchor, self.anchor_wall) {
+            (Some(anchor), Some(anchor_wall)) => {
+                let wall_elapsed_ms = (now - anchor_wall).num_milliseconds();
+
+                let elapsed_ms = if anchor.boot_id == stamp.boot_id {
+                    let monotonic_elapsed_ms =
+                        stamp.millis.saturating_sub(anchor.millis) as i64;
+                    let skew_ms = wall_elapsed_ms - monotonic_elapsed_ms;
+                    if skew_ms.abs() > NUCLEAR_CLOCK_SKEW_TOLERANCE_MS {
+                        outcome.clock_skew_seconds = Some(skew_ms / 1000);
+                    }
+                    monotonic_elapsed_ms
+                } else {
+                    let credit_limit_ms = stamp.millis as i64 + NUCLEAR_MAX_REBOOT_GAP_MS;
+                    let skew_ms = if wall_elapsed_ms < 0 {
+                        wall_elapsed_ms
+                    } else {
+                        (wall_elapsed_ms - credit_limit_ms).max(0)
+                    };
+                    if skew_ms.abs() > NUCLEAR_CLOCK_SKEW_TOLERANCE_MS {
+                        outcome.clock_skew_seconds = Some(skew_ms / 1000);
+                    }
+                    wall_elapsed_ms.clamp(0, credit_limit_ms)
+                };
+
+                self.remaining_ms -= elapsed_ms;
+            }
+            _ => {
+                // Legacy state without a monotonic anchor: fall back to the wall-clock deadline
+                self.remaining_ms = self
+                    .ends_at
+                    .map(|ends_at| (ends_at - now).num_milliseconds())
+                    .unwrap_or(0);
+            }
+        }
+
+        self.anchor = Some(stamp);
+        self.anchor_wall = Some(now);
+
+        if self.remaining_ms <= 0 {
+            *self = Self::default();
+            outcome.expired = true;
+        } else {
+            self.ends_at = Some(now + chrono::Duration::milliseconds(self.remaining_ms));
+        }
+
+        outcome
+    }
+
+    /// Draw down the remaining time after the system slept
+    ///
+    /// A clock that counts through suspend (the boot clock on Linux) measures
+    /// the sleep like any other interval, see `advance`. Otherwise the sleep
+    /// counts by the wall clock, which can't be checked for skew; the
+    /// countdown is re-anchored at `stamp`.
+    pub fn advance_across_sleep(&mut self, now: DateTime<Utc>, stamp: MonotonicStamp) -> NuclearAdvance {
+        if !self.active {
+            return NuclearAdvance::default();
+        }
+
+        let same_clock = self
+            .anchor
+            .as_ref()
+            .is_some_and(|anchor| anchor.boot_id == stamp.boot_id);
+        if same_clock && stamp.counts_suspend() {
+            return self.advance(now, stamp);
+        }
+
+        let slept_ms = self
+            .anchor_wall
+            .map(|anchor_wall| (now - anchor_wall).num_milliseconds().max(0))
+            .unwrap_or(0);
+        self.remaining_ms -= slept_ms;
+        self.anchor = Some(stamp);
+        self.anchor_wall = Some(now);
+
+        if self.remaining_ms <= 0 {
+            *self = Self::default();
+            return NuclearAdvance {
+                expired: true,
+                clock_skew_seconds: None,
+            };
+        }
+
+        self.ends_at = Some(now + chrono::Duration::milliseconds(self.remaining_ms));
+        NuclearAdvance::default()
+    }
+
+    /// Remaining lockdown time in whole seconds
+    pub fn remaining_seconds(&self) -> i64 {
+        (self.remaining_ms.max(0) + 999) / 1000
+    }
 }
 
 /// Timer state for tracking pause/resume during active sessions
@@ -265,6 +510,12 @@ pub struct TimerState {
     pub pause_elapsed_seconds: i64,
     /// When the current pause started (if paused)
     pub paused_at: Option<DateTime<Utc>>,
+    /// Whether the current pause was started by idle or screen-lock detection
+    #[serde(default)]
+    pub idle_paused: bool,
+    /// Cumulative seconds the user was away (not counting current idle pause)
+    #[serde(default)]
+    pub idle_elapsed_seconds: i64,
 }
 
 
@@ -276,6 +527,8 @@ impl TimerState {
             is_paused: false,
             pause_elapsed_seconds: 0,
             paused_at: None,
+            idle_paused: false,
+            idle_elapsed_seconds: 0,
         }
     }
 
@@ -287,14 +540,30 @@ impl TimerState {
         }
     }
 
+    /// Pause the timer because the user went idle or locked the screen
+    ///
+    /// The pause is backdated to when the user was last active, so the time
+    /// away doesn't count as focus time.
+    pub fn pause_for_idle(&mut self, since: DateTime<Utc>) {
+        if self.is_running && !self.is_paused {
+            self.is_paused = true;
+            self.idle_paused = true;
+            self.paused_at = Some(since.min(Utc::now()));
+        }
+    }
+
     /// Resume the timer
     pub fn resume(&mut self) {
         if self.is_paused {
             if let Some(paused_time) = self.paused_at {
-                let pause_duration = Utc::now() - paused_time;
-                self.pause_elapsed_seconds += pause_duration.num_seconds();
+                let pause_duration = (Utc::now() - paused_time).num_seconds();
+                self.pause_elapsed_seconds += pause_duration;
+                if self.idle_paused {
+                    self.idle_elapsed_seconds += pause_duration.max(0);
+                }
             }
             self.is_paused = false;
+            self.idle_paused = false;
             self.paused_at = None;
         }
     }
@@ -314,14 +583,30 @@ impl TimerState {
         self.is_paused = false;
     }
 
+    /// Seconds the user has been away as of a given instant, including a
+    /// running idle pause
+    pub fn idle_seconds_at(&self, at: DateTime<Utc>) -> i64 {
+        let current_idle = match (self.idle_paused, self.paused_at) {
+            (true, Some(since)) => (at - since).num_seconds().max(0),
+            _ => 0,
+        };
+
+        self.idle_elapsed_seconds + current_idle
+    }
+
     /// Calculate effective elapsed time accounting for pauses
     pub fn calculate_elapsed(&self, session_start: DateTime<Utc>) -> i64 {
-        let total_elapsed = (Utc::now() - session_start).num_seconds();
+        self.elapsed_at(session_start, Utc::now())
+    }
+
+    /// Calculate effective elapsed time as of a given instant
+    pub fn elapsed_at(&self, session_start: DateTime<Utc>, at: DateTime<Utc>) -> i64 {
+        let total_elapsed = (at - session_start).num_seconds();
 
         // Calculate current pause duration if currently paused
         let current_pause_duration = if self.is_paused {
             self.paused_at
-                .map(|p| (Utc::now() - p).num_seconds())
+                .map(|p| (at - p).num_seconds().max(0))
                 .unwrap_or(0)
         } else {
             0
@@ -331,3 +616,105 @@ impl TimerState {
         (total_elapsed - self.pause_elapsed_seconds - current_pause_duration).max(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_elapsed_at_excludes_pauses() {
+        let start = Utc::now() - Duration::minutes(30);
+        let timer = TimerState {
+            is_running: true,
+            is_paused: false,
+            pause_elapsed_seconds: 300,
+            paused_at: None,
+            ..TimerState::default()
+        };
+
+        assert_eq!(timer.elapsed_at(start, start + Duration::minutes(20)), 15 * 60);
+    }
+
+    #[test]
+    fn test_elapsed_at_excludes_current_pause() {
+        let start = Utc::now() - Duration::minutes(30);
+        let timer = TimerState {
+            is_running: true,
+            is_paused: true,
+            pause_elapsed_seconds: 0,
+            paused_at: Some(start + Duration::minutes(10)),
+            ..TimerState::default()
+        };
+
+        // Paused at minute 10, so still 10 minutes at minute 25
+        assert_eq!(timer.elapsed_at(start, start + Duration::minutes(25)), 10 * 60);
+    }
+
+    #[test]
+    fn test_idle_pause_is_backdated_and_counted() {
+        let mut timer = TimerState::new_running();
+        let since = Utc::now() - Duration::minutes(5);
+
+        timer.pause_for_idle(since);
+        assert!(timer.is_paused && timer.idle_paused);
+        assert_eq!(timer.paused_at, Some(since));
+        assert_eq!(timer.idle_seconds_at(since + Duration::minutes(5)), 300);
+
+        timer.resume();
+        assert!(!timer.idle_paused);
+        assert!(timer.idle_elapsed_seconds >= 300);
+
+        // A manual pause isn't idle time
+        timer.pause();
+        assert_eq!(timer.idle_seconds_at(Utc::now() + Duration::minutes(10)), timer.idle_elapsed_seconds);
+    }
+
+    #[test]
+    fn test_websites_version_tracks_changes() {
+        let mut blocking = BlockingState::default();
+
+        blocking.update_blocked_websites(vec!["reddit.com".to_string()]);
+        assert_eq!(blocking.websites_version, 1);
+
+        blocking.update_blocked_websites(vec!["reddit.com".to_string()]);
+        assert_eq!(blocking.websites_version, 1);
+
+        blocking.update_blocked_websites(Vec::new());
+        assert_eq!(blocking.websites_version, 2);
+    }
+
+    #[test]
+    fn test_session_checkpoint_round_trip() {
+        let mut session = ActiveSession::new(
+            25,
+            SessionType::Focus,
+            vec!["steam".to_string()],
+            vec!["reddit.com".to_string()],
+        );
+        session.start_time = Utc::now() - Duration::minutes(5);
+
+        let checkpoint = SessionCheckpoint {
+            active_session: Some(session.clone()),
+            timer_state: TimerState::new_running(),
+            strict_mode: StrictModeState {
+                enabled: true,
+                session_id: Some(session.id.clone()),
+                started_at: Some(session.start_time.to_rfc3339()),
+            },
+            nuclear_option: NuclearOptionState::default(),
+            cycle: CycleState::default(),
+        };
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        let restored: SessionCheckpoint = serde_json::from_str(&json).unwrap();
+
+        let restored_session = restored.active_session.unwrap();
+        assert_eq!(restored_session.id, session.id);
+        assert_eq!(restored_session.start_time, session.start_time);
+        assert_eq!(restored_session.blocked_websites, vec!["reddit.com".to_string()]);
+        assert!(restored.timer_state.is_running);
+        assert!(restored.strict_mode.enabled);
+        assert!(!restored.nuclear_option.active);
+    }
+}
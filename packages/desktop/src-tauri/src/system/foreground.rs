@@ -0,0 +1,127 @@
+// system/foreground.rs - Which process owns the focused window
+//
+// App time budgets count time an app is in front, not time it runs in the
+// background. X11 exposes the focused window through `_NET_ACTIVE_WINDOW`
+// (read with xprop), macOS through System Events and Windows through
+// GetForegroundWindow. Wayland has no portable equivalent, so detection
+// reports an error there and callers fall back to their own estimate.
+
+use crate::{Error, Result};
+
+/// PID of the process owning the focused window
+///
+/// Ok(None) means nothing is focused (e.g. the desktop); an error means
+/// focus cannot be detected on this system.
+pub async fn foreground_pid() -> Result<Option<u32>> {
+    platform::foreground_pid().await
+}
+
+/// Last token of an xprop line such as `_NET_WM_PID(CARDINAL) = 1234`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn xprop_value(output: &str) -> Option<&str> {
+    output.split_whitespace().last()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    async fn xprop(args: &[&str]) -> Result<String> {
+        let output = tokio::process::Command::new("xprop")
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| Error::System(format!("xprop unavailable: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(Error::System(format!(
+                "xprop failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub async fn foreground_pid() -> Result<Option<u32>> {
+        if std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland")
+            || std::env::var_os("DISPLAY").is_none()
+        {
+            return Err(Error::System("Focused window detection needs an X11 session".into()));
+        }
+
+        let root = xprop(&["-root", "_NET_ACTIVE_WINDOW"]).await?;
+        let Some(window) = xprop_value(&root).filter(|id| id.starts_with("0x") && *id != "0x0") else {
+            return Ok(None);
+        };
+
+        let pid = xprop(&["-id", window, "_NET_WM_PID"]).await?;
+        Ok(xprop_value(&pid).and_then(|pid| pid.parse().ok()))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    pub async fn foreground_pid() -> Result<Option<u32>> {
+        let output = tokio::process::Command::new("osascript")
+            .args([
+                "-e",
+                "tell application \"System Events\" to get unix id of first application process whose frontmost is true",
+            ])
+            .output()
+            .await
+            .map_err(|e| Error::System(format!("osascript unavailable: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(Error::System(format!(
+                "Reading the frontmost app failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    pub async fn foreground_pid() -> Result<Option<u32>> {
+        // SAFETY: both calls only read window manager state
+        let window = unsafe { GetForegroundWindow() };
+        if window.0.is_null() {
+            return Ok(None);
+        }
+
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(window, Some(&mut pid)) };
+        Ok((pid != 0).then_some(pid))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::*;
+
+    pub async fn foreground_pid() -> Result<Option<u32>> {
+        Err(Error::System("Focused window detection is not supported on this platform".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xprop_value() {
+        assert_eq!(
+            xprop_value("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n"),
+            Some("0x3a00007")
+        );
+        assert_eq!(xprop_value("_NET_WM_PID(CARDINAL) = 4242\n"), Some("4242"));
+        assert_eq!(xprop_value("_NET_WM_PID:  not found.\n").and_then(|v| v.parse::<u32>().ok()), None);
+        assert_eq!(xprop_value(""), None);
+    }
+}
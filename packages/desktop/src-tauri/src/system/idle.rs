@@ -0,0 +1,107 @@
+// system/idle.rs - Idle and screen-lock detection
+//
+// On Linux the session's idle and lock state comes from systemd-logind over
+// D-Bus (`IdleHint`, `IdleSinceHint` and `LockedHint` on the login1 Session
+// object), which desktop environments keep up to date. Other platforms have no
+// monitor yet, so sessions there are never auto-paused.
+
+use chrono::{DateTime, Utc};
+
+use crate::Result;
+
+/// Snapshot of whether the user is at the machine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IdleSample {
+    /// No input for the desktop's idle timeout
+    pub idle: bool,
+    /// When the user was last active, if known
+    pub idle_since: Option<DateTime<Utc>>,
+    /// The screen is locked
+    pub locked: bool,
+}
+
+/// Source of idle and lock state
+pub struct IdleMonitor {
+    #[cfg(target_os = "linux")]
+    inner: linux::LogindIdleMonitor,
+}
+
+impl IdleMonitor {
+    /// Read the current idle and lock state
+    pub async fn sample(&self) -> Result<IdleSample> {
+        #[cfg(target_os = "linux")]
+        {
+            self.inner.sample().await
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(crate::Error::System("Idle detection is not supported on this platform".into()))
+        }
+    }
+}
+
+/// Connect to the platform's idle monitor, if there is one
+pub async fn default_monitor() -> Option<IdleMonitor> {
+    #[cfg(target_os = "linux")]
+    {
+        match linux::LogindIdleMonitor::connect().await {
+            Ok(inner) => Some(IdleMonitor { inner }),
+            Err(e) => {
+                tracing::warn!("Idle detection unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        tracing::debug!("Idle detection is not supported on this platform");
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use crate::system::logind::{dbus_error, LoginSessionProxy};
+    use zbus::{proxy::CacheProperties, Connection};
+
+    /// Idle monitor backed by logind's view of the current session
+    pub struct LogindIdleMonitor {
+        session: LoginSessionProxy<'static>,
+    }
+
+    impl LogindIdleMonitor {
+        pub async fn connect() -> Result<Self> {
+            let connection = Connection::system().await.map_err(dbus_error)?;
+            // Read properties fresh on every sample rather than relying on change signals
+            let session = LoginSessionProxy::builder(&connection)
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await
+                .map_err(dbus_error)?;
+
+            Ok(Self { session })
+        }
+
+        pub async fn sample(&self) -> Result<IdleSample> {
+            let idle = self.session.idle_hint().await.map_err(dbus_error)?;
+            let locked = self.session.locked_hint().await.map_err(dbus_error)?;
+            let idle_since = if idle {
+                let micros = self.session.idle_since_hint().await.map_err(dbus_error)?;
+                (micros > 0)
+                    .then(|| DateTime::from_timestamp_micros(micros as i64))
+                    .flatten()
+            } else {
+                None
+            };
+
+            Ok(IdleSample {
+                idle,
+                idle_since,
+                locked,
+            })
+        }
+    }
+}
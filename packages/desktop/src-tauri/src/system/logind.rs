@@ -0,0 +1,45 @@
+// system/logind.rs - systemd-logind D-Bus proxies (Linux only)
+//
+// Shared by idle detection, suspend/resume handling and shutdown handling.
+
+use crate::Error;
+use zbus::zvariant::OwnedFd;
+
+/// The login1 Session object of the session the app runs in
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/session/auto"
+)]
+pub trait LoginSession {
+    #[zbus(property)]
+    fn idle_hint(&self) -> zbus::Result<bool>;
+
+    /// Microseconds since the Unix epoch, 0 when never idle
+    #[zbus(property)]
+    fn idle_since_hint(&self) -> zbus::Result<u64>;
+
+    #[zbus(property)]
+    fn locked_hint(&self) -> zbus::Result<bool>;
+}
+
+/// The login1 Manager object
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+pub trait LoginManager {
+    /// Take an inhibitor lock, held until the returned descriptor is closed
+    fn inhibit(&self, what: &str, who: &str, why: &str, mode: &str) -> zbus::Result<OwnedFd>;
+
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn prepare_for_shutdown(&self, start: bool) -> zbus::Result<()>;
+}
+
+pub fn dbus_error(e: zbus::Error) -> Error {
+    Error::System(format!("logind D-Bus error: {}", e))
+}
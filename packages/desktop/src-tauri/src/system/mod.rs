@@ -1,6 +1,12 @@
 // system/mod.rs - System integration modules
 
 pub mod dimming;
+pub mod foreground;
+pub mod idle;
+#[cfg(target_os = "linux")]
+pub mod logind;
 pub mod notification_control;
 pub mod notifications;
+pub mod power;
+pub mod shutdown;
 pub mod tray;
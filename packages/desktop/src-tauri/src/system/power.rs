@@ -0,0 +1,498 @@
+// system/power.rs - Suspend and resume awareness
+//
+// On Linux, logind announces sleep with the `PrepareForSleep` signal. A delay
+// inhibitor lock is held so there is time to pause the running session before
+// the machine actually suspends. Sleep is recorded as an idle pause (time
+// away, excluded from focus time), the nuclear option countdown is caught up
+// on both sides of the sleep, blocking is re-validated, and windows are told
+// to resync via the `system-resumed` event.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::{
+    blocking::{hosts, nuclear::{self, MonotonicStamp}, process},
+    commands::session_events::{self, SessionEventType},
+    state::{AppState, NuclearAdvance, NuclearOptionState, SessionType, TimerState},
+    system::tray::{update_tray_icon, TrayIconState},
+};
+
+/// Sleep-related system event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepEvent {
+    /// The system is about to suspend
+    Sleeping,
+    /// The system woke up
+    Resumed,
+}
+
+/// Source of sleep and wake-up notifications
+pub struct SleepMonitor {
+    #[cfg(target_os = "linux")]
+    inner: linux::LogindSleepMonitor,
+}
+
+impl SleepMonitor {
+    /// Wait for the next event; `None` once the source is gone
+    ///
+    /// Asking for the next event after `Sleeping` signals that the app is
+    /// ready for the system to suspend.
+    pub async fn next_event(&mut self) -> Option<SleepEvent> {
+        #[cfg(target_os = "linux")]
+        {
+            self.inner.next_event().await
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            None
+        }
+    }
+}
+
+/// Connect to the platform's sleep notifications, if there are any
+pub async fn default_monitor() -> Option<SleepMonitor> {
+    #[cfg(target_os = "linux")]
+    {
+        match linux::LogindSleepMonitor::connect().await {
+            Ok(inner) => Some(SleepMonitor { inner }),
+            Err(e) => {
+                tracing::warn!("Suspend/resume detection unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        tracing::debug!("Suspend/resume detection is not supported on this platform");
+        None
+    }
+}
+
+/// What going to sleep did to the running session and the lockdown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sleep {
+    /// The running session was paused for the sleep
+    pub paused_session: bool,
+    /// Outcome of catching the lockdown countdown up
+    pub lockdown: NuclearAdvance,
+}
+
+/// What a wake-up did to the running session and the lockdown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Wake {
+    /// How long the system slept, if the sleep was seen
+    pub slept_seconds: Option<i64>,
+    /// The session paused for sleep was resumed
+    pub resumed_session: bool,
+    /// Outcome of taking the time asleep off the lockdown
+    pub lockdown: NuclearAdvance,
+}
+
+/// Tracks a sleep in progress and the session it paused
+#[derive(Debug, Default)]
+pub struct SleepTracker {
+    slept_at: Option<DateTime<Utc>>,
+    paused_session: Option<String>,
+}
+
+impl SleepTracker {
+    /// The system is going to sleep: pause the running session's timer and
+    /// bring the lockdown countdown up to date before the clocks stop
+    ///
+    /// Sessions the user (or idle detection) already paused are left alone.
+    pub fn sleeping(
+        &mut self,
+        timer: &mut TimerState,
+        nuclear: &mut NuclearOptionState,
+        session_id: Option<&str>,
+        now: DateTime<Utc>,
+        stamp: MonotonicStamp,
+    ) -> Sleep {
+        self.slept_at = Some(now);
+        self.paused_session = None;
+
+        let lockdown = nuclear.advance(now, stamp);
+
+        let paused_session = match session_id {
+            Some(session_id) if timer.is_running && !timer.is_paused => {
+                // Time asleep is time away, so it is kept out of focus time like idle time
+                timer.pause_for_idle(now);
+                self.paused_session = Some(session_id.to_string());
+                true
+            }
+            _ => false,
+        };
+
+        Sleep {
+            paused_session,
+            lockdown,
+        }
+    }
+
+    /// The system woke up: take the time asleep off the lockdown and resume
+    /// the timer if the sleep paused it
+    pub fn resumed(
+        &mut self,
+        timer: &mut TimerState,
+        nuclear: &mut NuclearOptionState,
+        session_id: Option<&str>,
+        now: DateTime<Utc>,
+        stamp: MonotonicStamp,
+    ) -> Wake {
+        let lockdown = nuclear.advance_across_sleep(now, stamp);
+
+        let slept_seconds = self
+            .slept_at
+            .take()
+            .map(|slept_at| (now - slept_at).num_seconds().max(0));
+
+        let paused_here = self.paused_session.take();
+        let resumed_session = match (paused_here, session_id) {
+            (Some(paused), Some(current)) if paused == current && timer.idle_paused => {
+                timer.resume();
+                true
+            }
+            _ => false,
+        };
+
+        Wake {
+            slept_seconds,
+            resumed_session,
+            lockdown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemResumedPayload {
+    pub slept_seconds: Option<i64>,
+    pub session_id: Option<String>,
+    pub session_resumed: bool,
+}
+
+/// React to sleep and wake-up for the lifetime of the app
+pub async fn start_power_loop(state: AppState, mut monitor: SleepMonitor) {
+    let mut tracker = SleepTracker::default();
+
+    while let Some(event) = monitor.next_event().await {
+        match event {
+            SleepEvent::Sleeping => on_sleeping(&state, &mut tracker).await,
+            SleepEvent::Resumed => on_resumed(&state, &mut tracker).await,
+        }
+    }
+
+    tracing::warn!("Suspend/resume notifications stopped");
+}
+
+async fn on_sleeping(state: &AppState, tracker: &mut SleepTracker) {
+    let now = Utc::now();
+    let session_id = state.active_session.read().await.as_ref().map(|s| s.id.clone());
+
+    let sleep = {
+        let mut timer = state.timer_state.write().await;
+        let mut nuclear_state = state.nuclear_option_state.write().await;
+        tracker.sleeping(
+            &mut timer,
+            &mut nuclear_state,
+            session_id.as_deref(),
+            now,
+            MonotonicStamp::now(),
+        )
+    };
+
+    if let Some(skew_seconds) = sleep.lockdown.clock_skew_seconds {
+        nuclear::record_clock_tamper(state, skew_seconds).await;
+    }
+    if sleep.lockdown.expired {
+        nuclear::end_lockdown(state).await;
+    }
+
+    if let (true, Some(session_id)) = (sleep.paused_session, session_id.as_deref()) {
+        session_events::record_at(
+            state.pool(),
+            session_id,
+            SessionEventType::IdlePause,
+            now,
+            Some("System asleep"),
+            None,
+        )
+        .await;
+        update_tray_icon(&state.app_handle, TrayIconState::Paused);
+    }
+
+    // Persist everything in case the app doesn't survive the sleep
+    state.checkpoint_session().await;
+    tracing::info!("System going to sleep (session paused: {})", sleep.paused_session);
+}
+
+async fn on_resumed(state: &AppState, tracker: &mut SleepTracker) {
+    let now = Utc::now();
+    let session = state.active_session.read().await.clone();
+
+    let wake = {
+        let mut timer = state.timer_state.write().await;
+        let mut nuclear_state = state.nuclear_option_state.write().await;
+        tracker.resumed(
+            &mut timer,
+            &mut nuclear_state,
+            session.as_ref().map(|s| s.id.as_str()),
+            now,
+            MonotonicStamp::now(),
+        )
+    };
+
+    if let (true, Some(session)) = (wake.resumed_session, session.as_ref()) {
+        session_events::record(
+            state.pool(),
+            &session.id,
+            SessionEventType::Resume,
+            Some("System resumed"),
+            None,
+        )
+        .await;
+
+        let tray_state = match session.session_type {
+            SessionType::Break => TrayIconState::Break,
+            SessionType::Focus | SessionType::Custom => TrayIconState::Focus,
+        };
+        update_tray_icon(&state.app_handle, tray_state);
+    }
+
+    if let Some(skew_seconds) = wake.lockdown.clock_skew_seconds {
+        nuclear::record_clock_tamper(state, skew_seconds).await;
+    }
+    if wake.lockdown.expired {
+        nuclear::end_lockdown(state).await;
+    }
+    state.checkpoint_session().await;
+
+    // Other tools may have rewritten the hosts file, and launches were missed
+    hosts::verify_integrity(state).await;
+    process::request_full_scan();
+
+    tracing::info!(
+        "System resumed after {}s (session resumed: {})",
+        wake.slept_seconds.map_or_else(|| "?".to_string(), |s| s.to_string()),
+        wake.resumed_session
+    );
+
+    let payload = SystemResumedPayload {
+        slept_seconds: wake.slept_seconds,
+        session_id: session.map(|s| s.id),
+        session_resumed: wake.resumed_session,
+    };
+    if let Err(e) = state.app_handle.emit("system-resumed", &payload) {
+        tracing::error!("Failed to emit system-resumed: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use crate::{
+        system::logind::{dbus_error, LoginManagerProxy, PrepareForSleepStream},
+        Result,
+    };
+    use futures_util::StreamExt;
+    use zbus::{zvariant::OwnedFd, Connection};
+
+    /// Sleep monitor backed by logind
+    pub struct LogindSleepMonitor {
+        manager: LoginManagerProxy<'static>,
+        signals: PrepareForSleepStream<'static>,
+        /// Delay lock held while awake; closing it lets the system suspend
+        inhibitor: Option<OwnedFd>,
+        /// `Sleeping` was the last event handed out
+        going_to_sleep: bool,
+    }
+
+    impl LogindSleepMonitor {
+        pub async fn connect() -> Result<Self> {
+            let connection = Connection::system().await.map_err(dbus_error)?;
+            let manager = LoginManagerProxy::new(&connection).await.map_err(dbus_error)?;
+            let signals = manager.receive_prepare_for_sleep().await.map_err(dbus_error)?;
+
+            let mut monitor = Self {
+                manager,
+                signals,
+                inhibitor: None,
+                going_to_sleep: false,
+            };
+            monitor.take_inhibitor().await;
+            Ok(monitor)
+        }
+
+        async fn take_inhibitor(&mut self) {
+            match self
+                .manager
+                .inhibit("sleep", "FocusFlow", "Pause the focus timer", "delay")
+                .await
+            {
+                Ok(fd) => self.inhibitor = Some(fd),
+                Err(e) => tracing::debug!("Could not take sleep inhibitor lock: {}", e),
+            }
+        }
+
+        pub async fn next_event(&mut self) -> Option<SleepEvent> {
+            // The app has handled `Sleeping`; let the system go
+            if std::mem::take(&mut self.going_to_sleep) {
+                self.inhibitor = None;
+            }
+
+            loop {
+                let signal = self.signals.next().await?;
+                let start = match signal.args() {
+                    Ok(args) => args.start,
+                    Err(e) => {
+                        tracing::warn!("Malformed PrepareForSleep signal: {}", e);
+                        continue;
+                    }
+                };
+
+                if start {
+                    self.going_to_sleep = true;
+                    return Some(SleepEvent::Sleeping);
+                }
+
+                self.take_inhibitor().await;
+                return Some(SleepEvent::Resumed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn stamp(millis: u64) -> MonotonicStamp {
+        MonotonicStamp {
+            boot_id: "boot".to_string(),
+            millis,
+        }
+    }
+
+    #[test]
+    fn test_sleep_is_recorded_as_a_pause() {
+        let mut tracker = SleepTracker::default();
+        let mut timer = TimerState::new_running();
+        let mut nuclear = NuclearOptionState::default();
+        let slept_at = Utc::now() - Duration::minutes(45);
+
+        let sleep = tracker.sleeping(&mut timer, &mut nuclear, Some("s"), slept_at, stamp(0));
+        assert_eq!(
+            sleep,
+            Sleep {
+                paused_session: true,
+                lockdown: NuclearAdvance::default(),
+            }
+        );
+        assert!(timer.is_paused && timer.idle_paused);
+
+        let wake = tracker.resumed(
+            &mut timer,
+            &mut nuclear,
+            Some("s"),
+            slept_at + Duration::minutes(45),
+            stamp(1_000),
+        );
+        assert_eq!(wake.slept_seconds, Some(45 * 60));
+        assert!(wake.resumed_session);
+        assert_eq!(wake.lockdown, NuclearAdvance::default());
+        assert!(!timer.is_paused);
+        assert!(timer.idle_elapsed_seconds >= 45 * 60);
+    }
+
+    #[test]
+    fn test_lockdown_counts_down_across_sleep() {
+        let mut tracker = SleepTracker::default();
+        let mut timer = TimerState::default();
+        let mut nuclear = NuclearOptionState::default();
+        let start = Utc::now() - Duration::hours(3);
+        nuclear.activate(60, start, stamp(0));
+
+        // Caught up before the clocks stop
+        let slept_at = start + Duration::minutes(10);
+        let sleep = tracker.sleeping(&mut timer, &mut nuclear, None, slept_at, stamp(600_000));
+        assert_eq!(sleep.lockdown, NuclearAdvance::default());
+        assert_eq!(nuclear.remaining_seconds(), 50 * 60);
+
+        // The boot clock kept counting through the 30 minute sleep
+        let wake = tracker.resumed(&mut timer, &mut nuclear, None, slept_at + Duration::minutes(30), stamp(2_400_000));
+        assert_eq!(wake.lockdown, NuclearAdvance::default());
+        assert_eq!(nuclear.remaining_seconds(), 20 * 60);
+
+        tracker.sleeping(&mut timer, &mut nuclear, None, slept_at + Duration::minutes(31), stamp(2_460_000));
+        let wake = tracker.resumed(&mut timer, &mut nuclear, None, slept_at + Duration::hours(2), stamp(7_200_000));
+        assert!(wake.lockdown.expired);
+        assert!(!nuclear.active);
+    }
+
+    #[test]
+    fn test_clock_change_before_sleep_is_reported() {
+        let mut tracker = SleepTracker::default();
+        let mut timer = TimerState::default();
+        let mut nuclear = NuclearOptionState::default();
+        let start = Utc::now() - Duration::hours(3);
+        nuclear.activate(30, start, stamp(0));
+
+        // The wall clock jumped two hours while only a minute passed
+        let sleep = tracker.sleeping(&mut timer, &mut nuclear, None, start + Duration::minutes(121), stamp(60_000));
+        assert_eq!(sleep.lockdown.clock_skew_seconds, Some(2 * 60 * 60));
+        assert!(!sleep.lockdown.expired);
+        assert_eq!(nuclear.remaining_seconds(), 29 * 60);
+    }
+
+    #[test]
+    fn test_clock_change_during_sleep_is_reported() {
+        let mut tracker = SleepTracker::default();
+        let mut timer = TimerState::default();
+        let mut nuclear = NuclearOptionState::default();
+        let start = Utc::now() - Duration::hours(3);
+        nuclear.activate(30, start, stamp(0));
+
+        tracker.sleeping(&mut timer, &mut nuclear, None, start, stamp(0));
+        // Asleep for ten minutes, but the wall clock says two hours
+        let wake = tracker.resumed(&mut timer, &mut nuclear, None, start + Duration::hours(2), stamp(600_000));
+        assert!(wake.lockdown.clock_skew_seconds.unwrap() > 0);
+        assert!(!wake.lockdown.expired);
+        assert_eq!(nuclear.remaining_seconds(), 20 * 60);
+    }
+
+    #[test]
+    fn test_manual_pause_survives_sleep() {
+        let mut tracker = SleepTracker::default();
+        let mut timer = TimerState::new_running();
+        let mut nuclear = NuclearOptionState::default();
+        timer.pause();
+
+        let now = Utc::now();
+        assert!(!tracker.sleeping(&mut timer, &mut nuclear, Some("s"), now, stamp(0)).paused_session);
+        let wake = tracker.resumed(&mut timer, &mut nuclear, Some("s"), now + Duration::minutes(5), stamp(1_000));
+        assert!(!wake.resumed_session);
+        assert!(timer.is_paused);
+    }
+
+    #[test]
+    fn test_wake_without_sleep_or_session() {
+        let mut tracker = SleepTracker::default();
+        let mut timer = TimerState::default();
+        let mut nuclear = NuclearOptionState::default();
+
+        assert_eq!(tracker.sleeping(&mut timer, &mut nuclear, None, Utc::now(), stamp(0)), Sleep::default());
+        let wake = tracker.resumed(&mut timer, &mut nuclear, None, Utc::now(), stamp(1_000));
+        assert!(wake.slept_seconds.is_some());
+        assert!(!wake.resumed_session);
+
+        // A resume signal without a sleep signal first
+        assert_eq!(
+            tracker.resumed(&mut timer, &mut nuclear, None, Utc::now(), stamp(2_000)),
+            Wake::default()
+        );
+    }
+}
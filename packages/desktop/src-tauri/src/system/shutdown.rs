@@ -0,0 +1,397 @@
+// system/shutdown.rs - Restore the system when the app goes away
+//
+// Quitting, SIGTERM/SIGINT, SIGHUP (sent when the desktop session logs out)
+// and logind announcing a system shutdown all funnel into `shutdown`, which
+// runs once. It ends or checkpoints the running session and undoes what the
+// app changed on the system: paused apps, Do Not Disturb, dimming overlays and
+// the hosts block, unless strict mode or the nuclear option needs the block to
+// outlive the app. The release profile aborts on panic, so this is the only
+// chance to clean up. What was done is logged and kept for the next launch.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    blocking::{enforcement, hosts},
+    commands::{cycle, focus},
+    db::queries,
+    state::AppState,
+    system::{dimming, notification_control},
+};
+
+pub const SHUTDOWN_REPORT_KEY: &str = "last_shutdown";
+
+static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_FINISHED: AtomicBool = AtomicBool::new(false);
+
+/// What made the app shut down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShutdownReason {
+    /// The user quit the app
+    AppExit,
+    /// SIGTERM
+    Terminated,
+    /// SIGINT / Ctrl+C
+    Interrupted,
+    /// SIGHUP, sent when the desktop session ends
+    LoggedOut,
+    /// logind announced a power-off or reboot
+    SystemShutdown,
+}
+
+impl ShutdownReason {
+    fn label(self) -> &'static str {
+        match self {
+            ShutdownReason::AppExit => "App quit",
+            ShutdownReason::Terminated => "App terminated",
+            ShutdownReason::Interrupted => "App interrupted",
+            ShutdownReason::LoggedOut => "Logged out",
+            ShutdownReason::SystemShutdown => "System shut down",
+        }
+    }
+}
+
+/// What happened to the running session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionAction {
+    #[default]
+    None,
+    /// Ended as abandoned
+    Ended,
+    /// Saved to resume on next launch
+    Checkpointed,
+}
+
+/// Decisions made before touching anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownPlan {
+    pub session: SessionAction,
+    pub clear_hosts: bool,
+}
+
+impl ShutdownPlan {
+    /// A session the user quits on is ended; one cut short by the system is
+    /// checkpointed so it picks up again on next launch. Strict mode sessions
+    /// are always checkpointed and, like the nuclear option, keep the hosts
+    /// block in place.
+    pub fn new(
+        reason: ShutdownReason,
+        session_active: bool,
+        strict_session: bool,
+        nuclear_active: bool,
+    ) -> Self {
+        let session = match (session_active, strict_session, reason) {
+            (false, _, _) => SessionAction::None,
+            (true, false, ShutdownReason::AppExit) => SessionAction::Ended,
+            (true, _, _) => SessionAction::Checkpointed,
+        };
+
+        let keep_block = nuclear_active || (session_active && strict_session);
+
+        Self {
+            session,
+            clear_hosts: !keep_block,
+        }
+    }
+}
+
+/// Record of a shutdown
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownReport {
+    pub reason: ShutdownReason,
+    pub at: DateTime<Utc>,
+    pub session_id: Option<String>,
+    pub session: SessionAction,
+    pub processes_released: usize,
+    pub notifications_resumed: bool,
+    pub dimming_disabled: bool,
+    pub hosts_cleared: bool,
+    /// Why the hosts block was left in place
+    pub hosts_kept: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// Whether the app may exit now
+pub fn is_finished() -> bool {
+    SHUTDOWN_FINISHED.load(Ordering::SeqCst)
+}
+
+/// Whether a shutdown is under way (or done)
+pub fn is_started() -> bool {
+    SHUTDOWN_STARTED.load(Ordering::SeqCst)
+}
+
+/// Let the app exit without restoring anything
+///
+/// For exits before setup created the app state, when nothing was changed.
+pub fn mark_finished() {
+    SHUTDOWN_STARTED.store(true, Ordering::SeqCst);
+    SHUTDOWN_FINISHED.store(true, Ordering::SeqCst);
+}
+
+/// Wait for a shutdown started elsewhere to finish
+pub async fn wait_until_finished() {
+    while !is_finished() {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+}
+
+/// Restore the system and settle the running session
+///
+/// Runs once; later calls return `None` straight away.
+pub async fn shutdown(state: &AppState, reason: ShutdownReason) -> Option<ShutdownReport> {
+    if SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) {
+        return None;
+    }
+
+    tracing::info!("Shutting down ({})", reason.label());
+    let report = run(state, reason).await;
+
+    tracing::info!(
+        session = ?report.session,
+        processes_released = report.processes_released,
+        notifications_resumed = report.notifications_resumed,
+        dimming_disabled = report.dimming_disabled,
+        hosts_cleared = report.hosts_cleared,
+        hosts_kept = ?report.hosts_kept,
+        errors = report.errors.len(),
+        "Shutdown complete"
+    );
+    match serde_json::to_string(&report) {
+        Ok(json) => {
+            if let Err(e) = queries::set_user_setting(state.pool(), SHUTDOWN_REPORT_KEY, &json).await {
+                tracing::warn!("Failed to save shutdown report: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to serialize shutdown report: {}", e),
+    }
+
+    SHUTDOWN_FINISHED.store(true, Ordering::SeqCst);
+    Some(report)
+}
+
+async fn run(state: &AppState, reason: ShutdownReason) -> ShutdownReport {
+    let session_id = state.active_session.read().await.as_ref().map(|s| s.id.clone());
+    let strict_session = {
+        let strict = state.strict_mode_state.read().await;
+        strict.enabled && session_id.is_some() && strict.session_id == session_id
+    };
+    let nuclear_active = state.nuclear_option_state.read().await.active;
+    let plan = ShutdownPlan::new(reason, session_id.is_some(), strict_session, nuclear_active);
+
+    // What the app has changed on the system, to report what got undone
+    let dnd_was_paused = state.notification_control_state.read().await.paused;
+    let dimming_was_enabled = state.dimming_state.read().await.enabled;
+    let hosts_had_entries = hosts::has_focusflow_entries().await.unwrap_or(false);
+
+    let mut report = ShutdownReport {
+        reason,
+        at: Utc::now(),
+        session_id,
+        session: SessionAction::None,
+        processes_released: 0,
+        notifications_resumed: false,
+        dimming_disabled: false,
+        hosts_cleared: false,
+        hosts_kept: None,
+        errors: Vec::new(),
+    };
+
+    match plan.session {
+        SessionAction::None => {}
+        SessionAction::Ended => {
+            cycle::stop_cycle(state).await;
+            match focus::end_session(false, Some(reason.label()), state).await {
+                Ok(_) => report.session = SessionAction::Ended,
+                Err(e) => report.errors.push(format!("Failed to end session: {}", e)),
+            }
+        }
+        SessionAction::Checkpointed => {
+            state.checkpoint_session().await;
+            report.session = SessionAction::Checkpointed;
+        }
+    }
+
+    // Apps paused by the delay policy must never stay stopped
+    report.processes_released = state
+        .delayed_processes
+        .read()
+        .await
+        .iter()
+        .filter(|d| !d.resumed)
+        .count();
+    enforcement::release_all(state).await;
+
+    if state.notification_control_state.read().await.paused {
+        match notification_control::force_resume_notifications(state, &state.app_handle).await {
+            Ok(Some(result)) if !result.success => {
+                report.errors.push(format!("Failed to resume notifications: {}", result.message))
+            }
+            Ok(_) => {}
+            Err(e) => report.errors.push(format!("Failed to resume notifications: {}", e)),
+        }
+    }
+    report.notifications_resumed = dnd_was_paused && !state.notification_control_state.read().await.paused;
+
+    if state.dimming_state.read().await.enabled {
+        if let Err(e) = dimming::force_disable_dimming(state, &state.app_handle).await {
+            report.errors.push(format!("Failed to disable dimming: {}", e));
+        }
+    }
+    report.dimming_disabled = dimming_was_enabled && !state.dimming_state.read().await.enabled;
+
+    if plan.clear_hosts {
+        if let Err(e) = hosts::clear_hosts_file(state).await {
+            report.errors.push(format!("Failed to clear hosts file: {}", e));
+        }
+        report.hosts_cleared =
+            hosts_had_entries && !hosts::has_focusflow_entries().await.unwrap_or(true);
+    } else if hosts_had_entries {
+        report.hosts_kept = Some(
+            if nuclear_active {
+                "Nuclear option is active"
+            } else {
+                "Strict mode session is running"
+            }
+            .to_string(),
+        );
+    }
+
+    // Persist the nuclear countdown and anything else still running
+    state.checkpoint_session().await;
+
+    report
+}
+
+/// Shut down cleanly on SIGTERM, SIGINT and SIGHUP (or Ctrl+C elsewhere)
+pub fn spawn_signal_handler(state: AppState) {
+    tauri::async_runtime::spawn(async move {
+        let Some(reason) = wait_for_signal().await else {
+            return;
+        };
+
+        if shutdown(&state, reason).await.is_some() {
+            state.app_handle.exit(0);
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> Option<ShutdownReason> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (mut terminate, mut interrupt, mut hangup) = match (
+        signal(SignalKind::terminate()),
+        signal(SignalKind::interrupt()),
+        signal(SignalKind::hangup()),
+    ) {
+        (Ok(terminate), Ok(interrupt), Ok(hangup)) => (terminate, interrupt, hangup),
+        _ => {
+            tracing::warn!("Failed to install shutdown signal handlers");
+            return None;
+        }
+    };
+
+    Some(tokio::select! {
+        _ = terminate.recv() => ShutdownReason::Terminated,
+        _ = interrupt.recv() => ShutdownReason::Interrupted,
+        _ = hangup.recv() => ShutdownReason::LoggedOut,
+    })
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> Option<ShutdownReason> {
+    match tokio::signal::ctrl_c().await {
+        Ok(()) => Some(ShutdownReason::Interrupted),
+        Err(e) => {
+            tracing::warn!("Failed to install Ctrl+C handler: {}", e);
+            None
+        }
+    }
+}
+
+/// Shut down cleanly before logind powers off or reboots the machine
+#[cfg(target_os = "linux")]
+pub fn spawn_logind_handler(state: AppState) {
+    use crate::system::logind::{dbus_error, LoginManagerProxy};
+    use futures_util::StreamExt;
+
+    tauri::async_runtime::spawn(async move {
+        let setup = async {
+            let connection = zbus::Connection::system().await.map_err(dbus_error)?;
+            let manager = LoginManagerProxy::new(&connection).await.map_err(dbus_error)?;
+            let signals = manager.receive_prepare_for_shutdown().await.map_err(dbus_error)?;
+            // Delay the shutdown until the system has been restored
+            let inhibitor = manager
+                .inhibit("shutdown", "FocusFlow", "Restore blocked sites and notifications", "delay")
+                .await
+                .map_err(dbus_error)?;
+            crate::Result::Ok((signals, inhibitor))
+        };
+
+        let (mut signals, inhibitor) = match setup.await {
+            Ok(setup) => setup,
+            Err(e) => {
+                tracing::warn!("Shutdown detection unavailable: {}", e);
+                return;
+            }
+        };
+
+        while let Some(signal) = signals.next().await {
+            if !signal.args().map(|args| args.start).unwrap_or(false) {
+                continue;
+            }
+
+            let finished = shutdown(&state, ShutdownReason::SystemShutdown).await.is_some();
+            if !finished {
+                // Another path is restoring the system; keep the delay until it is done
+                wait_until_finished().await;
+            }
+            drop(inhibitor);
+            if finished {
+                state.app_handle.exit(0);
+            }
+            return;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_ends_session_on_quit() {
+        let plan = ShutdownPlan::new(ShutdownReason::AppExit, true, false, false);
+        assert_eq!(plan.session, SessionAction::Ended);
+        assert!(plan.clear_hosts);
+    }
+
+    #[test]
+    fn test_plan_checkpoints_when_system_cuts_session_short() {
+        for reason in [
+            ShutdownReason::Terminated,
+            ShutdownReason::LoggedOut,
+            ShutdownReason::SystemShutdown,
+        ] {
+            let plan = ShutdownPlan::new(reason, true, false, false);
+            assert_eq!(plan.session, SessionAction::Checkpointed);
+            assert!(plan.clear_hosts);
+        }
+    }
+
+    #[test]
+    fn test_plan_keeps_block_for_strict_and_nuclear() {
+        let strict = ShutdownPlan::new(ShutdownReason::AppExit, true, true, false);
+        assert_eq!(strict.session, SessionAction::Checkpointed);
+        assert!(!strict.clear_hosts);
+
+        let nuclear = ShutdownPlan::new(ShutdownReason::AppExit, false, false, true);
+        assert_eq!(nuclear.session, SessionAction::None);
+        assert!(!nuclear.clear_hosts);
+    }
+}
@@ -8,11 +8,17 @@
 
 use tauri::{
     image::Image,
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
     App, Emitter, Manager, Runtime,
 };
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Mutex,
+};
+
+/// Menu item id prefix for starting a session with a focus profile
+const PROFILE_MENU_PREFIX: &str = "profile:";
 
 /// Setup system tray with dynamic menu
 pub fn setup_tray<R: Runtime>(app: &App<R>) -> tauri::Result<()> {
@@ -66,6 +72,16 @@ fn create_tray_menu<R: Runtime>(app: &App<R>) -> tauri::Result<Menu<R>> {
 
 /// Handle tray menu events - should be called during app setup
 pub fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, event: MenuEvent) {
+    if let Some(profile_id) = event
+        .id()
+        .as_ref()
+        .strip_prefix(PROFILE_MENU_PREFIX)
+        .and_then(|id| id.parse::<i64>().ok())
+    {
+        start_profile_session(app, profile_id);
+        return;
+    }
+
     match event.id().as_ref() {
         "show" => {
             if let Some(window) = app.get_webview_window("main") {
@@ -106,6 +122,67 @@ pub fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, event: MenuEvent
     }
 }
 
+/// Start a focus session with a profile picked from the tray menu
+fn start_profile_session<R: Runtime>(app: &tauri::AppHandle<R>, profile_id: i64) {
+    let Some(state) = app.try_state::<crate::state::AppState>() else {
+        tracing::warn!("App state not available to start profile {}", profile_id);
+        return;
+    };
+    let state = (*state).clone();
+
+    tauri::async_runtime::spawn(async move {
+        let request = crate::commands::focus::StartSessionRequest::for_profile(profile_id);
+        if let Err(e) =
+            crate::commands::focus::start_session(request, &state, state.app_handle.clone()).await
+        {
+            tracing::warn!("Failed to start session with profile {}: {}", profile_id, e);
+            let _ = state.app_handle.emit(
+                "tray-profile-error",
+                serde_json::json!({
+                    "profileId": profile_id,
+                    "message": e.to_string(),
+                }),
+            );
+        }
+    });
+}
+
+/// Focus profiles offered in the tray's "Start with Profile" submenu
+static TRAY_PROFILES: Mutex<Vec<(i64, String)>> = Mutex::new(Vec::new());
+
+/// Replace the focus profiles shown in the tray menu and rebuild it
+pub fn set_tray_profiles<R: Runtime>(app: &tauri::AppHandle<R>, profiles: Vec<(i64, String)>) {
+    if let Ok(mut current) = TRAY_PROFILES.lock() {
+        *current = profiles;
+    }
+    update_tray_menu_for_state(app, get_current_tray_state());
+}
+
+/// Build the "Start with Profile" submenu
+fn create_profile_submenu<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    enabled: bool,
+) -> tauri::Result<Submenu<R>> {
+    let profiles = TRAY_PROFILES
+        .lock()
+        .map(|profiles| profiles.clone())
+        .unwrap_or_default();
+
+    let items = if profiles.is_empty() {
+        vec![MenuItem::with_id(app, "no_profiles", "No Profiles", false, None::<&str>)?]
+    } else {
+        profiles
+            .iter()
+            .map(|(id, name)| {
+                MenuItem::with_id(app, format!("{}{}", PROFILE_MENU_PREFIX, id), name, true, None::<&str>)
+            })
+            .collect::<tauri::Result<Vec<_>>>()?
+    };
+    let items: Vec<&dyn IsMenuItem<R>> = items.iter().map(|item| item as &dyn IsMenuItem<R>).collect();
+
+    Submenu::with_items(app, "Start with Profile", enabled, &items)
+}
+
 /// Update tray menu based on session state (legacy compatibility)
 ///
 /// Call this when session starts/stops to enable/disable menu items.
@@ -282,6 +359,7 @@ fn update_tray_menu_for_state<R: Runtime>(app: &tauri::AppHandle<R>, state: Tray
             !session_active, // Enabled when NO session is active
             None::<&str>,
         )?;
+        let start_profile = create_profile_submenu(app, !session_active)?;
 
         // Pause/Resume toggle
         let pause_resume_label = if is_paused { "Resume Session" } else { "Pause Session" };
@@ -318,6 +396,7 @@ fn update_tray_menu_for_state<R: Runtime>(app: &tauri::AppHandle<R>, state: Tray
             &separator1,
             &status,
             &start_focus,
+            &start_profile,
             &pause_resume,
             &stop_focus,
             &separator2,
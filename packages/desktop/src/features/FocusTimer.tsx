@@ -89,9 +89,9 @@ export function FocusTimer() {
 
   // Handle stopping session
   const handleStopSession = useCallback(
-    async (completed: boolean) => {
+    async (completed: boolean, sessionId?: string) => {
       try {
-        await endMutation.mutateAsync(completed);
+        await endMutation.mutateAsync({ completed, sessionId });
         endSession();
         setTimerTick(null);
       } catch (error: unknown) {
@@ -107,9 +107,9 @@ export function FocusTimer() {
       setTimerTick(event.payload);
     });
 
-    const unlistenComplete = listen<TimerTickPayload>("timer-completed", () => {
+    const unlistenComplete = listen<TimerTickPayload>("timer-completed", (event) => {
       // Auto-complete the session when timer finishes
-      handleStopSession(true);
+      handleStopSession(true, event.payload.sessionId);
     });
 
     // Request initial state on mount
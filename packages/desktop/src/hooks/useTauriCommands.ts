@@ -52,8 +52,8 @@ export function useEndSession() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: async (completed: boolean) => {
-      await invoke("end_focus_session", { completed });
+    mutationFn: async ({ completed, sessionId }: { completed: boolean; sessionId?: string }) => {
+      await invoke("end_focus_session", { completed, sessionId });
     },
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: queryKeys.activeSession });